@@ -7,160 +7,1211 @@
 use std::collections::HashMap;
 
 use quote::quote;
-use syn::{DataStruct, DeriveInput};
+use syn::DeriveInput;
 
 #[derive(deluxe::ExtractAttributes)]
 #[deluxe(attributes(data))]
 struct TagStructureAttributes {
     size: u64,
+    /// Fields without an offset are placed directly after the previous field.
+    auto_offsets: deluxe::Flag,
+    /// Generates a test checking the layout of the structure.
+    assert_layout: deluxe::Flag,
+    /// Generates `write`, which requires enum fields to implement `IntoPrimitive` and `Clone`.
+    write: deluxe::Flag,
+    /// GUID of the tag struct described by the structure, as a hexadecimal string.
+    guid: Option<syn::LitStr>,
 }
 
-#[derive(deluxe::ExtractAttributes, Clone)]
+#[derive(deluxe::ExtractAttributes)]
 #[deluxe(attributes(data))]
+struct FieldAttributes {
+    offset: Option<u64>,
+    count: Option<u64>,
+    /// Sibling field storing the number of elements of a `FieldArray`.
+    count_from: Option<syn::LitStr>,
+    length: Option<u64>,
+    /// Field is another `TagStructure` stored inline, instead of through a block.
+    inline: deluxe::Flag,
+    /// First `ModuleVersion` the field is present in.
+    since: Option<syn::Ident>,
+    /// First `ModuleVersion` the field is no longer present in.
+    until: Option<syn::Ident>,
+    /// Field is not stored in the tag, and keeps its default value.
+    skip: deluxe::Flag,
+    /// Primitive an enum field is stored as, without a `Field*Enum` wrapper.
+    r#enum: Option<syn::Ident>,
+    /// Primitive a bitflags field is stored as, without a `Field*Flags` wrapper.
+    flags: Option<syn::Ident>,
+}
+
+#[derive(Clone)]
 struct TagStructureFieldAttributes {
     offset: u64,
     count: Option<u64>,
+    count_from: Option<syn::LitStr>,
+    length: Option<u64>,
+    inline: bool,
+    raw_bytes: Option<RawBytes>,
+    since: Option<syn::Ident>,
+    until: Option<syn::Ident>,
+    /// Size in bytes of the field, if it is known from its type.
+    width: Option<u64>,
+    /// Field type used to read and write an enum or bitflags field stored directly.
+    wrapper: Option<syn::Ident>,
+    /// Field is an `Option`, only read if it lies within the data.
+    optional: bool,
+    /// Length and element size in bytes of an array of field types, such as
+    /// `[FieldShortInteger; 4]`.
+    elements: Option<(syn::Expr, u64)>,
+}
+
+/// Wraps the code reading or loading a field in a check of the module version, if the field is
+/// only present in some versions.
+fn version_gated(
+    attributes: &TagStructureFieldAttributes,
+    version: &proc_macro2::TokenStream,
+    body: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let since = attributes.since.as_ref().map(|since| {
+        quote! { *#version >= infinite_rs::module::header::ModuleVersion::#since }
+    });
+    let until = attributes.until.as_ref().map(|until| {
+        quote! { *#version < infinite_rs::module::header::ModuleVersion::#until }
+    });
+    match (since, until) {
+        (None, None) => body,
+        (Some(condition), None) | (None, Some(condition)) => quote! {
+            if #condition {
+                #body
+            }
+        },
+        (Some(since), Some(until)) => quote! {
+            if #since && #until {
+                #body
+            }
+        },
+    }
+}
+
+/// Name of the type of a field, without its path or generic arguments.
+fn type_name(ty: &syn::Type) -> Option<String> {
+    let syn::Type::Path(type_path) = ty else {
+        return None;
+    };
+    Some(type_path.path.segments.last()?.ident.to_string())
+}
+
+#[derive(Clone, Copy, PartialEq)]
+/// Opaque byte spans, used as fields without a field type.
+enum RawBytes {
+    /// `[u8; N]`, whose length is the length of the array.
+    Array,
+    /// `Vec<u8>`, whose length is given by `#[data(length())]`.
+    Vec,
+}
+
+fn is_u8(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(type_path) if type_path.path.is_ident("u8"))
+}
+
+/// Whether a field is a raw byte span, and which kind.
+fn raw_bytes(ty: &syn::Type) -> Option<RawBytes> {
+    match ty {
+        syn::Type::Array(array) if is_u8(&array.elem) => Some(RawBytes::Array),
+        syn::Type::Path(type_path) => {
+            let segment = type_path.path.segments.last()?;
+            let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+                return None;
+            };
+            let is_byte_vec = segment.ident == "Vec"
+                && matches!(arguments.args.first(), Some(syn::GenericArgument::Type(ty)) if is_u8(ty));
+            is_byte_vec.then_some(RawBytes::Vec)
+        }
+        _ => None,
+    }
+}
+
+/// Length of an array type, if it is written as an integer literal.
+fn array_length(ty: &syn::Type) -> Option<u64> {
+    let syn::Type::Array(array) = ty else {
+        return None;
+    };
+    let syn::Expr::Lit(syn::ExprLit {
+        lit: syn::Lit::Int(length),
+        ..
+    }) = &array.len
+    else {
+        return None;
+    };
+    length.base10_parse().ok()
+}
+
+/// Element type and length of an array of field types, such as `[FieldShortInteger; 4]`.
+fn value_array(ty: &syn::Type) -> Option<(&syn::Type, &syn::Expr)> {
+    match ty {
+        syn::Type::Array(array) if !is_u8(&array.elem) => Some((&array.elem, &array.len)),
+        _ => None,
+    }
+}
+
+/// Whether a field type holds a plain value of known size, without blocks, data or resources.
+fn is_plain_value(ty: &syn::Type) -> bool {
+    raw_bytes(ty).is_none()
+        && type_name(ty).is_some_and(|name| {
+            !matches!(
+                name.as_str(),
+                "FieldPad"
+                    | "FieldBlock"
+                    | "LazyFieldBlock"
+                    | "FieldData"
+                    | "LazyFieldData"
+                    | "FieldTagResource"
+            )
+        })
+        && field_width(ty, false, None).is_some()
+}
+
+/// Whether a field is a `Box`, which is read inline like a `TagStructure`.
+fn is_boxed(ty: &syn::Type) -> bool {
+    type_name(ty).is_some_and(|name| name == "Box")
+}
+
+/// Type of the value of an `Option` field.
+fn optional_inner(ty: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match arguments.args.first()? {
+        syn::GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
+
+/// Size in bytes of a field, if it can be known from its type alone.
+fn field_width(ty: &syn::Type, inline: bool, length: Option<u64>) -> Option<u64> {
+    if inline {
+        return None;
+    }
+    match raw_bytes(ty) {
+        Some(RawBytes::Array) => return array_length(ty).or(length),
+        Some(RawBytes::Vec) => return length,
+        None => {}
+    }
+    if let Some((element, _)) = value_array(ty) {
+        return Some(array_length(ty)? * field_width(element, false, None)?);
+    }
+    let width = match type_name(ty)?.as_str() {
+        "FieldExplanation" | "FieldCustom" => 0,
+        "FieldCharInteger"
+        | "FieldCharEnum"
+        | "FieldByteFlags"
+        | "FieldByteBlockFlags"
+        | "FieldCharBlockIndex"
+        | "FieldCustomCharBlockIndex"
+        | "FieldByteInteger" => 1,
+        "FieldShortInteger"
+        | "FieldShortEnum"
+        | "FieldWordFlags"
+        | "FieldWordBlockFlags"
+        | "FieldShortBlockIndex"
+        | "FieldCustomShortBlockIndex"
+        | "FieldWordInteger"
+        | "FieldCharBounds" => 2,
+        "FieldStringId"
+        | "FieldOldStringId"
+        | "FieldLongInteger"
+        | "FieldAngle"
+        | "FieldLongEnum"
+        | "FieldLongFlags"
+        | "FieldPoint2D"
+        | "FieldRGBColor"
+        | "FieldARGBColor"
+        | "FieldReal"
+        | "FieldRealFraction"
+        | "FieldShortBounds"
+        | "FieldLongBlockFlags"
+        | "FieldLongBlockIndex"
+        | "FieldCustomLongBlockIndex"
+        | "FieldDwordInteger" => 4,
+        "FieldInt64Integer"
+        | "FieldRectangle2D"
+        | "FieldRealPoint2D"
+        | "FieldRealVector2D"
+        | "FieldRealEulerAngles2D"
+        | "FieldAngleBounds"
+        | "FieldRealBounds"
+        | "FieldRealFractionBounds"
+        | "FieldLongBounds"
+        | "FieldQwordInteger"
+        | "FieldTimestamp"
+        | "AnyTagGuts" => 8,
+        "FieldRealPoint3D"
+        | "FieldRealVector3D"
+        | "FieldRealEulerAngles3D"
+        | "FieldRealEularAngles3D"
+        | "FieldRealPlane2D"
+        | "FieldRealRGBColor"
+        | "FieldRealHSVColor" => 12,
+        "FieldRealQuaternion"
+        | "FieldRealPlane3D"
+        | "FieldRealARGBColor"
+        | "FieldRealAHSVColor"
+        | "FieldTagResource"
+        | "FieldApiInterop"
+        | "AnyTag" => 0x10,
+        "FieldBlock" | "LazyFieldBlock" => 0x14,
+        "FieldData" | "LazyFieldData" => 0x18,
+        "FieldReference" => 0x1C,
+        "FieldString" => 32,
+        "FieldLongString" => 256,
+        "FieldWideString" => 2 * const_argument(ty, 0)?,
+        "FieldPad" => length?,
+        _ => return None,
+    };
+    Some(width)
+}
+
+/// Field type wrapping an enum or bitflags field with `#[data(enum())]` or `#[data(flags())]`,
+/// and its size in bytes.
+fn primitive_wrapper(
+    field: &syn::Field,
+    attributes: &FieldAttributes,
+) -> deluxe::Result<Option<(syn::Ident, u64)>> {
+    let (primitive, wrappers) = match (&attributes.r#enum, &attributes.flags) {
+        (None, None) => return Ok(None),
+        (Some(primitive), None) => (
+            primitive,
+            ["FieldCharEnum", "FieldShortEnum", "FieldLongEnum"],
+        ),
+        (None, Some(primitive)) => (
+            primitive,
+            ["FieldByteFlags", "FieldWordFlags", "FieldLongFlags"],
+        ),
+        (Some(_), Some(_)) => {
+            return Err(syn::Error::new_spanned(
+                field,
+                "#[data(enum())] and #[data(flags())] cannot be used together",
+            ));
+        }
+    };
+    let (wrapper, width) = match primitive.to_string().as_str() {
+        "u8" => (wrappers[0], 1),
+        "u16" => (wrappers[1], 2),
+        "u32" => (wrappers[2], 4),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                primitive,
+                "expected u8, u16 or u32",
+            ));
+        }
+    };
+    if attributes.inline.is_set()
+        || attributes.count.is_some()
+        || attributes.count_from.is_some()
+        || attributes.length.is_some()
+    {
+        return Err(syn::Error::new_spanned(
+            field,
+            "#[data(enum())] and #[data(flags())] cannot be combined with inline, count or length",
+        ));
+    }
+    Ok(Some((quote::format_ident!("{}", wrapper), width)))
+}
+
+/// Checks that an `Option` field holds a field type of known size that is read on its own.
+fn check_optional_field(
+    field: &syn::Field,
+    inner: &syn::Type,
+    attributes: &FieldAttributes,
+) -> deluxe::Result<()> {
+    if attributes.inline.is_set()
+        || attributes.count.is_some()
+        || attributes.count_from.is_some()
+        || attributes.length.is_some()
+        || attributes.r#enum.is_some()
+        || attributes.flags.is_some()
+    {
+        return Err(syn::Error::new_spanned(
+            field,
+            "Option fields cannot be combined with inline, count, length, enum or flags",
+        ));
+    }
+    if !is_plain_value(inner) {
+        return Err(syn::Error::new_spanned(
+            inner,
+            "Option fields must hold a field type of known size, without blocks, data or resources",
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that the attributes of a field match its type.
+fn check_field_attributes(field: &syn::Field, attributes: &FieldAttributes) -> deluxe::Result<()> {
+    if attributes.inline.is_set() || is_boxed(&field.ty) {
+        return Ok(());
+    }
+    if let Some(raw_bytes) = raw_bytes(&field.ty) {
+        if raw_bytes == RawBytes::Vec && attributes.length.is_none() {
+            return Err(syn::Error::new_spanned(
+                field,
+                "Vec<u8> fields require a #[data(length())] attribute",
+            ));
+        }
+        if attributes.count.is_some() || attributes.count_from.is_some() {
+            return Err(syn::Error::new_spanned(
+                field,
+                "#[data(count())] and #[data(count_from())] are only used by FieldArray",
+            ));
+        }
+        return Ok(());
+    }
+    if let Some((element, _)) = value_array(&field.ty) {
+        if attributes.count.is_some()
+            || attributes.count_from.is_some()
+            || attributes.length.is_some()
+        {
+            return Err(syn::Error::new_spanned(
+                field,
+                "arrays of field types cannot be combined with count or length",
+            ));
+        }
+        if !is_plain_value(element) {
+            return Err(syn::Error::new_spanned(
+                element,
+                "arrays must hold a field type of known size, without blocks, data or resources",
+            ));
+        }
+        return Ok(());
+    }
+    let Some(type_name) = type_name(&field.ty) else {
+        return Err(syn::Error::new_spanned(
+            &field.ty,
+            "unsupported field type, expected a field type or a TagStructure with #[data(inline)]",
+        ));
+    };
+    let is_array = type_name == "FieldArray";
+    let is_pad = type_name == "FieldPad";
+    if is_array && attributes.count.is_none() && attributes.count_from.is_none() {
+        return Err(syn::Error::new_spanned(
+            field,
+            "FieldArray requires a #[data(count())] or #[data(count_from())] attribute",
+        ));
+    }
+    if !is_array && (attributes.count.is_some() || attributes.count_from.is_some()) {
+        return Err(syn::Error::new_spanned(
+            field,
+            "#[data(count())] and #[data(count_from())] are only used by FieldArray",
+        ));
+    }
+    if is_pad
+        && attributes
+            .length
+            .is_none_or(|length| length > u64::from(u8::MAX))
+    {
+        return Err(syn::Error::new_spanned(
+            field,
+            "FieldPad requires a #[data(length())] attribute of at most 255 bytes",
+        ));
+    }
+    if !is_pad && attributes.length.is_some() {
+        return Err(syn::Error::new_spanned(
+            field,
+            "#[data(length())] is only used by FieldPad and raw byte fields",
+        ));
+    }
+    Ok(())
+}
+
+/// Extracts the attributes of a single field, placed after `next_offset` with `auto_offsets`.
+///
+/// Returns [`None`] for fields with `#[data(skip)]`.
+fn extract_field_attributes(
+    field: &mut syn::Field,
+    auto_offsets: bool,
+    next_offset: Option<u64>,
+) -> deluxe::Result<Option<TagStructureFieldAttributes>> {
+    let attributes: FieldAttributes = deluxe::extract_attributes(field)?;
+    if attributes.skip.is_set() {
+        if attributes.offset.is_some() {
+            return Err(syn::Error::new_spanned(
+                field,
+                "#[data(offset())] cannot be used on skipped fields",
+            ));
+        }
+        return Ok(None);
+    }
+    let optional = optional_inner(&field.ty);
+    let wrapper = match optional {
+        Some(inner) => {
+            check_optional_field(field, inner, &attributes)?;
+            None
+        }
+        None => primitive_wrapper(field, &attributes)?,
+    };
+    if optional.is_none() && wrapper.is_none() {
+        check_field_attributes(field, &attributes)?;
+    }
+    let offset = match (attributes.offset, auto_offsets, next_offset) {
+        (Some(offset), _, _) | (None, true, Some(offset)) => offset,
+        (None, true, None) => {
+            return Err(syn::Error::new_spanned(
+                &field.ident,
+                "size of the previous field is unknown, add #[data(offset())] to this field",
+            ));
+        }
+        (None, false, _) => {
+            return Err(syn::Error::new_spanned(
+                &field.ident,
+                "missing #[data(offset())], or add #[data(auto_offsets)] to the struct",
+            ));
+        }
+    };
+    let inline = wrapper.is_none() && (attributes.inline.is_set() || is_boxed(&field.ty));
+    let (wrapper, width) = match (wrapper, optional) {
+        (Some((wrapper, width)), _) => (Some(wrapper), Some(width)),
+        (None, Some(inner)) => (None, field_width(inner, false, None)),
+        (None, None) => (None, field_width(&field.ty, inline, attributes.length)),
+    };
+    Ok(Some(TagStructureFieldAttributes {
+        offset,
+        count: attributes.count,
+        count_from: attributes.count_from,
+        length: attributes.length,
+        inline,
+        raw_bytes: raw_bytes(&field.ty),
+        since: attributes.since,
+        until: attributes.until,
+        width,
+        wrapper,
+        optional: optional.is_some(),
+        elements: value_array(&field.ty).and_then(|(element, length)| {
+            Some((length.clone(), field_width(element, false, None)?))
+        }),
+    }))
 }
 
 fn extract_struct_field_attributes(
     ast: &mut DeriveInput,
+    auto_offsets: bool,
 ) -> deluxe::Result<HashMap<String, TagStructureFieldAttributes>> {
     let mut field_attributes = HashMap::new();
+    let mut errors: Option<syn::Error> = None;
     if let syn::Data::Struct(data) = &mut ast.data {
+        if !matches!(data.fields, syn::Fields::Named(_) | syn::Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                &data.fields,
+                "TagStructure can only be derived for structs with named fields",
+            ));
+        }
+        // End of the previous field, if its size is known.
+        let mut next_offset = Some(0);
         for field in &mut data.fields {
             let field_name = field.ident.as_ref().unwrap().to_string();
-            let attributes: TagStructureFieldAttributes = deluxe::extract_attributes(field)?;
-            field_attributes.insert(field_name, attributes);
+            match extract_field_attributes(field, auto_offsets, next_offset) {
+                Ok(Some(attributes)) => {
+                    next_offset = attributes.width.map(|width| attributes.offset + width);
+                    field_attributes.insert(field_name, attributes);
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    // Keep checking the other fields, without reporting offsets that are only
+                    // unknown because of this error.
+                    next_offset = Some(0);
+                    match &mut errors {
+                        Some(errors) => errors.combine(error),
+                        None => errors = Some(error),
+                    }
+                }
+            }
         }
     }
-    Ok(field_attributes)
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(field_attributes),
+    }
+}
+
+/// Checks that arrays using `count_from` refer to a field that is read before them.
+fn check_count_sources(
+    field_attributes: &HashMap<String, TagStructureFieldAttributes>,
+) -> deluxe::Result<()> {
+    for attributes in field_attributes.values() {
+        let Some(count_from) = &attributes.count_from else {
+            continue;
+        };
+        let is_read_before = field_attributes
+            .get(&count_from.value())
+            .is_some_and(|sibling| sibling.offset < attributes.offset);
+        if !is_read_before {
+            return Err(syn::Error::new_spanned(
+                count_from,
+                "#[data(count_from())] must name a field stored before the array",
+            ));
+        }
+    }
+    Ok(())
 }
 
 fn extract_field_maps(
+    fields: &[&syn::Field],
     field_attributes: &HashMap<String, TagStructureFieldAttributes>,
 ) -> (Vec<String>, Vec<u64>) {
-    field_attributes
-        .clone()
-        .into_iter()
-        .map(|(field, attrs)| (field, attrs.offset))
+    fields
+        .iter()
+        .map(|field| {
+            let name = field.ident.as_ref().unwrap().to_string();
+            let offset = field_attributes[&name].offset;
+            (name, offset)
+        })
         .unzip()
 }
 
-fn generate_field_reads(
-    data: &DataStruct,
+/// Kind of a field, as a variant of `FieldKind`.
+fn field_kind(field: &syn::Field, attributes: &TagStructureFieldAttributes) -> syn::Ident {
+    let ty = optional_inner(&field.ty).unwrap_or(&field.ty);
+    let kind = if let Some(wrapper) = &attributes.wrapper {
+        if wrapper.to_string().ends_with("Enum") {
+            "Enum"
+        } else {
+            "Flags"
+        }
+    } else if attributes.inline {
+        "Structure"
+    } else if attributes.elements.is_some() {
+        "Array"
+    } else if attributes.raw_bytes.is_some() {
+        "RawBytes"
+    } else {
+        match type_name(ty).as_deref() {
+            Some("FieldCharEnum" | "FieldShortEnum" | "FieldLongEnum") => "Enum",
+            Some("FieldByteFlags" | "FieldWordFlags" | "FieldLongFlags") => "Flags",
+            Some("FieldReference") => "Reference",
+            Some("FieldBlock" | "LazyFieldBlock") => "Block",
+            Some("FieldArray" | "FieldFixedArray") => "Array",
+            Some("FieldData" | "LazyFieldData") => "Data",
+            Some("FieldTagResource") => "Resource",
+            Some("FieldPad") => "Padding",
+            _ => "Value",
+        }
+    };
+    quote::format_ident!("{}", kind)
+}
+
+/// Value of a const generic argument of a type, such as the `N` of `FieldFixedArray<T, N>`, if it
+/// is written as an integer literal.
+fn const_argument(ty: &syn::Type, index: usize) -> Option<u64> {
+    let syn::Type::Path(type_path) = ty else {
+        return None;
+    };
+    let syn::PathArguments::AngleBracketed(arguments) = &type_path.path.segments.last()?.arguments
+    else {
+        return None;
+    };
+    match arguments.args.iter().nth(index)? {
+        syn::GenericArgument::Const(syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(value),
+            ..
+        })) => value.base10_parse().ok(),
+        _ => None,
+    }
+}
+
+/// Number of elements of a `FieldArray` or `FieldFixedArray`, if it is known.
+fn element_count(field: &syn::Field, attributes: &TagStructureFieldAttributes) -> Option<u64> {
+    match type_name(&field.ty)?.as_str() {
+        "FieldArray" => attributes.count,
+        "FieldFixedArray" => const_argument(&field.ty, 1),
+        _ => None,
+    }
+}
+
+/// Tokens of an optional number.
+fn optional_u64(value: Option<u64>) -> proc_macro2::TokenStream {
+    value.map_or_else(|| quote! { None }, |value| quote! { Some(#value) })
+}
+
+/// Tokens of the size in bytes of a field, also known for arrays of field types whose length is
+/// a constant.
+fn field_size(attributes: &TagStructureFieldAttributes) -> Option<proc_macro2::TokenStream> {
+    match (attributes.width, &attributes.elements) {
+        (Some(width), _) => Some(quote! { #width }),
+        (None, Some((length, width))) => Some(quote! { (#length as u64) * #width }),
+        (None, None) => None,
+    }
+}
+
+fn generate_field_descriptors(
+    fields: &[&syn::Field],
     field_attributes: &HashMap<String, TagStructureFieldAttributes>,
 ) -> Vec<proc_macro2::TokenStream> {
-    data.fields
+    fields
         .iter()
         .map(|field| {
-            let field_name = &field.ident;
-            let offset = field_attributes
-                .get(&field_name.as_ref().unwrap().to_string())
-                .unwrap()
-                .offset;
-            if let syn::Type::Path(type_path) = &field.ty {
-                if let Some(segment) = type_path.path.segments.last() {
-                    if segment.ident == "FieldArray" {
-                        let count = field_attributes
-                            .get(&field_name.as_ref().unwrap().to_string())
-                            .unwrap()
-                            .count
-                            .unwrap();
-                        return quote! {
-                            reader.seek(std::io::SeekFrom::Start(main_offset + #offset))?;
-                            self.#field_name.read(reader, #count)?;
-                        };
-                    }
+            let name = field.ident.as_ref().unwrap().to_string();
+            let attributes = &field_attributes[&name];
+            let offset = attributes.offset;
+            let ty = &field.ty;
+            let type_name = quote!(#ty).to_string().replace(' ', "");
+            let kind = field_kind(field, attributes);
+            let count = match &attributes.elements {
+                Some((length, _)) => quote! { Some(#length as u64) },
+                None => optional_u64(element_count(field, attributes)),
+            };
+            let size = field_size(attributes)
+                .map_or_else(|| quote! { None }, |size| quote! { Some(#size) });
+            quote! {
+                infinite_rs::module::file::FieldDescriptor {
+                    name: #name,
+                    offset: #offset,
+                    type_name: #type_name,
+                    kind: infinite_rs::module::file::FieldKind::#kind,
+                    count: #count,
+                    size: #size,
                 }
             }
-            quote! {
+        })
+        .collect()
+}
+
+/// Fields of the struct that are stored in the tag, sorted by offset so that generated code reads
+/// the structure sequentially and in the same order on every compilation.
+fn sorted_fields<'a>(
+    data: &'a syn::DataStruct,
+    field_attributes: &HashMap<String, TagStructureFieldAttributes>,
+) -> Vec<&'a syn::Field> {
+    let mut fields: Vec<&syn::Field> = data
+        .fields
+        .iter()
+        .filter(|field| field_attributes.contains_key(&field.ident.as_ref().unwrap().to_string()))
+        .collect();
+    fields.sort_by_key(|field| field_attributes[&field.ident.as_ref().unwrap().to_string()].offset);
+    fields
+}
+
+/// Value of `#[data(length())]` as an unsuffixed literal, so it can be used as any integer type.
+fn byte_length(attributes: &TagStructureFieldAttributes) -> proc_macro2::Literal {
+    proc_macro2::Literal::u64_unsuffixed(attributes.length.unwrap_or_default())
+}
+
+fn generate_field_reads(
+    fields: &[&syn::Field],
+    field_attributes: &HashMap<String, TagStructureFieldAttributes>,
+) -> Vec<proc_macro2::TokenStream> {
+    let version = quote! { version };
+    fields
+        .iter()
+        .map(|field| {
+            let attributes = field_attributes
+                .get(&field.ident.as_ref().unwrap().to_string())
+                .unwrap();
+            let check = generate_bounds_check(field, attributes);
+            let read = generate_field_read(field, attributes);
+            version_gated(attributes, &version, quote! { #check #read })
+        })
+        .collect()
+}
+
+/// Generates a check that the field lies within the data being read, so that a field past the
+/// end of its data block fails with `TagError::FieldOutOfBounds` instead of an IO error.
+/// Optional fields are left empty instead, and are not checked.
+///
+/// `limit` is the length of the reader, which is bounded to the data block being read, so nested
+/// structures (inline fields and array elements) are checked against the same data block.
+fn generate_bounds_check(
+    field: &syn::Field,
+    attributes: &TagStructureFieldAttributes,
+) -> proc_macro2::TokenStream {
+    if attributes.optional {
+        return quote! {};
+    }
+    let name = field.ident.as_ref().unwrap().to_string();
+    let offset = attributes.offset;
+    let size = field_size(attributes).unwrap_or_else(|| quote! { 0 });
+    quote! {
+        if main_offset + #offset + #size > limit {
+            return Err(infinite_rs::common::errors::TagError::FieldOutOfBounds {
+                field: #name,
+                offset: main_offset + #offset,
+                limit,
+            }
+            .into());
+        }
+    }
+}
+
+fn generate_field_read(
+    field: &syn::Field,
+    attributes: &TagStructureFieldAttributes,
+) -> proc_macro2::TokenStream {
+    let field_name = &field.ident;
+    let offset = attributes.offset;
+    if attributes.inline {
+        return quote! {
+            reader.seek(std::io::SeekFrom::Start(main_offset + #offset))?;
+            infinite_rs::module::file::TagStructure::read(&mut self.#field_name, reader, version)?;
+        };
+    }
+    if attributes.optional {
+        let inner = optional_inner(&field.ty);
+        let end = offset + attributes.width.unwrap_or_default();
+        return quote! {
+            if main_offset + #end <= limit {
                 reader.seek(std::io::SeekFrom::Start(main_offset + #offset))?;
-                self.#field_name.read(reader)?;
+                let mut value: #inner = Default::default();
+                value.read(reader)?;
+                self.#field_name = Some(value);
+            } else {
+                self.#field_name = None;
+            }
+        };
+    }
+    if let Some(wrapper) = &attributes.wrapper {
+        let ty = &field.ty;
+        return quote! {
+            reader.seek(std::io::SeekFrom::Start(main_offset + #offset))?;
+            let mut value = infinite_rs::tag::types::common_types::#wrapper::<#ty>(std::mem::take(&mut self.#field_name));
+            value.read(reader)?;
+            self.#field_name = value.0;
+        };
+    }
+    if attributes.elements.is_some() {
+        return quote! {
+            reader.seek(std::io::SeekFrom::Start(main_offset + #offset))?;
+            for element in &mut self.#field_name {
+                element.read(reader)?;
+            }
+        };
+    }
+    if let Some(raw_bytes) = attributes.raw_bytes {
+        let read = match raw_bytes {
+            RawBytes::Array => quote! { reader.read_exact(&mut self.#field_name)?; },
+            RawBytes::Vec => {
+                let length = byte_length(attributes);
+                quote! {
+                    self.#field_name = vec![0; #length];
+                    reader.read_exact(&mut self.#field_name)?;
+                }
+            }
+        };
+        return quote! {
+            reader.seek(std::io::SeekFrom::Start(main_offset + #offset))?;
+            #read
+        };
+    }
+    if let syn::Type::Path(type_path) = &field.ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            if segment.ident == "FieldArray" {
+                // Checked in `check_field_attributes`.
+                let count = match (&attributes.count_from, attributes.count) {
+                    (Some(count_from), capacity) => {
+                        let sibling = quote::format_ident!("{}", count_from.value());
+                        let capacity = capacity.map(|capacity| quote! { .min(#capacity) });
+                        quote! { u64::try_from(self.#sibling.0).unwrap_or_default()#capacity }
+                    }
+                    (None, count) => {
+                        let count = count.unwrap_or_default();
+                        quote! { #count }
+                    }
+                };
+                return quote! {
+                    reader.seek(std::io::SeekFrom::Start(main_offset + #offset))?;
+                    self.#field_name.read(reader, #count, version)?;
+                };
+            }
+            if segment.ident == "FieldFixedArray" {
+                return quote! {
+                    reader.seek(std::io::SeekFrom::Start(main_offset + #offset))?;
+                    self.#field_name.read(reader, version)?;
+                };
+            }
+            if segment.ident == "FieldPad" {
+                let length = byte_length(attributes);
+                return quote! {
+                    reader.seek(std::io::SeekFrom::Start(main_offset + #offset))?;
+                    self.#field_name.read(reader, #length)?;
+                };
             }
+        }
+    }
+    quote! {
+        reader.seek(std::io::SeekFrom::Start(main_offset + #offset))?;
+        self.#field_name.read(reader)?;
+    }
+}
+
+fn generate_field_writes(
+    fields: &[&syn::Field],
+    field_attributes: &HashMap<String, TagStructureFieldAttributes>,
+) -> Vec<proc_macro2::TokenStream> {
+    let version = quote! { version };
+    fields
+        .iter()
+        .map(|field| {
+            let attributes = field_attributes
+                .get(&field.ident.as_ref().unwrap().to_string())
+                .unwrap();
+            version_gated(
+                attributes,
+                &version,
+                generate_field_write(field, attributes),
+            )
         })
         .collect()
 }
 
+/// Generates `write`, writing each field back at its offset and seeking to the end of the
+/// structure.
+fn generate_write(
+    fields: &[&syn::Field],
+    field_attributes: &HashMap<String, TagStructureFieldAttributes>,
+    size: u64,
+) -> proc_macro2::TokenStream {
+    let field_writes = generate_field_writes(fields, field_attributes);
+    quote! {
+        fn write<W: infinite_rs::common::extensions::WriterExt>(
+            &self,
+            writer: &mut W,
+            version: &infinite_rs::module::header::ModuleVersion,
+        ) -> infinite_rs::Result<()> {
+            let main_offset = writer.stream_position()?;
+            #(#field_writes)*
+            writer.seek(std::io::SeekFrom::Start(main_offset + #size))?;
+            Ok(())
+        }
+    }
+}
+
+fn generate_field_write(
+    field: &syn::Field,
+    attributes: &TagStructureFieldAttributes,
+) -> proc_macro2::TokenStream {
+    let field_name = &field.ident;
+    let offset = attributes.offset;
+    if attributes.optional {
+        return quote! {
+            if let Some(value) = &self.#field_name {
+                writer.seek(std::io::SeekFrom::Start(main_offset + #offset))?;
+                value.write(writer)?;
+            }
+        };
+    }
+    let write = if attributes.inline {
+        quote! { infinite_rs::module::file::TagStructure::write(&self.#field_name, writer, version)?; }
+    } else if let Some(wrapper) = &attributes.wrapper {
+        let ty = &field.ty;
+        quote! {
+            infinite_rs::tag::types::common_types::#wrapper::<#ty>(self.#field_name.clone()).write(writer)?;
+        }
+    } else if attributes.elements.is_some() {
+        quote! {
+            for element in &self.#field_name {
+                element.write(writer)?;
+            }
+        }
+    } else if let Some(raw_bytes) = attributes.raw_bytes {
+        match raw_bytes {
+            RawBytes::Array => quote! { writer.write_all(&self.#field_name)?; },
+            RawBytes::Vec => {
+                let length = byte_length(attributes);
+                quote! {
+                    let mut bytes = self.#field_name.clone();
+                    bytes.resize(#length, 0);
+                    writer.write_all(&bytes)?;
+                }
+            }
+        }
+    } else {
+        let type_name = match &field.ty {
+            syn::Type::Path(type_path) => type_path
+                .path
+                .segments
+                .last()
+                .map(|segment| segment.ident.to_string()),
+            _ => None,
+        };
+        match type_name.as_deref() {
+            Some("FieldArray" | "FieldFixedArray") => {
+                quote! { self.#field_name.write(writer, version)?; }
+            }
+            Some("FieldPad") => {
+                let length = byte_length(attributes);
+                quote! { self.#field_name.write(writer, #length)?; }
+            }
+            _ => quote! { self.#field_name.write(writer)?; },
+        }
+    };
+    quote! {
+        writer.seek(std::io::SeekFrom::Start(main_offset + #offset))?;
+        #write
+    }
+}
+
 fn generate_field_blocks(
-    data: &DataStruct,
+    fields: &[&syn::Field],
     field_attributes: &HashMap<String, TagStructureFieldAttributes>,
 ) -> Vec<proc_macro2::TokenStream> {
-    data.fields.iter().filter_map(|field| {
-        if let syn::Type::Path(type_path) = &field.ty {
-            if let Some(segment) = type_path.path.segments.last() {
-                let field_name = &field.ident;
-                match segment.ident.to_string().as_str() {
-                    "FieldBlock" => {
-                        let offset = field_attributes.get(&field_name.as_ref().unwrap().to_string()).unwrap().offset;
-                        Some(quote! {
-                            self.#field_name.load_blocks(source_index, adjusted_base + #offset, reader, tag_file)?;
-                        })
-                    },
-                    "FieldTagResource" => {
-                        let offset = field_attributes.get(&field_name.as_ref().unwrap().to_string()).unwrap().offset;
-                        Some(quote! {
-                            self.#field_name.load_resource(adjusted_base + #offset, reader, tag_file)?;
-                        })
-                    },
-                    "FieldArray" => {
-                        let offset = field_attributes.get(&field_name.as_ref().unwrap().to_string()).unwrap().offset;
-                        Some(quote! {
-                            self.#field_name.load_blocks(reader, source_index, adjusted_base + #offset, tag_file)?;
-                        })
-                    },
-                    "FieldData" => {
-                        Some(quote! {
-                            self.#field_name.load_data(reader, source_index, parent_index, tag_file)?;
-                        })
-                    },
-                    _ => None
-                }
-            } else {
-                None
+    let version = quote! { &tag_file.version };
+    fields
+        .iter()
+        .filter_map(|field| {
+            let attributes = field_attributes
+                .get(&field.ident.as_ref().unwrap().to_string())
+                .unwrap();
+            generate_field_block(field, attributes)
+                .map(|body| version_gated(attributes, &version, body))
+        })
+        .collect()
+}
+
+fn generate_field_block(
+    field: &syn::Field,
+    attributes: &TagStructureFieldAttributes,
+) -> Option<proc_macro2::TokenStream> {
+    let field_name = &field.ident;
+    let offset = attributes.offset;
+    if attributes.inline {
+        return Some(quote! {
+            infinite_rs::module::file::TagStructure::load_field_blocks(&mut self.#field_name, source_index, parent_index, adjusted_base + #offset, reader, tag_file)?;
+        });
+    }
+    let syn::Type::Path(type_path) = &field.ty else {
+        return None;
+    };
+    match type_path.path.segments.last()?.ident.to_string().as_str() {
+        "FieldBlock" | "LazyFieldBlock" => Some(quote! {
+            self.#field_name.load_blocks(source_index, adjusted_base + #offset, reader, tag_file)?;
+        }),
+        "FieldTagResource" => Some(quote! {
+            self.#field_name.load_resource(source_index, adjusted_base + #offset, reader, tag_file)?;
+        }),
+        "FieldArray" | "FieldFixedArray" => Some(quote! {
+            self.#field_name.load_blocks(reader, source_index, adjusted_base + #offset, tag_file)?;
+        }),
+        "FieldData" | "LazyFieldData" => Some(quote! {
+            self.#field_name.load_data(reader, source_index, parent_index, tag_file)?;
+        }),
+        _ => None,
+    }
+}
+
+/// Generates a test checking the size and offsets of the structure, and that its fields fit
+/// within its size.
+fn generate_layout_test(
+    ast: &DeriveInput,
+    size: u64,
+    fields: &[&syn::Field],
+    field_attributes: &HashMap<String, TagStructureFieldAttributes>,
+) -> deluxe::Result<proc_macro2::TokenStream> {
+    let ident = &ast.ident;
+    if !ast.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &ast.generics,
+            "#[data(assert_layout)] is not supported on generic structs",
+        ));
+    }
+    let test_name = quote::format_ident!("__tag_structure_layout_{}", ident);
+    let field_count = fields.len();
+    let mut names = Vec::new();
+    let mut offsets = Vec::new();
+    let mut ends = Vec::new();
+    for field in fields {
+        let field_ident = field.ident.as_ref().unwrap();
+        let name = field_ident.to_string();
+        let attributes = &field_attributes[&name];
+        let offset = attributes.offset;
+        if let Some(size) = field_size(attributes) {
+            ends.push(quote! { (#name, #offset + #size) });
+        } else if attributes.inline {
+            ends.push(quote! {
+                (#name, #offset + infinite_rs::module::file::TagStructure::size(&mut structure.#field_ident))
+            });
+        }
+        names.push(name);
+        offsets.push(offset);
+    }
+    Ok(quote! {
+        #[cfg(test)]
+        #[test]
+        #[allow(non_snake_case)]
+        fn #test_name() {
+            use infinite_rs::module::file::TagStructure;
+
+            let mut structure = #ident::default();
+            let size = structure.size();
+            assert_eq!(size, #size, "size of `{}`", stringify!(#ident));
+            assert_eq!(structure.fields().len(), #field_count, "field count of `{}`", stringify!(#ident));
+            #(
+                assert_eq!(structure.offset_of(#names), Some(#offsets), "offset of `{}::{}`", stringify!(#ident), #names);
+            )*
+            let ends: Vec<(&str, u64)> = vec![#(#ends),*];
+            for (name, end) in ends {
+                assert!(
+                    end <= size,
+                    "`{}::{}` ends at {:#X}, past the size of the structure ({:#X})",
+                    stringify!(#ident),
+                    name,
+                    end,
+                    size,
+                );
             }
-        } else {
-            None
         }
-    }).collect()
+    })
+}
+
+/// Parses the hexadecimal GUID given with `#[data(guid())]`.
+fn parse_guid(guid: &syn::LitStr) -> deluxe::Result<u128> {
+    let value = guid.value().replace('_', "");
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(&value);
+    u128::from_str_radix(digits, 16).map_err(|_| {
+        syn::Error::new_spanned(guid, "expected a hexadecimal GUID, such as \"0x1234ABCD\"")
+    })
 }
+
+/// Whether a token stream mentions an identifier, such as a type parameter used in a field type.
+fn mentions_ident(tokens: proc_macro2::TokenStream, ident: &syn::Ident) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(token) => token == *ident,
+        proc_macro2::TokenTree::Group(group) => mentions_ident(group.stream(), ident),
+        _ => false,
+    })
+}
+
+/// Adds the bounds needed by the generated code to the type parameters used by stored fields.
+///
+/// Type parameters of enum and bitflags fields keep the bounds of the struct, as they are not
+/// tag structures.
+fn add_trait_bounds(
+    generics: &mut syn::Generics,
+    fields: &[&syn::Field],
+    field_attributes: &HashMap<String, TagStructureFieldAttributes>,
+) {
+    let field_types: Vec<proc_macro2::TokenStream> = fields
+        .iter()
+        .filter(|field| {
+            field_attributes[&field.ident.as_ref().unwrap().to_string()]
+                .wrapper
+                .is_none()
+        })
+        .map(|field| quote::ToTokens::to_token_stream(&field.ty))
+        .collect();
+    let bounded: Vec<syn::Ident> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .filter(|ident| {
+            field_types
+                .iter()
+                .any(|ty| mentions_ident(ty.clone(), ident))
+        })
+        .collect();
+    let where_clause = generics.make_where_clause();
+    for ident in bounded {
+        where_clause.predicates.push(syn::parse_quote! {
+            #ident: infinite_rs::module::file::TagStructure + Default + std::fmt::Debug
+        });
+    }
+}
+
 fn tag_structure_derive2(
     input: proc_macro2::TokenStream,
 ) -> deluxe::Result<proc_macro2::TokenStream> {
     let mut ast: DeriveInput = syn::parse2(input)?;
-    let TagStructureAttributes { size } = deluxe::extract_attributes(&mut ast)?;
+    if !matches!(ast.data, syn::Data::Struct(_)) {
+        return Err(syn::Error::new_spanned(
+            &ast.ident,
+            "TagStructure can only be derived for structs",
+        ));
+    }
+    let TagStructureAttributes {
+        size,
+        auto_offsets,
+        assert_layout,
+        write,
+        guid,
+    } = deluxe::extract_attributes(&mut ast)?;
+    let guid = guid.as_ref().map(parse_guid).transpose()?.map(|guid| {
+        quote! {
+            fn guid(&self) -> Option<u128> {
+                Some(#guid)
+            }
+        }
+    });
     let field_attributes: HashMap<String, TagStructureFieldAttributes> =
-        extract_struct_field_attributes(&mut ast)?;
+        extract_struct_field_attributes(&mut ast, auto_offsets.is_set())?;
+    check_count_sources(&field_attributes)?;
     let ident: &syn::Ident = &ast.ident;
-    let (impl_generics, type_generics, where_clause) = ast.generics.split_for_impl();
 
     let syn::Data::Struct(data) = &ast.data else {
-        panic!("TagStructure can only be derived for structs")
+        unreachable!("checked above")
     };
-    let (name, field_offset) = extract_field_maps(&field_attributes);
+    let fields = sorted_fields(data, &field_attributes);
+    let mut generics = ast.generics.clone();
+    add_trait_bounds(&mut generics, &fields, &field_attributes);
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    let (name, field_offset) = extract_field_maps(&fields, &field_attributes);
 
-    let field_reads = generate_field_reads(data, &field_attributes);
-    let field_blocks = generate_field_blocks(data, &field_attributes);
+    let field_reads = generate_field_reads(&fields, &field_attributes);
+    // Read once per structure, and without seeking for readers bounded to a data block.
+    let limit = if fields.is_empty() {
+        quote! {}
+    } else {
+        quote! { let limit = infinite_rs::common::extensions::BufReaderExt::stream_length(reader)?; }
+    };
+    let write = if write.is_set() {
+        generate_write(&fields, &field_attributes, size)
+    } else {
+        quote! {}
+    };
+    let field_blocks = generate_field_blocks(&fields, &field_attributes);
+    let field_descriptors = generate_field_descriptors(&fields, &field_attributes);
+    let layout_test = if assert_layout.is_set() {
+        generate_layout_test(&ast, size, &fields, &field_attributes)?
+    } else {
+        quote! {}
+    };
 
     Ok(quote! {
         impl #impl_generics infinite_rs::module::file::TagStructure for #ident #type_generics #where_clause {
             fn size(&mut self) -> u64 {
                 #size
             }
-            fn read<R: infinite_rs::common::extensions::BufReaderExt>(&mut self, reader: &mut R) -> infinite_rs::Result<()> {
+            fn read<R: infinite_rs::common::extensions::BufReaderExt>(
+                &mut self,
+                reader: &mut R,
+                version: &infinite_rs::module::header::ModuleVersion,
+            ) -> infinite_rs::Result<()> {
                 let main_offset = reader.stream_position()?;
+                #limit
                 #(#field_reads)*
                 reader.seek(std::io::SeekFrom::Start(main_offset + self.size()))?;
                 Ok(())
             }
 
-            fn offsets(&self) -> std::collections::HashMap<&'static str, u64> {
-                let field_names = [#(#name),*];
-                let field_offsets = [#(#field_offset),*];
+            #write
 
-                let map: std::collections::HashMap<&'static str, u64> = field_names.iter().zip(field_offsets.iter()).map(|(&name, &offset)| (name, offset)).collect();
-                map
+            fn fields(&self) -> &'static [infinite_rs::module::file::FieldDescriptor] {
+                &[#(#field_descriptors),*]
             }
 
+            fn offset_of(&self, name: &str) -> Option<u64> {
+                match name {
+                    #(#name => Some(#field_offset),)*
+                    _ => None,
+                }
+            }
+
+            #guid
+
             fn load_field_blocks<R: std::io::BufRead + std::io::Seek + infinite_rs::common::extensions::BufReaderExt>(
                 &mut self,
                 source_index: i32,
@@ -173,11 +1224,59 @@ fn tag_structure_derive2(
                 Ok(())
             }
         }
+
+        #layout_test
     })
 }
 
 #[proc_macro_derive(TagStructure, attributes(data))]
 /// For implementing Tag Structures as described in documentation.
+///
+/// # Struct attributes
+/// - `#[data(size())]` - The size of the tag structure in bytes. Required.
+/// - `#[data(auto_offsets)]` - Makes `offset` optional: a field without one is placed directly
+///   after the previous field, using the size of its type. Explicit offsets can still be used as
+///   anchors, and are required after fields whose size is not known from their type alone (inline
+///   structures, `FieldArray` and `FieldFixedArray`).
+/// - `#[data(guid("0x..."))]` - The GUID of the tag struct described by the structure.
+///   `read_metadata` fails with `TagError::GuidMismatch` if the main struct of the tag has another
+///   GUID.
+/// - `#[data(write)]` - Generates `write`, writing each field back at
+///   its offset. Enum fields then need to implement `IntoPrimitive` and `Clone`. Without it,
+///   `write` returns `TagError::WriteUnsupported`.
+/// - `#[data(assert_layout)]` - Generates a test checking `size` and `fields`, and that every field
+///   whose size is known ends within the size of the structure. Not supported on generic structs.
+///
+/// # Field attributes
+/// - `#[data(offset())]` - The offset in bytes from the start of the tag structure.
+/// - `#[data(count())]` - The number of elements of a `FieldArray`.
+/// - `#[data(count_from())]` - An integer field stored before a `FieldArray` that holds its number
+///   of elements. If `count` is also present, it is the capacity of the array.
+/// - `#[data(length())]` - The length in bytes of a `FieldPad`, or of a `Vec<u8>` of raw bytes.
+/// - `#[data(inline)]` - Reads another `TagStructure` stored at the offset, instead of through a
+///   `FieldBlock`. Its fields and blocks are read relative to the offset. Fields of type `Box<T>`
+///   are always read inline.
+/// - `#[data(enum())]` and `#[data(flags())]` - Reads an enum or bitflags directly, through the
+///   matching enum or flags wrapper, such as `FieldLongEnum`, of the given primitive (`u8`, `u16`
+///   or `u32`). The type needs to implement `Clone` to be written.
+/// - `#[data(since())]` and `#[data(until())]` - The first `ModuleVersion` the field is present
+///   in, and the first one it is no longer present in. Fields that are not present in the version
+///   of the module are not read and keep their default value.
+/// - `#[data(skip)]` - The field is not stored in the tag. It is not read, written or described,
+///   and keeps its default value.
+///
+/// # Field types
+/// - `[u8; N]` and `Vec<u8>` fields keep regions of unknown purpose as raw bytes, written back
+///   unchanged.
+/// - `[T; N]` fields of other field types of known size, such as `[FieldShortInteger; 4]`, are
+///   read element by element. `N` can be a constant, in which case the offset of the next field
+///   has to be given.
+/// - `Option<T>` fields are only read if they end within the data the structure is read from, and
+///   are `None` otherwise. Fields that are `None` are not written.
+/// - Type parameters of generic structs used by fields are bound by `TagStructure`, `Default` and
+///   `Debug` in the generated implementation.
 pub fn tag_structure_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    tag_structure_derive2(input.into()).unwrap().into()
+    tag_structure_derive2(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
@@ -1,9 +1,43 @@
 //! Originally from: <https://github.com/rfuzzo/red4lib>
 
+#[cfg(feature = "kraken")]
 use cmake::Config;
+#[cfg(feature = "kraken")]
 use std::path::Path;
 
 fn main() {
+    println!("cargo:rustc-check-cfg=cfg(kraken_backend, values(none(), \"native\", \"rust\"))");
+    let backend = kraken_backend();
+    if let Some(backend) = backend {
+        // `kraken_backend` alone is set whenever any decompressor is built.
+        println!("cargo:rustc-cfg=kraken_backend");
+        println!("cargo:rustc-cfg=kraken_backend=\"{backend}\"");
+    }
+
+    // Without the C++ backend, nothing needs to be built and the crate builds with `cargo` alone.
+    #[cfg(feature = "kraken")]
+    if backend == Some("native") {
+        build_kraken();
+    }
+}
+
+/// Selects the Kraken decompressor: the C++ one (`kraken`) is preferred over the pure-Rust one
+/// (`kraken-rs`). `no-kraken` disables both, even when another crate enables them.
+fn kraken_backend() -> Option<&'static str> {
+    if cfg!(feature = "no-kraken") {
+        None
+    } else if cfg!(feature = "kraken") {
+        Some("native")
+    } else if cfg!(feature = "kraken-rs") {
+        Some("rust")
+    } else {
+        None
+    }
+}
+
+/// Builds and links the C++ Kraken decompressor.
+#[cfg(feature = "kraken")]
+fn build_kraken() {
     let kraken_path = Path::new("ext").join("kraken");
     let mut cfg = Config::new(kraken_path);
 
@@ -0,0 +1,230 @@
+//! Command line interface over `infinite-rs`, to inspect and extract modules without writing Rust.
+//!
+//! Requires the `cli` feature:
+//!
+//! ```text
+//! cargo install infinite-rs --features cli
+//! infinite-cli list deploy/any/globals-rtx-new.module --group bitm
+//! infinite-cli info deploy/any/globals-rtx-new.module
+//! infinite-cli extract deploy/any/globals-rtx-new.module ./out
+//! infinite-cli dump-json deploy/any/globals-rtx-new.module 0x1234ABCD
+//! ```
+
+use std::{
+    fs::{create_dir_all, write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::Arc,
+};
+
+use argh::FromArgs;
+use infinite_rs::{
+    ModuleFile, Result, TagId,
+    common::{
+        errors::TagError, hash::compute_tag_id, options::ParseOptions, tag_names::TagNameDatabase,
+    },
+    module::cache::TagCache,
+};
+
+#[derive(FromArgs, Debug)]
+/// Inspect and extract Halo Infinite modules.
+struct Cli {
+    /// tag list used to name tags of modules without a string table
+    #[argh(option)]
+    tag_names: Option<PathBuf>,
+    /// directory caching decompressed tags between runs
+    #[argh(option)]
+    cache: Option<PathBuf>,
+    /// record recoverable issues as warnings instead of failing
+    #[argh(switch)]
+    lenient: bool,
+    #[argh(subcommand)]
+    command: Command,
+}
+
+#[derive(FromArgs, Debug)]
+#[argh(subcommand)]
+enum Command {
+    List(ListCommand),
+    Info(InfoCommand),
+    Extract(ExtractCommand),
+    DumpJson(DumpJsonCommand),
+}
+
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "list")]
+/// List the files of a module.
+struct ListCommand {
+    #[argh(positional)]
+    /// path of the module
+    module: PathBuf,
+    /// only list tags of this group, such as `bitm`
+    #[argh(option)]
+    group: Option<String>,
+}
+
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "info")]
+/// Show the header of a module.
+struct InfoCommand {
+    #[argh(positional)]
+    /// path of the module
+    module: PathBuf,
+}
+
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "extract")]
+/// Extract the raw data of the tags of a module.
+struct ExtractCommand {
+    #[argh(positional)]
+    /// path of the module
+    module: PathBuf,
+    #[argh(positional)]
+    /// directory to write the tags to
+    output: PathBuf,
+    /// only extract tags of this group, such as `bitm`
+    #[argh(option)]
+    group: Option<String>,
+}
+
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "dump-json")]
+/// Export a tag as JSON.
+struct DumpJsonCommand {
+    #[argh(positional)]
+    /// path of the module
+    module: PathBuf,
+    #[argh(positional)]
+    /// index, global id (such as `0x1234ABCD`) or name of the tag
+    tag: String,
+    /// file to write the JSON to, instead of standard output
+    #[argh(option)]
+    output: Option<PathBuf>,
+}
+
+/// Opens a module with the options given on the command line.
+fn open_module(cli: &Cli, path: &Path) -> Result<ModuleFile> {
+    let tag_names = match &cli.tag_names {
+        Some(path) => Some(Arc::new(TagNameDatabase::from_path(path)?)),
+        None => None,
+    };
+    let tag_cache = match &cli.cache {
+        Some(path) => Some(Arc::new(TagCache::new(path)?)),
+        None => None,
+    };
+    let options = ParseOptions {
+        strict: !cli.lenient,
+        tag_names,
+        tag_cache,
+        ..Default::default()
+    };
+    ModuleFile::from_path_with_options(path, options)
+}
+
+/// Gets the indices of the files of a module, optionally only of a tag group.
+fn file_indices(module: &ModuleFile, group: Option<&str>) -> Vec<u32> {
+    (0..module.files.len())
+        .filter(|&index| group.is_none_or(|group| module.files[index].tag_group == group))
+        .filter_map(|index| u32::try_from(index).ok())
+        .collect()
+}
+
+/// Finds a tag by its index, global id or name.
+fn find_tag(module: &ModuleFile, tag: &str) -> Option<u32> {
+    if let Ok(index) = tag.parse::<u32>() {
+        return ((index as usize) < module.files.len()).then_some(index);
+    }
+    let tag_id = if tag.starts_with("0x") {
+        tag.parse::<TagId>().ok()
+    } else {
+        None
+    };
+    let index = module.files.iter().position(|file| {
+        Some(file.tag_id) == tag_id || file.tag_name == tag || file.tag_id == compute_tag_id(tag)
+    })?;
+    u32::try_from(index).ok()
+}
+
+/// Converts a tag name into a relative path that is valid on every platform.
+fn sanitize_path(tag_name: &str) -> PathBuf {
+    tag_name
+        .replace('\\', "/")
+        .split('/')
+        .map(|component| component.replace(['*', ':', '?', '"', '<', '>', '|', ' '], "_"))
+        .filter(|component| !component.is_empty() && component != "..")
+        .collect()
+}
+
+fn list(cli: &Cli, command: &ListCommand) -> Result<()> {
+    let module = open_module(cli, &command.module)?;
+    for index in file_indices(&module, command.group.as_deref()) {
+        let file = &module.files[index as usize];
+        println!(
+            "{index:>6}  {:<4}  {}  {}",
+            file.tag_group, file.tag_id, file.tag_name
+        );
+    }
+    Ok(())
+}
+
+fn info(cli: &Cli, command: &InfoCommand) -> Result<()> {
+    let module = open_module(cli, &command.module)?;
+    println!("version:    {:?}", module.header.version);
+    println!("module id:  {:#018X}", module.header.module_id);
+    println!("files:      {}", module.files.len());
+    println!("resources:  {}", module.resource_indices.len());
+    println!("uses HD1:   {}", module.use_hd1);
+    for warning in &module.warnings {
+        println!("warning:    {warning}");
+    }
+    Ok(())
+}
+
+fn extract(cli: &Cli, command: &ExtractCommand) -> Result<()> {
+    let mut module = open_module(cli, &command.module)?;
+    let mut count = 0;
+    for index in file_indices(&module, command.group.as_deref()) {
+        let Some(file) = module.read_tag(index)? else {
+            eprintln!("skipping {index}: stored in a missing HD1 module");
+            continue;
+        };
+        let path = command.output.join(sanitize_path(&file.tag_name));
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        write(path, file.get_raw_data(true)?)?;
+        count += 1;
+    }
+    println!("extracted {count} files to {}", command.output.display());
+    Ok(())
+}
+
+fn dump_json(cli: &Cli, command: &DumpJsonCommand) -> Result<()> {
+    let mut module = open_module(cli, &command.module)?;
+    let index =
+        find_tag(&module, &command.tag).ok_or(TagError::InvalidTagId(command.tag.clone()))?;
+    let file = module.read_tag(index)?.ok_or(TagError::NotLoaded)?;
+    let json = serde_json::to_string_pretty(&file.to_json()?)?;
+    match &command.output {
+        Some(path) => write(path, json)?,
+        None => println!("{json}"),
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let cli: Cli = argh::from_env();
+    let result = match &cli.command {
+        Command::List(command) => list(&cli, command),
+        Command::Info(command) => info(&cli, command),
+        Command::Extract(command) => extract(&cli, command),
+        Command::DumpJson(command) => dump_json(&cli, command),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
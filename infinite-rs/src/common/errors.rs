@@ -1,17 +1,21 @@
 //! Common errors used throughout `infinite-rs`.
 
 use num_enum::TryFromPrimitiveError;
+use std::fmt::{self, Display};
 use std::io::Error as StdIoError;
 use std::num::TryFromIntError;
+use std::path::PathBuf;
 use std::result::Result as StdResult;
-use std::string::FromUtf8Error;
+use std::string::{FromUtf8Error, FromUtf16Error};
 use thiserror::Error;
 
 use crate::{
+    TagId,
     module::header::ModuleVersion,
     tag::{
         datablock::TagSectionType,
         structure::{TagStructLocation, TagStructType},
+        types::{bitmap::BitmapFormat, pixel_format::DxgiFormat},
     },
 };
 
@@ -73,10 +77,118 @@ pub enum TagError {
     /// This error should not occur as [`TagStructLocation`] enum is exhaustive.
     #[error("Invalid TagStruct location encountered!")]
     InvalidTagStructLocation(#[from] TryFromPrimitiveError<TagStructLocation>),
+    /// The GUID of the main struct does not match the GUID the structure was registered or declared with.
+    /// This usually means that the structure is being read from the wrong tag group, or is meant for another build.
+    #[error("Struct GUID mismatch! Expected {expected:#034X}, found {found:#034X}!")]
+    GuidMismatch {
+        /// GUID the structure was registered or declared with.
+        expected: u128,
+        /// GUID of the main struct found in the tag.
+        found: u128,
+    },
+    /// No structure has been registered in the [`StructRegistry`](`crate::tag::registry::StructRegistry`) for the GUID.
+    #[error("No structure registered for GUID {0:#034X}!")]
+    UnregisteredStruct(u128),
+    /// The structure does not implement [`write`](`crate::module::file::TagStructure::write`),
+    /// as it was derived without `#[data(write)]`.
+    #[error("Structure {0} does not support writing!")]
+    WriteUnsupported(&'static str),
+    /// No structure has been registered in the [`StructRegistry`](`crate::tag::registry::StructRegistry`) for the tag group.
+    #[error("No structure registered for tag group {0:?}!")]
+    UnregisteredGroup(String),
+    /// The tag buffer is smaller than the sections described by its header.
+    #[error("Tag is truncated! Expected at least {expected:#X} bytes, found {found:#X}!")]
+    Truncated {
+        /// Size of the tag according to its header.
+        expected: u64,
+        /// Actual size of the tag buffer.
+        found: u64,
+    },
+    /// A data block points outside of the tag buffer.
+    #[error("Data block {0} is out of bounds!")]
+    DatablockOutOfBounds(usize),
+    /// A field of a [`TagStructure`](`crate::module::file::TagStructure`) lies past the end of
+    /// the data it is read from, usually its data block.
+    #[error("Field `{field}` at {offset:#X} is past the end of its data ({limit:#X})!")]
+    FieldOutOfBounds {
+        /// Name of the field in the structure.
+        field: &'static str,
+        /// Offset of the field from the start of tag data.
+        offset: u64,
+        /// End of the data the field is read from.
+        limit: u64,
+    },
+    /// String could not be parsed as a hexadecimal tag id.
+    #[error("Invalid tag id '{0}'!")]
+    InvalidTagId(String),
+    /// A tag block has more elements than fit in its data block.
+    #[error(
+        "Tag block in data block {datablock} needs {required:#X} bytes, but only {available:#X} are available!"
+    )]
+    BlockOutOfBounds {
+        /// Index of the data block containing the elements.
+        datablock: usize,
+        /// Size in bytes of the elements, based on the element count stored in the field.
+        required: u64,
+        /// Size in bytes of the data block.
+        available: u32,
+    },
+    /// No block or data field with a target was found at the given location.
+    #[error("No field found in data block {field_block} at offset {field_offset:#X}!")]
+    FieldNotFound {
+        /// Index of the data block containing the field.
+        field_block: i32,
+        /// Offset of the field inside the data block.
+        field_offset: u32,
+    },
+    /// Attempted to write outside of the bounds of a data block.
+    #[error(
+        "Writing {length:#X} bytes at offset {offset:#X} is out of bounds of data block {datablock}!"
+    )]
+    WriteOutOfBounds {
+        /// Index of the data block written to.
+        datablock: usize,
+        /// Offset of the write inside the data block.
+        offset: usize,
+        /// Number of bytes written.
+        length: usize,
+    },
+    /// The pixel format of a bitmap has no DDS equivalent.
+    #[error("Bitmap format {0:?} is not supported!")]
+    UnsupportedBitmapFormat(BitmapFormat),
+    /// A vertex or index buffer lies outside of the data it is read from.
+    #[error(
+        "Buffer of {size:#X} bytes at offset {offset:#X} is out of bounds of {available:#X} bytes!"
+    )]
+    BufferOutOfBounds {
+        /// Offset of the buffer in the data.
+        offset: u64,
+        /// Size in bytes of the buffer.
+        size: u64,
+        /// Size in bytes of the data.
+        available: u64,
+    },
+    /// Vertices in this format cannot be decoded.
+    #[error("Vertex format {0} is not supported!")]
+    UnsupportedVertexFormat(u8),
+    /// Data is not a valid Wwise soundbank.
+    #[error("Invalid soundbank: {0}!")]
+    InvalidSoundbank(&'static str),
+    /// Pixels in this format cannot be converted to RGBA8.
+    #[error("Pixel format {0:?} cannot be converted to RGBA8!")]
+    UnsupportedPixelFormat(DxgiFormat),
+    /// The pixel data of a bitmap is smaller than its mipmaps require.
+    #[error("Bitmap data is too small! Expected at least {expected:#X} bytes, found {found:#X}!")]
+    BitmapDataTooSmall {
+        /// Size in bytes required by the mipmaps.
+        expected: u64,
+        /// Size in bytes of the available data.
+        found: u64,
+    },
 }
 
 #[derive(Error, Debug)]
-/// Errors that can occur when decompressing data.
+/// Errors that can occur when compressing or decompressing data.
 pub enum DecompressionError {
     /// Buffer size is insufficient for decompressed data.
     /// This should not occur in Infinite module decompression.
@@ -86,6 +198,31 @@ pub enum DecompressionError {
     /// Negative error codes indicate decompression failure.
     #[error("Decompression failed with error code {0}")]
     DecompressionFailed(i32),
+    /// The decompressed data of a block does not have the size stored in the module.
+    #[error("Decompressed {found} bytes, expected {expected}")]
+    SizeMismatch {
+        /// Size of the block stored in the module.
+        expected: usize,
+        /// Size of the decompressed data.
+        found: usize,
+    },
+    /// Compression failed with Kraken compressor error code.
+    #[error("Compression failed with error code {0}")]
+    CompressionFailed(i32),
+    /// Compressed data was read, but the crate was built without a Kraken decoder (the `kraken`
+    /// or `kraken-rs` feature) and no codec in
+    /// [`ParseOptions::codecs`](`crate::common::options::ParseOptions::codecs`) matches it. Also
+    /// returned when compressing without the `kraken` feature.
+    #[error(
+        "Kraken compression is unavailable: build with the `kraken` or `kraken-rs` feature, or add an Oodle library to the parse options"
+    )]
+    CompressionUnavailable,
+    /// The Oodle shared library could not be loaded from the given path.
+    #[error("Failed to load Oodle library {0}")]
+    LibraryLoadFailed(String),
+    /// The Oodle shared library does not export a required function.
+    #[error("Oodle library does not export {0}")]
+    SymbolNotFound(&'static str),
 }
 
 #[derive(Error, Debug)]
@@ -97,6 +234,14 @@ pub enum Error {
     /// UTF-8 decoding error in [`read_fixed_string`](`crate::common::extensions::BufReaderExt::read_fixed_string`).
     #[error("Incorrect UTF-8 encoding found when reading string!")]
     Utf8ReadingError(#[from] FromUtf8Error),
+    /// UTF-16 decoding error in [`read_utf16_string`](`crate::common::extensions::BufReaderExt::read_utf16_string`).
+    #[error("Incorrect UTF-16 encoding found when reading string!")]
+    Utf16ReadingError(#[from] FromUtf16Error),
+    /// No null terminator was found within the maximum length of a string, see
+    /// [`read_null_terminated_string_bounded`](`crate::common::extensions::BufReaderExt::read_null_terminated_string_bounded`).
+    /// This usually means that the string offset or the data is corrupt.
+    #[error("String is longer than the maximum of {0} bytes!")]
+    StringTooLong(usize),
     /// Kraken decompression error.
     #[error("Error occurred while decompressing!")]
     DecompressionError(#[from] DecompressionError),
@@ -109,6 +254,135 @@ pub enum Error {
     /// Tag file loading error.
     #[error("Error occurred while loading a tag!")]
     TagError(#[from] TagError),
+    /// JSON serialization error.
+    #[cfg(feature = "json")]
+    #[error("Error occurred while serializing to JSON!")]
+    JsonError(#[from] serde_json::Error),
+    /// Error with information on where it occurred, attached by module and tag loaders.
+    /// Use [`Error::root`] to match on the underlying error.
+    #[error("{source} ({context})")]
+    WithContext {
+        /// Where the error occurred.
+        context: Box<ErrorContext>,
+        /// The underlying error.
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Attaches context to the error. If the error already has context, only the fields it does
+    /// not set yet are filled in, as the innermost context is the most precise.
+    #[must_use]
+    pub fn with_context(self, context: ErrorContext) -> Self {
+        match self {
+            Self::WithContext {
+                context: mut existing,
+                source,
+            } => {
+                existing.merge(context);
+                Self::WithContext {
+                    context: existing,
+                    source,
+                }
+            }
+            error => Self::WithContext {
+                context: Box::new(context),
+                source: Box::new(error),
+            },
+        }
+    }
+
+    /// Context attached to the error, if any.
+    #[must_use]
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Self::WithContext { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// The underlying error, without the context attached to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use infinite_rs::Error;
+    /// use infinite_rs::common::errors::{ErrorContext, TagError};
+    ///
+    /// let error = Error::TagError(TagError::NotLoaded).with_context(ErrorContext {
+    ///     block_index: Some(2),
+    ///     ..Default::default()
+    /// });
+    /// assert!(matches!(error.root(), Error::TagError(TagError::NotLoaded)));
+    /// assert_eq!(error.context().unwrap().block_index, Some(2));
+    /// ```
+    #[must_use]
+    pub fn root(&self) -> &Self {
+        match self {
+            Self::WithContext { source, .. } => source.root(),
+            error => error,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+/// Information on where an [`Error`] occurred.
+pub struct ErrorContext {
+    /// Path of the module being read.
+    pub module_path: Option<PathBuf>,
+    /// Id of the tag being read.
+    pub tag_id: Option<TagId>,
+    /// Name of the tag being read.
+    pub tag_name: Option<String>,
+    /// Index of the block being read, relative to the first block of the file entry.
+    pub block_index: Option<usize>,
+    /// Offset in the module (or HD1) file where the failing read started.
+    pub offset: Option<u64>,
+}
+
+impl ErrorContext {
+    /// Fills in the fields that are not set yet from another context.
+    fn merge(&mut self, other: Self) {
+        self.module_path = self.module_path.take().or(other.module_path);
+        self.tag_id = self.tag_id.or(other.tag_id);
+        self.tag_name = self.tag_name.take().or(other.tag_name);
+        self.block_index = self.block_index.or(other.block_index);
+        self.offset = self.offset.or(other.offset);
+    }
+}
+
+impl Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(module_path) = &self.module_path {
+            parts.push(format!("module {}", module_path.display()));
+        }
+        match (&self.tag_name, self.tag_id) {
+            (Some(name), Some(id)) => parts.push(format!("tag {name} ({id})")),
+            (Some(name), None) => parts.push(format!("tag {name}")),
+            (None, Some(id)) => parts.push(format!("tag {id}")),
+            (None, None) => {}
+        }
+        if let Some(block_index) = self.block_index {
+            parts.push(format!("block {block_index}"));
+        }
+        if let Some(offset) = self.offset {
+            parts.push(format!("offset {offset:#X}"));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Attaches context to the error of a [`Result`].
+pub(crate) trait ResultExt<T> {
+    /// Attaches the context returned by `context` if the result is an error.
+    fn with_context(self, context: impl FnOnce() -> ErrorContext) -> Result<T>;
+}
+
+impl<T> ResultExt<T> for Result<T> {
+    fn with_context(self, context: impl FnOnce() -> ErrorContext) -> Result<T> {
+        self.map_err(|error| error.with_context(context()))
+    }
 }
 
 /// Standard result type used throughout `infinite-rs`.
@@ -4,6 +4,9 @@
 //!
 //! * [`read_fixed_string`](`BufReaderExt::read_fixed_string`): Reads a fixed number of bytes and converts them to a UTF-8 string.
 //!   Special handling is included for sequences of `0xFF` bytes which are treated as empty strings.
+//!   [`read_fixed_string_lossy`](`BufReaderExt::read_fixed_string_lossy`) also trims trailing null
+//!   bytes, and [`read_fixed_bytes`](`BufReaderExt::read_fixed_bytes`) reads strings that are not
+//!   valid UTF-8.
 //!
 //! * [`read_enumerable`](`BufReaderExt::read_enumerable`): Generic method for reading a sequence of items that implement the
 //!   [`Enumerable`] trait. Reads the specified type `count` times and collects the results into a [`Vec`].
@@ -11,10 +14,27 @@
 //! These extensions are implemented as traits and require the reader to implement both
 //! [`Read`] and [`Seek`] traits.
 //!
+//! Field types read numbers using the [`endianness`](`BufReaderExt::endianness`) of the reader,
+//! which is little endian unless the reader is wrapped in an [`EndianReader`]. They are written
+//! back using the [`endianness`](`WriterExt::endianness`) of a [`WriterExt`], which is set with an
+//! [`EndianWriter`].
+//!
+
+use byteorder::{BE, LE, ReadBytesExt, WriteBytesExt};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
 
-use std::io::{BufRead, BufReader, Read, Seek};
+use crate::{Error, Result};
 
-use crate::Result;
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+/// Byte order of the numbers read and written by field types.
+pub enum Endianness {
+    #[default]
+    /// Least significant byte first, used by all current platforms.
+    Little,
+    /// Most significant byte first.
+    Big,
+}
 
 /// Trait for types that can be read sequentially from a buffered reader.
 ///
@@ -33,11 +53,127 @@ pub trait Enumerable {
 }
 
 /// Extension trait for [`BufRead`] to add custom reading methods.
+///
+/// Implemented for [`BufReader`], [`Cursor`] and [`EndianReader`]. Any other reader implementing
+/// [`BufRead`] and [`Seek`] can be wrapped in a [`ReaderAdapter`]. There is no blanket
+/// implementation, as readers such as [`EndianReader`] override
+/// [`endianness`](`BufReaderExt::endianness`).
+///
+/// ```
+/// use std::io::Cursor;
+/// use infinite_rs::common::extensions::BufReaderExt;
+///
+/// let mut reader = Cursor::new(vec![0x2A, 0x00, 0x00, 0x00]);
+/// assert_eq!(reader.read_u32_ordered().unwrap(), 0x2A);
+/// ```
 pub trait BufReaderExt: BufRead + Seek {
+    /// Byte order used by field types read from this reader.
+    fn endianness(&self) -> Endianness {
+        Endianness::Little
+    }
+
+    /// Returns the length of the underlying stream, keeping the current position.
+    ///
+    /// A [`SubReader`] returns the length of its window without seeking.
+    ///
+    /// # Errors
+    /// - If the reader fails to seek [`ReadError`](`crate::Error::ReadError`)
+    fn stream_length(&mut self) -> Result<u64> {
+        let position = self.stream_position()?;
+        let length = self.seek(SeekFrom::End(0))?;
+        self.seek(SeekFrom::Start(position))?;
+        Ok(length)
+    }
+
+    /// Offset of the start of this reader in the outermost stream, which is only non-zero for a
+    /// [`SubReader`]. Adding it to [`stream_position`](`Seek::stream_position`) gives the position
+    /// in the outermost stream.
+    fn window_offset(&self) -> u64 {
+        0
+    }
+
+    /// Reads an unsigned 16-bit integer using the [`endianness`](`BufReaderExt::endianness`) of the reader.
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    fn read_u16_ordered(&mut self) -> Result<u16> {
+        Ok(match self.endianness() {
+            Endianness::Little => self.read_u16::<LE>()?,
+            Endianness::Big => self.read_u16::<BE>()?,
+        })
+    }
+
+    /// Reads a signed 16-bit integer using the [`endianness`](`BufReaderExt::endianness`) of the reader.
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    fn read_i16_ordered(&mut self) -> Result<i16> {
+        Ok(match self.endianness() {
+            Endianness::Little => self.read_i16::<LE>()?,
+            Endianness::Big => self.read_i16::<BE>()?,
+        })
+    }
+
+    /// Reads an unsigned 32-bit integer using the [`endianness`](`BufReaderExt::endianness`) of the reader.
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    fn read_u32_ordered(&mut self) -> Result<u32> {
+        Ok(match self.endianness() {
+            Endianness::Little => self.read_u32::<LE>()?,
+            Endianness::Big => self.read_u32::<BE>()?,
+        })
+    }
+
+    /// Reads a signed 32-bit integer using the [`endianness`](`BufReaderExt::endianness`) of the reader.
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    fn read_i32_ordered(&mut self) -> Result<i32> {
+        Ok(match self.endianness() {
+            Endianness::Little => self.read_i32::<LE>()?,
+            Endianness::Big => self.read_i32::<BE>()?,
+        })
+    }
+
+    /// Reads an unsigned 64-bit integer using the [`endianness`](`BufReaderExt::endianness`) of the reader.
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    fn read_u64_ordered(&mut self) -> Result<u64> {
+        Ok(match self.endianness() {
+            Endianness::Little => self.read_u64::<LE>()?,
+            Endianness::Big => self.read_u64::<BE>()?,
+        })
+    }
+
+    /// Reads a signed 64-bit integer using the [`endianness`](`BufReaderExt::endianness`) of the reader.
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    fn read_i64_ordered(&mut self) -> Result<i64> {
+        Ok(match self.endianness() {
+            Endianness::Little => self.read_i64::<LE>()?,
+            Endianness::Big => self.read_i64::<BE>()?,
+        })
+    }
+
+    /// Reads a 32-bit float using the [`endianness`](`BufReaderExt::endianness`) of the reader.
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    fn read_f32_ordered(&mut self) -> Result<f32> {
+        Ok(match self.endianness() {
+            Endianness::Little => self.read_f32::<LE>()?,
+            Endianness::Big => self.read_f32::<BE>()?,
+        })
+    }
+
     /// Reads a fixed-length UTF-8 encoded string from the reader.
     ///
     /// This function reads exactly `length` bytes and converts them to a String.
-    /// If the bytes read are all 0xFF, an empty string is returned.
+    /// If the bytes read are all 0xFF, an empty string is returned. Null bytes are kept, as they
+    /// are significant in tag groups which are read reversed.
     ///
     /// # Arguments
     ///
@@ -60,14 +196,58 @@ pub trait BufReaderExt: BufRead + Seek {
     /// assert_eq!(string, "I love cats!");
     /// ```
     fn read_fixed_string(&mut self, length: usize) -> Result<String> {
+        let buffer = self.read_fixed_bytes(length)?;
+        if is_empty_fixed_string(&buffer) {
+            return Ok(String::new());
+        }
+        Ok(String::from_utf8(buffer)?)
+    }
+
+    /// Reads a fixed-length string like [`read_fixed_string`](`BufReaderExt::read_fixed_string`),
+    /// replacing invalid UTF-8 sequences with `U+FFFD` instead of failing. Trailing null bytes used
+    /// as padding are removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - The exact number of bytes to read
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use infinite_rs::common::extensions::BufReaderExt;
+    ///
+    /// let mut reader = Cursor::new(b"cat\xFF\0\0");
+    /// let string = reader.read_fixed_string_lossy(6).unwrap();
+    /// assert_eq!(string, "cat\u{FFFD}");
+    /// ```
+    fn read_fixed_string_lossy(&mut self, length: usize) -> Result<String> {
+        let buffer = self.read_fixed_bytes(length)?;
+        if is_empty_fixed_string(&buffer) {
+            return Ok(String::new());
+        }
+        let length = buffer
+            .iter()
+            .rposition(|&byte| byte != 0)
+            .map_or(0, |position| position + 1);
+        Ok(String::from_utf8_lossy(&buffer[..length]).into_owned())
+    }
+
+    /// Reads a fixed number of bytes, without interpreting them.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - The exact number of bytes to read
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    fn read_fixed_bytes(&mut self, length: usize) -> Result<Vec<u8>> {
         let mut buffer = vec![0; length];
         self.read_exact(&mut buffer)?;
-
-        Ok(if buffer == [0xFF; 4] {
-            String::new() // Return empty string if all bytes are 0xFF
-        } else {
-            String::from_utf8(buffer)?
-        })
+        Ok(buffer)
     }
 
     /// Reads a null-terminated string from the reader.
@@ -102,6 +282,100 @@ pub trait BufReaderExt: BufRead + Seek {
         Ok(string)
     }
 
+    /// Reads a null-terminated string like
+    /// [`read_null_terminated_string`](`BufReaderExt::read_null_terminated_string`), reading at
+    /// most `max_length` bytes before the null terminator.
+    ///
+    /// This guards against reading until the end of the stream when the data is corrupt. A string
+    /// ending at the end of the stream without a null terminator is returned as is.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_length` - Maximum length of the string in bytes, without the null terminator
+    ///
+    /// # Errors
+    /// - If the reader fails to read [`ReadError`](`crate::Error::ReadError`)
+    /// - If no null terminator is found within `max_length` bytes [`StringTooLong`](`crate::Error::StringTooLong`)
+    /// - If the bytes read are not valid UTF-8 [`Utf8ReadingError`](`crate::Error::Utf8ReadingError`)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use infinite_rs::common::extensions::BufReaderExt;
+    ///
+    /// let mut reader = Cursor::new(b"cats\0");
+    /// assert_eq!(reader.read_null_terminated_string_bounded(4).unwrap(), "cats");
+    /// let mut reader = Cursor::new(b"cats\0");
+    /// assert!(reader.read_null_terminated_string_bounded(3).is_err());
+    /// ```
+    fn read_null_terminated_string_bounded(&mut self, max_length: usize) -> Result<String>
+    where
+        Self: Sized,
+    {
+        let mut buffer = Vec::new();
+        self.take(max_length as u64 + 1)
+            .read_until(0x00, &mut buffer)?;
+        if buffer.last() == Some(&0x00) {
+            buffer.pop();
+        } else if buffer.len() > max_length {
+            return Err(Error::StringTooLong(max_length));
+        }
+        Ok(String::from_utf8(buffer)?)
+    }
+
+    /// Reads a fixed-length UTF-16 string from the reader.
+    ///
+    /// Code units are read using the [`endianness`](`BufReaderExt::endianness`) of the reader.
+    /// Trailing null characters used as padding are removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - The exact number of UTF-16 code units (2 bytes each) to read
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    /// - If the code units read are not valid UTF-16 [`Utf16ReadingError`](`crate::Error::Utf16ReadingError`)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use infinite_rs::common::extensions::BufReaderExt;
+    ///
+    /// let mut reader = Cursor::new([0x63, 0x00, 0x61, 0x00, 0x74, 0x00, 0x00, 0x00]);
+    /// assert_eq!(reader.read_utf16_string(4).unwrap(), "cat");
+    /// ```
+    fn read_utf16_string(&mut self, length: usize) -> Result<String> {
+        let mut units = Vec::with_capacity(length);
+        for _ in 0..length {
+            units.push(self.read_u16_ordered()?);
+        }
+        while units.last() == Some(&0) {
+            units.pop();
+        }
+        Ok(String::from_utf16(&units)?)
+    }
+
+    /// Reads a null-terminated UTF-16 string from the reader.
+    ///
+    /// Code units are read using the [`endianness`](`BufReaderExt::endianness`) of the reader
+    /// until a null character, which is removed from the final output.
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    /// - If the code units read are not valid UTF-16 [`Utf16ReadingError`](`crate::Error::Utf16ReadingError`)
+    fn read_null_terminated_utf16(&mut self) -> Result<String> {
+        let mut units = Vec::new();
+        loop {
+            match self.read_u16_ordered()? {
+                0 => break,
+                unit => units.push(unit),
+            }
+        }
+        Ok(String::from_utf16(&units)?)
+    }
+
     /// Reads multiple instances of an enumerable type into a vector.
     ///
     /// Creates a vector of type T by reading the type `count` times from the buffer.
@@ -160,10 +434,592 @@ pub trait BufReaderExt: BufRead + Seek {
         }
         Ok(enumerables)
     }
+
+    /// Reads a plain-old-data value in a single [`read_exact`](`Read::read_exact`).
+    ///
+    /// The bytes are copied as-is, so numbers are in the byte order of the host rather than the
+    /// [`endianness`](`BufReaderExt::endianness`) of the reader. Convert them with methods such as
+    /// [`u32::from_le`] when reading on big endian hosts.
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use infinite_rs::common::extensions::BufReaderExt;
+    ///
+    /// let mut reader = Cursor::new(b"\x01\x00\x00\x00\x02\x00\x00\x00");
+    /// let value = reader.read_pod::<[u32; 2]>().unwrap();
+    /// assert_eq!(value.map(u32::from_le), [1, 2]);
+    /// ```
+    #[cfg(feature = "bytemuck")]
+    fn read_pod<T: bytemuck::Pod>(&mut self) -> Result<T> {
+        let mut value = T::zeroed();
+        self.read_exact(bytemuck::bytes_of_mut(&mut value))?;
+        Ok(value)
+    }
+
+    /// Reads `count` plain-old-data values in a single [`read_exact`](`Read::read_exact`).
+    ///
+    /// This is much faster than [`read_enumerable`](`BufReaderExt::read_enumerable`) for large
+    /// tables of fixed-size entries. See [`read_pod`](`BufReaderExt::read_pod`) for byte order.
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use infinite_rs::common::extensions::BufReaderExt;
+    ///
+    /// let mut reader = Cursor::new(b"\x01\x00\x02\x00\x03\x00");
+    /// let values = reader.read_pod_slice::<u16>(3).unwrap();
+    /// assert_eq!(values.into_iter().map(u16::from_le).collect::<Vec<_>>(), [1, 2, 3]);
+    /// ```
+    #[cfg(feature = "bytemuck")]
+    fn read_pod_slice<T: bytemuck::Pod>(&mut self, count: usize) -> Result<Vec<T>> {
+        let mut values = vec![T::zeroed(); count];
+        self.read_exact(bytemuck::cast_slice_mut(&mut values))?;
+        Ok(values)
+    }
+
+    /// Reads an unsigned 32-bit integer using the [`endianness`](`BufReaderExt::endianness`) of
+    /// the reader, without advancing the position.
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use infinite_rs::common::extensions::BufReaderExt;
+    ///
+    /// let mut reader = Cursor::new(b"ucsh");
+    /// assert_eq!(reader.peek_u32().unwrap(), 0x6873_6375);
+    /// assert_eq!(reader.read_fixed_string(4).unwrap(), "ucsh");
+    /// ```
+    fn peek_u32(&mut self) -> Result<u32>
+    where
+        Self: Sized,
+    {
+        let position = self.stream_position()?;
+        self.sub_reader(position, 4)?.read_u32_ordered()
+    }
+
+    /// Reads a fixed number of bytes without advancing the position.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - The exact number of bytes to read
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    fn peek_bytes(&mut self, length: usize) -> Result<Vec<u8>>
+    where
+        Self: Sized,
+    {
+        let position = self.stream_position()?;
+        self.sub_reader(position, length as u64)?
+            .read_fixed_bytes(length)
+    }
+
+    /// Reads a fixed-length string like [`read_fixed_string`](`BufReaderExt::read_fixed_string`),
+    /// without advancing the position.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - The exact number of bytes to read
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    /// - If the bytes read are not valid UTF-8 [`Utf8ReadingError`](`crate::Error::Utf8ReadingError`)
+    fn peek_fixed_string(&mut self, length: usize) -> Result<String>
+    where
+        Self: Sized,
+    {
+        let position = self.stream_position()?;
+        self.sub_reader(position, length as u64)?
+            .read_fixed_string(length)
+    }
+
+    /// Creates a reader over `length` bytes of the stream starting at `offset`.
+    ///
+    /// Positions in the returned [`SubReader`] are relative to `offset`, and reads stop at the end
+    /// of the window, which is clamped to the end of the stream. The position of this reader is
+    /// restored when the [`SubReader`] is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - Start of the window, from the start of the stream
+    /// * `length` - Length of the window in bytes
+    ///
+    /// # Errors
+    /// - If the reader fails to seek [`ReadError`](`crate::Error::ReadError`)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Cursor, Read};
+    /// use infinite_rs::common::extensions::BufReaderExt;
+    ///
+    /// let mut reader = Cursor::new([1, 2, 3, 4, 5, 6]);
+    /// let mut window = Vec::new();
+    /// reader.sub_reader(2, 3).unwrap().read_to_end(&mut window).unwrap();
+    /// assert_eq!(window, [3, 4, 5]);
+    /// assert_eq!(reader.position(), 0);
+    /// ```
+    fn sub_reader(&mut self, offset: u64, length: u64) -> Result<SubReader<'_, Self>>
+    where
+        Self: Sized,
+    {
+        let original_position = self.stream_position()?;
+        let length = length.min(self.stream_length()?.saturating_sub(offset));
+        self.seek(SeekFrom::Start(offset))?;
+        Ok(SubReader {
+            inner: self,
+            start: offset,
+            length,
+            position: 0,
+            original_position,
+        })
+    }
+}
+
+/// Whether a fixed-length string is made only of `0xFF` bytes, which marks an empty string.
+fn is_empty_fixed_string(buffer: &[u8]) -> bool {
+    buffer.iter().all(|&byte| byte == 0xFF)
 }
 
 impl<R: Read + Seek> BufReaderExt for BufReader<R> {}
 
+/// In-memory buffers can be read directly, without a [`BufReader`].
+impl<T: AsRef<[u8]>> BufReaderExt for Cursor<T> {}
+
+impl<R: BufReaderExt + ?Sized> BufReaderExt for &mut R {
+    fn endianness(&self) -> Endianness {
+        (**self).endianness()
+    }
+
+    fn stream_length(&mut self) -> Result<u64> {
+        (**self).stream_length()
+    }
+
+    fn window_offset(&self) -> u64 {
+        (**self).window_offset()
+    }
+}
+
+/// Reader over a window of another reader, created by [`sub_reader`](`BufReaderExt::sub_reader`).
+///
+/// The position of the inner reader is restored when this reader is dropped.
+pub struct SubReader<'a, R: BufReaderExt> {
+    inner: &'a mut R,
+    /// Start of the window in the inner reader.
+    start: u64,
+    /// Length of the window.
+    length: u64,
+    /// Position relative to the start of the window.
+    position: u64,
+    /// Position of the inner reader before the window was created.
+    original_position: u64,
+}
+
+impl<R: BufReaderExt> SubReader<'_, R> {
+    /// Number of bytes left before the end of the window.
+    fn remaining(&self) -> usize {
+        usize::try_from(self.length.saturating_sub(self.position)).unwrap_or(usize::MAX)
+    }
+}
+
+impl<R: BufReaderExt> Read for SubReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let length = buf.len().min(self.remaining());
+        let read = self.inner.read(&mut buf[..length])?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<R: BufReaderExt> BufRead for SubReader<'_, R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let remaining = self.remaining();
+        if remaining == 0 {
+            return Ok(&[]);
+        }
+        let buf = self.inner.fill_buf()?;
+        Ok(&buf[..buf.len().min(remaining)])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.inner.consume(amount);
+        self.position += amount as u64;
+    }
+}
+
+impl<R: BufReaderExt> Seek for SubReader<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.length.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        self.inner.seek(SeekFrom::Start(self.start + position))?;
+        self.position = position;
+        Ok(position)
+    }
+}
+
+impl<R: BufReaderExt> BufReaderExt for SubReader<'_, R> {
+    fn endianness(&self) -> Endianness {
+        self.inner.endianness()
+    }
+
+    fn stream_length(&mut self) -> Result<u64> {
+        Ok(self.length)
+    }
+
+    fn window_offset(&self) -> u64 {
+        self.inner.window_offset() + self.start
+    }
+}
+
+impl<R: BufReaderExt> Drop for SubReader<'_, R> {
+    fn drop(&mut self) {
+        let _ = self.inner.seek(SeekFrom::Start(self.original_position));
+    }
+}
+
+/// Buffered reader with a configurable [`Endianness`] for field types.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+/// use infinite_rs::common::extensions::{BufReaderExt, EndianReader, Endianness};
+///
+/// let mut reader = EndianReader::new(Cursor::new([0x00, 0x00, 0xCA, 0x7F]), Endianness::Big);
+/// assert_eq!(reader.read_u32_ordered().unwrap(), 0xCA7F);
+/// ```
+pub struct EndianReader<R> {
+    inner: BufReader<R>,
+    endianness: Endianness,
+}
+
+impl<R: Read> EndianReader<R> {
+    /// Wraps a reader, reading field types with the given byte order.
+    pub fn new(reader: R, endianness: Endianness) -> Self {
+        Self {
+            inner: BufReader::new(reader),
+            endianness,
+        }
+    }
+
+    /// Unwraps the reader, discarding any buffered data.
+    pub fn into_inner(self) -> R {
+        self.inner.into_inner()
+    }
+}
+
+impl<R: Read> Read for EndianReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl<R: Read> BufRead for EndianReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.inner.consume(amount);
+    }
+}
+
+impl<R: Read + Seek> Seek for EndianReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+impl<R: Read + Seek> BufReaderExt for EndianReader<R> {
+    fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+}
+
+/// Adapter implementing [`BufReaderExt`] for any reader implementing [`BufRead`] and [`Seek`],
+/// without adding another layer of buffering.
+///
+/// Numbers are read as little endian, see [`EndianReader`] for other byte orders.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{BufRead, Read, Seek, SeekFrom};
+/// use infinite_rs::common::extensions::{BufReaderExt, ReaderAdapter};
+///
+/// /// Custom reader over an in-memory buffer.
+/// struct Bytes(std::io::Cursor<Vec<u8>>);
+///
+/// impl Read for Bytes {
+///     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+///         self.0.read(buf)
+///     }
+/// }
+///
+/// impl BufRead for Bytes {
+///     fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+///         self.0.fill_buf()
+///     }
+///
+///     fn consume(&mut self, amount: usize) {
+///         self.0.consume(amount);
+///     }
+/// }
+///
+/// impl Seek for Bytes {
+///     fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+///         self.0.seek(pos)
+///     }
+/// }
+///
+/// let mut reader = ReaderAdapter::new(Bytes(std::io::Cursor::new(b"cat\0".to_vec())));
+/// assert_eq!(reader.read_null_terminated_string().unwrap(), "cat");
+/// ```
+#[derive(Debug)]
+pub struct ReaderAdapter<R>(R);
+
+impl<R: BufRead + Seek> ReaderAdapter<R> {
+    /// Wraps a reader.
+    pub fn new(reader: R) -> Self {
+        Self(reader)
+    }
+
+    /// Gets a reference to the wrapped reader.
+    pub fn get_ref(&self) -> &R {
+        &self.0
+    }
+
+    /// Gets a mutable reference to the wrapped reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.0
+    }
+
+    /// Unwraps the reader.
+    pub fn into_inner(self) -> R {
+        self.0
+    }
+}
+
+impl<R: BufRead> Read for ReaderAdapter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl<R: BufRead> BufRead for ReaderAdapter<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.0.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.0.consume(amount);
+    }
+}
+
+impl<R: Seek> Seek for ReaderAdapter<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.0.seek(pos)
+    }
+}
+
+impl<R: BufRead + Seek> BufReaderExt for ReaderAdapter<R> {}
+
+/// Extension trait for [`Write`] and [`Seek`], used by field types to write numbers back in the
+/// byte order they were read in.
+///
+/// Implemented for [`BufWriter`], [`Cursor`], [`File`] and [`EndianWriter`], which write little
+/// endian unless they are an [`EndianWriter`]. Any other writer can be wrapped in an
+/// [`EndianWriter`].
+///
+/// ```
+/// use std::io::Cursor;
+/// use infinite_rs::common::extensions::WriterExt;
+///
+/// let mut writer = Cursor::new(Vec::new());
+/// writer.write_u32_ordered(0x2A).unwrap();
+/// assert_eq!(writer.into_inner(), [0x2A, 0x00, 0x00, 0x00]);
+/// ```
+pub trait WriterExt: Write + Seek {
+    /// Byte order used by field types written to this writer.
+    fn endianness(&self) -> Endianness {
+        Endianness::Little
+    }
+
+    /// Writes an unsigned 16-bit integer using the [`endianness`](`WriterExt::endianness`) of the writer.
+    ///
+    /// # Errors
+    /// - If the writer fails to write the bytes [`ReadError`](`crate::Error::ReadError`)
+    fn write_u16_ordered(&mut self, value: u16) -> Result<()> {
+        match self.endianness() {
+            Endianness::Little => self.write_u16::<LE>(value)?,
+            Endianness::Big => self.write_u16::<BE>(value)?,
+        }
+        Ok(())
+    }
+
+    /// Writes a signed 16-bit integer using the [`endianness`](`WriterExt::endianness`) of the writer.
+    ///
+    /// # Errors
+    /// - If the writer fails to write the bytes [`ReadError`](`crate::Error::ReadError`)
+    fn write_i16_ordered(&mut self, value: i16) -> Result<()> {
+        match self.endianness() {
+            Endianness::Little => self.write_i16::<LE>(value)?,
+            Endianness::Big => self.write_i16::<BE>(value)?,
+        }
+        Ok(())
+    }
+
+    /// Writes an unsigned 32-bit integer using the [`endianness`](`WriterExt::endianness`) of the writer.
+    ///
+    /// # Errors
+    /// - If the writer fails to write the bytes [`ReadError`](`crate::Error::ReadError`)
+    fn write_u32_ordered(&mut self, value: u32) -> Result<()> {
+        match self.endianness() {
+            Endianness::Little => self.write_u32::<LE>(value)?,
+            Endianness::Big => self.write_u32::<BE>(value)?,
+        }
+        Ok(())
+    }
+
+    /// Writes a signed 32-bit integer using the [`endianness`](`WriterExt::endianness`) of the writer.
+    ///
+    /// # Errors
+    /// - If the writer fails to write the bytes [`ReadError`](`crate::Error::ReadError`)
+    fn write_i32_ordered(&mut self, value: i32) -> Result<()> {
+        match self.endianness() {
+            Endianness::Little => self.write_i32::<LE>(value)?,
+            Endianness::Big => self.write_i32::<BE>(value)?,
+        }
+        Ok(())
+    }
+
+    /// Writes an unsigned 64-bit integer using the [`endianness`](`WriterExt::endianness`) of the writer.
+    ///
+    /// # Errors
+    /// - If the writer fails to write the bytes [`ReadError`](`crate::Error::ReadError`)
+    fn write_u64_ordered(&mut self, value: u64) -> Result<()> {
+        match self.endianness() {
+            Endianness::Little => self.write_u64::<LE>(value)?,
+            Endianness::Big => self.write_u64::<BE>(value)?,
+        }
+        Ok(())
+    }
+
+    /// Writes a signed 64-bit integer using the [`endianness`](`WriterExt::endianness`) of the writer.
+    ///
+    /// # Errors
+    /// - If the writer fails to write the bytes [`ReadError`](`crate::Error::ReadError`)
+    fn write_i64_ordered(&mut self, value: i64) -> Result<()> {
+        match self.endianness() {
+            Endianness::Little => self.write_i64::<LE>(value)?,
+            Endianness::Big => self.write_i64::<BE>(value)?,
+        }
+        Ok(())
+    }
+
+    /// Writes a 32-bit float using the [`endianness`](`WriterExt::endianness`) of the writer.
+    ///
+    /// # Errors
+    /// - If the writer fails to write the bytes [`ReadError`](`crate::Error::ReadError`)
+    fn write_f32_ordered(&mut self, value: f32) -> Result<()> {
+        match self.endianness() {
+            Endianness::Little => self.write_f32::<LE>(value)?,
+            Endianness::Big => self.write_f32::<BE>(value)?,
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write + Seek> WriterExt for BufWriter<W> {}
+
+impl<T: AsRef<[u8]>> WriterExt for Cursor<T> where Cursor<T>: Write {}
+
+impl WriterExt for File {}
+
+impl<W: WriterExt + ?Sized> WriterExt for &mut W {
+    fn endianness(&self) -> Endianness {
+        (**self).endianness()
+    }
+}
+
+/// Writer with a configurable [`Endianness`] for field types, used to write back structures read
+/// through an [`EndianReader`].
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+/// use infinite_rs::common::extensions::{EndianWriter, Endianness, WriterExt};
+///
+/// let mut writer = EndianWriter::new(Cursor::new(Vec::new()), Endianness::Big);
+/// writer.write_u32_ordered(0xCA7F).unwrap();
+/// assert_eq!(writer.into_inner().into_inner(), [0x00, 0x00, 0xCA, 0x7F]);
+/// ```
+#[derive(Debug)]
+pub struct EndianWriter<W> {
+    inner: W,
+    endianness: Endianness,
+}
+
+impl<W: Write> EndianWriter<W> {
+    /// Wraps a writer, writing field types with the given byte order.
+    pub fn new(writer: W, endianness: Endianness) -> Self {
+        Self {
+            inner: writer,
+            endianness,
+        }
+    }
+
+    /// Unwraps the writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for EndianWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Seek> Seek for EndianWriter<W> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+impl<W: Write + Seek> WriterExt for EndianWriter<W> {
+    fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -178,4 +1034,59 @@ mod tests {
         let string = reader.read_fixed_string(data.len()).unwrap();
         assert_eq!(string, "");
     }
+
+    #[test]
+    /// Verifies that `0xFF` padding is detected for fixed-length strings of any length, and that
+    /// null padding is only trimmed by the lossy variant.
+    fn test_read_fixed_string_padding() {
+        let mut reader = Cursor::new([0xFF; 32]);
+        assert_eq!(reader.read_fixed_string(32).unwrap(), "");
+        let mut reader = Cursor::new(b"cats\0\0\0\0");
+        assert_eq!(reader.read_fixed_string(8).unwrap(), "cats\0\0\0\0");
+        let mut reader = Cursor::new(b"cats\0\0\0\0");
+        assert_eq!(reader.read_fixed_string_lossy(8).unwrap(), "cats");
+        let mut reader = Cursor::new([0xFF, 0x00]);
+        assert!(reader.read_fixed_string(2).is_err());
+    }
+
+    #[test]
+    /// Verifies that null bytes in tag groups are kept, as the group is reversed after reading.
+    fn test_read_tag_group_with_null() {
+        let mut reader = Cursor::new(b"ab\0\0");
+        let group: String = reader.read_fixed_string(4).unwrap().chars().rev().collect();
+        assert_eq!(group, "\0\0ba");
+    }
+
+    #[test]
+    /// Verifies that sub readers report the length of their window, clamped to the stream, and
+    /// their offset in the outermost stream.
+    fn test_sub_reader_window() {
+        let mut reader = Cursor::new([0_u8; 0x10]);
+        let mut window = reader.sub_reader(0x4, 0x8).unwrap();
+        assert_eq!(window.stream_length().unwrap(), 0x8);
+        let mut nested = window.sub_reader(0x6, 0x8).unwrap();
+        assert_eq!(nested.stream_length().unwrap(), 0x2);
+        assert_eq!(nested.window_offset(), 0xA);
+    }
+
+    #[test]
+    /// Verifies that peeking leaves the position unchanged, even when the read fails.
+    fn test_peek() {
+        let mut reader = Cursor::new([1, 0, 0, 0, b'c', b'a', b't']);
+        assert_eq!(reader.peek_u32().unwrap(), 1);
+        assert_eq!(reader.peek_bytes(2).unwrap(), [1, 0]);
+        reader.set_position(4);
+        assert_eq!(reader.peek_fixed_string(3).unwrap(), "cat");
+        assert!(reader.peek_u32().is_err());
+        assert_eq!(reader.position(), 4);
+    }
+
+    #[test]
+    /// Verifies that UTF-16 strings follow the endianness of the reader and reject lone surrogates.
+    fn test_read_utf16() {
+        let mut reader = EndianReader::new(Cursor::new([0x00, 0x63, 0x00, 0x00]), Endianness::Big);
+        assert_eq!(reader.read_null_terminated_utf16().unwrap(), "c");
+        let mut reader = Cursor::new([0x00, 0xD8, 0x00, 0x00]);
+        assert!(reader.read_utf16_string(2).is_err());
+    }
 }
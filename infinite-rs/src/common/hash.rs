@@ -0,0 +1,421 @@
+//! Hashing functions used by Halo Infinite to identify tags and strings, and a reader hashing
+//! data while it is parsed.
+
+use std::hash::Hasher;
+use std::io::{self, BufRead, Read, Seek, SeekFrom};
+
+use super::extensions::{BufReaderExt, Endianness};
+use super::tag_id::TagId;
+
+/// Computes the 32-bit `MurmurHash3_x86_32` hash of the given bytes.
+///
+/// This is the hash used for string ids and global tag ids.
+///
+/// # Examples
+///
+/// ```
+/// use infinite_rs::common::hash::murmur3_x86_32;
+///
+/// assert_eq!(murmur3_x86_32(b"hello", 0), 0x248B_FA47);
+/// ```
+#[must_use]
+pub fn murmur3_x86_32(data: &[u8], seed: u32) -> u32 {
+    let mut hasher = Murmur3Hasher::with_seed(seed);
+    hasher.write(data);
+    hasher.finish_u32()
+}
+
+const C1: u32 = 0xCC9E_2D51;
+const C2: u32 = 0x1B87_3593;
+
+/// Mixes a block of 4 bytes before it is combined with the hash.
+fn mix_block(k: u32) -> u32 {
+    k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2)
+}
+
+#[derive(Debug, Clone, Default)]
+/// Incremental `MurmurHash3_x86_32` hasher, giving the same result as [`murmur3_x86_32`] over
+/// all the bytes written to it.
+///
+/// [`finish`](`Hasher::finish`) returns the 32-bit hash extended to 64 bits.
+pub struct Murmur3Hasher {
+    hash: u32,
+    /// Bytes written since the last complete block.
+    tail: [u8; 4],
+    tail_length: usize,
+    length: u64,
+}
+
+impl Murmur3Hasher {
+    /// Creates a hasher with the given seed.
+    #[must_use]
+    pub fn with_seed(seed: u32) -> Self {
+        Self {
+            hash: seed,
+            ..Self::default()
+        }
+    }
+
+    fn write_block(&mut self, block: [u8; 4]) {
+        self.hash ^= mix_block(u32::from_le_bytes(block));
+        self.hash = self
+            .hash
+            .rotate_left(13)
+            .wrapping_mul(5)
+            .wrapping_add(0xE654_6B64);
+    }
+
+    /// Returns the hash of the bytes written so far.
+    #[must_use]
+    pub fn finish_u32(&self) -> u32 {
+        let mut hash = self.hash;
+        if self.tail_length > 0 {
+            let mut block = [0; 4];
+            block[..self.tail_length].copy_from_slice(&self.tail[..self.tail_length]);
+            hash ^= mix_block(u32::from_le_bytes(block));
+        }
+
+        // The length is truncated to 32 bits, like the reference implementation.
+        #[allow(clippy::cast_possible_truncation)]
+        let length = self.length as u32;
+        hash ^= length;
+        hash ^= hash >> 16;
+        hash = hash.wrapping_mul(0x85EB_CA6B);
+        hash ^= hash >> 13;
+        hash = hash.wrapping_mul(0xC2B2_AE35);
+        hash ^= hash >> 16;
+        hash
+    }
+}
+
+impl Hasher for Murmur3Hasher {
+    fn write(&mut self, mut bytes: &[u8]) {
+        self.length += bytes.len() as u64;
+        if self.tail_length > 0 {
+            let needed = (4 - self.tail_length).min(bytes.len());
+            self.tail[self.tail_length..self.tail_length + needed]
+                .copy_from_slice(&bytes[..needed]);
+            self.tail_length += needed;
+            bytes = &bytes[needed..];
+            if self.tail_length < 4 {
+                return;
+            }
+            self.write_block(self.tail);
+            self.tail_length = 0;
+        }
+        let mut chunks = bytes.chunks_exact(4);
+        for chunk in &mut chunks {
+            self.write_block([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        let remainder = chunks.remainder();
+        self.tail[..remainder.len()].copy_from_slice(remainder);
+        self.tail_length = remainder.len();
+    }
+
+    fn finish(&self) -> u64 {
+        u64::from(self.finish_u32())
+    }
+}
+
+/// Computes the 128-bit `MurmurHash3_x64_128` hash of the given bytes.
+///
+/// This is the hash used for [`asset_hash`](`crate::module::file::ModuleFileEntry::asset_hash`),
+/// which holds the 16 bytes of the hash read as a little-endian integer. It is the hash of the
+/// asset the file was built from, which is not always the data stored in the module.
+///
+/// # Examples
+///
+/// ```
+/// use infinite_rs::common::hash::murmur3_x64_128;
+///
+/// assert_eq!(
+///     murmur3_x64_128(b"hello", 0),
+///     0x5B1E_906A_48AE_1D19_CBD8_A7B3_41BD_9B02
+/// );
+/// ```
+#[must_use]
+pub fn murmur3_x64_128(data: &[u8], seed: u32) -> u128 {
+    let mut hasher = Murmur3X64Hasher::with_seed(seed);
+    hasher.write(data);
+    hasher.finish_u128()
+}
+
+const C1_X64: u64 = 0x87C3_7B91_1142_53D5;
+const C2_X64: u64 = 0x4CF5_AD43_2745_937F;
+
+/// Mixes the first half of a block of 16 bytes before it is combined with the hash.
+fn mix_block_x64_1(k: u64) -> u64 {
+    k.wrapping_mul(C1_X64).rotate_left(31).wrapping_mul(C2_X64)
+}
+
+/// Mixes the second half of a block of 16 bytes before it is combined with the hash.
+fn mix_block_x64_2(k: u64) -> u64 {
+    k.wrapping_mul(C2_X64).rotate_left(33).wrapping_mul(C1_X64)
+}
+
+/// Final avalanche of a 64-bit half of the hash.
+fn fmix64(mut k: u64) -> u64 {
+    k ^= k >> 33;
+    k = k.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+    k ^= k >> 33;
+    k = k.wrapping_mul(0xC4CE_B9FE_1A85_EC53);
+    k ^= k >> 33;
+    k
+}
+
+/// Splits 16 bytes into the two little-endian halves of a block.
+fn block_halves(block: &[u8; 16]) -> (u64, u64) {
+    let mut low = [0; 8];
+    let mut high = [0; 8];
+    low.copy_from_slice(&block[..8]);
+    high.copy_from_slice(&block[8..]);
+    (u64::from_le_bytes(low), u64::from_le_bytes(high))
+}
+
+#[derive(Debug, Clone, Default)]
+/// Incremental `MurmurHash3_x64_128` hasher, giving the same result as [`murmur3_x64_128`] over
+/// all the bytes written to it.
+///
+/// [`finish`](`Hasher::finish`) returns the lower 64 bits of the hash.
+pub struct Murmur3X64Hasher {
+    h1: u64,
+    h2: u64,
+    /// Bytes written since the last complete block.
+    tail: [u8; 16],
+    tail_length: usize,
+    length: u64,
+}
+
+impl Murmur3X64Hasher {
+    /// Creates a hasher with the given seed.
+    #[must_use]
+    pub fn with_seed(seed: u32) -> Self {
+        Self {
+            h1: seed.into(),
+            h2: seed.into(),
+            ..Self::default()
+        }
+    }
+
+    fn write_block(&mut self, block: &[u8; 16]) {
+        let (k1, k2) = block_halves(block);
+        self.h1 ^= mix_block_x64_1(k1);
+        self.h1 = self
+            .h1
+            .rotate_left(27)
+            .wrapping_add(self.h2)
+            .wrapping_mul(5)
+            .wrapping_add(0x52DC_E729);
+        self.h2 ^= mix_block_x64_2(k2);
+        self.h2 = self
+            .h2
+            .rotate_left(31)
+            .wrapping_add(self.h1)
+            .wrapping_mul(5)
+            .wrapping_add(0x3849_5AB5);
+    }
+
+    /// Returns the hash of the bytes written so far, with the first 8 bytes of the reference
+    /// output in the lower half.
+    #[must_use]
+    pub fn finish_u128(&self) -> u128 {
+        let (mut h1, mut h2) = (self.h1, self.h2);
+        if self.tail_length > 0 {
+            let mut block = [0; 16];
+            block[..self.tail_length].copy_from_slice(&self.tail[..self.tail_length]);
+            let (k1, k2) = block_halves(&block);
+            if self.tail_length > 8 {
+                h2 ^= mix_block_x64_2(k2);
+            }
+            h1 ^= mix_block_x64_1(k1);
+        }
+
+        h1 ^= self.length;
+        h2 ^= self.length;
+        h1 = h1.wrapping_add(h2);
+        h2 = h2.wrapping_add(h1);
+        h1 = fmix64(h1);
+        h2 = fmix64(h2);
+        h1 = h1.wrapping_add(h2);
+        h2 = h2.wrapping_add(h1);
+        (u128::from(h2) << 64) | u128::from(h1)
+    }
+}
+
+impl Hasher for Murmur3X64Hasher {
+    fn write(&mut self, mut bytes: &[u8]) {
+        self.length += bytes.len() as u64;
+        if self.tail_length > 0 {
+            let needed = (16 - self.tail_length).min(bytes.len());
+            self.tail[self.tail_length..self.tail_length + needed]
+                .copy_from_slice(&bytes[..needed]);
+            self.tail_length += needed;
+            bytes = &bytes[needed..];
+            if self.tail_length < 16 {
+                return;
+            }
+            let tail = self.tail;
+            self.write_block(&tail);
+            self.tail_length = 0;
+        }
+        let mut chunks = bytes.chunks_exact(16);
+        for chunk in &mut chunks {
+            let mut block = [0; 16];
+            block.copy_from_slice(chunk);
+            self.write_block(&block);
+        }
+        let remainder = chunks.remainder();
+        self.tail[..remainder.len()].copy_from_slice(remainder);
+        self.tail_length = remainder.len();
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn finish(&self) -> u64 {
+        self.finish_u128() as u64
+    }
+}
+
+/// Reader feeding every byte it reads into a [`Hasher`], so data can be hashed while it is
+/// parsed instead of in a second pass.
+///
+/// Bytes are hashed in the order they are read. Seeking does not hash the bytes that were skipped,
+/// and bytes read again after seeking back are hashed again.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{Cursor, Read};
+/// use infinite_rs::common::hash::{HashingReader, Murmur3Hasher, murmur3_x86_32};
+///
+/// let mut reader = HashingReader::new(Cursor::new(b"hello"), Murmur3Hasher::with_seed(0));
+/// reader.read_to_end(&mut Vec::new()).unwrap();
+/// assert_eq!(reader.hasher().finish_u32(), murmur3_x86_32(b"hello", 0));
+/// ```
+pub struct HashingReader<R, H> {
+    inner: R,
+    hasher: H,
+}
+
+impl<R, H: Hasher> HashingReader<R, H> {
+    /// Wraps a reader, hashing the bytes read with `hasher`.
+    pub fn new(reader: R, hasher: H) -> Self {
+        Self {
+            inner: reader,
+            hasher,
+        }
+    }
+
+    /// Hasher the bytes read so far were written to.
+    pub fn hasher(&self) -> &H {
+        &self.hasher
+    }
+
+    /// Unwraps the reader and its hasher.
+    pub fn into_parts(self) -> (R, H) {
+        (self.inner, self.hasher)
+    }
+}
+
+impl<R: Read, H: Hasher> Read for HashingReader<R, H> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.write(&buf[..read]);
+        Ok(read)
+    }
+}
+
+impl<R: BufRead, H: Hasher> BufRead for HashingReader<R, H> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        // The buffer is already filled, so this does not read from the inner reader again.
+        if let Ok(buf) = self.inner.fill_buf() {
+            self.hasher.write(&buf[..amount.min(buf.len())]);
+        }
+        self.inner.consume(amount);
+    }
+}
+
+impl<R: Seek, H> Seek for HashingReader<R, H> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+impl<R: BufReaderExt, H: Hasher> BufReaderExt for HashingReader<R, H> {
+    fn endianness(&self) -> Endianness {
+        self.inner.endianness()
+    }
+}
+
+/// Computes the global tag id of a tag from its path.
+///
+/// The global tag id is the [`murmur3_x86_32`] hash of the tag path, with a seed of 0.
+/// This is the value stored in [`tag_id`](`crate::module::file::ModuleFileEntry::tag_id`).
+///
+/// # Arguments
+///
+/// * `path` - Path of the tag.
+#[must_use]
+pub fn compute_tag_id(path: &str) -> TagId {
+    TagId(i32::from_ne_bytes(
+        murmur3_x86_32(path.as_bytes(), 0).to_ne_bytes(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Verifies the hash against reference `MurmurHash3_x86_32` outputs.
+    fn test_murmur3_x86_32() {
+        assert_eq!(murmur3_x86_32(b"", 0), 0);
+        assert_eq!(murmur3_x86_32(b"hello", 0), 0x248B_FA47);
+        assert_eq!(
+            murmur3_x86_32(b"The quick brown fox jumps over the lazy dog", 0),
+            0x2E4F_F723
+        );
+    }
+
+    #[test]
+    /// Verifies the hash against reference `MurmurHash3_x64_128` outputs.
+    fn test_murmur3_x64_128() {
+        assert_eq!(murmur3_x64_128(b"", 0), 0);
+        assert_eq!(
+            murmur3_x64_128(b"hello", 0),
+            0x5B1E_906A_48AE_1D19_CBD8_A7B3_41BD_9B02
+        );
+        assert_eq!(
+            murmur3_x64_128(b"The quick brown fox jumps over the lazy dog", 0),
+            0x7A43_3CA9_C49A_9347_E34B_BC7B_BC07_1B6C
+        );
+    }
+
+    #[test]
+    /// Verifies that hashing in pieces and through a reader matches hashing all bytes at once.
+    fn test_incremental_murmur3() {
+        let data = b"The quick brown fox jumps over the lazy dog";
+        let mut hasher = Murmur3Hasher::with_seed(0);
+        for piece in data.chunks(3) {
+            hasher.write(piece);
+        }
+        assert_eq!(hasher.finish_u32(), 0x2E4F_F723);
+
+        let mut reader = HashingReader::new(io::Cursor::new(data), Murmur3Hasher::with_seed(0));
+        reader.read_fixed_string(4).unwrap();
+        reader.read_null_terminated_string().unwrap();
+        assert_eq!(reader.hasher().finish(), 0x2E4F_F723);
+
+        let mut hasher = Murmur3X64Hasher::with_seed(0);
+        for piece in data.chunks(5) {
+            hasher.write(piece);
+        }
+        assert_eq!(
+            hasher.finish_u128(),
+            0x7A43_3CA9_C49A_9347_E34B_BC7B_BC07_1B6C
+        );
+    }
+}
@@ -2,3 +2,9 @@
 
 pub mod errors;
 pub mod extensions;
+pub mod hash;
+pub mod options;
+pub mod string_id;
+pub mod tag_id;
+pub mod tag_names;
+pub mod trace;
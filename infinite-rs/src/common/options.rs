@@ -0,0 +1,167 @@
+//! Options controlling how modules and tags are parsed.
+
+use std::fmt::{self, Display};
+use std::sync::Arc;
+
+use super::tag_names::TagNameDatabase;
+use crate::module::cache::TagCache;
+use crate::module::codec::Codec;
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+/// Determines whether the string table of a tag is read.
+///
+/// The string table stores the names of dependencies and tag references, and only exists in tags
+/// from modules before [`Season3`](`crate::module::header::ModuleVersion::Season3`). Some tag
+/// groups (such as `psod`) do not contain one in any version.
+pub enum StringTableMode {
+    #[default]
+    /// Read the string table if the module version supports it and the tag header describes one
+    /// that fits inside the header section.
+    Detect,
+    /// Read the string table based on the module version only, regardless of the tag header.
+    FromVersion,
+    /// Always read the string table described by the tag header.
+    Always,
+    /// Never read the string table.
+    Never,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// Determines what happens when a tag block or resource points to data that is not loaded.
+///
+/// Resource data of a tag can be stored in separate files (such as an HD1 module) that are not
+/// always present. Used by [`FieldBlock`](`crate::tag::types::common_types::FieldBlock`) and
+/// [`FieldTagResource`](`crate::tag::types::common_types::FieldTagResource`) when reading metadata.
+pub enum MissingResourcePolicy {
+    #[default]
+    /// Fail the entire read with [`TagError::DatablockOutOfBounds`](`crate::common::errors::TagError::DatablockOutOfBounds`).
+    Error,
+    /// Leave the field empty, and mark it as missing.
+    Skip,
+    /// Read the field from zeroed data, and mark it as missing.
+    ZeroFill,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// Determines what happens when the element count of a tag block does not fit in its data block.
+///
+/// Corrupt or mismatched tags can store counts that would make
+/// [`FieldBlock`](`crate::tag::types::common_types::FieldBlock`) read past the end of the data
+/// block into unrelated data.
+pub enum BlockBoundsPolicy {
+    #[default]
+    /// Fail the entire read with [`TagError::BlockOutOfBounds`](`crate::common::errors::TagError::BlockOutOfBounds`).
+    Error,
+    /// Only read the elements that fit in the data block.
+    Clamp,
+}
+
+#[derive(Debug, Clone)]
+/// Options used when reading modules and tags.
+///
+/// By default, reading is strict and fails on the first malformed structure. With
+/// [`strict`](`ParseOptions::strict`) disabled, recoverable issues are recorded as [`Warning`]s
+/// instead, so that a bad entry does not abort reading an entire module.
+///
+/// # Examples
+///
+/// ```no_run
+/// use infinite_rs::ModuleFile;
+/// use infinite_rs::common::options::{ParseOptions, StringTableMode};
+///
+/// let options = ParseOptions {
+///     string_table: StringTableMode::Never,
+///     ..Default::default()
+/// };
+/// let module = ModuleFile::from_path_with_options("deploy/any/globals-rtx-new.module", options).unwrap();
+/// ```
+pub struct ParseOptions {
+    /// Whether the string table of tags is read.
+    pub string_table: StringTableMode,
+    /// What happens when tag blocks or resources point to data that is not loaded.
+    pub missing_resources: MissingResourcePolicy,
+    /// What happens when tag blocks have more elements than fit in their data block.
+    pub block_bounds: BlockBoundsPolicy,
+    /// Whether recoverable issues fail the read. When `false`, they are recorded in
+    /// [`ModuleFile::warnings`](`crate::ModuleFile::warnings`) or
+    /// [`TagFile::warnings`](`crate::TagFile::warnings`) instead. Defaults to `true`.
+    pub strict: bool,
+    /// Database used to resolve the
+    /// [`tag_name`](`crate::module::file::ModuleFileEntry::tag_name`) of files in modules without
+    /// a string table. Files missing from the database keep a name derived from their group and
+    /// id. Defaults to [`None`].
+    pub tag_names: Option<Arc<TagNameDatabase>>,
+    /// Cache storing the decompressed data of tags on disk, so that reading them again skips
+    /// decompression. Defaults to [`None`].
+    pub tag_cache: Option<Arc<TagCache>>,
+    /// Decoders tried in order before the built-in Kraken decompressor, for blocks they
+    /// [match](`Codec::matches`). Defaults to none.
+    pub codecs: Vec<Arc<dyn Codec>>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            string_table: StringTableMode::default(),
+            missing_resources: MissingResourcePolicy::default(),
+            block_bounds: BlockBoundsPolicy::default(),
+            strict: true,
+            tag_names: None,
+            tag_cache: None,
+            codecs: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// Recoverable issue found while reading a module or tag.
+///
+/// Issues that used to be ignored silently (such as unknown flag bits) are recorded in both modes,
+/// the others only when [`ParseOptions::strict`] is disabled.
+#[non_exhaustive]
+pub enum Warning {
+    /// A file entry has flag bits that are not known, which were dropped.
+    UnknownFileFlags {
+        /// Index of the file entry in the module.
+        file_index: usize,
+        /// The unknown bits.
+        bits: u8,
+    },
+    /// A file entry could not be read, and was replaced by a default entry so that the indices of
+    /// the other entries are kept.
+    InvalidFileEntry {
+        /// Index of the file entry in the module.
+        file_index: usize,
+        /// Message of the error that occurred while reading the entry.
+        error: String,
+    },
+    /// A block entry has an `is_compressed` value other than 0 or 1, and was treated as compressed.
+    IncorrectCompressedValue {
+        /// Index of the block entry in the module.
+        block_index: usize,
+        /// The stored value.
+        value: u32,
+    },
+    /// The tag header has a version other than the one supported, and was read as if it matched.
+    IncorrectTagVersion(i32),
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownFileFlags { file_index, bits } => {
+                write!(f, "file {file_index} has unknown flags {bits:#04X}")
+            }
+            Self::InvalidFileEntry { file_index, error } => {
+                write!(f, "file {file_index} could not be read: {error}")
+            }
+            Self::IncorrectCompressedValue { block_index, value } => {
+                write!(f, "block {block_index} has is_compressed value {value}")
+            }
+            Self::IncorrectTagVersion(version) => write!(f, "tag has version {version}"),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Reverse lookup of string ids.
+//!
+//! String ids are stored in tags as a `MurmurHash3_x86_32` hash of the string, so the original
+//! string cannot be recovered from the tag alone. A [`StringIdDictionary`] maps hashes back to
+//! strings, using wordlists supplied by the user or the string tables of older tags.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    sync::RwLock,
+};
+
+use super::hash::murmur3_x86_32;
+use crate::Result;
+use crate::TagFile;
+
+/// Dictionary used when formatting [`FieldStringId`](`crate::tag::types::common_types::FieldStringId`)
+/// with [`Debug`], see [`StringIdDictionary::install`].
+static GLOBAL_DICTIONARY: RwLock<Option<StringIdDictionary>> = RwLock::new(None);
+
+#[derive(Default, Debug, Clone)]
+/// Dictionary mapping string id hashes back to their strings.
+///
+/// # Examples
+///
+/// ```
+/// use infinite_rs::common::string_id::StringIdDictionary;
+///
+/// let mut dictionary = StringIdDictionary::new();
+/// let hash = dictionary.insert("default");
+/// assert_eq!(dictionary.lookup(hash), Some("default"));
+/// ```
+pub struct StringIdDictionary {
+    /// Strings keyed by their hash.
+    strings: HashMap<i32, String>,
+}
+
+impl StringIdDictionary {
+    /// Creates an empty dictionary.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a dictionary from a wordlist file, containing one string per line.
+    ///
+    /// # Errors
+    /// - If the file cannot be opened or read [`ReadError`](`crate::Error::ReadError`)
+    pub fn from_wordlist_path<T: AsRef<Path>>(path: T) -> Result<Self> {
+        let mut dictionary = Self::new();
+        dictionary.extend_from_wordlist(BufReader::new(File::open(path)?))?;
+        Ok(dictionary)
+    }
+
+    /// Hashes a string and adds it to the dictionary, returning its string id.
+    pub fn insert(&mut self, string: &str) -> i32 {
+        #[allow(clippy::cast_possible_wrap)]
+        let hash = murmur3_x86_32(string.as_bytes(), 0) as i32;
+        self.strings.insert(hash, string.to_string());
+        hash
+    }
+
+    /// Adds every line of a wordlist to the dictionary. Surrounding whitespace is trimmed, and
+    /// empty lines are skipped.
+    ///
+    /// # Errors
+    /// - If the reader fails to read a line [`ReadError`](`crate::Error::ReadError`)
+    pub fn extend_from_wordlist<R: BufRead>(&mut self, reader: R) -> Result<()> {
+        for line in reader.lines() {
+            let line = line?;
+            let word = line.trim();
+            if !word.is_empty() {
+                self.insert(word);
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds every string of the [`string_table`](`TagFile::string_table`) of a tag.
+    ///
+    /// Only tags from modules before Season 3 contain a string table.
+    pub fn extend_from_tag(&mut self, tag_file: &TagFile) {
+        for string in &tag_file.string_table {
+            self.insert(string);
+        }
+    }
+
+    /// Gets the string a string id was hashed from, if present in the dictionary.
+    #[must_use]
+    pub fn lookup(&self, string_id: i32) -> Option<&str> {
+        self.strings.get(&string_id).map(String::as_str)
+    }
+
+    /// Number of strings in the dictionary.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Whether the dictionary contains no strings.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Installs the dictionary globally, so that [`Debug`] formatting of
+    /// [`FieldStringId`](`crate::tag::types::common_types::FieldStringId`) shows the string
+    /// instead of the raw hash. Replaces any previously installed dictionary.
+    pub fn install(self) {
+        if let Ok(mut global) = GLOBAL_DICTIONARY.write() {
+            *global = Some(self);
+        }
+    }
+
+    /// Removes the globally installed dictionary, if any.
+    pub fn uninstall() {
+        if let Ok(mut global) = GLOBAL_DICTIONARY.write() {
+            *global = None;
+        }
+    }
+}
+
+/// Looks up a string id in the globally installed dictionary.
+pub(crate) fn lookup_global(string_id: i32) -> Option<String> {
+    GLOBAL_DICTIONARY
+        .read()
+        .ok()?
+        .as_ref()?
+        .lookup(string_id)
+        .map(ToString::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tag::types::common_types::FieldStringId;
+
+    #[test]
+    /// Verifies that installed dictionaries are used when formatting string ids.
+    fn test_install() {
+        let mut dictionary = StringIdDictionary::new();
+        dictionary
+            .extend_from_wordlist(&b"default\n\n  cats  \n"[..])
+            .unwrap();
+        assert_eq!(dictionary.len(), 2);
+        #[allow(clippy::cast_possible_wrap)]
+        let hash = murmur3_x86_32(b"cats", 0) as i32;
+        assert_eq!(dictionary.lookup(hash), Some("cats"));
+
+        dictionary.install();
+        assert_eq!(
+            format!("{:?}", FieldStringId(hash)),
+            "FieldStringId(\"cats\")"
+        );
+        assert_eq!(format!("{:?}", FieldStringId(1)), "FieldStringId(1)");
+        StringIdDictionary::uninstall();
+        assert_eq!(
+            format!("{:?}", FieldStringId(hash)),
+            format!("FieldStringId({hash})")
+        );
+    }
+}
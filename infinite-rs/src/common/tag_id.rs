@@ -0,0 +1,85 @@
+//! Global tag ids, used to identify tags across modules.
+
+use std::{
+    fmt::{self, Display, LowerHex, UpperHex},
+    str::FromStr,
+};
+
+use super::errors::{Error, TagError};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(transparent))]
+/// Global id of a tag, the `MurmurHash3_x86_32` hash of its path.
+///
+/// See [`compute_tag_id`](`super::hash::compute_tag_id`). A value of -1 is used for null
+/// references and for files that are not tags (such as resources).
+///
+/// Displayed as hexadecimal, which is also the format accepted by [`FromStr`].
+///
+/// # Examples
+///
+/// ```
+/// use infinite_rs::common::tag_id::TagId;
+///
+/// let id: TagId = "0x0000CA7F".parse().unwrap();
+/// assert_eq!(id, TagId(0xCA7F));
+/// assert_eq!(id.to_string(), "0x0000CA7F");
+/// assert!(TagId::NULL.is_null());
+/// ```
+pub struct TagId(pub i32);
+
+impl TagId {
+    /// Id used for null references and files that are not tags.
+    pub const NULL: Self = Self(-1);
+
+    /// Whether the id is [`NULL`](`TagId::NULL`).
+    #[must_use]
+    pub fn is_null(self) -> bool {
+        self == Self::NULL
+    }
+}
+
+impl From<i32> for TagId {
+    fn from(value: i32) -> Self {
+        Self(value)
+    }
+}
+
+impl From<TagId> for i32 {
+    fn from(value: TagId) -> Self {
+        value.0
+    }
+}
+
+impl Display for TagId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#010X}", self.0)
+    }
+}
+
+impl UpperHex for TagId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        UpperHex::fmt(&self.0, f)
+    }
+}
+
+impl LowerHex for TagId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl FromStr for TagId {
+    type Err = Error;
+
+    /// Parses a hexadecimal id, with or without a `0x` prefix.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .unwrap_or(s);
+        let value =
+            u32::from_str_radix(digits, 16).map_err(|_| TagError::InvalidTagId(s.to_string()))?;
+        Ok(Self(i32::from_ne_bytes(value.to_ne_bytes())))
+    }
+}
@@ -0,0 +1,184 @@
+//! Resolution of tag paths from their global tag id.
+//!
+//! Modules from [`Season3`](`crate::module::header::ModuleVersion::Season3`) onwards do not
+//! contain a string table, so the [`tag_name`](`crate::module::file::ModuleFileEntry::tag_name`)
+//! of their files is derived from their group and id, such as `bitm/-1234.bitm`. A
+//! [`TagNameDatabase`] maps global tag ids back to their paths, using tag lists supplied by the
+//! user (such as those maintained by the community). When set in
+//! [`ParseOptions::tag_names`](`crate::common::options::ParseOptions::tag_names`), it is applied
+//! while reading modules.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use super::hash::compute_tag_id;
+use crate::{Result, TagId};
+
+#[derive(Default, Debug, Clone)]
+/// Database mapping global tag ids to tag paths.
+///
+/// # Examples
+///
+/// ```
+/// use infinite_rs::TagId;
+/// use infinite_rs::common::tag_names::TagNameDatabase;
+///
+/// let mut database = TagNameDatabase::new();
+/// database
+///     .extend_from_list(&b"0x1234ABCD objects/cat.model\nobjects/dog.model\n"[..])
+///     .unwrap();
+/// assert_eq!(database.len(), 2);
+/// assert_eq!(database.get(TagId(0x1234_ABCD)), Some("objects/cat.model"));
+/// ```
+pub struct TagNameDatabase {
+    /// Tag paths keyed by their global tag id.
+    paths: HashMap<TagId, String>,
+}
+
+/// Parses a global tag id written in hexadecimal (with a `0x` prefix) or signed decimal.
+fn parse_tag_id(text: &str) -> Option<TagId> {
+    let id = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => i32::from_ne_bytes(u32::from_str_radix(hex, 16).ok()?.to_ne_bytes()),
+        None => text.parse().ok()?,
+    };
+    Some(TagId(id))
+}
+
+impl TagNameDatabase {
+    /// Creates an empty database.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a database from a tag list file, see
+    /// [`extend_from_list`](`TagNameDatabase::extend_from_list`).
+    ///
+    /// # Errors
+    /// - If the file cannot be opened or read [`ReadError`](`crate::Error::ReadError`)
+    pub fn from_path<T: AsRef<Path>>(path: T) -> Result<Self> {
+        let mut database = Self::new();
+        database.extend_from_list(BufReader::new(File::open(path)?))?;
+        Ok(database)
+    }
+
+    /// Adds every line of a tag list to the database.
+    ///
+    /// Lines contain a global tag id followed by its path, separated by whitespace, a comma or a
+    /// colon. The id is written in hexadecimal with a `0x` prefix, or in signed decimal. Lines
+    /// that only contain a path are hashed with [`compute_tag_id`]. Surrounding whitespace is
+    /// trimmed, and empty lines are skipped.
+    ///
+    /// # Errors
+    /// - If the reader fails to read a line [`ReadError`](`crate::Error::ReadError`)
+    pub fn extend_from_list<R: BufRead>(&mut self, reader: R) -> Result<()> {
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let entry = line
+                .split_once(|c: char| c == ',' || c == ':' || c.is_whitespace())
+                .and_then(|(id, path)| {
+                    let path = path
+                        .trim_start_matches(|c: char| c == ',' || c == ':' || c.is_whitespace());
+                    Some((parse_tag_id(id)?, path))
+                });
+            match entry {
+                Some((id, path)) if !path.is_empty() => self.insert(id, path),
+                _ => {
+                    self.insert_path(line);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds a tag path to the database under the given global tag id.
+    pub fn insert(&mut self, tag_id: TagId, path: &str) {
+        self.paths.insert(tag_id, path.to_string());
+    }
+
+    /// Hashes a tag path and adds it to the database, returning its global tag id.
+    pub fn insert_path(&mut self, path: &str) -> TagId {
+        let tag_id = compute_tag_id(path);
+        self.insert(tag_id, path);
+        tag_id
+    }
+
+    /// Gets the path of a tag, if present in the database.
+    #[must_use]
+    pub fn get(&self, tag_id: TagId) -> Option<&str> {
+        self.paths.get(&tag_id).map(String::as_str)
+    }
+
+    /// Number of tag paths in the database.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Whether the database contains no tag paths.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env::temp_dir, fs::remove_file, sync::Arc};
+
+    use super::*;
+    use crate::ModuleFile;
+    use crate::common::options::ParseOptions;
+    use crate::module::header::ModuleVersion;
+    use crate::module::writer::{ModuleWriter, ModuleWriterEntry};
+
+    #[test]
+    /// Verifies that tag lists are parsed in every supported layout.
+    fn test_extend_from_list() {
+        let mut database = TagNameDatabase::new();
+        database
+            .extend_from_list(&b"0x0000CA7F, a.bitm\n-2 : b.bitm\n\n  c.bitm  \n"[..])
+            .unwrap();
+        assert_eq!(database.len(), 3);
+        assert_eq!(database.get(TagId(0xCA7F)), Some("a.bitm"));
+        assert_eq!(database.get(TagId(-2)), Some("b.bitm"));
+        assert_eq!(database.get(compute_tag_id("c.bitm")), Some("c.bitm"));
+    }
+
+    #[test]
+    /// Verifies that tag names are resolved from the database when reading a module.
+    fn test_read_with_tag_names() {
+        let mut writer = ModuleWriter::new(ModuleVersion::Season3);
+        for tag_id in [TagId(0x1234), TagId(0x5678)] {
+            writer.add_file(ModuleWriterEntry {
+                tag_group: String::from("bitm"),
+                tag_id,
+                parent_index: -1,
+                is_raw_file: true,
+                data: vec![0xCA, 0x7F],
+                ..Default::default()
+            });
+        }
+        let path = temp_dir().join("infinite-rs-tag-names.module");
+        writer.write_to_path(&path).unwrap();
+
+        let mut database = TagNameDatabase::new();
+        database.insert(TagId(0x1234), "objects/cat.bitmap");
+        let options = ParseOptions {
+            tag_names: Some(Arc::new(database)),
+            ..Default::default()
+        };
+        let module = ModuleFile::from_path_with_options(&path, options).unwrap();
+        remove_file(path).unwrap();
+        assert_eq!(module.files[0].tag_name, "objects/cat.bitmap");
+        assert_eq!(module.files[1].tag_name, "bitm/22136.bitm");
+    }
+}
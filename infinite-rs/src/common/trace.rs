@@ -0,0 +1,202 @@
+//! Reader wrapper recording the most recent reads, to find where parsing failed.
+//!
+//! Errors such as "failed to fill whole buffer" do not say which read failed. Wrapping the reader
+//! in a [`TracingReader`] keeps a log of the last reads (their offset, length and an optional tag
+//! describing what was being read), which can be printed once an error occurs.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Display, Write as _};
+use std::io::{self, BufRead, Read, Seek, SeekFrom};
+
+use super::extensions::{BufReaderExt, Endianness};
+use crate::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A single read recorded by a [`TracingReader`].
+pub struct ReadOperation {
+    /// Offset of the read from the start of the stream.
+    pub offset: u64,
+    /// Number of bytes read.
+    pub length: usize,
+    /// Tag set with [`set_tag`](`TracingReader::set_tag`) when the read happened.
+    pub tag: Option<&'static str>,
+}
+
+impl Display for ReadOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#010X} +{:#X}", self.offset, self.length)?;
+        if let Some(tag) = self.tag {
+            write!(f, " ({tag})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Reader recording the last reads made through it.
+///
+/// Only the most recent `capacity` reads are kept. Reads of zero bytes are not recorded, and a
+/// read that fails part way is recorded with the number of bytes that were available.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+/// use infinite_rs::common::extensions::BufReaderExt;
+/// use infinite_rs::common::trace::TracingReader;
+///
+/// let mut reader = TracingReader::new(Cursor::new([0_u8; 6]), 16).unwrap();
+/// reader.set_tag(Some("header"));
+/// reader.read_u32_ordered().unwrap();
+/// reader.set_tag(Some("count"));
+/// if let Err(error) = reader.read_u32_ordered() {
+///     eprintln!("{error}\n{}", reader.dump());
+/// }
+/// assert_eq!(reader.operations().next().unwrap().tag, Some("header"));
+/// ```
+pub struct TracingReader<R> {
+    inner: R,
+    capacity: usize,
+    operations: VecDeque<ReadOperation>,
+    tag: Option<&'static str>,
+    /// Position of the inner reader, tracked to avoid querying it on every read.
+    position: u64,
+}
+
+impl<R: BufReaderExt> TracingReader<R> {
+    /// Wraps a reader, keeping the last `capacity` reads.
+    ///
+    /// # Errors
+    /// - If the position of the reader cannot be read [`ReadError`](`crate::Error::ReadError`)
+    pub fn new(mut reader: R, capacity: usize) -> Result<Self> {
+        let position = reader.stream_position()?;
+        Ok(Self {
+            inner: reader,
+            capacity,
+            operations: VecDeque::with_capacity(capacity),
+            tag: None,
+            position,
+        })
+    }
+}
+
+impl<R> TracingReader<R> {
+    /// Sets the tag recorded with the following reads, such as the name of the structure or
+    /// field being read.
+    pub fn set_tag(&mut self, tag: Option<&'static str>) {
+        self.tag = tag;
+    }
+
+    /// Recorded reads, from oldest to most recent.
+    pub fn operations(&self) -> impl Iterator<Item = &ReadOperation> {
+        self.operations.iter()
+    }
+
+    /// Formats the recorded reads, one per line, from oldest to most recent.
+    #[must_use]
+    pub fn dump(&self) -> String {
+        let mut dump = String::new();
+        for operation in &self.operations {
+            let _ = writeln!(dump, "{operation}");
+        }
+        dump
+    }
+
+    /// Clears the recorded reads.
+    pub fn clear(&mut self) {
+        self.operations.clear();
+    }
+
+    /// Unwraps the reader, discarding the recorded reads.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn record(&mut self, length: usize) {
+        if length == 0 {
+            return;
+        }
+        if self.capacity > 0 {
+            if self.operations.len() == self.capacity {
+                self.operations.pop_front();
+            }
+            self.operations.push_back(ReadOperation {
+                offset: self.position,
+                length,
+                tag: self.tag,
+            });
+        }
+        self.position += length as u64;
+    }
+}
+
+impl<R: Read> Read for TracingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.record(read);
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for TracingReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.inner.consume(amount);
+        self.record(amount);
+    }
+}
+
+impl<R: Seek> Seek for TracingReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = self.inner.seek(pos)?;
+        Ok(self.position)
+    }
+}
+
+impl<R: BufReaderExt> BufReaderExt for TracingReader<R> {
+    fn endianness(&self) -> Endianness {
+        self.inner.endianness()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    /// Verifies that only the most recent reads are kept, with their offsets and tags.
+    fn test_tracing_reader() {
+        let mut reader = TracingReader::new(Cursor::new([0_u8; 0x10]), 2).unwrap();
+        reader.read_u32_ordered().unwrap();
+        reader.set_tag(Some("value"));
+        reader.seek(SeekFrom::Start(0x8)).unwrap();
+        reader.read_u16_ordered().unwrap();
+        reader.read_null_terminated_string().unwrap();
+        assert!(reader.read_u64_ordered().is_err());
+
+        let operations: Vec<_> = reader.operations().copied().collect();
+        assert_eq!(
+            operations,
+            [
+                ReadOperation {
+                    offset: 0xA,
+                    length: 1,
+                    tag: Some("value"),
+                },
+                // The failed read stopped at the end of the stream.
+                ReadOperation {
+                    offset: 0xB,
+                    length: 5,
+                    tag: Some("value"),
+                },
+            ]
+        );
+        assert_eq!(
+            reader.dump(),
+            "0x0000000A +0x1 (value)\n0x0000000B +0x5 (value)\n"
+        );
+    }
+}
@@ -10,6 +10,10 @@
 #![allow(clippy::missing_errors_doc)]
 #![allow(clippy::module_name_repetitions)]
 #![allow(rustdoc::private_intra_doc_links)]
+// `deluxe`, used by the derive macro, depends on an older `proc-macro-crate` than `num_enum`.
+#![allow(clippy::multiple_crate_versions)]
+// `no-kraken` has to be additive to override `kraken` enabled by another crate.
+#![allow(clippy::negative_feature_names)]
 /*!
 Simple and fast deserialization library for Halo Infinite.
 
@@ -157,11 +161,25 @@ struct ExampleStruct {
 }
 ```
 
+### Building without Kraken
+Kraken (de)compression is provided by a C++ library compiled by the build script, through the `kraken` feature which is enabled by default. The `kraken-rs` feature decompresses with a pure-Rust port of the decoder instead, so that `infinite-rs` builds without a C++ toolchain (compressing still requires `kraken`):
+
+```toml
+[dependencies]
+infinite-rs = { version = "0.13", default-features = false, features = ["kraken-rs"] }
+```
+
+Crates that only read uncompressed blocks or pre-extracted tags can also leave out `kraken-rs`. Reading a compressed block then fails with `DecompressionError::CompressionUnavailable`.
+
+When another dependency enables `kraken` and the C++ toolchain is missing, enabling the `no-kraken` feature skips building the C++ library and turns off both decompressors, even though `kraken` stays enabled.
+
 ## Credits
 - [libinfinite](https://github.com/Coreforge/libInfinite) by Coreforge, which this project is mostly based on.
 - [Reclaimer](https://github.com/Gravemind2401/Reclaimer) by Gravemind2401, which helped me get familiar with Blam file formats.
 - [AusarDocs](https://github.com/ElDewrito/AusarDocs) by Shockfire, a very useful resource on Ausar/Slipspace file formats.
 - [Kraken](https://github.com/WolvenKit/kraken) by Wolvenkit team, a re-implementation of Oodle Kraken, removing the need for any binary blobs being required for decompression.
+- [oozextract](https://github.com/lvlvllvlvllvlvl/oozextract), a pure-Rust Kraken decompressor used by the `kraken-rs` feature.
+- [bcdec_rs](https://github.com/ScanMountGoat/bcdec_rs), a Rust port of bcdec used by the `bcn` feature to decode BC6H and BC7 textures.
 - [TagFramework](https://github.com/Codename-Atriox/TagFramework) by Codename Atriox, which was a common reference point for Slipspace internals.
 - [red4lib](https://github.com/rfuzzo/red4lib) by rfuzzo, acting as the main inspiration for this project.
 - [HIRT](https://github.com/urium1186/HIRT) by urium1186, which was very useful in debugging and verifying output from this project.
@@ -175,10 +193,13 @@ pub mod tag;
 #[doc(inline)]
 pub use crate::common::errors::{Error, Result};
 #[doc(inline)]
+pub use crate::common::tag_id::TagId;
+#[doc(inline)]
 pub use crate::{module::loader::ModuleFile, tag::loader::TagFile};
 
-#[cfg(feature = "derive")]
-extern crate infinite_rs_derive;
+// Lets the tag definitions of this crate derive `TagStructure`, as the generated code refers to
+// `infinite_rs`.
+extern crate self as infinite_rs;
 
 #[cfg(feature = "derive")]
 pub use infinite_rs_derive::TagStructure;
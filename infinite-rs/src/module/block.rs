@@ -1,13 +1,15 @@
 //! Module block containing info relating to Kraken compression.
 
-use byteorder::{LE, ReadBytesExt};
-use std::io::BufRead;
+use byteorder::{LE, ReadBytesExt, WriteBytesExt};
+use std::io::{BufRead, Write};
 
 use crate::Result;
 use crate::common::errors::ModuleError;
-use crate::common::extensions::Enumerable;
+use crate::common::extensions::{BufReaderExt, Enumerable};
+use crate::common::options::Warning;
 
 #[derive(Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// Represents a module block entry containing information related to Kraken compression.
 /// This struct is used to determine how to read bytes in [`ModuleFileEntry`](`super::file::ModuleFileEntry`).
 pub(crate) struct ModuleBlockEntry {
@@ -38,3 +40,78 @@ impl Enumerable for ModuleBlockEntry {
         Ok(())
     }
 }
+
+impl ModuleBlockEntry {
+    /// Reads `count` block entries, validating each one like [`Enumerable::read`].
+    ///
+    /// When `strict` is disabled, entries with an `is_compressed` value other than 0 or 1 are
+    /// treated as compressed, and a [`Warning::IncorrectCompressedValue`] is recorded instead.
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    /// - If an entry has an `is_compressed` value other than 0 or 1 [`ModuleError::IncorrectCompressedValue`]
+    pub(super) fn read_table<R: BufReaderExt>(
+        reader: &mut R,
+        count: u64,
+        strict: bool,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<Vec<Self>> {
+        #[cfg(feature = "bytemuck")]
+        let entries = reader
+            .read_pod_slice::<[u32; 5]>(usize::try_from(count)?)?
+            .into_iter()
+            .map(|entry| entry.map(u32::from_le))
+            .collect::<Vec<_>>();
+        #[cfg(not(feature = "bytemuck"))]
+        let entries = (0..count)
+            .map(|_| -> Result<[u32; 5]> {
+                let mut entry = [0; 5];
+                reader.read_u32_into::<LE>(&mut entry)?;
+                Ok(entry)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        entries
+            .into_iter()
+            .enumerate()
+            .map(|(block_index, entry)| {
+                let [
+                    compressed_offset,
+                    compressed_size,
+                    decompressed_offset,
+                    decompressed_size,
+                    is_compressed,
+                ] = entry;
+                if is_compressed != 0 && is_compressed != 1 {
+                    if strict {
+                        return Err(ModuleError::IncorrectCompressedValue.into());
+                    }
+                    warnings.push(Warning::IncorrectCompressedValue {
+                        block_index,
+                        value: is_compressed,
+                    });
+                }
+                Ok(Self {
+                    compressed_offset,
+                    compressed_size,
+                    decompressed_offset,
+                    decompressed_size,
+                    is_compressed: is_compressed != 0,
+                })
+            })
+            .collect()
+    }
+
+    /// Writes the block entry to the given writer, mirroring [`Enumerable::read`].
+    ///
+    /// # Errors
+    /// - If the writer fails to write the structure [`ReadError`](`crate::Error::ReadError`)
+    pub(super) fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32::<LE>(self.compressed_offset)?;
+        writer.write_u32::<LE>(self.compressed_size)?;
+        writer.write_u32::<LE>(self.decompressed_offset)?;
+        writer.write_u32::<LE>(self.decompressed_size)?;
+        writer.write_u32::<LE>(u32::from(self.is_compressed))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,213 @@
+//! On-disk cache of decompressed tags.
+//!
+//! Decompressing the blocks of large tags (such as bitmaps and render models) dominates the time
+//! spent reading modules. A [`TagCache`] stores the decompressed data of every tag read, keyed by
+//! the id of its module, its global tag id and its asset hash, so that later runs over the same
+//! modules skip decompression entirely. When set in
+//! [`ParseOptions::tag_cache`](`crate::common::options::ParseOptions::tag_cache`), it is used by
+//! [`ModuleFile::read_tag`](`super::loader::ModuleFile::read_tag`).
+//!
+//! Entries of a module are removed when it is opened with a different build version, as game
+//! updates can change the contents of a tag without changing its asset hash.
+
+use std::{
+    fs::{create_dir_all, read, read_to_string, remove_dir_all, rename, write},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process,
+};
+
+use crate::{Result, TagId};
+
+/// Name of the file storing the build version of the entries of a module.
+const BUILD_VERSION_FILE: &str = "build_version";
+
+#[derive(Debug, Clone)]
+/// Directory storing decompressed tags.
+///
+/// Entries are laid out as `{module_id}/{tag_id}_{asset_hash}.bin` inside the directory, with all
+/// ids written in hexadecimal. Files without a global tag id (such as resources) are not cached,
+/// as they cannot be told apart by their key.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use infinite_rs::ModuleFile;
+/// use infinite_rs::common::options::ParseOptions;
+/// use infinite_rs::module::cache::TagCache;
+///
+/// let options = ParseOptions {
+///     tag_cache: Some(Arc::new(TagCache::new("cache").unwrap())),
+///     ..Default::default()
+/// };
+/// let mut module = ModuleFile::from_path_with_options("deploy/any/globals-rtx-new.module", options).unwrap();
+/// // Decompressed on the first run, read from the cache on the next ones.
+/// module.read_tag(0).unwrap();
+/// ```
+pub struct TagCache {
+    /// Root directory of the cache.
+    directory: PathBuf,
+}
+
+impl TagCache {
+    /// Opens a cache stored in the given directory, creating it if it does not exist.
+    ///
+    /// # Errors
+    /// - If the directory cannot be created [`ReadError`](`crate::Error::ReadError`)
+    pub fn new<T: AsRef<Path>>(directory: T) -> Result<Self> {
+        let directory = directory.as_ref().to_path_buf();
+        create_dir_all(&directory)?;
+        Ok(Self { directory })
+    }
+
+    /// Root directory of the cache.
+    #[must_use]
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Gets the directory storing the entries of a module.
+    fn module_directory(&self, module_id: i64) -> PathBuf {
+        self.directory.join(format!("{module_id:016X}"))
+    }
+
+    /// Gets the path of the entry of a tag.
+    fn entry_path(&self, module_id: i64, tag_id: TagId, asset_hash: i128) -> PathBuf {
+        self.module_directory(module_id)
+            .join(format!("{:08X}_{asset_hash:032X}.bin", tag_id.0))
+    }
+
+    /// Removes the entries of a module if they were stored for a different build version.
+    ///
+    /// Called by [`ModuleFile`](`super::loader::ModuleFile`) after reading the module header.
+    /// Returns whether entries were removed.
+    ///
+    /// # Errors
+    /// - If the entries cannot be removed, or the build version cannot be stored [`ReadError`](`crate::Error::ReadError`)
+    pub fn validate(&self, module_id: i64, build_version: u64) -> Result<bool> {
+        let directory = self.module_directory(module_id);
+        let marker = directory.join(BUILD_VERSION_FILE);
+        let build_version = format!("{build_version:016X}");
+        let is_stale = match read_to_string(&marker) {
+            Ok(stored) if stored.trim() == build_version => return Ok(false),
+            Ok(_) => true,
+            Err(error) if error.kind() == ErrorKind::NotFound => directory.exists(),
+            Err(error) => return Err(error.into()),
+        };
+        if is_stale {
+            remove_dir_all(&directory)?;
+        }
+        create_dir_all(&directory)?;
+        write(marker, build_version)?;
+        Ok(is_stale)
+    }
+
+    /// Reads the decompressed data of a tag, if present in the cache.
+    ///
+    /// # Errors
+    /// - If the entry exists but cannot be read [`ReadError`](`crate::Error::ReadError`)
+    pub fn get(&self, module_id: i64, tag_id: TagId, asset_hash: i128) -> Result<Option<Vec<u8>>> {
+        if tag_id.is_null() {
+            return Ok(None);
+        }
+        match read(self.entry_path(module_id, tag_id, asset_hash)) {
+            Ok(data) => Ok(Some(data)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Stores the decompressed data of a tag.
+    ///
+    /// The data is written to a temporary file first, so that concurrent runs never read a
+    /// partially written entry.
+    ///
+    /// # Errors
+    /// - If the entry cannot be written [`ReadError`](`crate::Error::ReadError`)
+    pub fn insert(
+        &self,
+        module_id: i64,
+        tag_id: TagId,
+        asset_hash: i128,
+        data: &[u8],
+    ) -> Result<()> {
+        if tag_id.is_null() {
+            return Ok(());
+        }
+        let path = self.entry_path(module_id, tag_id, asset_hash);
+        let temporary = path.with_extension(format!("{}.tmp", process::id()));
+        create_dir_all(self.module_directory(module_id))?;
+        write(&temporary, data)?;
+        rename(temporary, path)?;
+        Ok(())
+    }
+
+    /// Removes every entry of the cache.
+    ///
+    /// # Errors
+    /// - If the entries cannot be removed [`ReadError`](`crate::Error::ReadError`)
+    pub fn clear(&self) -> Result<()> {
+        remove_dir_all(&self.directory)?;
+        create_dir_all(&self.directory)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env::temp_dir, fs::remove_file, sync::Arc};
+
+    use super::*;
+    use crate::ModuleFile;
+    use crate::common::options::ParseOptions;
+    use crate::module::header::ModuleVersion;
+    use crate::module::writer::{ModuleWriter, ModuleWriterEntry};
+
+    /// Writes a module containing a single raw file, and reads it back through the cache.
+    fn read_through_cache(cache: &Arc<TagCache>, build_version: u64) -> Vec<u8> {
+        let mut writer = ModuleWriter::new(ModuleVersion::Season3);
+        writer.module_id = 0x0123_4567;
+        writer.build_version = build_version;
+        writer.add_file(ModuleWriterEntry {
+            tag_group: String::from("bitm"),
+            tag_id: TagId(0x1234),
+            parent_index: -1,
+            asset_hash: 0xCA7F,
+            is_raw_file: true,
+            data: vec![0xCA, 0x7F],
+            ..Default::default()
+        });
+        let path = temp_dir().join("infinite-rs-tag-cache.module");
+        writer.write_to_path(&path).unwrap();
+
+        let options = ParseOptions {
+            tag_cache: Some(Arc::clone(cache)),
+            ..Default::default()
+        };
+        let mut module = ModuleFile::from_path_with_options(&path, options).unwrap();
+        let data = module
+            .read_tag(0)
+            .unwrap()
+            .unwrap()
+            .get_raw_data(true)
+            .unwrap();
+        remove_file(path).unwrap();
+        data
+    }
+
+    #[test]
+    /// Verifies that cached tags are read from the cache, and dropped when the build changes.
+    fn test_cache_invalidation() {
+        let cache = Arc::new(TagCache::new(temp_dir().join("infinite-rs-tag-cache")).unwrap());
+        cache.clear().unwrap();
+        assert_eq!(read_through_cache(&cache, 1), vec![0xCA, 0x7F]);
+
+        let entry = cache.entry_path(0x0123_4567, TagId(0x1234), 0xCA7F);
+        assert_eq!(read(&entry).unwrap(), vec![0xCA, 0x7F]);
+        write(&entry, [0xAB, 0xCD]).unwrap();
+        assert_eq!(read_through_cache(&cache, 1), vec![0xAB, 0xCD]);
+        assert_eq!(read_through_cache(&cache, 2), vec![0xCA, 0x7F]);
+        remove_dir_all(cache.directory()).unwrap();
+    }
+}
@@ -0,0 +1,167 @@
+//! Detection of the codec used by compressed blocks, and additional codecs supplied by the user.
+//!
+//! Every compressed block in current builds of the game uses Oodle Kraken, decompressed by
+//! [`decompress`](`super::kraken::decompress`). Decoders for other formats can be added to
+//! [`ParseOptions::codecs`](`crate::common::options::ParseOptions::codecs`), and are used for the
+//! blocks they [match](`Codec::matches`) before falling back to Kraken.
+
+use std::fmt::{self, Debug};
+use std::sync::Arc;
+
+use crate::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+/// Compression format of a block, detected by [`detect_codec`].
+pub enum CodecKind {
+    /// Oodle block header, used by Kraken and the other Oodle compressors.
+    Oodle,
+    /// Unrecognized data, which is assumed to be Kraken.
+    Unknown,
+}
+
+/// Detects the compression format of a block from its first bytes.
+///
+/// Oodle blocks start with a header byte whose low nibble is always `0xC`, and whose bits 4 and 5
+/// are reserved and zero. Bit 6 marks uncompressed chunks and bit 7 resets the decoder. The
+/// layout follows `Kraken_ParseHeader` from [ooz](https://github.com/powzix/ooz).
+///
+/// # Examples
+///
+/// ```
+/// use infinite_rs::module::codec::{CodecKind, detect_codec};
+///
+/// assert_eq!(detect_codec(&[0x8C, 0x06, 0x00]), CodecKind::Oodle);
+/// assert_eq!(detect_codec(&[0x28, 0xB5, 0x2F, 0xFD]), CodecKind::Unknown);
+/// ```
+#[must_use]
+pub fn detect_codec(compressed: &[u8]) -> CodecKind {
+    if compressed
+        .first()
+        .is_some_and(|&header| header & 0x0F == 0x0C && header & 0x30 == 0)
+    {
+        CodecKind::Oodle
+    } else {
+        CodecKind::Unknown
+    }
+}
+
+/// Decoder for a compression format, added to
+/// [`ParseOptions::codecs`](`crate::common::options::ParseOptions::codecs`).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use infinite_rs::{ModuleFile, Result};
+/// use infinite_rs::common::options::ParseOptions;
+/// use infinite_rs::module::codec::Codec;
+///
+/// /// Blocks starting with `RAW!`, followed by the uncompressed data.
+/// struct RawCodec;
+///
+/// impl Codec for RawCodec {
+///     fn name(&self) -> &'static str {
+///         "raw"
+///     }
+///
+///     fn matches(&self, compressed: &[u8]) -> bool {
+///         compressed.starts_with(b"RAW!")
+///     }
+///
+///     fn decompress(&self, compressed: &[u8], output: &mut Vec<u8>, _size: usize) -> Result<()> {
+///         output.clear();
+///         output.extend_from_slice(&compressed[4..]);
+///         Ok(())
+///     }
+/// }
+///
+/// let options = ParseOptions {
+///     codecs: vec![Arc::new(RawCodec)],
+///     ..Default::default()
+/// };
+/// let module = ModuleFile::from_path_with_options("deploy/any/globals-rtx-new.module", options).unwrap();
+/// ```
+pub trait Codec: Send + Sync {
+    /// Name of the codec, such as `lz4`.
+    fn name(&self) -> &'static str;
+
+    /// Whether the compressed data is in the format of this codec, usually by checking a magic.
+    fn matches(&self, compressed: &[u8]) -> bool;
+
+    /// Decompresses a block into `output`, replacing its contents.
+    ///
+    /// # Arguments
+    ///
+    /// * `compressed` - The compressed data of the block.
+    /// * `output` - Buffer receiving the decompressed data, whose allocation can be reused.
+    /// * `size` - The expected size of the decompressed data.
+    ///
+    /// # Errors
+    /// - If the data cannot be decompressed, with any [`Error`](`crate::Error`)
+    fn decompress(&self, compressed: &[u8], output: &mut Vec<u8>, size: usize) -> Result<()>;
+}
+
+impl Debug for dyn Codec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Codec").field(&self.name()).finish()
+    }
+}
+
+/// Decompresses a block with the first codec matching it, if any.
+pub(super) fn decompress_with_codecs(
+    codecs: &[Arc<dyn Codec>],
+    compressed: &[u8],
+    output: &mut Vec<u8>,
+    size: usize,
+) -> Option<Result<()>> {
+    let codec = codecs.iter().find(|codec| codec.matches(compressed))?;
+    Some(codec.decompress(compressed, output, size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::kraken::DecompressionContext;
+
+    /// Blocks starting with `TEST`, followed by the uncompressed data.
+    struct TestCodec;
+
+    impl Codec for TestCodec {
+        fn name(&self) -> &'static str {
+            "test"
+        }
+
+        fn matches(&self, compressed: &[u8]) -> bool {
+            compressed.starts_with(b"TEST")
+        }
+
+        fn decompress(&self, compressed: &[u8], output: &mut Vec<u8>, _size: usize) -> Result<()> {
+            output.clear();
+            output.extend_from_slice(&compressed[4..]);
+            Ok(())
+        }
+    }
+
+    #[test]
+    /// Verifies that Oodle block headers are detected, and reserved bits are rejected.
+    fn test_detect_codec() {
+        // Kraken block resetting the decoder, as found at the start of module blocks.
+        assert_eq!(detect_codec(&[0x8C, 0x06]), CodecKind::Oodle);
+        // Uncompressed chunk.
+        assert_eq!(detect_codec(&[0xCC, 0x06]), CodecKind::Oodle);
+        assert_eq!(detect_codec(&[0x9C, 0x06]), CodecKind::Unknown);
+        assert_eq!(detect_codec(&[0x8D, 0x06]), CodecKind::Unknown);
+        assert_eq!(detect_codec(&[]), CodecKind::Unknown);
+    }
+
+    #[test]
+    /// Verifies that blocks are routed to the codecs they match.
+    fn test_codec_dispatch() {
+        let codecs: Vec<Arc<dyn Codec>> = vec![Arc::new(TestCodec)];
+        let mut context = DecompressionContext::new();
+        let output = unsafe { context.decompress(&codecs, b"TESTdata", 4) }.unwrap();
+        assert_eq!(output, b"data");
+        assert_eq!(format!("{:?}", codecs[0]), "Codec(\"test\")");
+    }
+}
@@ -0,0 +1,172 @@
+//! Collection of modules that tags can be looked up in.
+//!
+//! Tags reference each other by their global ID, and the referenced tag is usually stored in
+//! another module. [`ModuleCollection`] holds every module of interest so that references can be
+//! resolved without knowing which module contains them.
+
+use std::path::Path;
+
+use super::{file::ModuleFileEntry, loader::ModuleFile};
+use crate::{Result, TagId};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// Location of a tag inside a [`ModuleCollection`].
+pub struct TagHandle {
+    /// Index of the module in [`modules`](`ModuleCollection::modules`).
+    pub module_index: usize,
+    /// Index of the tag in the [`files`](`ModuleFile::files`) of the module.
+    pub file_index: u32,
+}
+
+#[derive(Default, Debug)]
+/// Collection of modules, used to find and load tags by their global ID.
+///
+/// # Examples
+///
+/// ```no_run
+/// use infinite_rs::TagId;
+/// use infinite_rs::module::collection::ModuleCollection;
+///
+/// let mut modules = ModuleCollection::from_paths([
+///     "C:/XboxGames/Halo Infinite/Content/deploy/any/globals-rtx-new.module",
+///     "C:/XboxGames/Halo Infinite/Content/deploy/pc/globals-rtx-new.module",
+/// ])
+/// .unwrap();
+/// if let Some(handle) = modules.find_tag(TagId(0x1234_5678)) {
+///     let tag = modules.read_tag(handle).unwrap();
+/// }
+/// ```
+pub struct ModuleCollection {
+    /// Modules in the collection, searched in order.
+    pub modules: Vec<ModuleFile>,
+}
+
+impl ModuleCollection {
+    /// Creates an empty collection.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a collection from the modules at the given paths.
+    ///
+    /// # Errors
+    /// - If any module fails to be read, see [`ModuleFile::read`]
+    pub fn from_paths<I, P>(paths: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let modules = paths
+            .into_iter()
+            .map(ModuleFile::from_path)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { modules })
+    }
+
+    /// Adds a module to the collection, returning its index.
+    pub fn add(&mut self, module: ModuleFile) -> usize {
+        self.modules.push(module);
+        self.modules.len() - 1
+    }
+
+    /// Finds a tag by its global ID.
+    ///
+    /// Tags can be stored in multiple modules, in which case the first module containing it is used.
+    /// Returns [`None`] for null references ([`TagId::NULL`]) or if no module contains the tag.
+    #[must_use]
+    pub fn find_tag(&self, global_id: TagId) -> Option<TagHandle> {
+        if global_id.is_null() {
+            return None;
+        }
+        self.modules
+            .iter()
+            .enumerate()
+            .find_map(|(module_index, module)| {
+                let file_index = module
+                    .files
+                    .iter()
+                    .position(|file| file.tag_id == global_id)?;
+                Some(TagHandle {
+                    module_index,
+                    file_index: u32::try_from(file_index).ok()?,
+                })
+            })
+    }
+
+    /// Gets the file entry of a tag, without loading it.
+    #[must_use]
+    pub fn get(&self, handle: TagHandle) -> Option<&ModuleFileEntry> {
+        self.modules
+            .get(handle.module_index)?
+            .files
+            .get(handle.file_index as usize)
+    }
+
+    /// Loads a tag from the module containing it.
+    ///
+    /// See [`ModuleFile::read_tag`].
+    ///
+    /// # Errors
+    /// - If reading the tag fails, see [`ModuleFile::read_tag`]
+    pub fn read_tag(&mut self, handle: TagHandle) -> Result<Option<&mut ModuleFileEntry>> {
+        match self.modules.get_mut(handle.module_index) {
+            Some(module) if (handle.file_index as usize) < module.files.len() => {
+                module.read_tag(handle.file_index)
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env::temp_dir, fs::remove_file};
+
+    use super::*;
+    use crate::module::header::ModuleVersion;
+    use crate::module::writer::{ModuleWriter, ModuleWriterEntry};
+    use crate::tag::types::common_types::FieldReference;
+
+    /// Writes a module containing a single raw file and reads it back.
+    fn module_with_tag(tag_id: TagId, name: &str) -> ModuleFile {
+        let mut writer = ModuleWriter::new(ModuleVersion::Season3);
+        writer.add_file(ModuleWriterEntry {
+            tag_group: String::from("bitm"),
+            tag_id,
+            parent_index: -1,
+            is_raw_file: true,
+            data: vec![0xCA, 0x7F],
+            ..Default::default()
+        });
+        let path = temp_dir().join(format!("infinite-rs-{name}.module"));
+        writer.write_to_path(&path).unwrap();
+        let module = ModuleFile::from_path(&path).unwrap();
+        remove_file(path).unwrap();
+        module
+    }
+
+    #[test]
+    /// Verifies that references are resolved to the module containing the tag.
+    fn test_resolve_reference() {
+        let mut modules = ModuleCollection::new();
+        modules.add(module_with_tag(TagId(0x1234), "collection-first"));
+        modules.add(module_with_tag(TagId(0x5678), "collection-second"));
+
+        let mut reference = FieldReference::default();
+        reference.global_id = TagId(0x5678);
+        let handle = reference.resolve(&mut modules).unwrap().unwrap();
+        assert_eq!(
+            handle,
+            TagHandle {
+                module_index: 1,
+                file_index: 0,
+            }
+        );
+        assert!(modules.get(handle).unwrap().is_loaded);
+
+        reference.global_id = TagId::NULL;
+        assert!(reference.resolve(&mut modules).unwrap().is_none());
+    }
+}
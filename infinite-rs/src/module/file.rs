@@ -1,21 +1,32 @@
 //! Module file entry containing metadata relating to tags and functions to read them.
 
 use bitflags::bitflags;
-use byteorder::{LE, ReadBytesExt};
+use byteorder::{LE, ReadBytesExt, WriteBytesExt};
+#[cfg(feature = "parallel")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::any::{Any, type_name};
 use std::collections::HashMap;
 use std::fmt::Debug;
-use std::{
-    fs::File,
-    io::{BufReader, Cursor, Read, Seek, SeekFrom},
-};
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom, Write};
+use std::sync::Arc;
 
+use super::block::ModuleBlockEntry;
+use super::codec::Codec;
 use super::header::ModuleVersion;
-use super::{block::ModuleBlockEntry, kraken::decompress};
-use crate::common::errors::{ModuleError, TagError};
-use crate::tag::datablock::TagDataBlock;
-use crate::tag::structure::TagStructType;
-use crate::{Error, Result};
-use crate::{common::extensions::BufReaderExt, tag::loader::TagFile};
+use super::kraken::DecompressionContext;
+use crate::common::errors::{ErrorContext, ModuleError, ResultExt, TagError};
+use crate::common::extensions::{BufReaderExt, WriterExt};
+use crate::common::options::ParseOptions;
+use crate::tag::diff::{TagDifference, diff};
+use crate::tag::dynamic::DynamicStruct;
+use crate::tag::editor::TagEditor;
+use crate::tag::group::{TagGroupCategory, tag_group_category, tag_group_name};
+use crate::tag::loader::TagFile;
+use crate::tag::provenance::FieldProvenance;
+use crate::tag::registry::StructRegistry;
+use crate::tag::types::common_types::{LazyFieldBlock, LazyFieldData};
+use crate::tag::validation::LayoutMismatch;
+use crate::{Error, Result, TagId};
 
 /// Trait for defining tag structures.
 ///
@@ -28,8 +39,18 @@ use crate::{common::extensions::BufReaderExt, tag::loader::TagFile};
 /// For each of its fields, the following attributes are required:
 /// - `#[data(offset())]` - The offset in bytes from the start of the tag structure.
 ///
+/// Some field types require an additional attribute:
+/// - `#[data(count())]` - The number of elements of a [`FieldArray`](`crate::tag::types::common_types::FieldArray`).
+/// - `#[data(length())]` - The length in bytes of a [`FieldPad`](`crate::tag::types::common_types::FieldPad`).
+///
 /// Any padding between fields should be accounted for in the offset.
 ///
+/// The derive macro also supports inline and boxed structures, generic structs, enums and
+/// bitflags, raw bytes, `Option` fields, skipped fields, per-version fields, automatic offsets,
+/// GUID checks and layout tests. These are described in the documentation of the derive macro.
+///
+/// The layout of the fields is available at runtime through [`fields`](`TagStructure::fields`).
+///
 /// # Examples
 ///
 /// ```rust
@@ -50,16 +71,60 @@ use crate::{common::extensions::BufReaderExt, tag::loader::TagFile};
 ///    let mut material = file_entry.read_metadata::<MaterialTag>().unwrap();
 ///
 ///    assert_eq!(material.size(), 0x30);
-///    assert_eq!(material.offsets().get("any_tag"), Some(&0x00));
+///    assert_eq!(material.offset_of("any_tag"), Some(0x00));
 /// }
+/// ```
 pub trait TagStructure {
     /// Returns the size of the tag structure in bytes.
     /// Determined by the [data(size())] attribute.
     fn size(&mut self) -> u64;
     /// Function that calls all [`read`](`crate::common::extensions::Enumerable::read`) functions for each field in the tag structure.
-    fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()>;
+    ///
+    /// Fields that are only present in some module versions are skipped if they are not present
+    /// in `version`.
+    fn read<R: BufReaderExt>(&mut self, reader: &mut R, version: &ModuleVersion) -> Result<()>;
+    /// Writes each field of the tag structure back at its offset, and seeks to the end of the
+    /// structure.
+    ///
+    /// Numbers are written using the [`endianness`](`WriterExt::endianness`) of the writer. Bytes
+    /// not covered by a field are left unchanged, so writing over the original data of a structure only updates its fields.
+    /// Elements of blocks, data and resources are stored in other data blocks, and are not written.
+    ///
+    /// The derive macro only generates this for structures marked with `#[data(write)]`.
+    ///
+    /// # Errors
+    /// - If the structure does not support writing [`TagError::WriteUnsupported`]
+    /// - If the writer fails to write the fields [`ReadError`](`crate::Error::ReadError`)
+    fn write<W: WriterExt>(&self, _writer: &mut W, _version: &ModuleVersion) -> Result<()> {
+        Err(TagError::WriteUnsupported(type_name::<Self>()).into())
+    }
+    /// Returns the layout of the fields of the tag structure, in order of their offsets.
+    fn fields(&self) -> &'static [FieldDescriptor];
+    /// Returns the offset of a field in the tag structure, or [`None`] if there is no field with
+    /// that name.
+    fn offset_of(&self, name: &str) -> Option<u64> {
+        self.fields()
+            .iter()
+            .find(|field| field.name == name)
+            .map(|field| field.offset)
+    }
     /// Returns a map of field names to their offsets in the tag structure.
-    fn offsets(&self) -> HashMap<&'static str, u64>;
+    ///
+    /// The map is built on every call, [`fields`](`TagStructure::fields`) and
+    /// [`offset_of`](`TagStructure::offset_of`) do not allocate.
+    fn offsets(&self) -> HashMap<&'static str, u64> {
+        self.fields()
+            .iter()
+            .map(|field| (field.name, field.offset))
+            .collect()
+    }
+    /// Returns the GUID of the tag struct described by the structure, if it was declared with
+    /// `#[data(guid())]`.
+    ///
+    /// [`TagFile::read_metadata`] checks it against the GUID of the main struct of the tag.
+    fn guid(&self) -> Option<u128> {
+        None
+    }
     /// Function that loads all field blocks for the tag structure, if any.
     fn load_field_blocks<R: BufReaderExt>(
         &mut self,
@@ -71,8 +136,96 @@ pub trait TagStructure {
     ) -> Result<()>;
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+/// Kind of a field of a [`TagStructure`], used by tools to handle fields without knowing their
+/// type.
+pub enum FieldKind {
+    /// Plain value, such as a number, vector, color or string.
+    Value,
+    /// Enum stored as an integer.
+    Enum,
+    /// Bitflags stored as an integer.
+    Flags,
+    /// Reference to another tag.
+    Reference,
+    /// Block of elements stored in another data block.
+    Block,
+    /// Array of elements stored inline.
+    Array,
+    /// Data stored in another data block.
+    Data,
+    /// Resource stored in another data block or in a resource file.
+    Resource,
+    /// Another [`TagStructure`] stored inline.
+    Structure,
+    /// Bytes of unknown purpose.
+    RawBytes,
+    /// Padding between fields.
+    Padding,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// Layout of a field of a [`TagStructure`], as returned by [`fields`](`TagStructure::fields`).
+pub struct FieldDescriptor {
+    /// Name of the field in the structure.
+    pub name: &'static str,
+    /// Offset in bytes from the start of the structure.
+    pub offset: u64,
+    /// Type of the field, as written in the structure.
+    pub type_name: &'static str,
+    /// Kind of the field.
+    pub kind: FieldKind,
+    /// Number of elements of an array, or its capacity if the count is stored in another field.
+    pub count: Option<u64>,
+    /// Size of the field in bytes, if it is known from its type.
+    pub size: Option<u64>,
+}
+
+/// Boxed structures are read in place of the structure itself, which keeps large structures
+/// nested in blocks, resources or inline fields off the stack.
+impl<T: TagStructure> TagStructure for Box<T> {
+    fn size(&mut self) -> u64 {
+        (**self).size()
+    }
+
+    fn read<R: BufReaderExt>(&mut self, reader: &mut R, version: &ModuleVersion) -> Result<()> {
+        (**self).read(reader, version)
+    }
+
+    fn write<W: WriterExt>(&self, writer: &mut W, version: &ModuleVersion) -> Result<()> {
+        (**self).write(writer, version)
+    }
+
+    fn fields(&self) -> &'static [FieldDescriptor] {
+        (**self).fields()
+    }
+
+    fn offset_of(&self, name: &str) -> Option<u64> {
+        (**self).offset_of(name)
+    }
+
+    fn guid(&self) -> Option<u128> {
+        (**self).guid()
+    }
+
+    fn load_field_blocks<R: BufReaderExt>(
+        &mut self,
+        source_index: i32,
+        parent_index: usize,
+        adjusted_base: u64,
+        reader: &mut R,
+        tag_file: &TagFile,
+    ) -> Result<()> {
+        (**self).load_field_blocks(source_index, parent_index, adjusted_base, reader, tag_file)
+    }
+}
+
 bitflags! {
     #[derive(Debug, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
     /// Flags for the last 2 bytes of the data offset.
     pub struct DataOffsetType : u16  {
         /// No additional HD1 module is required.
@@ -86,6 +239,7 @@ bitflags! {
 
 bitflags! {
     #[derive(Debug, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
     /// Flags that determine how a tag should be read.
     pub struct FileEntryFlags : u8  {
         /// If tag is compressed or not.
@@ -98,17 +252,21 @@ bitflags! {
     }
 }
 
+/// Size in bytes of a [`ModuleFileEntry`] in the file table, which is the same in every version.
+pub(super) const FILE_ENTRY_SIZE: u64 = 0x58;
+
 #[derive(Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// Module file entry structure containing metadata relating to file and required buffer sizes and offsets for the decompressor, as well as global tag ID, resource references and class.
 pub struct ModuleFileEntry {
     /// Unknown, some sort of size?
-    unknown: u8,
+    pub(super) unknown: u8,
     /// Determine how the file should be read.
     pub flags: FileEntryFlags,
     /// Number of blocks that make up the file.
-    block_count: u16,
+    pub(super) block_count: u16,
     /// Index of the first block in the module.
-    block_index: i32,
+    pub(super) block_index: i32,
     /// Index of the first resource in the module's resource list.
     pub resource_index: i32,
     /// 4 byte-long string for tag group, stored as big endian. This determines how the rest of the tag is read.
@@ -117,7 +275,7 @@ pub struct ModuleFileEntry {
     /// * `mat `: Material
     pub tag_group: String,
     /// Offset of compressed/uncompressed data in from the start of compressed data in the module.
-    data_offset: u64,
+    pub(super) data_offset: u64,
     /// Where the offset is located.
     pub data_offset_flags: DataOffsetType,
     /// Size in bytes of compressed buffer in module.
@@ -126,8 +284,8 @@ pub struct ModuleFileEntry {
     pub total_uncompressed_size: u32,
     /// `MurmurHash3_x86_64` 32 bit hash of tag path.
     /// Referred to in-memory as "global tag id"
-    /// Is set to -1 if file is resource.
-    pub tag_id: i32,
+    /// Is set to [`TagId::NULL`] if file is resource.
+    pub tag_id: TagId,
     /// Size in bytes of header in decompressed buffer.
     pub uncompressed_header_size: u32,
     /// Size in bytes of actual tag data in decompressed buffer.
@@ -137,25 +295,26 @@ pub struct ModuleFileEntry {
     /// Size in bytes of "external" resource data in decompressed buffer. (for instance, havok data or bitmaps)
     pub uncompressed_actual_resource_size: u32,
     /// Power of 2 to align the header buffer to (ex w. 4 = align to a multiple of 16 bytes).
-    header_alignment: u8,
+    pub(super) header_alignment: u8,
     /// Power of 2 to align the tag data buffer to.
-    tag_data_alignment: u8,
+    pub(super) tag_data_alignment: u8,
     /// Power of 2 to align the resource data buffer to.
-    resource_data_alignment: u8,
+    pub(super) resource_data_alignment: u8,
     /// Power of 2 to align the actual resource data buffer to.
-    actual_resource_data_alignment: u8,
+    pub(super) actual_resource_data_alignment: u8,
     /// Offset where the name of the file is located in the string table.
     /// This is not read after [`ModuleVersion::Season3`].
     pub(crate) name_offset: u32,
     /// Used with resources to point back to the parent file. -1 = none
     pub parent_index: i32,
-    /// `Murmur3_x64_128` hash of (what appears to be) the original file that this file was built from.
+    /// [`Murmur3_x64_128`](`crate::common::hash::murmur3_x64_128`) hash of (what appears to be) the original file that this file was built from.
     /// This is not always the same thing as the file stored in the module.
     /// Only verified if the `HasBlocks` flag is not set.
     pub asset_hash: i128,
     /// Number of resources owned by the file.
     pub resource_count: i32,
     /// Data stream containing a buffer of bytes to read/seek.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub data_stream: Option<BufReader<Cursor<Vec<u8>>>>,
     /// The actual tag file read from the contents (including header), only valid if file is not a resource.
     pub tag_info: Option<TagFile>,
@@ -174,9 +333,13 @@ impl ModuleFileEntry {
     /// * `reader` - A mutable reference to a reader implementing [`BufReaderExt`]
     /// * `is_flight1` - Whether the module is a Flight1 module
     ///
+    /// Returns the bits of the stored flags that are not known, which are dropped from
+    /// [`flags`](`ModuleFileEntry::flags`).
+    ///
     /// # Errors
     /// - If the reader fails to read the structure [`ReadError`](`crate::Error::ReadError`)
-    pub(super) fn read<R: BufReaderExt>(&mut self, reader: &mut R, is_flight1: bool) -> Result<()> {
+    pub(super) fn read<R: BufReaderExt>(&mut self, reader: &mut R, is_flight1: bool) -> Result<u8> {
+        let mut flags = 0;
         if is_flight1 {
             self.name_offset = reader.read_u32::<LE>()?;
             self.parent_index = reader.read_i32::<LE>()?;
@@ -186,7 +349,7 @@ impl ModuleFileEntry {
             self.block_index = reader.read_i32::<LE>()?;
         } else {
             self.unknown = reader.read_u8()?;
-            self.flags = FileEntryFlags::from_bits_truncate(reader.read_u8()?);
+            flags = reader.read_u8()?;
             self.block_count = reader.read_u16::<LE>()?;
             self.block_index = reader.read_i32::<LE>()?;
             self.resource_index = reader.read_i32::<LE>()?;
@@ -203,7 +366,7 @@ impl ModuleFileEntry {
             self.asset_hash = reader.read_i128::<LE>()?;
         }
 
-        self.tag_id = reader.read_i32::<LE>()?;
+        self.tag_id = TagId(reader.read_i32::<LE>()?);
         self.uncompressed_header_size = reader.read_u32::<LE>()?;
         self.uncompressed_tag_data_size = reader.read_u32::<LE>()?;
         self.uncompressed_resource_data_size = reader.read_u32::<LE>()?;
@@ -216,7 +379,7 @@ impl ModuleFileEntry {
         if is_flight1 {
             reader.seek_relative(1)?;
             self.unknown = reader.read_u8()?;
-            self.flags = FileEntryFlags::from_bits_truncate(reader.read_u8()?);
+            flags = reader.read_u8()?;
             reader.seek_relative(1)?;
         } else {
             self.name_offset = reader.read_u32::<LE>()?;
@@ -225,6 +388,74 @@ impl ModuleFileEntry {
             self.resource_count = reader.read_i32::<LE>()?;
         }
         reader.seek_relative(4)?; // Skip some padding
+        self.flags = FileEntryFlags::from_bits_truncate(flags);
+        Ok(flags & !FileEntryFlags::all().bits())
+    }
+
+    /// Writes the module file entry to the given writer, mirroring [`read`](`ModuleFileEntry::read`).
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - A mutable reference to a writer implementing [`Write`]
+    /// * `is_flight1` - Whether the module is a Flight1 module
+    ///
+    /// # Errors
+    /// - If the writer fails to write the structure [`ReadError`](`crate::Error::ReadError`)
+    /// - If a count does not fit in the Flight1 layout [`TryFromIntError`](`crate::Error::TryFromIntError`)
+    pub(super) fn write<W: Write>(&self, writer: &mut W, is_flight1: bool) -> Result<()> {
+        if is_flight1 {
+            writer.write_u32::<LE>(self.name_offset)?;
+            writer.write_i32::<LE>(self.parent_index)?;
+            writer.write_u16::<LE>(u16::try_from(self.resource_count)?)?;
+            writer.write_u16::<LE>(self.block_count)?;
+            writer.write_i32::<LE>(self.resource_index)?;
+            writer.write_i32::<LE>(self.block_index)?;
+        } else {
+            writer.write_u8(self.unknown)?;
+            writer.write_u8(self.flags.bits())?;
+            writer.write_u16::<LE>(self.block_count)?;
+            writer.write_i32::<LE>(self.block_index)?;
+            writer.write_i32::<LE>(self.resource_index)?;
+        }
+
+        let mut tag_group = [0xFF; 4];
+        for (byte, char) in tag_group.iter_mut().zip(self.tag_group.bytes().rev()) {
+            *byte = char;
+        }
+        writer.write_all(&tag_group)?;
+        writer.write_u64::<LE>(
+            (self.data_offset & 0x0000_FFFF_FFFF_FFFF)
+                | (u64::from(self.data_offset_flags.bits()) << 48),
+        )?;
+        writer.write_u32::<LE>(self.total_compressed_size)?;
+        writer.write_u32::<LE>(self.total_uncompressed_size)?;
+
+        if is_flight1 {
+            writer.write_i128::<LE>(self.asset_hash)?;
+        }
+
+        writer.write_i32::<LE>(self.tag_id.0)?;
+        writer.write_u32::<LE>(self.uncompressed_header_size)?;
+        writer.write_u32::<LE>(self.uncompressed_tag_data_size)?;
+        writer.write_u32::<LE>(self.uncompressed_resource_data_size)?;
+        writer.write_u32::<LE>(self.uncompressed_actual_resource_size)?;
+        writer.write_u8(self.header_alignment)?;
+        writer.write_u8(self.tag_data_alignment)?;
+        writer.write_u8(self.resource_data_alignment)?;
+        writer.write_u8(self.actual_resource_data_alignment)?;
+
+        if is_flight1 {
+            writer.write_u8(0)?;
+            writer.write_u8(self.unknown)?;
+            writer.write_u8(self.flags.bits())?;
+            writer.write_u8(0)?;
+        } else {
+            writer.write_u32::<LE>(self.name_offset)?;
+            writer.write_i32::<LE>(self.parent_index)?;
+            writer.write_i128::<LE>(self.asset_hash)?;
+            writer.write_i32::<LE>(self.resource_count)?;
+        }
+        writer.write_u32::<LE>(0)?; // Padding
         Ok(())
     }
 
@@ -232,51 +463,81 @@ impl ModuleFileEntry {
     ///
     /// # Arguments
     ///
-    /// * `reader` -  A mutable reference to a [`BufReader<File>`] from which to read the data.
-    /// * `data_offset` - Starting offset in bytes of the data in the file.
+    /// * `reader` - A mutable reference to the module (or HD1) file from which to read the data.
+    /// * `file_offset` - Offset of the data of the file entry in the reader, see [`file_offset`](`ModuleFileEntry::file_offset`).
     /// * `blocks` - Metadata for data blocks.
     /// * `module_version` - Version of the module being read
+    /// * `options` - Options controlling how the tag is parsed.
+    /// * `context` - Buffers reused to decompress blocks.
     ///
     /// # Errors
     /// - If the reader fails to read [`ReadError`](`crate::Error::ReadError`)
     /// - If any issues arise while reading non-raw tags: [`TagError`](`crate::common::errors::TagError`)
-    pub(super) fn read_tag(
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                tag_id = %self.tag_id,
+                compressed_bytes = self.total_compressed_size,
+                uncompressed_bytes = self.total_uncompressed_size,
+                blocks = self.block_count,
+            ),
+            err
+        )
+    )]
+    pub(super) fn read_tag<R: Read + Seek>(
         &mut self,
-        reader: &mut BufReader<File>,
-        data_offset: u64,
+        reader: &mut R,
+        file_offset: u64,
         blocks: &[ModuleBlockEntry],
         module_version: &ModuleVersion,
-        uses_hd1: bool,
+        options: &ParseOptions,
+        context: &mut DecompressionContext,
     ) -> Result<()> {
         if self.is_loaded {
             return Ok(());
         }
-        let file_offset = if uses_hd1 {
-            self.data_offset - data_offset
-        } else {
-            data_offset + self.data_offset
-        };
         let mut data = vec![0u8; self.total_uncompressed_size as usize];
 
         // Set position to start as we are already adding the file offset to it.
         reader.rewind()?;
 
+        let codecs = &options.codecs;
         if self.block_count != 0 {
-            self.read_multiple_blocks(reader, blocks, file_offset, &mut data)?;
+            self.read_multiple_blocks(reader, blocks, file_offset, &mut data, codecs, context)?;
         } else {
-            read_single_block(reader, self, file_offset, &mut data)?;
+            read_single_block(reader, self, file_offset, &mut data, codecs, context)?;
         }
+        #[cfg(feature = "tracing")]
+        tracing::trace!(bytes = data.len(), "decompressed tag");
+        self.load_data(data, module_version, options)
+    }
+
+    /// Loads the tag from its already decompressed data, such as data read from a
+    /// [`TagCache`](`super::cache::TagCache`).
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Entire decompressed data of the file entry, including the tag header.
+    /// * `module_version` - The version of the module being read.
+    /// * `options` - Options controlling how the tag header is read.
+    ///
+    /// # Errors
+    /// - If the tag header cannot be read [`TagError`](`crate::common::errors::TagError`)
+    pub(super) fn load_data(
+        &mut self,
+        data: Vec<u8>,
+        module_version: &ModuleVersion,
+        options: &ParseOptions,
+    ) -> Result<()> {
         let data_stream = BufReader::new(Cursor::new(data));
         self.data_stream = Some(data_stream);
         if !self.flags.contains(FileEntryFlags::RAW_FILE) {
             let mut tagfile = TagFile::default();
             if let Some(ref mut stream) = self.data_stream {
-                if self.tag_group == "psod" {
-                    // HACK: "psod" tags do not have string tables in any version.
-                    tagfile.read(stream, &ModuleVersion::Season3)?;
-                } else {
-                    tagfile.read(stream, module_version)?;
-                }
+                tagfile.read_with_options(stream, module_version, options)?;
             }
             self.tag_info = Some(tagfile);
         }
@@ -285,6 +546,20 @@ impl ModuleFileEntry {
         Ok(())
     }
 
+    /// Gets the offset of the data of the file entry inside the module (or HD1) file.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_offset` - Starting offset in bytes of the data in the file.
+    /// * `uses_hd1` - Whether the data is read from the HD1 module.
+    pub(super) fn file_offset(&self, data_offset: u64, uses_hd1: bool) -> u64 {
+        if uses_hd1 {
+            self.data_offset - data_offset
+        } else {
+            data_offset + self.data_offset
+        }
+    }
+
     /// Reads multiple blocks of data from the file.
     ///
     /// This function reads multiple blocks of data, which can be either compressed or uncompressed,
@@ -292,24 +567,28 @@ impl ModuleFileEntry {
     ///
     /// # Arguments
     ///
-    /// * `reader` - A mutable reference to a [`BufReader<File>`] from which to read the data.
+    /// * `reader` - A mutable reference to the module (or HD1) file from which to read the data.
     /// * `blocks` - A slice of [`ModuleBlockEntry`] containing metadata about each block.
     /// * `file_offset` - The offset in the file where the data blocks start.
     /// * `data` - A mutable slice where the (decompressed) data will be stored.
+    /// * `codecs` - Codecs tried before Kraken to decompress blocks.
+    /// * `context` - Buffers reused to decompress blocks.
     ///
     /// # Errors
     /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
     /// - If the block index is negative [`ModuleError::NegativeBlockIndex`]
     ///
     /// # Safety
-    /// - This function has an unsafe component because it can call the [`read_compressed_block`] function, which uses [`decompress`] which is unsafe.
+    /// - This function has an unsafe component because it can call the [`read_compressed_block`] function, which uses [`decompress`](`super::kraken::decompress`) which is unsafe.
     #[allow(clippy::cast_sign_loss)]
-    fn read_multiple_blocks(
+    fn read_multiple_blocks<R: Read + Seek>(
         &self,
-        reader: &mut BufReader<File>,
+        reader: &mut R,
         blocks: &[ModuleBlockEntry],
         file_offset: u64,
         data: &mut [u8],
+        codecs: &[Arc<dyn Codec>],
+        context: &mut DecompressionContext,
     ) -> Result<()> {
         if self.block_index < 0 {
             return Err(ModuleError::NegativeBlockIndex(self.block_index).into());
@@ -318,26 +597,33 @@ impl ModuleFileEntry {
         reader.seek(SeekFrom::Start(file_offset))?;
 
         let initial_block_offset = reader.stream_position()?;
-        for block in &blocks[first_block_index..(first_block_index + self.block_count as usize)] {
+        let entry_blocks =
+            &blocks[first_block_index..(first_block_index + self.block_count as usize)];
+        #[cfg(feature = "parallel")]
+        if has_disjoint_compressed_blocks(entry_blocks, data.len()) {
+            return read_blocks_parallel(reader, entry_blocks, initial_block_offset, data, codecs);
+        }
+        for (block_index, block) in entry_blocks.iter().enumerate() {
             // even though blocks are sequential, we still should seek to the correct position.
-            reader.seek(SeekFrom::Start(
-                initial_block_offset + u64::from(block.compressed_offset),
-            ))?;
+            let offset = initial_block_offset + u64::from(block.compressed_offset);
+            reader.seek(SeekFrom::Start(offset))?;
             if block.is_compressed {
-                unsafe { read_compressed_block(reader, block, data)? };
+                unsafe { read_compressed_block(reader, block, data, codecs, context) }
             } else {
-                read_uncompressed_block(reader, block, data)?;
+                read_uncompressed_block(reader, block, data)
             }
+            .with_context(|| block_error_context(block_index, offset))?;
         }
         Ok(())
     }
 
     /// Reads a specified structure implementing [`TagStructure`] from the tag data.
     ///
-    /// This function exhausts the inner [`data_stream`](`ModuleFileEntry::data_stream`) buffer to read the contents of the specified
-    /// struct. It first looks for the main struct definition of the file, then gets the referenced
-    /// data block and creates a reader for it. The initial contents of the struct are read, and
-    /// field block definitions are loaded recursively.
+    /// This function reads the contents of the specified struct from the inner
+    /// [`data_stream`](`ModuleFileEntry::data_stream`) buffer, using
+    /// [`TagFile::read_metadata`]. It first looks for the main struct definition of the file, then
+    /// gets the referenced data block and creates a reader for it. The initial contents of the
+    /// struct are read, and field block definitions are loaded recursively.
     ///
     ///
     /// # Generic Arguments
@@ -348,40 +634,294 @@ impl ModuleFileEntry {
     /// - If the tag data is not loaded [`TagError::NotLoaded`]
     /// - If the tag info is not present [`TagError::NoTagInfo`]
     /// - If the main struct definition is not found [`TagError::MainStructNotFound`]
+    /// - If `T` declares a GUID that does not match the main struct [`TagError::GuidMismatch`]
+    /// - If a field lies past the end of its data block [`TagError::FieldOutOfBounds`]
     /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
     pub fn read_metadata<T: Default + TagStructure>(&mut self) -> Result<T> {
-        let mut struct_type = T::default();
-        let mut full_tag = Vec::with_capacity(
-            self.total_uncompressed_size as usize - self.uncompressed_header_size as usize,
-        );
-        self.data_stream
-            .as_mut()
-            .ok_or(TagError::NotLoaded)?
-            .read_to_end(&mut full_tag)?;
+        let (tag_info, data) = self.loaded_tag()?;
+        tag_info
+            .read_metadata(data)
+            .with_context(|| self.error_context())
+    }
+
+    /// Reads a structure implementing [`TagStructure`], along with the location each of its fields
+    /// was read from.
+    ///
+    /// See [`TagFile::read_metadata_with_provenance`].
+    ///
+    /// # Errors
+    /// - If the tag data is not loaded [`TagError::NotLoaded`]
+    /// - If the tag info is not present [`TagError::NoTagInfo`]
+    /// - If the main struct definition is not found [`TagError::MainStructNotFound`]
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    pub fn read_metadata_with_provenance<T: Default + TagStructure>(
+        &mut self,
+    ) -> Result<(T, Vec<FieldProvenance>)> {
+        let (tag_info, data) = self.loaded_tag()?;
+        tag_info
+            .read_metadata_with_provenance(data)
+            .with_context(|| self.error_context())
+    }
+
+    /// Reads a structure implementing [`TagStructure`] after verifying its GUID against the tag.
+    ///
+    /// See [`StructRegistry::read_verified`].
+    ///
+    /// # Errors
+    /// - If the tag data is not loaded [`TagError::NotLoaded`]
+    /// - If the tag info is not present [`TagError::NoTagInfo`]
+    /// - If `T` has not been registered [`TagError::UnregisteredStruct`]
+    /// - If the GUID of `T` does not match the main struct of the tag [`TagError::GuidMismatch`]
+    pub fn read_metadata_verified<T: Default + TagStructure + 'static>(
+        &mut self,
+        registry: &StructRegistry,
+    ) -> Result<T> {
+        let (tag_info, data) = self.loaded_tag()?;
+        registry
+            .read_verified(tag_info, data)
+            .with_context(|| self.error_context())
+    }
+
+    /// Reads the structure registered for the GUID of the main struct of the tag.
+    ///
+    /// See [`StructRegistry::read`].
+    ///
+    /// # Errors
+    /// - If the tag data is not loaded [`TagError::NotLoaded`]
+    /// - If the tag info is not present [`TagError::NoTagInfo`]
+    /// - If no type has been registered for the GUID [`TagError::UnregisteredStruct`]
+    pub fn read_metadata_registered(&mut self, registry: &StructRegistry) -> Result<Box<dyn Any>> {
+        let (tag_info, data) = self.loaded_tag()?;
+        registry
+            .read(tag_info, data)
+            .with_context(|| self.error_context())
+    }
+
+    /// Reads the structure registered for the tag group of this tag.
+    ///
+    /// See [`StructRegistry::read_group`]. The result can be downcast to the concrete type using
+    /// [`Box::downcast`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use infinite_rs::tag::registry::StructRegistry;
+    /// use infinite_rs::tag::types::common_types::AnyTag;
+    /// use infinite_rs::{ModuleFile, TagStructure};
+    ///
+    /// #[derive(Default, Debug, TagStructure)]
+    /// #[data(size(0x88))]
+    /// struct MaterialTag {
+    ///     #[data(offset(0x00))]
+    ///     any_tag: AnyTag,
+    /// }
+    ///
+    /// let mut registry = StructRegistry::new();
+    /// registry.register_group::<MaterialTag>("mat ");
+    ///
+    /// let mut module = ModuleFile::from_path("C:/XboxGames/Halo Infinite/Content/deploy/any/globals-rtx-new.module").unwrap();
+    /// for index in 0..module.files.len() {
+    ///     let Some(tag) = module.read_tag(index as u32).unwrap() else {
+    ///         continue;
+    ///     };
+    ///     if let Ok(metadata) = tag.read_metadata_any(&registry) {
+    ///         if let Ok(material) = metadata.downcast::<MaterialTag>() {
+    ///             println!("{:?}", material.any_tag);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// - If the tag data is not loaded [`TagError::NotLoaded`]
+    /// - If the tag info is not present [`TagError::NoTagInfo`]
+    /// - If no type has been registered for the group [`TagError::UnregisteredGroup`]
+    pub fn read_metadata_any(&mut self, registry: &StructRegistry) -> Result<Box<dyn Any>> {
+        let (tag_info, data) = self.loaded_tag()?;
+        registry
+            .read_group(&self.tag_group, tag_info, data)
+            .with_context(|| self.error_context())
+    }
+
+    /// Cross-checks the layout of a [`TagStructure`] against the struct definitions of the tag.
+    ///
+    /// See [`TagFile::validate_layout`].
+    ///
+    /// # Errors
+    /// - If the tag info is not present [`TagError::NoTagInfo`]
+    /// - If the main struct definition is not found [`TagError::MainStructNotFound`]
+    pub fn validate_layout<T: Default + TagStructure>(&self) -> Result<Vec<LayoutMismatch>> {
+        self.tag_info
+            .as_ref()
+            .ok_or(TagError::NoTagInfo)?
+            .validate_layout::<T>()
+    }
+
+    /// Reads all elements of a [`LazyFieldBlock`] read from this tag.
+    ///
+    /// See [`LazyFieldBlock::load`].
+    ///
+    /// # Errors
+    /// - If the tag data is not loaded [`TagError::NotLoaded`]
+    /// - If the tag info is not present [`TagError::NoTagInfo`]
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    pub fn load_block<T: Default + Debug + TagStructure>(
+        &self,
+        block: &mut LazyFieldBlock<T>,
+    ) -> Result<()> {
+        let (tag_info, data) = self.loaded_tag()?;
+        block
+            .load(tag_info, data)
+            .with_context(|| self.error_context())
+    }
+
+    /// Borrows the bytes of a [`LazyFieldData`] read from this tag.
+    ///
+    /// See [`LazyFieldData::as_slice`].
+    ///
+    /// # Errors
+    /// - If the tag data is not loaded [`TagError::NotLoaded`]
+    /// - If the tag info is not present [`TagError::NoTagInfo`]
+    /// - If the data lies outside of the tag buffer [`TagError::DatablockOutOfBounds`]
+    pub fn data_of(&self, field: &LazyFieldData) -> Result<&[u8]> {
+        let (_, data) = self.loaded_tag()?;
+        field.as_slice(data)
+    }
+
+    /// Reads the tag into a [`DynamicStruct`] tree without requiring a [`TagStructure`] definition.
+    ///
+    /// # Errors
+    /// - If the tag data is not loaded [`TagError::NotLoaded`]
+    /// - If the tag info is not present [`TagError::NoTagInfo`]
+    /// - If the main struct definition is not found [`TagError::MainStructNotFound`]
+    pub fn read_dynamic(&self) -> Result<DynamicStruct> {
+        let (tag_info, data) = self.loaded_tag()?;
+        DynamicStruct::read(tag_info, data).with_context(|| self.error_context())
+    }
 
+    /// Exports the entire tag as a nested JSON document, without requiring a [`TagStructure`] definition.
+    ///
+    /// See [`TagFile::to_json`].
+    ///
+    /// # Errors
+    /// - If the tag data is not loaded [`TagError::NotLoaded`]
+    /// - If the tag info is not present [`TagError::NoTagInfo`]
+    /// - If the main struct definition is not found [`TagError::MainStructNotFound`]
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<serde_json::Value> {
+        let (tag_info, data) = self.loaded_tag()?;
+        tag_info.to_json(data)
+    }
+
+    /// Reads a structure implementing [`TagStructure`] and exports it as JSON.
+    ///
+    /// # Generic Arguments
+    ///
+    /// * `T` - The type of the struct implementing [`TagStructure`] and [`Serialize`](`serde::Serialize`) to read the data into.
+    ///
+    /// # Errors
+    /// - If the tag data is not loaded [`TagError::NotLoaded`]
+    /// - If the tag info is not present [`TagError::NoTagInfo`]
+    /// - If the main struct definition is not found [`TagError::MainStructNotFound`]
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    /// - If the structure cannot be serialized [`JsonError`](`crate::Error::JsonError`)
+    #[cfg(feature = "json")]
+    pub fn export_json<T: Default + TagStructure + serde::Serialize>(
+        &mut self,
+    ) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self.read_metadata::<T>()?)?)
+    }
+
+    /// Exports the entire tag as an XML document, without requiring a [`TagStructure`] definition.
+    ///
+    /// See [`TagFile::to_xml`].
+    ///
+    /// # Errors
+    /// - If the tag data is not loaded [`TagError::NotLoaded`]
+    /// - If the tag info is not present [`TagError::NoTagInfo`]
+    /// - If the main struct definition is not found [`TagError::MainStructNotFound`]
+    pub fn to_xml(&self) -> Result<String> {
+        let (tag_info, data) = self.loaded_tag()?;
+        tag_info.to_xml(data)
+    }
+
+    /// Creates a [`TagEditor`] from the loaded tag, to modify its data and write it back.
+    ///
+    /// # Errors
+    /// - If the tag data is not loaded [`TagError::NotLoaded`]
+    /// - If the tag info is not present [`TagError::NoTagInfo`]
+    /// - If the tag is truncated [`TagError::Truncated`]
+    /// - If a data block points outside of the tag [`TagError::DatablockOutOfBounds`]
+    pub fn to_editor(&self) -> Result<TagEditor> {
+        let (tag_info, data) = self.loaded_tag()?;
+        TagEditor::new(tag_info.clone(), data.to_vec())
+    }
+
+    /// Gets the descriptive name of the tag group of this entry, such as `"Bitmap"` for `bitm`.
+    ///
+    /// See [`tag_group_name`].
+    #[must_use]
+    pub fn tag_group_name(&self) -> Option<&'static str> {
+        tag_group_name(&self.tag_group)
+    }
+
+    /// Gets the category of the tag group of this entry.
+    ///
+    /// See [`tag_group_category`].
+    #[must_use]
+    pub fn tag_group_category(&self) -> Option<TagGroupCategory> {
+        tag_group_category(&self.tag_group)
+    }
+
+    /// Checks whether a string id value is stored anywhere in the loaded tag data.
+    ///
+    /// See [`TagFile::contains_string_id`].
+    ///
+    /// # Errors
+    /// - If the tag data is not loaded [`TagError::NotLoaded`]
+    /// - If the tag info is not present [`TagError::NoTagInfo`]
+    pub fn contains_string_id(&self, string_id: i32) -> Result<bool> {
+        let (tag_info, data) = self.loaded_tag()?;
+        Ok(tag_info.contains_string_id(data, string_id))
+    }
+
+    /// Compares the loaded tag with another loaded tag of the same group.
+    ///
+    /// See [`diff`](`crate::tag::diff::diff`).
+    ///
+    /// # Errors
+    /// - If either tag is not loaded [`TagError::NotLoaded`]
+    /// - If the tag info of either tag is not present [`TagError::NoTagInfo`]
+    /// - If the main struct definition of either tag is not found [`TagError::MainStructNotFound`]
+    pub fn diff(&self, other: &Self) -> Result<Vec<TagDifference>> {
+        let (left_tag, left_data) = self.loaded_tag()?;
+        let (right_tag, right_data) = other.loaded_tag()?;
+        diff(left_tag, left_data, right_tag, right_data)
+    }
+
+    /// Gets the tag info and the entire loaded tag buffer (including header).
+    ///
+    /// # Errors
+    /// - If the tag data is not loaded [`TagError::NotLoaded`]
+    /// - If the tag info is not present [`TagError::NoTagInfo`]
+    fn loaded_tag(&self) -> Result<(&TagFile, &[u8])> {
+        let data = self
+            .data_stream
+            .as_ref()
+            .ok_or(TagError::NotLoaded)?
+            .get_ref()
+            .get_ref();
         let tag_info = self.tag_info.as_ref().ok_or(TagError::NoTagInfo)?;
+        Ok((tag_info, data))
+    }
 
-        let main_struct = tag_info
-            .struct_definitions
-            .iter()
-            .find(|s| s.struct_type == TagStructType::MainStruct)
-            .ok_or(TagError::MainStructNotFound)?;
-
-        #[allow(clippy::cast_sign_loss)]
-        let main_block: &TagDataBlock =
-            &tag_info.datablock_definitions[main_struct.target_index as usize];
-        let full_tag_buffer = &full_tag[0..];
-        let mut full_tag_reader = BufReader::new(Cursor::new(full_tag_buffer));
-        full_tag_reader.seek(SeekFrom::Current(i64::try_from(main_block.offset)?))?;
-        struct_type.read(&mut full_tag_reader)?;
-        struct_type.load_field_blocks(
-            main_struct.target_index,
-            0,
-            0,
-            &mut full_tag_reader,
-            tag_info,
-        )?;
-        Ok(struct_type)
+    /// Context attached to errors while reading the tag.
+    pub(super) fn error_context(&self) -> ErrorContext {
+        ErrorContext {
+            tag_id: (!self.tag_id.is_null()).then_some(self.tag_id),
+            tag_name: (!self.tag_name.is_empty()).then(|| self.tag_name.clone()),
+            ..Default::default()
+        }
     }
 
     /// Reads data from internal buffer into a [`Vec<u8>`].
@@ -417,14 +957,14 @@ impl ModuleFileEntry {
 ///
 /// # Arguments
 ///
-/// * `reader` - A mutable reference to a [`BufReader<File>`] from which to read the data.
+/// * `reader` - A mutable reference to the module (or HD1) file from which to read the data.
 /// * `block` - A reference to the [`ModuleBlockEntry`] containing metadata about the block.
 /// * `data` - A mutable slice where the uncompressed data will be stored.
 ///
 /// # Errors
 /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
-fn read_uncompressed_block(
-    reader: &mut BufReader<File>,
+fn read_uncompressed_block<R: Read + Seek>(
+    reader: &mut R,
     block: &ModuleBlockEntry,
     data: &mut [u8],
 ) -> Result<()> {
@@ -442,37 +982,116 @@ fn read_uncompressed_block(
 ///
 /// # Arguments
 ///
-/// * `reader` - A mutable reference to a [`BufReader<File>`] from which to read the data.
+/// * `reader` - A mutable reference to the module (or HD1) file from which to read the data.
 /// * `block` - A reference to the [`ModuleBlockEntry`] containing metadata about the block.
 /// * `data` - A mutable slice where the decompressed data will be stored.
+/// * `codecs` - Codecs tried before Kraken to decompress the block.
+/// * `context` - Buffers reused to decompress blocks.
 ///
 /// # Errors
 /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
 /// - If the decompression operation fails [`Error::DecompressionError`]
 ///
 /// # Safety
-/// - This function is unsafe because it calls the [`decompress`] function, which is unsafe.
-unsafe fn read_compressed_block(
-    reader: &mut BufReader<File>,
+/// - This function is unsafe because it calls the [`decompress`](`super::kraken::decompress`) function, which is unsafe.
+unsafe fn read_compressed_block<R: Read + Seek>(
+    reader: &mut R,
     block: &ModuleBlockEntry,
     data: &mut [u8],
+    codecs: &[Arc<dyn Codec>],
+    context: &mut DecompressionContext,
 ) -> Result<()> {
     unsafe {
-        let mut compressed_data = vec![0u8; block.compressed_size as usize];
-        reader.read_exact(&mut compressed_data)?;
-        let mut decompressed_data = vec![0u8; block.decompressed_size as usize];
-        decompress(
-            &compressed_data,
-            &mut decompressed_data,
-            block.decompressed_size as usize,
-        )?;
-        data[block.decompressed_offset as usize
-            ..(block.decompressed_offset + block.decompressed_size) as usize]
-            .copy_from_slice(&decompressed_data);
-        Ok(())
+        context.read_block(
+            codecs,
+            reader,
+            block.compressed_size as usize,
+            &mut data[block.decompressed_offset as usize
+                ..(block.decompressed_offset + block.decompressed_size) as usize],
+        )
     }
 }
 
+/// Minimum number of compressed blocks in an entry for them to be decompressed in parallel, as
+/// handing a few small blocks to the thread pool costs more than decompressing them.
+#[cfg(feature = "parallel")]
+const PARALLEL_BLOCK_THRESHOLD: usize = 4;
+
+/// Whether an entry has enough compressed blocks (see [`PARALLEL_BLOCK_THRESHOLD`]),
+/// decompressing into disjoint ranges of the output buffer, so they can be decompressed in
+/// parallel.
+#[cfg(feature = "parallel")]
+fn has_disjoint_compressed_blocks(blocks: &[ModuleBlockEntry], size: usize) -> bool {
+    let mut ranges = blocks
+        .iter()
+        .filter(|block| block.is_compressed)
+        .map(|block| {
+            let start = u64::from(block.decompressed_offset);
+            (start, start + u64::from(block.decompressed_size))
+        })
+        .collect::<Vec<_>>();
+    ranges.sort_unstable();
+    ranges.len() >= PARALLEL_BLOCK_THRESHOLD
+        && ranges.windows(2).all(|pair| pair[0].1 <= pair[1].0)
+        && ranges.last().is_some_and(|&(_, end)| end <= size as u64)
+}
+
+/// Reads multiple blocks of data from the file, decompressing compressed blocks on the thread pool
+/// of [`rayon`].
+///
+/// The compressed payloads are read first, then decompressed in parallel into their
+/// `decompressed_offset` ranges, which must not overlap (see [`has_disjoint_compressed_blocks`]).
+///
+/// # Errors
+/// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+/// - If the decompression operation fails [`Error::DecompressionError`]
+#[cfg(feature = "parallel")]
+fn read_blocks_parallel<R: Read + Seek>(
+    reader: &mut R,
+    blocks: &[ModuleBlockEntry],
+    initial_block_offset: u64,
+    data: &mut [u8],
+    codecs: &[Arc<dyn Codec>],
+) -> Result<()> {
+    let mut compressed_blocks = Vec::new();
+    for (block_index, block) in blocks.iter().enumerate() {
+        let offset = initial_block_offset + u64::from(block.compressed_offset);
+        let context = || block_error_context(block_index, offset);
+        reader.seek(SeekFrom::Start(offset))?;
+        if block.is_compressed {
+            let mut compressed = vec![0u8; block.compressed_size as usize];
+            reader
+                .read_exact(&mut compressed)
+                .map_err(Error::from)
+                .with_context(context)?;
+            compressed_blocks.push((block_index, offset, block, compressed));
+        } else {
+            read_uncompressed_block(reader, block, data).with_context(context)?;
+        }
+    }
+    compressed_blocks.sort_unstable_by_key(|(_, _, block, _)| block.decompressed_offset);
+
+    // Split the output into the range of each block, so every thread gets its own slices.
+    let mut jobs = Vec::with_capacity(compressed_blocks.len());
+    let mut remaining = data;
+    let mut position = 0;
+    for (block_index, offset, block, compressed) in &compressed_blocks {
+        let start = block.decompressed_offset as usize - position;
+        let (output, rest) = remaining[start..].split_at_mut(block.decompressed_size as usize);
+        jobs.push((*block_index, *offset, compressed.as_slice(), output));
+        remaining = rest;
+        position = (block.decompressed_offset + block.decompressed_size) as usize;
+    }
+
+    jobs.into_par_iter().try_for_each_init(
+        DecompressionContext::new,
+        |context, (block_index, offset, compressed, output)| {
+            unsafe { context.decompress_into(codecs, compressed, output) }
+                .with_context(|| block_error_context(block_index, offset))
+        },
+    )
+}
+
 /// Reads a single block of data from the file.
 ///
 /// This function is used when the file entry contains only one block of data.
@@ -481,32 +1100,490 @@ unsafe fn read_compressed_block(
 ///
 /// # Arguments
 ///
-/// * `reader` - A mutable reference to a [`BufReader<File>`] from which to read the data.
+/// * `reader` - A mutable reference to the module (or HD1) file from which to read the data.
 /// * `file_entry` - A reference to the [`ModuleFileEntry`] containing metadata about the file.
 /// * `file_offset` - The offset in the file where the data block starts.
-/// * `data` - A mutable reference to the [`Vec<u8>`] where the (decompressed) data will be stored.
+/// * `data` - A mutable slice where the (decompressed) data will be stored.
+/// * `codecs` - Codecs tried before Kraken to decompress the block.
+/// * `context` - Buffers reused to decompress the block.
 ///
 /// # Errors
 /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
 /// - If the decompression operation fails [`Error::DecompressionError`]
 ///
 /// # Safety
-/// - This function can be unsafe because it may call the [`decompress`] function, which is unsafe.
-fn read_single_block(
-    reader: &mut BufReader<File>,
+/// - This function can be unsafe because it may call the [`decompress`](`super::kraken::decompress`) function, which is unsafe.
+fn read_single_block<R: Read + Seek>(
+    reader: &mut R,
     file_entry: &ModuleFileEntry,
     file_offset: u64,
-    data: &mut Vec<u8>,
+    data: &mut [u8],
+    codecs: &[Arc<dyn Codec>],
+    context: &mut DecompressionContext,
 ) -> Result<()> {
     reader.seek(SeekFrom::Start(file_offset))?;
     let compressed_size = file_entry.total_compressed_size as usize;
-    let mut block = vec![0u8; compressed_size];
-    reader.read_exact(&mut block)?;
 
     if compressed_size == file_entry.total_uncompressed_size as usize {
-        data.copy_from_slice(&block);
+        reader.read_exact(data).map_err(Error::from)
     } else {
-        unsafe { decompress(&block, data, file_entry.total_uncompressed_size as usize)? };
+        unsafe { context.read_block(codecs, reader, compressed_size, data) }
+    }
+    .with_context(|| ErrorContext {
+        offset: Some(file_offset),
+        ..Default::default()
+    })
+}
+
+/// Context attached to errors while reading a block of a file entry.
+fn block_error_context(block_index: usize, offset: u64) -> ErrorContext {
+    ErrorContext {
+        block_index: Some(block_index),
+        offset: Some(offset),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::errors::TagError;
+    use crate::common::extensions::{EndianReader, EndianWriter, Endianness};
+    use crate::common::hash::{HashingReader, Murmur3X64Hasher};
+    use crate::tag::structure::{TagStruct, TagStructType};
+    use crate::tag::types::common_types::{
+        FieldArray, FieldBlock, FieldCustom, FieldExplanation, FieldLongInteger, FieldPad,
+        FieldReal, FieldShortInteger, FieldStringId,
+    };
+    use infinite_rs_derive::TagStructure;
+    use num_enum::TryFromPrimitive;
+
+    #[test]
+    /// Verifies that the asset hash of an entry matches the `MurmurHash3_x64_128` hash of its
+    /// asset computed while reading it.
+    fn test_asset_hash() {
+        let asset = b"The quick brown fox jumps over the lazy dog";
+        // Entry of a module after Flight1, with the reference output of the hash at 0x40.
+        let mut data = vec![0_u8; 0x58];
+        data[0x40..0x48].copy_from_slice(&0xE34B_BC7B_BC07_1B6C_u64.to_le_bytes());
+        data[0x48..0x50].copy_from_slice(&0x7A43_3CA9_C49A_9347_u64.to_le_bytes());
+        let mut entry = ModuleFileEntry::default();
+        entry
+            .read(&mut BufReader::new(Cursor::new(&data)), false)
+            .unwrap();
+
+        let mut reader = HashingReader::new(Cursor::new(asset), Murmur3X64Hasher::with_seed(0));
+        reader.read_to_end(&mut Vec::new()).unwrap();
+        assert_eq!(
+            reader.hasher().finish_u128(),
+            u128::from_ne_bytes(entry.asset_hash.to_ne_bytes())
+        );
+    }
+
+    #[derive(Default, Debug, TagStructure)]
+    #[data(size(0x8))]
+    struct Range {
+        #[data(offset(0x00))]
+        min: FieldReal,
+        #[data(offset(0x04))]
+        max: FieldReal,
+    }
+
+    #[derive(Default, TagStructure)]
+    #[data(size(0x18))]
+    struct Function {
+        #[data(offset(0x08), inline)]
+        range: Range,
+        #[data(offset(0x10))]
+        boxed_range: Box<Range>,
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    /// Verifies that inline and boxed structures are read relative to their offset.
+    fn test_inline_structure() {
+        let mut data = [0_u8; 0x18];
+        data[0x0C..0x10].copy_from_slice(&2.0_f32.to_le_bytes());
+        data[0x10..0x14].copy_from_slice(&1.0_f32.to_le_bytes());
+        let mut function = Function::default();
+        function
+            .read(
+                &mut BufReader::new(Cursor::new(data)),
+                &ModuleVersion::default(),
+            )
+            .unwrap();
+        assert_eq!(function.range.max.0, 2.0);
+        assert_eq!(function.boxed_range.min.0, 1.0);
+    }
+
+    #[derive(Default, Debug, TagStructure)]
+    #[data(size(0x04))]
+    struct Key {
+        #[data(offset(0x00))]
+        time: FieldReal,
+    }
+
+    #[derive(Default, Debug, TagStructure)]
+    #[data(size(0x18))]
+    struct Curve<T: TagStructure> {
+        #[data(offset(0x00))]
+        flags: FieldShortInteger,
+        #[data(offset(0x04))]
+        keys: FieldBlock<T>,
+    }
+
+    #[derive(Default, Debug, TagStructure)]
+    #[data(size(0x04))]
+    struct Pair<T> {
+        #[data(offset(0x00), inline)]
+        value: T,
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    /// Verifies that generic structures can hold their elements inline or in a block.
+    fn test_generic_structure() {
+        let mut pair = Pair::<Key>::default();
+        pair.read(
+            &mut BufReader::new(Cursor::new(1.0_f32.to_le_bytes())),
+            &ModuleVersion::default(),
+        )
+        .unwrap();
+        assert_eq!(pair.value.time.0, 1.0);
+        assert_eq!(Curve::<Key>::default().offset_of("keys"), Some(0x04));
+    }
+
+    #[derive(Default, TagStructure)]
+    #[data(size(0x08))]
+    struct Wind {
+        #[data(offset(0x00))]
+        speed: FieldReal,
+        #[data(offset(0x04))]
+        gust_speed: Option<FieldReal>,
+    }
+
+    #[test]
+    /// Verifies that `Option` fields are only read if they end within the data.
+    fn test_optional_field() {
+        let version = ModuleVersion::default();
+        let mut wind = Wind::default();
+        wind.read(&mut BufReader::new(Cursor::new([0_u8; 4])), &version)
+            .unwrap();
+        assert!(wind.gust_speed.is_none());
+        wind.read(&mut BufReader::new(Cursor::new([0_u8; 8])), &version)
+            .unwrap();
+        assert!(wind.gust_speed.is_some());
+    }
+
+    #[derive(Default, TagStructure)]
+    #[data(size(0x08))]
+    struct Light {
+        #[data(offset(0x00))]
+        intensity: FieldReal,
+        #[data(offset(0x04), until(Season3))]
+        old_flags: FieldLongInteger,
+        #[data(offset(0x04), since(Season3))]
+        flags: FieldLongInteger,
+    }
+
+    #[test]
+    /// Verifies that fields are only read in the module versions they are present in.
+    fn test_versioned_fields() {
+        let data = [0, 0, 0, 0, 1, 0, 0, 0];
+        let mut light = Light::default();
+        light
+            .read(
+                &mut BufReader::new(Cursor::new(data)),
+                &ModuleVersion::Release,
+            )
+            .unwrap();
+        assert_eq!(light.old_flags.0, 1);
+        assert_eq!(light.flags.0, 0);
+    }
+
+    #[derive(Default, TagStructure)]
+    #[data(size(0x0C), write)]
+    struct Parameter {
+        #[data(offset(0x00))]
+        name: FieldStringId,
+        #[data(offset(0x04))]
+        unknown: [u8; 4],
+        #[data(offset(0x08))]
+        value: FieldReal,
+    }
+
+    #[test]
+    /// Verifies that raw bytes are kept, and that edited fields are written back in place.
+    fn test_write_structure() {
+        let version = ModuleVersion::default();
+        let mut data = vec![0_u8; 0x0C];
+        data[0..4].copy_from_slice(&0x1234_i32.to_le_bytes());
+        data[4..8].copy_from_slice(&[1, 2, 3, 4]);
+        let mut parameter = Parameter::default();
+        parameter
+            .read(&mut BufReader::new(Cursor::new(&data)), &version)
+            .unwrap();
+        assert_eq!(parameter.unknown, [1, 2, 3, 4]);
+
+        parameter.value.0 = 0.5;
+        let mut writer = Cursor::new(Vec::new());
+        parameter.write(&mut writer, &version).unwrap();
+        data[8..12].copy_from_slice(&0.5_f32.to_le_bytes());
+        assert_eq!(writer.into_inner(), data);
+    }
+
+    #[test]
+    /// Verifies that structures read from big-endian data are written back in the same byte order.
+    fn test_write_big_endian() {
+        let version = ModuleVersion::default();
+        let mut data = vec![0_u8; 0x0C];
+        data[0..4].copy_from_slice(&0x1234_i32.to_be_bytes());
+        data[8..12].copy_from_slice(&0.5_f32.to_be_bytes());
+        let mut parameter = Parameter::default();
+        parameter
+            .read(
+                &mut EndianReader::new(Cursor::new(&data), Endianness::Big),
+                &version,
+            )
+            .unwrap();
+        assert_eq!(parameter.name.0, 0x1234);
+
+        let mut writer = EndianWriter::new(Cursor::new(Vec::new()), Endianness::Big);
+        parameter.write(&mut writer, &version).unwrap();
+        assert_eq!(writer.into_inner().into_inner(), data);
+    }
+
+    #[derive(Default, TagStructure)]
+    #[data(size(0x04), write)]
+    struct Annotated {
+        #[data(offset(0x00))]
+        explanation: FieldExplanation,
+        #[data(offset(0x00))]
+        custom: FieldCustom,
+        #[data(offset(0x00))]
+        value: FieldReal,
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    /// Verifies that explanation and custom fields take no space and write nothing.
+    fn test_write_annotations() {
+        let version = ModuleVersion::default();
+        let data = 1.0_f32.to_le_bytes();
+        let mut annotated = Annotated::default();
+        annotated
+            .read(&mut BufReader::new(Cursor::new(data)), &version)
+            .unwrap();
+        assert_eq!(annotated.value.0, 1.0);
+
+        let mut writer = Cursor::new(Vec::new());
+        annotated.write(&mut writer, &version).unwrap();
+        assert_eq!(writer.into_inner(), data);
+    }
+
+    #[derive(Default, TagStructure)]
+    #[data(size(0x04))]
+    struct Entry {
+        #[data(offset(0x00))]
+        value: FieldLongInteger,
+    }
+
+    #[derive(Default, TagStructure)]
+    #[data(size(0x14))]
+    struct Table {
+        #[data(offset(0x00))]
+        entry_count: FieldShortInteger,
+        #[data(offset(0x04), count(4), count_from("entry_count"))]
+        entries: FieldArray<Entry>,
+    }
+
+    #[test]
+    /// Verifies that arrays read the number of elements stored in another field, and are described
+    /// with their capacity.
+    fn test_array_count_from() {
+        let mut data = [0_u8; 0x14];
+        data[0] = 2;
+        let mut table = Table::default();
+        table
+            .read(
+                &mut BufReader::new(Cursor::new(data)),
+                &ModuleVersion::default(),
+            )
+            .unwrap();
+        assert_eq!(table.entries.elements.len(), 2);
+
+        let entries = &table.fields()[1];
+        assert_eq!(entries.kind, FieldKind::Array);
+        assert_eq!(entries.type_name, "FieldArray<Entry>");
+        assert_eq!(entries.count, Some(4));
+    }
+
+    /// Number of channels of a [`Mixer`].
+    const CHANNEL_COUNT: usize = 3;
+
+    #[derive(Default, TagStructure)]
+    #[data(size(0x0C), auto_offsets, write, assert_layout)]
+    struct Mixer {
+        gains: [FieldShortInteger; 2],
+        volumes: [FieldShortInteger; CHANNEL_COUNT],
+        #[data(offset(0x0A))]
+        pan: FieldShortInteger,
+    }
+
+    #[test]
+    /// Verifies that arrays of field types are read and written element by element, and are
+    /// described with their length, also when it is a constant.
+    fn test_value_array() {
+        let version = ModuleVersion::default();
+        let data: Vec<u8> = (1..=6_i16).flat_map(i16::to_le_bytes).collect();
+        let mut mixer = Mixer::default();
+        mixer
+            .read(&mut BufReader::new(Cursor::new(&data)), &version)
+            .unwrap();
+        assert_eq!(mixer.gains.each_ref().map(|gain| gain.0), [1, 2]);
+        assert_eq!(mixer.volumes.each_ref().map(|volume| volume.0), [3, 4, 5]);
+        assert_eq!(mixer.pan.0, 6);
+
+        let volumes = &mixer.fields()[1];
+        assert_eq!(volumes.kind, FieldKind::Array);
+        assert_eq!(volumes.offset, 0x04);
+        assert_eq!(volumes.count, Some(3));
+        assert_eq!(volumes.size, Some(6));
+
+        let mut writer = Cursor::new(Vec::new());
+        mixer.write(&mut writer, &version).unwrap();
+        assert_eq!(writer.into_inner(), data);
+
+        assert!(matches!(
+            Mixer::default().read(&mut BufReader::new(Cursor::new(&data[..8])), &version),
+            Err(Error::TagError(TagError::FieldOutOfBounds {
+                field: "volumes",
+                ..
+            }))
+        ));
+    }
+
+    #[derive(Default, Debug, PartialEq, TryFromPrimitive)]
+    #[repr(u32)]
+    enum ParameterType {
+        #[default]
+        Bitmap,
+        Color,
+    }
+
+    bitflags! {
+        #[derive(Default, Debug, Clone, PartialEq)]
+        struct ParameterFlags: u8 {
+            const HIDDEN = 1 << 0;
+        }
+    }
+
+    #[derive(Default, TagStructure)]
+    #[data(size(0x08))]
+    struct TypedParameter {
+        #[data(offset(0x00), enum(u32))]
+        parameter_type: ParameterType,
+        #[data(offset(0x04), flags(u8))]
+        flags: ParameterFlags,
+    }
+
+    #[test]
+    /// Verifies that enums and bitflags are read without a wrapper, and without `IntoPrimitive`
+    /// for structures that are not written.
+    fn test_enum_and_flags() {
+        let data = [1, 0, 0, 0, 1, 0, 0, 0];
+        let mut parameter = TypedParameter::default();
+        parameter
+            .read(
+                &mut BufReader::new(Cursor::new(data)),
+                &ModuleVersion::default(),
+            )
+            .unwrap();
+        assert_eq!(parameter.parameter_type, ParameterType::Color);
+        assert_eq!(parameter.flags, ParameterFlags::HIDDEN);
+        assert!(matches!(
+            parameter.write(&mut Cursor::new(Vec::new()), &ModuleVersion::default()),
+            Err(Error::TagError(TagError::WriteUnsupported(_)))
+        ));
+    }
+
+    #[derive(Default, TagStructure)]
+    #[data(size(0x04), guid("0x1234ABCD"))]
+    struct Fog {
+        #[data(offset(0x00))]
+        density: FieldReal,
+    }
+
+    #[test]
+    /// Verifies that reading a structure from a tag with another main struct GUID fails.
+    fn test_guid_mismatch() {
+        assert_eq!(Fog::default().guid(), Some(0x1234_ABCD));
+
+        let mut tag_file = TagFile::default();
+        let main_struct = TagStruct {
+            guid: 0x5678,
+            struct_type: TagStructType::MainStruct,
+            ..Default::default()
+        };
+        tag_file.struct_definitions.push(main_struct);
+        assert!(matches!(
+            tag_file.read_metadata::<Fog>(&[]),
+            Err(Error::TagError(TagError::GuidMismatch {
+                expected: 0x1234_ABCD,
+                found: 0x5678
+            }))
+        ));
+    }
+
+    #[derive(Default, TagStructure)]
+    #[data(size(0x08))]
+    struct Direction {
+        #[data(offset(0x00))]
+        speed: FieldReal,
+        #[data(offset(0x04))]
+        direction: FieldReal,
+    }
+
+    #[test]
+    /// Verifies that fields past the end of the data are reported with the name of the field.
+    fn test_field_out_of_bounds() {
+        let mut wind = Direction::default();
+        let result = wind.read(
+            &mut BufReader::new(Cursor::new([0; 6])),
+            &ModuleVersion::default(),
+        );
+        assert!(matches!(
+            result,
+            Err(Error::TagError(TagError::FieldOutOfBounds {
+                field: "direction",
+                offset: 4,
+                limit: 6
+            }))
+        ));
+    }
+
+    #[derive(Default, TagStructure)]
+    #[data(size(0x10), auto_offsets, assert_layout)]
+    struct Sound {
+        volume: FieldReal,
+        flags: FieldShortInteger,
+        #[data(length(2))]
+        pad: FieldPad,
+        pitch: FieldReal,
+        #[data(offset(0x0C))]
+        priority: FieldShortInteger,
+        #[data(skip)]
+        name: Option<String>,
+    }
+
+    #[test]
+    /// Verifies that fields without an offset are placed after the previous field, and that
+    /// skipped fields are not described.
+    fn test_auto_offsets() {
+        let sound = Sound::default();
+        assert_eq!(sound.offset_of("pitch"), Some(0x08));
+        assert_eq!(sound.offset_of("name"), None);
+        assert!(sound.name.is_none());
+        assert_eq!(sound.offset_of("priority"), Some(0x0C));
     }
-    Ok(())
 }
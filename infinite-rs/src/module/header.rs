@@ -1,15 +1,19 @@
 //! Module Header containing info on the layout of the module file.
 
-use byteorder::{LE, ReadBytesExt};
+use byteorder::{LE, ReadBytesExt, WriteBytesExt};
 use num_enum::TryFromPrimitive;
-use std::{fs::File, io::BufReader};
+use std::{
+    fs::File,
+    io::{BufReader, Write},
+};
 
 use crate::Result;
 use crate::common::errors::ModuleError;
 
-const HEADER_MAGIC: u32 = 0x6468_6F6D; // "mohd"
+pub(super) const HEADER_MAGIC: u32 = 0x6468_6F6D; // "mohd"
 
-#[derive(Default, Debug, PartialEq, Eq, TryFromPrimitive, PartialOrd, Ord)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, TryFromPrimitive, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[repr(i32)]
 /// Revision number of a module file.
 /// This version number determines how tags should be read.
@@ -26,10 +30,11 @@ pub enum ModuleVersion {
 }
 
 #[derive(Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// Module Header structure containing info on the layout of the module file.
 pub struct ModuleHeader {
     /// Should be "mohd" (0x64686F6D)
-    magic: u32,
+    pub(super) magic: u32,
     /// Revision number of the module.
     /// This determines how offsets are calculated and if tag names should be read.
     pub version: ModuleVersion,
@@ -38,13 +43,13 @@ pub struct ModuleHeader {
     /// Number of files in the module.
     pub file_count: u32,
     /// Index of `loadmanifest` tag, which contains the tag ids that the module will load.
-    loadmanifest_index: i32,
+    pub(super) loadmanifest_index: i32,
     /// Index of `runtimeloadmetadata` tag, which contains info on how tags should be loaded at runtime.
-    runtimeloadmetadata_index: i32,
+    pub(super) runtimeloadmetadata_index: i32,
     /// Index of `resourcemetadata` tag, which contains info on how resources should be loaded.
-    resourcemetadata_index: i32,
+    pub(super) resourcemetadata_index: i32,
     /// Index of the first resource entry ([`file_count`](`ModuleHeader::file_count`) - [`resource_count`](`ModuleHeader::resource_count`)).
-    resource_index: i32,
+    pub(super) resource_index: i32,
     /// Total size in bytes of the string table.
     pub(super) strings_size: u32,
     /// Number of resource files.
@@ -52,7 +57,7 @@ pub struct ModuleHeader {
     /// Number of data blocks.
     pub(super) block_count: u32,
     /// Same between modules, changes per build?
-    build_version: u64,
+    pub(super) build_version: u64,
     /// If non-zero, requires hd1 file.
     pub(super) hd1_delta: u64,
     /// Total size of packed data in the module.
@@ -98,4 +103,36 @@ impl ModuleHeader {
         }
         Ok(())
     }
+
+    /// Writes the module header to the given writer.
+    ///
+    /// This is the inverse of [`read`](`ModuleHeader::read`), and writes the reserved bytes after
+    /// [`data_size`](`ModuleHeader::data_size`) as zeroes for versions that contain them.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - A mutable reference to a writer implementing [`Write`].
+    ///
+    /// # Errors
+    /// - If the writer fails to write the structure [`ReadError`](`crate::Error::ReadError`)
+    pub(super) fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32::<LE>(self.magic)?;
+        writer.write_i32::<LE>(self.version.clone() as i32)?;
+        writer.write_i64::<LE>(self.module_id)?;
+        writer.write_u32::<LE>(self.file_count)?;
+        writer.write_i32::<LE>(self.loadmanifest_index)?;
+        writer.write_i32::<LE>(self.runtimeloadmetadata_index)?;
+        writer.write_i32::<LE>(self.resourcemetadata_index)?;
+        writer.write_i32::<LE>(self.resource_index)?;
+        writer.write_u32::<LE>(self.strings_size)?;
+        writer.write_u32::<LE>(self.resource_count)?;
+        writer.write_u32::<LE>(self.block_count)?;
+        writer.write_u64::<LE>(self.build_version)?;
+        writer.write_u64::<LE>(self.hd1_delta)?;
+        writer.write_u64::<LE>(self.data_size)?;
+        if self.version >= ModuleVersion::Release {
+            writer.write_u64::<LE>(0)?;
+        }
+        Ok(())
+    }
 }
@@ -1,10 +1,23 @@
-//! Kraken decompressor wrapper.
+//! Kraken compressor and decompressor wrapper.
 //!
 //! Originally from: <https://github.com/rfuzzo/red4lib>
+//!
+//! The C++ decompressor is built and linked by the `kraken` feature, which is enabled by default.
+//! The `kraken-rs` feature instead decompresses with [oozextract](https://crates.io/crates/oozextract),
+//! a pure-Rust port, so the crate builds with `cargo` alone (for example when cross-compiling or
+//! targeting wasm). When both are enabled, the C++ decompressor is used. Compressing always
+//! requires the `kraken` feature. Without either, or with the `no-kraken` feature (which overrides
+//! them when another crate enables them), reading compressed blocks fails with
+//! [`DecompressionError::CompressionUnavailable`].
+
+use std::io::Read;
+use std::sync::Arc;
 
+use super::codec::{Codec, decompress_with_codecs};
 use crate::Result;
 use crate::common::errors::DecompressionError;
 
+#[cfg(kraken_backend = "native")]
 #[link(name = "kraken_static")]
 unsafe extern "C" {
     // EXPORT int Kraken_Decompress(const byte *src, size_t src_len, byte *dst, size_t dst_len)
@@ -14,14 +27,33 @@ unsafe extern "C" {
         outputBuffer: *mut u8,
         outputBufferSize: usize,
     ) -> i32;
+    // EXPORT int Kraken_Compress(uint8 *src, size_t src_len, byte *dst, int level)
+    fn Kraken_Compress(
+        buffer: *const u8,
+        bufferSize: usize,
+        outputBuffer: *mut u8,
+        level: i32,
+    ) -> i32;
 }
 
+/// Size of the chunks the compressor splits its input into.
+const COMPRESSION_CHUNK_SIZE: usize = 0x40000;
+/// Extra space each chunk may need beyond its size, for incompressible data.
+const COMPRESSION_CHUNK_OVERHEAD: usize = 274;
+/// Extra space the compressor may write to past the end of the compressed data.
+const COMPRESSION_PADDING: usize = 0x10000;
+
 /// UNSAFE: Decompresses a Kraken-compressed buffer.
 ///
+/// Use a [`DecompressionContext`] to try the codecs of
+/// [`ParseOptions`](`crate::common::options::ParseOptions::codecs`) first, such as an
+/// `OodleLibrary` (`oodle` feature).
+///
 /// # Arguments
 ///
 /// * `compressed_buffer` - A vector containing the compressed data.
 /// * `output_buffer` - A mutable reference to a vector where the decompressed data will be stored.
+///   Its allocation is reused if it is large enough.
 /// * `size` - The expected size of the decompressed data.
 ///
 /// # Returns
@@ -32,6 +64,7 @@ unsafe extern "C" {
 /// - If the decompression fails [`DecompressionError::DecompressionFailed`]
 /// - If the decompressed buffer size exceeds the maximum size of [`i32`] [`DecompressionError::BufferSizeOverflow`]
 /// - If the decompressed buffer size exceeds the maximum size of [`usize`] [`DecompressionError::BufferSizeOverflow`]
+/// - If the crate was built without the `kraken` and `kraken-rs` features [`DecompressionError::CompressionUnavailable`]
 ///
 /// # Safety
 ///
@@ -41,13 +74,24 @@ pub unsafe fn decompress(
     compressed_buffer: &[u8],
     output_buffer: &mut Vec<u8>,
     size: usize,
+) -> Result<i32> {
+    unsafe { decompress_bundled(compressed_buffer, output_buffer, size) }
+}
+
+/// Decompresses with the bundled Kraken decompressor, see [`decompress`].
+#[cfg(kraken_backend = "native")]
+unsafe fn decompress_bundled(
+    compressed_buffer: &[u8],
+    output_buffer: &mut Vec<u8>,
+    size: usize,
 ) -> Result<i32> {
     unsafe {
-        let mut buffer = vec![0; size + 8]; // HACK: Ensures that pointer for memory buffer is aligned.
+        output_buffer.clear();
+        output_buffer.resize(size + 8, 0); // HACK: Ensures that pointer for memory buffer is aligned.
         let result = Kraken_Decompress(
             compressed_buffer.as_ptr(),
             compressed_buffer.len(),
-            buffer.as_mut_ptr(),
+            output_buffer.as_mut_ptr(),
             size,
         );
 
@@ -58,12 +102,344 @@ pub unsafe fn decompress(
         let result_usize =
             usize::try_from(result).map_err(|_| DecompressionError::BufferSizeOverflow)?;
 
-        if result_usize > buffer.len() {
+        if result_usize > output_buffer.len() {
             return Err(DecompressionError::BufferSizeOverflow.into());
         }
 
-        buffer.resize(result_usize, 0);
-        *output_buffer = buffer;
+        output_buffer.truncate(result_usize);
         Ok(result)
     }
 }
+
+/// Decompresses with the pure-Rust Kraken decoder, see [`decompress`].
+///
+/// The decoder allocates large scratch buffers, so one is kept per thread.
+#[cfg(kraken_backend = "rust")]
+#[allow(clippy::unnecessary_wraps)]
+unsafe fn decompress_bundled(
+    compressed_buffer: &[u8],
+    output_buffer: &mut Vec<u8>,
+    size: usize,
+) -> Result<i32> {
+    use oozextract::Extractor;
+    use std::cell::RefCell;
+
+    thread_local! {
+        static EXTRACTOR: RefCell<Option<Extractor>> = const { RefCell::new(None) };
+    }
+
+    output_buffer.clear();
+    output_buffer.resize(size, 0);
+    let result = EXTRACTOR.with_borrow_mut(|extractor| {
+        extractor
+            .get_or_insert_with(Extractor::new)
+            .read_from_slice(compressed_buffer, output_buffer)
+    });
+    let length = result.map_err(|_| DecompressionError::DecompressionFailed(-1))?;
+    output_buffer.truncate(length);
+    Ok(i32::try_from(length).map_err(|_| DecompressionError::BufferSizeOverflow)?)
+}
+
+/// Without a Kraken decoder, only the codecs of a [`DecompressionContext`] can decompress.
+#[cfg(not(kraken_backend))]
+#[allow(clippy::unnecessary_wraps)]
+unsafe fn decompress_bundled(_: &[u8], _: &mut Vec<u8>, _: usize) -> Result<i32> {
+    Err(DecompressionError::CompressionUnavailable.into())
+}
+
+#[derive(Default, Debug)]
+/// Buffers reused between decompressions, to avoid allocating new ones for every block.
+///
+/// [`ModuleFile`](`super::loader::ModuleFile`) keeps one for all the tags it reads.
+pub struct DecompressionContext {
+    /// Compressed data of the block being read.
+    compressed: Vec<u8>,
+    /// Output of the decompressor, which needs extra space past the decompressed data.
+    decompressed: Vec<u8>,
+}
+
+impl DecompressionContext {
+    /// Creates a context with empty buffers, which grow to the size of the largest block.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decompresses a buffer, returning the decompressed data held by the context.
+    ///
+    /// The first of `codecs` [matching](`Codec::matches`) the buffer is used, otherwise it is
+    /// decompressed with [`decompress`].
+    ///
+    /// # Errors
+    /// - If the decompression fails [`DecompressionError::DecompressionFailed`]
+    /// - If the crate was built without the `kraken` and `kraken-rs` features [`DecompressionError::CompressionUnavailable`]
+    /// - If a codec fails, with the error it returns
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because it calls [`decompress`].
+    pub unsafe fn decompress(
+        &mut self,
+        codecs: &[Arc<dyn Codec>],
+        compressed_buffer: &[u8],
+        size: usize,
+    ) -> Result<&[u8]> {
+        unsafe { decompress_block(codecs, compressed_buffer, &mut self.decompressed, size)? };
+        Ok(&self.decompressed)
+    }
+
+    /// Reads `compressed_size` bytes from the reader and decompresses them into `output`.
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    /// - If the decompression fails [`DecompressionError::DecompressionFailed`]
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because it calls [`decompress`].
+    pub(super) unsafe fn read_block<R: Read>(
+        &mut self,
+        codecs: &[Arc<dyn Codec>],
+        reader: &mut R,
+        compressed_size: usize,
+        output: &mut [u8],
+    ) -> Result<()> {
+        self.compressed.clear();
+        self.compressed.resize(compressed_size, 0);
+        reader.read_exact(&mut self.compressed)?;
+        unsafe { decompress_into(codecs, &self.compressed, &mut self.decompressed, output) }
+    }
+
+    /// Decompresses a buffer into `output`, which has the size of the decompressed data.
+    ///
+    /// # Errors
+    /// - If the decompression fails [`DecompressionError::DecompressionFailed`]
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because it calls [`decompress`].
+    #[cfg(feature = "parallel")]
+    pub(super) unsafe fn decompress_into(
+        &mut self,
+        codecs: &[Arc<dyn Codec>],
+        compressed_buffer: &[u8],
+        output: &mut [u8],
+    ) -> Result<()> {
+        unsafe { decompress_into(codecs, compressed_buffer, &mut self.decompressed, output) }
+    }
+}
+
+/// Decompresses a buffer with the first of `codecs` matching it, or with [`decompress`].
+///
+/// # Safety
+///
+/// This function is unsafe because it calls [`decompress`].
+unsafe fn decompress_block(
+    codecs: &[Arc<dyn Codec>],
+    compressed_buffer: &[u8],
+    output_buffer: &mut Vec<u8>,
+    size: usize,
+) -> Result<()> {
+    match decompress_with_codecs(codecs, compressed_buffer, output_buffer, size) {
+        Some(result) => result,
+        None => unsafe { decompress(compressed_buffer, output_buffer, size).map(|_| ()) },
+    }
+}
+
+/// Decompresses a buffer into `output` through `scratch`, as the decompressor may write past the
+/// end of the decompressed data.
+///
+/// # Errors
+/// - If the decompression fails [`DecompressionError::DecompressionFailed`]
+/// - If the decompressed data is not the size of `output` [`DecompressionError::SizeMismatch`]
+///
+/// # Safety
+///
+/// This function is unsafe because it calls [`decompress`].
+unsafe fn decompress_into(
+    codecs: &[Arc<dyn Codec>],
+    compressed_buffer: &[u8],
+    scratch: &mut Vec<u8>,
+    output: &mut [u8],
+) -> Result<()> {
+    unsafe { decompress_block(codecs, compressed_buffer, scratch, output.len())? };
+    if scratch.len() != output.len() {
+        return Err(DecompressionError::SizeMismatch {
+            expected: output.len(),
+            found: scratch.len(),
+        }
+        .into());
+    }
+    output.copy_from_slice(scratch);
+    Ok(())
+}
+
+/// Returns the size of the buffer needed to compress `length` bytes with [`compress`], in the
+/// worst case of incompressible data.
+///
+/// Returns [`None`] if the size overflows [`usize`].
+#[must_use]
+pub fn compressed_bound(length: usize) -> Option<usize> {
+    length
+        .div_ceil(COMPRESSION_CHUNK_SIZE)
+        .checked_mul(COMPRESSION_CHUNK_OVERHEAD)?
+        .checked_add(length)?
+        .checked_add(COMPRESSION_PADDING)
+}
+
+/// Compresses a buffer with Kraken.
+///
+/// # Arguments
+///
+/// * `buffer` - The data to compress.
+/// * `level` - Oodle compression level, from 0 (no compression) to 9 (optimal but slowest).
+///   Level 4 ("normal") is a good balance between speed and size.
+///
+/// # Errors
+/// - If the compression fails [`DecompressionError::CompressionFailed`]
+/// - If the worst case size of the compressed data overflows [`usize`] [`DecompressionError::BufferSizeOverflow`]
+/// - If the crate was built without the `kraken` feature [`DecompressionError::CompressionUnavailable`]
+#[cfg(kraken_backend = "native")]
+pub fn compress(buffer: &[u8], level: i32) -> Result<Vec<u8>> {
+    let bound = compressed_bound(buffer.len()).ok_or(DecompressionError::BufferSizeOverflow)?;
+    let mut output = vec![0; bound];
+    // SAFETY: The output buffer is at least the worst case size of the compressed data, as
+    // computed by `compressed_bound` from the size of the input.
+    let result =
+        unsafe { Kraken_Compress(buffer.as_ptr(), buffer.len(), output.as_mut_ptr(), level) };
+    let size = usize::try_from(result)
+        .ok()
+        .filter(|&size| size > 0 || buffer.is_empty())
+        .ok_or(DecompressionError::CompressionFailed(result))?;
+    if size > output.len() {
+        return Err(DecompressionError::BufferSizeOverflow.into());
+    }
+    output.truncate(size);
+    Ok(output)
+}
+
+/// Fallback used when the crate is built without the `kraken` feature, or with `no-kraken`.
+///
+/// # Errors
+/// - Always fails with [`DecompressionError::CompressionUnavailable`]
+#[cfg(not(kraken_backend = "native"))]
+pub fn compress(_buffer: &[u8], _level: i32) -> Result<Vec<u8>> {
+    Err(DecompressionError::CompressionUnavailable.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::Error;
+
+    /// Blocks starting with `TEST`, followed by the uncompressed data.
+    struct TestCodec;
+
+    impl Codec for TestCodec {
+        fn name(&self) -> &'static str {
+            "test"
+        }
+
+        fn matches(&self, compressed: &[u8]) -> bool {
+            compressed.starts_with(b"TEST")
+        }
+
+        fn decompress(&self, compressed: &[u8], output: &mut Vec<u8>, _size: usize) -> Result<()> {
+            output.clear();
+            output.extend_from_slice(&compressed[4..]);
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[cfg(kraken_backend)]
+    /// Verifies that Kraken blocks are decompressed, using a block made of a single uncompressed
+    /// chunk (header `0xCC`, Kraken decoder `0x06`).
+    fn test_decompress_uncompressed_chunk() {
+        let mut compressed = vec![0xCC, 0x06];
+        compressed.extend_from_slice(b"Halo Infinite tag data");
+        let mut output = Vec::new();
+        let length = unsafe { decompress(&compressed, &mut output, 22) }.unwrap();
+        assert_eq!(length, 22);
+        assert_eq!(output, b"Halo Infinite tag data");
+        assert!(unsafe { decompress(&[0x8C, 0x06], &mut output, 22) }.is_err());
+    }
+
+    #[test]
+    #[cfg(not(kraken_backend))]
+    /// Verifies that compressed blocks are rejected with a clear error without a Kraken decoder.
+    fn test_decompress_unavailable() {
+        let mut output = Vec::new();
+        assert!(matches!(
+            unsafe { decompress(&[0xCC, 0x06, 0x00], &mut output, 1) },
+            Err(Error::DecompressionError(
+                DecompressionError::CompressionUnavailable
+            ))
+        ));
+        assert!(matches!(
+            compress(&[0], 4),
+            Err(Error::DecompressionError(
+                DecompressionError::CompressionUnavailable
+            ))
+        ));
+    }
+
+    #[test]
+    /// Verifies that the compression bound grows with every started chunk of the input.
+    fn test_compressed_bound() {
+        assert_eq!(compressed_bound(0), Some(COMPRESSION_PADDING));
+        assert_eq!(compressed_bound(1), Some(1 + 274 + COMPRESSION_PADDING));
+        assert_eq!(
+            compressed_bound(0x0400_0001),
+            Some(0x0400_0001 + 257 * 274 + COMPRESSION_PADDING)
+        );
+        assert_eq!(compressed_bound(usize::MAX), None);
+    }
+
+    #[test]
+    #[cfg(kraken_backend = "native")]
+    /// Verifies that incompressible buffers larger than the fixed padding of the output buffer
+    /// can be compressed and decompressed back.
+    fn test_compress_incompressible() {
+        let mut state = 0x2545_F491_u32;
+        let buffer: Vec<u8> = (0..0x0400_0001)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state.to_le_bytes()[0]
+            })
+            .collect();
+        let compressed = compress(&buffer, 1).unwrap();
+        assert!(compressed.len() <= compressed_bound(buffer.len()).unwrap());
+
+        let mut output = Vec::new();
+        unsafe { decompress(&compressed, &mut output, buffer.len()) }.unwrap();
+        assert_eq!(output, buffer);
+    }
+
+    #[test]
+    /// Verifies that blocks decompressing to fewer bytes than expected are rejected.
+    fn test_read_block_size_mismatch() {
+        let codecs: Vec<Arc<dyn Codec>> = vec![Arc::new(TestCodec)];
+        let mut context = DecompressionContext::new();
+        let mut output = [0xFF; 4];
+        unsafe { context.read_block(&codecs, &mut Cursor::new(b"TESTdata"), 8, &mut output) }
+            .unwrap();
+        assert_eq!(&output, b"data");
+
+        let result =
+            unsafe { context.read_block(&codecs, &mut Cursor::new(b"TESTda"), 6, &mut output) };
+        assert!(matches!(
+            result,
+            Err(Error::DecompressionError(
+                DecompressionError::SizeMismatch {
+                    expected: 4,
+                    found: 2
+                }
+            ))
+        ));
+    }
+}
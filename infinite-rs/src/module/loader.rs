@@ -1,25 +1,41 @@
 //! Main abstraction file for modules.
 
 use byteorder::{LE, ReadBytesExt};
+#[cfg(feature = "parallel")]
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+#[cfg(feature = "parallel")]
+use std::{
+    collections::HashMap,
+    io::{Cursor, Read},
+};
 use std::{
     fs::File,
     io::{BufReader, Seek, SeekFrom},
-    path::Path,
+    ops::Range,
+    path::{Path, PathBuf},
     ptr::eq,
 };
 
 use super::{
     block::ModuleBlockEntry,
-    file::{DataOffsetType, ModuleFileEntry},
+    file::{DataOffsetType, FILE_ENTRY_SIZE, ModuleFileEntry},
     header::{ModuleHeader, ModuleVersion},
+    kraken::DecompressionContext,
+    resource::{ActualResourceReader, BlockReader},
 };
-use crate::Result;
 use crate::{
     Error,
-    common::{errors::TagError, extensions::BufReaderExt},
+    common::{
+        errors::{ErrorContext, ResultExt, TagError},
+        extensions::BufReaderExt,
+        hash::compute_tag_id,
+        options::{ParseOptions, Warning},
+    },
 };
+use crate::{Result, TagId};
 
 #[derive(Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// Module structure which contains the layout of the entire module file.
 pub struct ModuleFile {
     /// Information relating to how the other fields should be read.
@@ -33,11 +49,24 @@ pub struct ModuleFile {
     /// Offset in [`BufReader`] where file data starts.
     file_data_offset: u64,
     /// Reference to the module file buffer.
+    #[cfg_attr(feature = "serde", serde(skip))]
     file_handle: Option<BufReader<File>>,
     /// Reference to HD1 buffer if it exists.
+    #[cfg_attr(feature = "serde", serde(skip))]
     hd1_file: Option<BufReader<File>>,
     /// Whether to use the HD1 module or not.
     pub use_hd1: bool,
+    /// Options controlling how tags are parsed.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub options: ParseOptions,
+    /// Recoverable issues found while reading the module, see [`ParseOptions::strict`].
+    pub warnings: Vec<Warning>,
+    /// Buffers reused to decompress the blocks of every tag read.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    decompression_context: DecompressionContext,
+    /// Path of the module file, attached to errors while reading tags.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    file_path: Option<PathBuf>,
 }
 
 impl ModuleFile {
@@ -48,6 +77,19 @@ impl ModuleFile {
         Ok(module)
     }
 
+    /// Instantiates a [`ModuleFile`] object from the given file path, using the given parse options.
+    pub fn from_path_with_options<T: AsRef<Path>>(
+        file_path: T,
+        options: ParseOptions,
+    ) -> Result<Self> {
+        let mut module = Self {
+            options,
+            ..Default::default()
+        };
+        module.read(file_path)?;
+        Ok(module)
+    }
+
     /// Reads the module file from the given file path.
     /// This function reads the entire structure of the module file.
     /// It also calculates and stores important offsets within the file.
@@ -59,16 +101,54 @@ impl ModuleFile {
     /// # Errors
     /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
     /// - If the string table has invalid UTF-8 [`Utf8ReadingError`](`crate::Error::Utf8ReadingError`)
+    ///
+    /// Errors are wrapped in [`WithContext`](`crate::Error::WithContext`), containing the path of
+    /// the module.
     pub fn read<T: AsRef<Path>>(&mut self, file_path: T) -> Result<()> {
-        let file = File::open(&file_path)?;
+        let file_path = file_path.as_ref();
+        self.file_path = Some(file_path.to_path_buf());
+        self.read_module(file_path).with_context(|| ErrorContext {
+            module_path: self.file_path.clone(),
+            ..Default::default()
+        })
+    }
+
+    /// Reads the module file from the given file path, see [`read`](`ModuleFile::read`).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %file_path.display()), err)
+    )]
+    fn read_module(&mut self, file_path: &Path) -> Result<()> {
+        let file = File::open(file_path)?;
         let mut reader = BufReader::new(file);
+        self.warnings.clear();
 
         self.header.read(&mut reader)?;
+        if let Some(cache) = &self.options.tag_cache {
+            cache.validate(self.header.module_id, self.header.build_version)?;
+        }
         self.open_hd1(file_path)?;
 
-        for _ in 0..self.header.file_count {
+        for file_index in 0..self.header.file_count as usize {
+            let entry_offset = reader.stream_position()?;
             let mut file = ModuleFileEntry::default();
-            file.read(&mut reader, self.header.version == ModuleVersion::Flight1)?;
+            match file.read(&mut reader, self.header.version == ModuleVersion::Flight1) {
+                Ok(0) => {}
+                Ok(unknown_flags) => self.warnings.push(Warning::UnknownFileFlags {
+                    file_index,
+                    bits: unknown_flags,
+                }),
+                Err(error) if !self.options.strict => {
+                    // Keep a placeholder so that the indices of the other entries stay valid.
+                    self.warnings.push(Warning::InvalidFileEntry {
+                        file_index,
+                        error: error.to_string(),
+                    });
+                    file = ModuleFileEntry::default();
+                    reader.seek(SeekFrom::Start(entry_offset + FILE_ENTRY_SIZE))?;
+                }
+                Err(error) => return Err(error),
+            }
             self.files.push(file);
         }
 
@@ -93,7 +173,9 @@ impl ModuleFile {
                 reader.seek(SeekFrom::Start(
                     strings_offset + u64::from(file.name_offset),
                 ))?;
-                file.tag_name = reader.read_null_terminated_string()?;
+                let max_length = self.header.strings_size.saturating_sub(file.name_offset);
+                file.tag_name =
+                    reader.read_null_terminated_string_bounded(usize::try_from(max_length)?)?;
             }
         } else {
             let tag_paths: Vec<String> = (0..self.files.len())
@@ -106,14 +188,25 @@ impl ModuleFile {
         }
 
         reader.seek(SeekFrom::Start(post_resource_offset))?;
-        self.blocks =
-            reader.read_enumerable::<ModuleBlockEntry>(u64::from(self.header.block_count))?;
+        self.blocks = ModuleBlockEntry::read_table(
+            &mut reader,
+            u64::from(self.header.block_count),
+            self.options.strict,
+            &mut self.warnings,
+        )?;
 
         // Align to 0x?????000
         let stream_position = reader.stream_position()?;
         reader.seek(SeekFrom::Start((stream_position / 0x1000 + 1) * 0x1000))?;
         self.file_data_offset = reader.stream_position()?;
         self.file_handle = Some(reader);
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            files = self.files.len(),
+            blocks = self.blocks.len(),
+            warnings = self.warnings.len(),
+            "opened module"
+        );
         Ok(())
     }
 
@@ -134,6 +227,7 @@ impl ModuleFile {
     ///
     /// This function returns the tag path of a file entry based on the provided index.
     /// For file entries that have a parent, the function recursively gets the tag path of the parent and appends the child index to the path.
+    /// Other file entries are looked up in [`ParseOptions::tag_names`], and are named after their group and id otherwise.
     ///
     /// # Arguments
     /// * `index` - The index of the file entry to get the tag path from.
@@ -146,7 +240,7 @@ impl ModuleFile {
             return Err(Error::TagError(TagError::RecursionDepth));
         }
         let file = &self.files[index];
-        if file.tag_id == -1 && file.parent_index != -1 {
+        if file.tag_id.is_null() && file.parent_index != -1 {
             let parent = &self.files[usize::try_from(file.parent_index)?];
             let mut parent_name: String = String::new();
             let child_index = self.resource_indices[usize::try_from(parent.resource_index)?
@@ -159,20 +253,47 @@ impl ModuleFile {
             if parent.tag_name.is_empty() {
                 parent_name = self.get_tag_path(usize::try_from(file.parent_index)?, depth + 1)?;
             }
-            if parent.tag_id == -1 {
+            if parent.tag_id.is_null() {
                 parent_name = self.get_tag_path(usize::try_from(file.parent_index)?, depth + 1)?;
                 Ok(format!("{parent_name}[{child_index}:block]"))
             } else {
                 Ok(format!("{parent_name}[{child_index}:resource]"))
             }
+        } else if let Some(path) = self
+            .options
+            .tag_names
+            .as_ref()
+            .and_then(|tag_names| tag_names.get(file.tag_id))
+        {
+            Ok(path.to_string())
         } else {
             Ok(format!(
                 "{}/{}.{}",
-                file.tag_group, file.tag_id, file.tag_group
+                file.tag_group, file.tag_id.0, file.tag_group
             ))
         }
     }
 
+    /// Gets the indices of the resource files of a file entry, such as the streamed mipmaps of a
+    /// bitmap, in the order they are referenced by its resources.
+    ///
+    /// Returns an empty slice if the index is out of bounds or the entry has no resources.
+    #[must_use]
+    pub fn resources_of(&self, index: u32) -> &[u32] {
+        let Some(file) = self.files.get(index as usize) else {
+            return &[];
+        };
+        let (Ok(start), Ok(count)) = (
+            usize::try_from(file.resource_index),
+            usize::try_from(file.resource_count),
+        ) else {
+            return &[];
+        };
+        self.resource_indices
+            .get(start..start + count)
+            .unwrap_or_default()
+    }
+
     /// Reads a specific tag from the module file.
     ///
     /// This function reads a specific tag from the module file based on the provided index.
@@ -185,12 +306,35 @@ impl ModuleFile {
     /// # Returns
     ///
     /// Returns a mutable reference to the file if the read operation is successful, or an [`Error`](`crate::Error`), a [`None`] if the file was not read (if tag offset is specified as invalid) or the containing the I/O error if any reading operation fails.
+    ///
+    /// Errors are wrapped in [`WithContext`](`crate::Error::WithContext`), containing the path of
+    /// the module, the id and name of the tag, and the block and offset being read.
     pub fn read_tag(&mut self, index: u32) -> Result<Option<&mut ModuleFileEntry>> {
+        let is_read = self.load_tag(index).with_context(|| ErrorContext {
+            module_path: self.file_path.clone(),
+            ..self.files[index as usize].error_context()
+        })?;
+        Ok(is_read.then(|| &mut self.files[index as usize]))
+    }
+
+    /// Reads a specific tag from the module file, see [`read_tag`](`ModuleFile::read_tag`).
+    /// Returns whether the tag was read.
+    fn load_tag(&mut self, index: u32) -> Result<bool> {
         let file = &mut self.files[index as usize];
         if file.data_offset_flags.contains(DataOffsetType::DEBUG) {
-            return Ok(None); // Currently not reading debug modules because we don't have an
+            return Ok(false); // Currently not reading debug modules because we don't have an
             // example.
         }
+        let cache = self.options.tag_cache.clone();
+        let was_loaded = file.is_loaded;
+        if let Some(cache) = cache.as_ref().filter(|_| !was_loaded) {
+            if let Some(data) = cache.get(self.header.module_id, file.tag_id, file.asset_hash)? {
+                #[cfg(feature = "tracing")]
+                tracing::trace!(bytes = data.len(), "read tag from cache");
+                file.load_data(data, &self.header.version, &self.options)?;
+                return Ok(true);
+            }
+        }
 
         let mut offset = self.header.hd1_delta;
         if file.data_offset_flags.contains(DataOffsetType::USE_HD1) {
@@ -200,24 +344,289 @@ impl ModuleFile {
                 }
                 file.read_tag(
                     module_file,
-                    offset,
+                    file.file_offset(offset, true),
                     &self.blocks,
                     &self.header.version,
-                    true,
+                    &self.options,
+                    &mut self.decompression_context,
                 )?;
             } else {
-                return Ok(None);
+                return Ok(false);
             }
         } else if let Some(ref mut module_file) = self.file_handle {
             file.read_tag(
                 module_file,
-                self.file_data_offset,
+                file.file_offset(self.file_data_offset, false),
                 &self.blocks,
                 &self.header.version,
-                false,
+                &self.options,
+                &mut self.decompression_context,
             )?;
         }
-        Ok(Some(file))
+        if let (Some(cache), false) = (cache, was_loaded) {
+            if let Some(stream) = &file.data_stream {
+                cache.insert(
+                    self.header.module_id,
+                    file.tag_id,
+                    file.asset_hash,
+                    stream.get_ref().get_ref(),
+                )?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Reads several tags from the module file, decompressing and parsing them in parallel.
+    ///
+    /// The compressed data of every tag is read from the module first, then the tags are
+    /// decompressed and parsed on the thread pool of [`rayon`], which also decompresses the blocks
+    /// of large tags. Tags that are already loaded, or that [`read_tag`](`ModuleFile::read_tag`)
+    /// would not read, are skipped.
+    ///
+    /// When [`ParseOptions::tag_cache`] is set, tags are read one at a time with
+    /// [`read_tag`](`ModuleFile::read_tag`) instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `indices` - The indices of the file entries to read, in the [`files`](`ModuleFile::files`) vector.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use infinite_rs::ModuleFile;
+    ///
+    /// let mut module = ModuleFile::from_path("C:/XboxGames/Halo Infinite/Content/deploy/any/globals-rtx-new.module").unwrap();
+    /// let indices = (0..module.files.len() as u32).collect::<Vec<_>>();
+    /// module.read_tags(&indices).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// - If reading a tag fails, see [`read_tag`](`ModuleFile::read_tag`)
+    #[cfg(feature = "parallel")]
+    pub fn read_tags(&mut self, indices: &[u32]) -> Result<()> {
+        if self.options.tag_cache.is_some() {
+            for &index in indices {
+                self.read_tag(index)?;
+            }
+            return Ok(());
+        }
+
+        let mut pending = HashMap::new();
+        for &index in indices {
+            let file = &self.files[index as usize];
+            if file.is_loaded
+                || file.data_offset_flags.contains(DataOffsetType::DEBUG)
+                || pending.contains_key(&(index as usize))
+            {
+                continue;
+            }
+            let compressed = self
+                .read_compressed_data(index)
+                .with_context(|| ErrorContext {
+                    module_path: self.file_path.clone(),
+                    ..self.files[index as usize].error_context()
+                })?;
+            if let Some(compressed) = compressed {
+                pending.insert(index as usize, compressed);
+            }
+        }
+
+        let (blocks, version, options) = (&self.blocks, &self.header.version, &self.options);
+        let module_path = &self.file_path;
+        self.files.par_iter_mut().enumerate().try_for_each_init(
+            DecompressionContext::new,
+            |context, (index, file)| {
+                let Some(compressed) = pending.get(&index) else {
+                    return Ok(());
+                };
+                file.read_tag(
+                    &mut Cursor::new(compressed),
+                    0,
+                    blocks,
+                    version,
+                    options,
+                    context,
+                )
+                .with_context(|| ErrorContext {
+                    module_path: module_path.clone(),
+                    ..file.error_context()
+                })
+            },
+        )
+    }
+
+    /// Reads the compressed data of a file entry, as stored in the module (or HD1) file.
+    /// Returns [`None`] if the data is in an HD1 file that is not loaded.
+    #[cfg(feature = "parallel")]
+    fn read_compressed_data(&mut self, index: u32) -> Result<Option<Vec<u8>>> {
+        let file = &self.files[index as usize];
+        let (module_file, file_offset) = if file.data_offset_flags.contains(DataOffsetType::USE_HD1)
+        {
+            let Some(module_file) = self.hd1_file.as_mut() else {
+                return Ok(None);
+            };
+            let mut offset = self.header.hd1_delta;
+            if self.header.version <= ModuleVersion::CampaignFlight {
+                offset += self.header.hd1_delta;
+            }
+            (module_file, file.file_offset(offset, true))
+        } else if let Some(module_file) = self.file_handle.as_mut() {
+            (module_file, file.file_offset(self.file_data_offset, false))
+        } else {
+            return Ok(None);
+        };
+        module_file.seek(SeekFrom::Start(file_offset))?;
+        let mut compressed = vec![0u8; file.total_compressed_size as usize];
+        module_file.read_exact(&mut compressed)?;
+        Ok(Some(compressed))
+    }
+
+    /// Creates a reader over the entire data of a file entry (including header), without loading
+    /// the tag.
+    ///
+    /// Blocks are read and decompressed one at a time, so huge tags can be written to disk or
+    /// hashed with bounded memory. See [`BlockReader`].
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the file entry to read. This index corresponds to the position of the file entry in the [`files`](`ModuleFile::files`) vector.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::io::copy;
+    /// use infinite_rs::ModuleFile;
+    ///
+    /// let mut module = ModuleFile::from_path("C:/XboxGames/Halo Infinite/Content/deploy/any/globals-rtx-new.module").unwrap();
+    /// if let Some(mut reader) = module.file_reader(0) {
+    ///     let mut output = std::fs::File::create("tag.bin").unwrap();
+    ///     copy(&mut reader, &mut output).unwrap();
+    /// }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// Returns [`None`] under the same conditions as [`read_tag`](`ModuleFile::read_tag`).
+    /// Errors while reading or decompressing blocks are returned by [`Read::read`](`std::io::Read::read`).
+    pub fn file_reader(&mut self, index: u32) -> Option<BlockReader<'_>> {
+        self.block_reader(index, |file| 0..u64::from(file.total_uncompressed_size))
+    }
+
+    /// Creates a reader over the actual resource section of a tag, without loading the tag.
+    ///
+    /// Only the module blocks overlapping the section are read and decompressed, one at a time,
+    /// so large resources (such as bitmaps) can be streamed without keeping the entire tag in
+    /// memory. See [`ActualResourceReader`].
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the file entry to read. This index corresponds to the position of the file entry in the [`files`](`ModuleFile::files`) vector.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::io::copy;
+    /// use infinite_rs::ModuleFile;
+    ///
+    /// let mut module = ModuleFile::from_path("C:/XboxGames/Halo Infinite/Content/deploy/any/globals-rtx-new.module").unwrap();
+    /// if let Some(mut reader) = module.actual_resource_reader(0) {
+    ///     let mut output = std::fs::File::create("resource.bin").unwrap();
+    ///     copy(&mut reader, &mut output).unwrap();
+    /// }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// Returns [`None`] under the same conditions as [`read_tag`](`ModuleFile::read_tag`).
+    /// Errors while reading or decompressing blocks are returned by [`Read::read`](`std::io::Read::read`).
+    pub fn actual_resource_reader(&mut self, index: u32) -> Option<ActualResourceReader<'_>> {
+        self.block_reader(index, BlockReader::actual_resource_region)
+    }
+
+    /// Creates a reader over a region of the data of a file entry, computed from the entry.
+    fn block_reader(
+        &mut self,
+        index: u32,
+        region: impl FnOnce(&ModuleFileEntry) -> Range<u64>,
+    ) -> Option<BlockReader<'_>> {
+        let file = &self.files[index as usize];
+        if file.data_offset_flags.contains(DataOffsetType::DEBUG) {
+            return None;
+        }
+
+        if file.data_offset_flags.contains(DataOffsetType::USE_HD1) {
+            let module_file = self.hd1_file.as_mut()?;
+            let mut offset = self.header.hd1_delta;
+            if self.header.version <= ModuleVersion::CampaignFlight {
+                offset += self.header.hd1_delta;
+            }
+            let file_offset = file.file_offset(offset, true);
+            Some(BlockReader::new(
+                module_file,
+                file_offset,
+                file,
+                &self.blocks,
+                region(file),
+                self.options.codecs.clone(),
+            ))
+        } else if let Some(ref mut module_file) = self.file_handle {
+            let file_offset = file.file_offset(self.file_data_offset, false);
+            Some(BlockReader::new(
+                module_file,
+                file_offset,
+                file,
+                &self.blocks,
+                region(file),
+                self.options.codecs.clone(),
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Finds the tags containing a string id value in their data.
+    ///
+    /// Every tag in the module is loaded with [`read_tag`](`ModuleFile::read_tag`) and scanned
+    /// using [`ModuleFileEntry::contains_string_id`]. Raw files and tags that cannot be read
+    /// (see [`read_tag`](`ModuleFile::read_tag`)) are skipped. With the `parallel` feature, the
+    /// tags are first loaded together with [`read_tags`](`ModuleFile::read_tags`).
+    ///
+    /// # Arguments
+    ///
+    /// * `string_id` - `MurmurHash3_x86_32` hash of the string.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use infinite_rs::ModuleFile;
+    /// use infinite_rs::common::hash::murmur3_x86_32;
+    ///
+    /// let mut module = ModuleFile::from_path("C:/XboxGames/Halo Infinite/Content/deploy/any/globals-rtx-new.module").unwrap();
+    /// let string_id = murmur3_x86_32(b"default", 0) as i32;
+    /// for index in module.find_tags_containing_string_id(string_id).unwrap() {
+    ///     println!("{}", module.files[index as usize].tag_name);
+    /// }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// Returns the indices of the matching tags in [`files`](`ModuleFile::files`).
+    ///
+    /// # Errors
+    /// - If reading a tag fails, see [`read_tag`](`ModuleFile::read_tag`)
+    pub fn find_tags_containing_string_id(&mut self, string_id: i32) -> Result<Vec<u32>> {
+        let mut matches = Vec::new();
+        #[cfg(feature = "parallel")]
+        self.read_tags(&(0..u32::try_from(self.files.len())?).collect::<Vec<_>>())?;
+        for index in 0..u32::try_from(self.files.len())? {
+            let Some(file) = self.read_tag(index)? else {
+                continue;
+            };
+            if file.tag_info.is_some() && file.contains_string_id(string_id)? {
+                matches.push(index);
+            }
+        }
+        Ok(matches)
     }
 
     /// Searches for the index of the tag given the `global_id`.
@@ -235,7 +644,7 @@ impl ModuleFile {
     /// Returns a mutable reference to the file if successful. If the tag is not
     /// found or couldn't be read, it returns [`None`]. Any I/O error encountered during the operation is also returned
     /// if it occurs.
-    pub fn read_tag_from_id(&mut self, global_id: i32) -> Result<Option<&mut ModuleFileEntry>> {
+    pub fn read_tag_from_id(&mut self, global_id: TagId) -> Result<Option<&mut ModuleFileEntry>> {
         if let Some(index) = self.files.iter().position(|file| file.tag_id == global_id) {
             let has_read = self.read_tag(u32::try_from(index)?)?;
             if let Some(tag) = has_read {
@@ -247,4 +656,21 @@ impl ModuleFile {
             Ok(None)
         }
     }
+
+    /// Reads a tag from the module file given its path.
+    ///
+    /// The path is hashed using [`compute_tag_id`] and the tag is then read using
+    /// [`read_tag_from_id`](`ModuleFile::read_tag_from_id`).
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the tag to read.
+    ///
+    /// # Returns
+    ///
+    /// Returns a mutable reference to the file if successful. If the tag is not
+    /// found or couldn't be read, it returns [`None`].
+    pub fn read_tag_from_path(&mut self, path: &str) -> Result<Option<&mut ModuleFileEntry>> {
+        self.read_tag_from_id(compute_tag_id(path))
+    }
 }
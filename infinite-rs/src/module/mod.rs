@@ -1,7 +1,14 @@
 //! Main Interface for reading module files.
 
 pub mod block;
+pub mod cache;
+pub mod codec;
+pub mod collection;
 pub mod file;
 pub mod header;
 pub mod kraken;
 pub mod loader;
+#[cfg(all(feature = "oodle", any(unix, windows)))]
+pub mod oodle;
+pub mod resource;
+pub mod writer;
@@ -0,0 +1,161 @@
+//! Decompression through the official Oodle shared library.
+//!
+//! The bundled Kraken decompressor is a re-implementation of Oodle Kraken. For bit-exact behavior,
+//! the `oo2core` library shipped with the game (`oo2core_8_win64.dll` on Windows) can be loaded at
+//! runtime instead, and added to [`ParseOptions::codecs`](`crate::common::options::ParseOptions::codecs`)
+//! so that Oodle blocks are decompressed with it. The library is not distributed with this crate,
+//! and its path has to be supplied by the user.
+
+use std::ffi::{c_int, c_void};
+use std::path::Path;
+use std::ptr;
+
+use libloading::Library;
+
+use super::codec::{Codec, CodecKind, detect_codec};
+use crate::Result;
+use crate::common::errors::DecompressionError;
+
+/// `OodleLZ_Decompress` from `oodle2.h`.
+type OodleLzDecompress = unsafe extern "system" fn(
+    compressed_buffer: *const c_void,
+    compressed_size: isize,
+    raw_buffer: *mut c_void,
+    raw_size: isize,
+    fuzz_safe: c_int,
+    check_crc: c_int,
+    verbosity: c_int,
+    decoder_buffer_base: *mut c_void,
+    decoder_buffer_size: isize,
+    callback: *mut c_void,
+    callback_user_data: *mut c_void,
+    decoder_memory: *mut c_void,
+    decoder_memory_size: isize,
+    thread_phase: c_int,
+) -> isize;
+
+/// `OodleLZ_FuzzSafe_Yes`, rejecting corrupt data instead of reading out of bounds.
+const FUZZ_SAFE_YES: c_int = 1;
+/// `OodleLZ_Decode_Unthreaded`, decoding the whole buffer on the calling thread.
+const DECODE_UNTHREADED: c_int = 3;
+
+#[derive(Debug)]
+/// Handle to a loaded Oodle shared library, used as a [`Codec`] for Oodle blocks.
+///
+/// The library stays loaded until the handle is dropped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use infinite_rs::ModuleFile;
+/// use infinite_rs::common::options::ParseOptions;
+/// use infinite_rs::module::oodle::OodleLibrary;
+///
+/// let oodle = unsafe { OodleLibrary::load("oo2core_8_win64.dll") }.unwrap();
+/// let options = ParseOptions {
+///     codecs: vec![Arc::new(oodle)],
+///     ..Default::default()
+/// };
+/// let module = ModuleFile::from_path_with_options("deploy/any/globals-rtx-new.module", options).unwrap();
+/// ```
+pub struct OodleLibrary {
+    /// Loaded library, which `decompress` points into.
+    _library: Library,
+    /// Address of `OodleLZ_Decompress` inside the library.
+    decompress: OodleLzDecompress,
+}
+
+impl OodleLibrary {
+    /// Loads an Oodle shared library from the given path.
+    ///
+    /// # Errors
+    /// - If the library cannot be loaded [`DecompressionError::LibraryLoadFailed`]
+    /// - If the library does not export `OodleLZ_Decompress` [`DecompressionError::SymbolNotFound`]
+    ///
+    /// # Safety
+    ///
+    /// Loading a library runs its initialization code. The caller must ensure that the path points
+    /// to a genuine Oodle library, as the exported function is called with the Oodle signature.
+    pub unsafe fn load<T: AsRef<Path>>(path: T) -> Result<Self> {
+        let path = path.as_ref();
+        let library = unsafe { Library::new(path) }
+            .map_err(|_| DecompressionError::LibraryLoadFailed(path.display().to_string()))?;
+        // SAFETY: The symbol is `OodleLZ_Decompress`, which has the signature above. The function
+        // pointer stays valid as long as the library, which is kept alongside it.
+        let decompress = *unsafe { library.get::<OodleLzDecompress>(b"OodleLZ_Decompress\0") }
+            .map_err(|_| DecompressionError::SymbolNotFound("OodleLZ_Decompress"))?;
+        Ok(Self {
+            _library: library,
+            decompress,
+        })
+    }
+}
+
+impl Codec for OodleLibrary {
+    fn name(&self) -> &'static str {
+        "oodle"
+    }
+
+    fn matches(&self, compressed: &[u8]) -> bool {
+        detect_codec(compressed) == CodecKind::Oodle
+    }
+
+    /// Decompresses an Oodle-compressed buffer of `size` bytes into `output`.
+    ///
+    /// # Errors
+    /// - If the decompression fails [`DecompressionError::DecompressionFailed`]
+    /// - If the buffer sizes exceed the maximum size of [`isize`] or [`i32`] [`DecompressionError::BufferSizeOverflow`]
+    fn decompress(&self, compressed: &[u8], output: &mut Vec<u8>, size: usize) -> Result<()> {
+        let compressed_size = isize::try_from(compressed.len())
+            .map_err(|_| DecompressionError::BufferSizeOverflow)?;
+        let raw_size = isize::try_from(size).map_err(|_| DecompressionError::BufferSizeOverflow)?;
+        output.clear();
+        output.resize(size, 0);
+        // SAFETY: Both buffers are valid for the sizes passed, and the optional arguments are
+        // documented to accept null pointers.
+        let result = unsafe {
+            (self.decompress)(
+                compressed.as_ptr().cast(),
+                compressed_size,
+                output.as_mut_ptr().cast(),
+                raw_size,
+                FUZZ_SAFE_YES,
+                0,
+                0,
+                ptr::null_mut(),
+                0,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                0,
+                DECODE_UNTHREADED,
+            )
+        };
+        if result <= 0 {
+            let code = i32::try_from(result).unwrap_or(i32::MIN);
+            return Err(DecompressionError::DecompressionFailed(code).into());
+        }
+        let result = usize::try_from(result).map_err(|_| DecompressionError::BufferSizeOverflow)?;
+        output.truncate(result);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    /// Verifies that missing libraries are reported instead of panicking.
+    fn test_load_missing_library() {
+        let result = unsafe { OodleLibrary::load("missing/oo2core_9_win64.dll") };
+        assert!(matches!(
+            result,
+            Err(Error::DecompressionError(
+                DecompressionError::LibraryLoadFailed(_)
+            ))
+        ));
+    }
+}
@@ -0,0 +1,288 @@
+//! Streaming readers over the data of a file entry.
+//!
+//! Instead of loading the entire tag with [`read_tag`](`super::loader::ModuleFile::read_tag`),
+//! a [`BlockReader`] reads and decompresses the module blocks making up part of a file entry one
+//! block at a time, so huge tags can be piped to disk or hashed with bounded memory.
+//!
+//! Actual resources (such as bitmap mipmaps or havok data) make up most of the size of some tags,
+//! and can be read on their own with an [`ActualResourceReader`].
+
+use std::{
+    fs::File,
+    io::{self, BufReader, Read, Seek, SeekFrom},
+    ops::Range,
+    sync::Arc,
+};
+
+use super::{
+    block::ModuleBlockEntry, codec::Codec, file::ModuleFileEntry, kraken::DecompressionContext,
+};
+
+#[derive(Debug)]
+/// Block of data making up part of a tag inside the module.
+struct ResourceChunk {
+    /// Offset of the compressed data, relative to the start of the file entry data.
+    compressed_offset: u64,
+    /// Size in bytes of the compressed data.
+    compressed_size: usize,
+    /// Range of the decompressed data inside the entire tag buffer.
+    decompressed: Range<u64>,
+    /// Whether the block is compressed.
+    is_compressed: bool,
+}
+
+/// Reader over the actual resource section of a tag.
+///
+/// Created by [`ModuleFile::actual_resource_reader`](`super::loader::ModuleFile::actual_resource_reader`).
+pub type ActualResourceReader<'a> = BlockReader<'a>;
+
+#[derive(Debug)]
+/// Reader over part of the data of a file entry, decompressing blocks as they are reached.
+///
+/// Created by [`ModuleFile::file_reader`](`super::loader::ModuleFile::file_reader`) and
+/// [`ModuleFile::actual_resource_reader`](`super::loader::ModuleFile::actual_resource_reader`).
+/// Only the most recently read block is kept in memory.
+pub struct BlockReader<'a> {
+    /// Reader of the module (or HD1) file containing the tag.
+    reader: &'a mut BufReader<File>,
+    /// Offset of the data of the file entry inside the module.
+    file_offset: u64,
+    /// Blocks overlapping the region, sorted by decompressed offset.
+    chunks: Vec<ResourceChunk>,
+    /// Range of the data read inside the entire tag buffer.
+    region: Range<u64>,
+    /// Current position inside the entire tag buffer.
+    position: u64,
+    /// Index of the last block read, whose contents are in `data`.
+    current: Option<usize>,
+    /// Contents of the last block read.
+    data: Vec<u8>,
+    /// Buffers reused to decompress blocks.
+    context: DecompressionContext,
+    /// Codecs tried before Kraken to decompress blocks.
+    codecs: Vec<Arc<dyn Codec>>,
+}
+
+impl<'a> BlockReader<'a> {
+    /// Creates a reader over a region of the data of a file entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Reader of the module (or HD1) file containing the tag.
+    /// * `file_offset` - Offset of the data of the file entry inside the module.
+    /// * `file_entry` - File entry to read.
+    /// * `blocks` - Metadata for data blocks of the module.
+    /// * `region` - Range to read inside the entire (decompressed) tag buffer.
+    /// * `codecs` - Codecs tried before Kraken to decompress blocks.
+    pub(super) fn new(
+        reader: &'a mut BufReader<File>,
+        file_offset: u64,
+        file_entry: &ModuleFileEntry,
+        blocks: &[ModuleBlockEntry],
+        region: Range<u64>,
+        codecs: Vec<Arc<dyn Codec>>,
+    ) -> Self {
+        let chunks = if file_entry.block_count == 0 {
+            vec![ResourceChunk {
+                compressed_offset: 0,
+                compressed_size: file_entry.total_compressed_size as usize,
+                decompressed: 0..u64::from(file_entry.total_uncompressed_size),
+                is_compressed: file_entry.total_compressed_size
+                    != file_entry.total_uncompressed_size,
+            }]
+        } else {
+            let first = usize::try_from(file_entry.block_index).unwrap_or(usize::MAX);
+            let last = first.saturating_add(file_entry.block_count as usize);
+            blocks
+                .get(first..last)
+                .unwrap_or_default()
+                .iter()
+                .map(|block| {
+                    let offset = u64::from(block.decompressed_offset);
+                    ResourceChunk {
+                        compressed_offset: u64::from(block.compressed_offset),
+                        compressed_size: block.compressed_size as usize,
+                        decompressed: offset..offset + u64::from(block.decompressed_size),
+                        is_compressed: block.is_compressed,
+                    }
+                })
+                .filter(|chunk| {
+                    chunk.decompressed.start < region.end && chunk.decompressed.end > region.start
+                })
+                .collect()
+        };
+
+        Self {
+            reader,
+            file_offset,
+            chunks,
+            position: region.start,
+            region,
+            current: None,
+            data: Vec::new(),
+            context: DecompressionContext::new(),
+            codecs,
+        }
+    }
+
+    /// Range of the actual resource section of a file entry inside the entire tag buffer.
+    pub(super) fn actual_resource_region(file_entry: &ModuleFileEntry) -> Range<u64> {
+        let start = u64::from(file_entry.uncompressed_header_size)
+            + u64::from(file_entry.uncompressed_tag_data_size)
+            + u64::from(file_entry.uncompressed_resource_data_size);
+        start..start + u64::from(file_entry.uncompressed_actual_resource_size)
+    }
+
+    /// Size in bytes of the data read.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.region.end - self.region.start
+    }
+
+    /// Whether there is no data to read.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.region.is_empty()
+    }
+
+    /// Reads and decompresses a block, unless it is already loaded.
+    fn load_chunk(&mut self, index: usize) -> io::Result<&[u8]> {
+        if self.current != Some(index) {
+            // Do not keep a partially read block if reading fails.
+            self.current = None;
+            let chunk = &self.chunks[index];
+            self.reader
+                .seek(SeekFrom::Start(self.file_offset + chunk.compressed_offset))?;
+            if chunk.is_compressed {
+                let size = usize::try_from(chunk.decompressed.end - chunk.decompressed.start)
+                    .map_err(io::Error::other)?;
+                self.data.clear();
+                self.data.resize(size, 0);
+                unsafe {
+                    self.context.read_block(
+                        &self.codecs,
+                        self.reader,
+                        chunk.compressed_size,
+                        &mut self.data,
+                    )
+                }
+                .map_err(io::Error::other)?;
+            } else {
+                self.data.clear();
+                self.data.resize(chunk.compressed_size, 0);
+                self.reader.read_exact(&mut self.data)?;
+            }
+            self.current = Some(index);
+        }
+        Ok(&self.data)
+    }
+}
+
+impl Read for BlockReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.region.end || buf.is_empty() {
+            return Ok(0);
+        }
+        let position = self.position;
+        let index = self
+            .chunks
+            .iter()
+            .position(|chunk| chunk.decompressed.contains(&position))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("no block contains offset {position:#X} of the tag"),
+                )
+            })?;
+        let chunk_start = self.chunks[index].decompressed.start;
+        let end = self.chunks[index].decompressed.end.min(self.region.end);
+        let data = self.load_chunk(index)?;
+
+        let start = usize::try_from(position - chunk_start).map_err(io::Error::other)?;
+        let available = usize::try_from(end - position).map_err(io::Error::other)?;
+        let length = buf
+            .len()
+            .min(available)
+            .min(data.len().saturating_sub(start));
+        if length == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        buf[..length].copy_from_slice(&data[start..start + length]);
+        self.position += length as u64;
+        Ok(length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env::temp_dir, fs::remove_file};
+
+    use crate::module::header::ModuleVersion;
+    use crate::module::writer::{ModuleWriter, ModuleWriterEntry};
+    use crate::{ModuleFile, TagId};
+
+    use super::*;
+
+    #[test]
+    /// Verifies that the actual resource section is read across block boundaries.
+    fn test_read_across_blocks() {
+        let mut writer = ModuleWriter::new(ModuleVersion::Season3);
+        writer.max_block_size = 0x10;
+        let data = (0..0x45).collect::<Vec<u8>>();
+        writer.add_file(ModuleWriterEntry {
+            tag_group: String::from("bitm"),
+            tag_id: TagId(0x5678),
+            parent_index: -1,
+            is_raw_file: true,
+            data: data.clone(),
+            ..Default::default()
+        });
+        let path = temp_dir().join("infinite-rs-actual-resource.module");
+        writer.write_to_path(&path).unwrap();
+        let mut module = ModuleFile::from_path(&path).unwrap();
+
+        // Raw files have no sections, so pretend the data is split into them.
+        module.files[0].uncompressed_header_size = 0x8;
+        module.files[0].uncompressed_tag_data_size = 0x4;
+        module.files[0].uncompressed_resource_data_size = 0x2;
+        module.files[0].uncompressed_actual_resource_size = 0x30;
+
+        let mut reader = module.actual_resource_reader(0).unwrap();
+        assert_eq!(reader.len(), 0x30);
+        let mut resource = Vec::new();
+        reader.read_to_end(&mut resource).unwrap();
+        assert_eq!(resource, data[0xE..0x3E]);
+        drop(module);
+        remove_file(path).unwrap();
+    }
+
+    #[test]
+    /// Verifies that the entire file entry can be streamed block by block.
+    fn test_file_reader() {
+        let mut writer = ModuleWriter::new(ModuleVersion::Season3);
+        writer.max_block_size = 0x10;
+        let data = (0..0x45).collect::<Vec<u8>>();
+        writer.add_file(ModuleWriterEntry {
+            tag_group: String::from("bitm"),
+            tag_id: TagId(0x5678),
+            parent_index: -1,
+            is_raw_file: true,
+            data: data.clone(),
+            ..Default::default()
+        });
+        let path = temp_dir().join("infinite-rs-file-reader.module");
+        writer.write_to_path(&path).unwrap();
+        let mut module = ModuleFile::from_path(&path).unwrap();
+
+        let mut reader = module.file_reader(0).unwrap();
+        assert_eq!(reader.len(), 0x45);
+        let mut buffer = [0; 0x18];
+        reader.read_exact(&mut buffer).unwrap();
+        assert_eq!(buffer, data[..0x18]);
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, data[0x18..]);
+        drop(module);
+        remove_file(path).unwrap();
+    }
+}
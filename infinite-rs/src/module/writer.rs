@@ -0,0 +1,460 @@
+//! Module writer for building `.module` files from tag buffers.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Cursor, Seek, Write},
+    path::Path,
+};
+
+use super::{
+    block::ModuleBlockEntry,
+    file::{FileEntryFlags, ModuleFileEntry},
+    header::{HEADER_MAGIC, ModuleHeader, ModuleVersion},
+    kraken::compress,
+};
+use crate::tag::header::TagHeader;
+use crate::{Result, TagId};
+
+/// Default maximum size in bytes of a single block inside a written module.
+pub const DEFAULT_MAX_BLOCK_SIZE: u32 = 0x10_0000;
+
+#[derive(Default, Debug)]
+/// File (tag) to be packed into a module by [`ModuleWriter`].
+pub struct ModuleWriterEntry {
+    /// 4 byte-long string for tag group, for example `bitm` or `mat `.
+    pub tag_group: String,
+    /// `MurmurHash3_x86_32` hash of the tag path (see [`compute_tag_id`](`crate::common::hash::compute_tag_id`)).
+    /// Should be set to [`TagId::NULL`] if file is a resource.
+    pub tag_id: TagId,
+    /// Name of the tag, only written to modules that contain a string table
+    /// ([`ModuleVersion::CampaignFlight`] and earlier).
+    pub tag_name: String,
+    /// Index of the parent file inside the module, -1 if none.
+    pub parent_index: i32,
+    /// `Murmur3_x64_128` hash of the source asset.
+    pub asset_hash: i128,
+    /// Indices of files in the module that are resources of this file.
+    pub resources: Vec<u32>,
+    /// "Raw file" that does not contain a tag header.
+    pub is_raw_file: bool,
+    /// Full contents of the file, including the tag header if not raw.
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug)]
+/// Builder used to create a module file from a set of tag buffers.
+///
+/// The writer computes the block layout of each file, emits the file table, string table,
+/// resource list and block table, and finally the packed file data.
+///
+/// # Examples
+///
+/// ```no_run
+/// use infinite_rs::module::header::ModuleVersion;
+/// use infinite_rs::module::writer::{ModuleWriter, ModuleWriterEntry};
+/// use infinite_rs::TagId;
+///
+/// let mut writer = ModuleWriter::new(ModuleVersion::Season3);
+/// writer.add_file(ModuleWriterEntry {
+///     tag_group: String::from("hsc*"),
+///     tag_id: TagId(0x1234),
+///     parent_index: -1,
+///     data: std::fs::read("script.hsc").unwrap(),
+///     ..Default::default()
+/// });
+/// writer.write_to_path("custom.module").unwrap();
+/// ```
+pub struct ModuleWriter {
+    /// Revision of the module to write.
+    pub version: ModuleVersion,
+    /// Unique identifier of module.
+    pub module_id: i64,
+    /// Build version stored in the module header.
+    pub build_version: u64,
+    /// Maximum size in bytes of a single block. Sections larger than this are split.
+    pub max_block_size: u32,
+    /// Kraken compression level used for blocks, see [`compress`]. Blocks are stored
+    /// uncompressed if this is [`None`], or if compressing them does not make them smaller.
+    pub compression_level: Option<i32>,
+    /// Files that will be written into the module, in order.
+    pub files: Vec<ModuleWriterEntry>,
+}
+
+impl Default for ModuleWriter {
+    fn default() -> Self {
+        Self::new(ModuleVersion::default())
+    }
+}
+
+impl ModuleWriter {
+    /// Creates a new, empty writer for the given module version.
+    #[must_use]
+    pub fn new(version: ModuleVersion) -> Self {
+        Self {
+            version,
+            module_id: 0,
+            build_version: 0,
+            max_block_size: DEFAULT_MAX_BLOCK_SIZE,
+            compression_level: None,
+            files: Vec::new(),
+        }
+    }
+
+    /// Adds a file to the module, returning its index in the file table.
+    pub fn add_file(&mut self, file: ModuleWriterEntry) -> usize {
+        self.files.push(file);
+        self.files.len() - 1
+    }
+
+    /// Writes the module to the given file path.
+    ///
+    /// # Errors
+    /// - If the file cannot be created or written to [`ReadError`](`crate::Error::ReadError`)
+    /// - If any size does not fit the module layout [`TryFromIntError`](`crate::Error::TryFromIntError`)
+    pub fn write_to_path<T: AsRef<Path>>(&self, file_path: T) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(file_path)?);
+        self.write(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes the entire module to the given writer.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - A mutable reference to a writer implementing [`Write`] and [`Seek`].
+    ///
+    /// # Errors
+    /// - If the writer fails to write [`ReadError`](`crate::Error::ReadError`)
+    /// - If a non-raw file contains an invalid tag header [`TagError`](`crate::common::errors::TagError`)
+    /// - If any size does not fit the module layout [`TryFromIntError`](`crate::Error::TryFromIntError`)
+    /// - If a block fails to compress [`DecompressionError`](`crate::common::errors::DecompressionError`)
+    pub fn write<W: Write + Seek>(&self, writer: &mut W) -> Result<()> {
+        let is_flight1 = self.version == ModuleVersion::Flight1;
+        let has_strings = self.version <= ModuleVersion::CampaignFlight;
+
+        let mut entries = Vec::with_capacity(self.files.len());
+        let mut blocks = Vec::new();
+        let mut resource_indices = Vec::new();
+        let mut strings = Vec::new();
+        let mut packed_data = Vec::new();
+
+        for file in &self.files {
+            let mut entry = ModuleFileEntry {
+                tag_group: file.tag_group.clone(),
+                tag_id: file.tag_id,
+                parent_index: file.parent_index,
+                asset_hash: file.asset_hash,
+                resource_index: i32::try_from(resource_indices.len())?,
+                resource_count: i32::try_from(file.resources.len())?,
+                total_uncompressed_size: u32::try_from(file.data.len())?,
+                data_offset: u64::try_from(packed_data.len())?,
+                block_index: i32::try_from(blocks.len())?,
+                ..Default::default()
+            };
+            resource_indices.extend_from_slice(&file.resources);
+
+            if has_strings {
+                entry.name_offset = u32::try_from(strings.len())?;
+                strings.extend_from_slice(file.tag_name.as_bytes());
+                strings.push(0);
+            }
+
+            let sections = if file.is_raw_file {
+                entry.flags |= FileEntryFlags::RAW_FILE;
+                vec![file.data.len()]
+            } else {
+                let mut header = TagHeader::default();
+                header.read(&mut Cursor::new(&file.data))?;
+                entry.uncompressed_header_size = header.header_size;
+                entry.uncompressed_tag_data_size = header.data_size;
+                entry.uncompressed_resource_data_size = header.resource_size;
+                entry.uncompressed_actual_resource_size = header.actual_resource_size;
+                [
+                    header.header_size,
+                    header.data_size,
+                    header.resource_size,
+                    header.actual_resource_size,
+                ]
+                .iter()
+                .map(|&size| size as usize)
+                .collect()
+            };
+
+            let mut file_blocks = self.compute_block_layout(&file.data, &sections)?;
+            let entry_start = packed_data.len();
+            if self.pack_blocks(&file.data, &mut file_blocks, &mut packed_data)? {
+                entry.flags |= FileEntryFlags::COMPRESSED;
+            }
+            entry.total_compressed_size = u32::try_from(packed_data.len() - entry_start)?;
+
+            // Files consisting of a single uncompressed block do not need a block table.
+            if file_blocks.len() > 1 {
+                entry.flags |= FileEntryFlags::HAS_BLOCKS;
+                entry.block_count = u16::try_from(file_blocks.len())?;
+                blocks.extend(file_blocks);
+            }
+            entries.push(entry);
+        }
+
+        let file_count = u32::try_from(entries.len())?;
+        let resource_count = u32::try_from(resource_indices.len())?;
+        let header = ModuleHeader {
+            magic: HEADER_MAGIC,
+            version: self.version.clone(),
+            module_id: self.module_id,
+            file_count,
+            loadmanifest_index: -1,
+            runtimeloadmetadata_index: -1,
+            resourcemetadata_index: -1,
+            resource_index: i32::try_from(file_count - resource_count.min(file_count))?,
+            strings_size: u32::try_from(strings.len())?,
+            resource_count,
+            block_count: u32::try_from(blocks.len())?,
+            build_version: self.build_version,
+            hd1_delta: 0,
+            data_size: u64::try_from(packed_data.len())?,
+        };
+
+        header.write(writer)?;
+        for entry in &entries {
+            entry.write(writer, is_flight1)?;
+        }
+        writer.write_all(&strings)?;
+        for index in &resource_indices {
+            writer.write_all(&index.to_le_bytes())?;
+        }
+        for block in &blocks {
+            block.write(writer)?;
+        }
+
+        // Align to 0x?????000, matching how the loader computes the start of file data.
+        let stream_position = writer.stream_position()?;
+        let padding = (stream_position / 0x1000 + 1) * 0x1000 - stream_position;
+        writer.write_all(&vec![0; usize::try_from(padding)?])?;
+        writer.write_all(&packed_data)?;
+        Ok(())
+    }
+
+    /// Appends the data of each block to `packed_data`, compressing blocks if enabled and
+    /// updating their compressed offsets and sizes. Returns whether any block was compressed.
+    ///
+    /// # Errors
+    /// - If a block fails to compress [`DecompressionError`](`crate::common::errors::DecompressionError`)
+    /// - If any size does not fit the module layout [`TryFromIntError`](`crate::Error::TryFromIntError`)
+    fn pack_blocks(
+        &self,
+        data: &[u8],
+        blocks: &mut [ModuleBlockEntry],
+        packed_data: &mut Vec<u8>,
+    ) -> Result<bool> {
+        let entry_start = packed_data.len();
+        let mut any_compressed = false;
+        for block in blocks {
+            let start = block.decompressed_offset as usize;
+            let end = start + block.decompressed_size as usize;
+            block.compressed_offset = u32::try_from(packed_data.len() - entry_start)?;
+            if let Some(compressed) = self.compress_block(&data[start..end])? {
+                block.compressed_size = u32::try_from(compressed.len())?;
+                block.is_compressed = true;
+                any_compressed = true;
+                packed_data.extend_from_slice(&compressed);
+            } else {
+                packed_data.extend_from_slice(&data[start..end]);
+            }
+        }
+        Ok(any_compressed)
+    }
+
+    /// Compresses a block if a [`compression_level`](`ModuleWriter::compression_level`) is set,
+    /// returning [`None`] if the block should be stored uncompressed.
+    ///
+    /// # Errors
+    /// - If the compression fails [`DecompressionError`](`crate::common::errors::DecompressionError`)
+    fn compress_block(&self, data: &[u8]) -> Result<Option<Vec<u8>>> {
+        let Some(level) = self.compression_level else {
+            return Ok(None);
+        };
+        let compressed = compress(data, level)?;
+        // Single block files are detected as compressed by their sizes differing.
+        Ok((compressed.len() < data.len()).then_some(compressed))
+    }
+
+    /// Splits a file into blocks along its section boundaries.
+    ///
+    /// Each non-empty section becomes at least one block, and sections larger than
+    /// [`max_block_size`](`ModuleWriter::max_block_size`) are split further.
+    /// Any trailing data not covered by the sections is appended to the last block.
+    fn compute_block_layout(
+        &self,
+        data: &[u8],
+        sections: &[usize],
+    ) -> Result<Vec<ModuleBlockEntry>> {
+        let max_block_size = (self.max_block_size as usize).max(1);
+        let mut blocks = Vec::new();
+        let mut section_start = 0;
+        for (index, &size) in sections.iter().enumerate() {
+            let section_end = if index == sections.len() - 1 {
+                data.len()
+            } else {
+                (section_start + size).min(data.len())
+            };
+            let mut block_start = section_start;
+            while block_start < section_end {
+                let block_end = (block_start + max_block_size).min(section_end);
+                let block_size = u32::try_from(block_end - block_start)?;
+                blocks.push(ModuleBlockEntry {
+                    compressed_offset: u32::try_from(block_start)?,
+                    compressed_size: block_size,
+                    decompressed_offset: u32::try_from(block_start)?,
+                    decompressed_size: block_size,
+                    is_compressed: false,
+                });
+                block_start = block_end;
+            }
+            section_start = section_end;
+        }
+        Ok(blocks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModuleFile;
+    use crate::common::options::{ParseOptions, Warning};
+    use std::{
+        env::temp_dir,
+        fs::{read, remove_file, write},
+    };
+
+    fn write_and_reload(writer: &ModuleWriter, name: &str) -> ModuleFile {
+        let path = temp_dir().join(format!("infinite-rs-{name}.module"));
+        writer.write_to_path(&path).unwrap();
+        let module = ModuleFile::from_path(&path).unwrap();
+        remove_file(path).unwrap();
+        module
+    }
+
+    #[test]
+    /// Verifies that raw files written by [`ModuleWriter`] are read back unchanged, including
+    /// files that are split into multiple blocks.
+    fn test_write_raw_files_round_trip() {
+        let mut writer = ModuleWriter::new(ModuleVersion::Season3);
+        writer.max_block_size = 0x10;
+        let small = b"I love cats!".to_vec();
+        let large = (0..0x45).collect::<Vec<u8>>();
+        writer.add_file(ModuleWriterEntry {
+            tag_group: String::from("hsc*"),
+            tag_id: TagId(0x1234),
+            parent_index: -1,
+            is_raw_file: true,
+            data: small.clone(),
+            ..Default::default()
+        });
+        writer.add_file(ModuleWriterEntry {
+            tag_group: String::from("bitm"),
+            tag_id: TagId(0x5678),
+            parent_index: -1,
+            is_raw_file: true,
+            data: large.clone(),
+            ..Default::default()
+        });
+
+        let mut module = write_and_reload(&writer, "writer-raw");
+        assert_eq!(module.files.len(), 2);
+        assert_eq!(module.files[0].tag_group, "hsc*");
+        assert_eq!(module.files[1].tag_name, "bitm/22136.bitm");
+
+        let first = module.read_tag(0).unwrap().unwrap();
+        assert_eq!(first.get_raw_data(true).unwrap(), small);
+        let second = module.read_tag(1).unwrap().unwrap();
+        assert!(second.flags.contains(FileEntryFlags::HAS_BLOCKS));
+        assert_eq!(second.get_raw_data(true).unwrap(), large);
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    /// Verifies that tags loaded together by [`ModuleFile::read_tags`] match the written data.
+    fn test_read_tags() {
+        let mut writer = ModuleWriter::new(ModuleVersion::Season3);
+        writer.max_block_size = 0x10;
+        let files = (0..8u8)
+            .map(|index| (0..0x20 + index * 0x10).collect::<Vec<u8>>())
+            .collect::<Vec<_>>();
+        for (index, data) in files.iter().enumerate() {
+            writer.add_file(ModuleWriterEntry {
+                tag_group: String::from("bitm"),
+                tag_id: TagId(i32::try_from(index).unwrap()),
+                parent_index: -1,
+                is_raw_file: true,
+                data: data.clone(),
+                ..Default::default()
+            });
+        }
+
+        let mut module = write_and_reload(&writer, "writer-read-tags");
+        module.read_tags(&[1, 5, 2, 5]).unwrap();
+        for (index, file) in module.files.iter_mut().enumerate() {
+            assert_eq!(file.is_loaded, [1, 2, 5].contains(&index));
+            if file.is_loaded {
+                assert_eq!(file.get_raw_data(true).unwrap(), files[index]);
+            }
+        }
+    }
+
+    #[test]
+    /// Verifies that unreadable file entries are replaced by placeholders when not strict.
+    fn test_recover_invalid_file_entry() {
+        let mut writer = ModuleWriter::new(ModuleVersion::Season3);
+        for (index, tag_group) in ["hsc*", "bitm", "mat "].into_iter().enumerate() {
+            writer.add_file(ModuleWriterEntry {
+                tag_group: String::from(tag_group),
+                tag_id: TagId(i32::try_from(index).unwrap()),
+                parent_index: -1,
+                is_raw_file: true,
+                data: vec![0; 8],
+                ..Default::default()
+            });
+        }
+        let path = temp_dir().join("infinite-rs-writer-recover.module");
+        writer.write_to_path(&path).unwrap();
+        let mut data = read(&path).unwrap();
+        let group = data.windows(4).position(|bytes| bytes == b"mtib").unwrap();
+        data[group..group + 4].fill(0xC0);
+        write(&path, data).unwrap();
+
+        assert!(ModuleFile::from_path(&path).is_err());
+        let options = ParseOptions {
+            strict: false,
+            ..Default::default()
+        };
+        let module = ModuleFile::from_path_with_options(&path, options).unwrap();
+        remove_file(path).unwrap();
+        assert_eq!(module.files.len(), 3);
+        assert!(matches!(
+            module.warnings[..],
+            [Warning::InvalidFileEntry { file_index: 1, .. }]
+        ));
+        assert_eq!(module.files[1].tag_group, "");
+        assert_eq!(module.files[2].tag_group, "mat ");
+    }
+
+    #[test]
+    /// Verifies that tag names are written to the string table for versions that have one.
+    fn test_write_string_table() {
+        let mut writer = ModuleWriter::new(ModuleVersion::CampaignFlight);
+        writer.add_file(ModuleWriterEntry {
+            tag_group: String::from("mat "),
+            tag_id: TagId(1),
+            tag_name: String::from("objects/cats/cat.material"),
+            parent_index: -1,
+            is_raw_file: true,
+            data: vec![0; 8],
+            ..Default::default()
+        });
+
+        let module = write_and_reload(&writer, "writer-strings");
+        assert_eq!(module.files[0].tag_name, "objects/cats/cat.material");
+    }
+}
@@ -0,0 +1,245 @@
+//! Generation of Rust [`TagStructure`](`crate::module::file::TagStructure`) definitions from loaded tags.
+//!
+//! Layouts are inferred from [`DynamicStruct`] trees: every field pointing to another structure,
+//! data or tag becomes a `FieldBlock`, `FieldTagResource`, `FieldData` or `FieldReference` at its
+//! offset. Reading several tags of the same group fills in fields that are empty in some of them.
+//! Offsets of plain values (integers, floats, string ids) cannot be inferred, so the generated
+//! structures are meant as a starting point to be completed by hand.
+
+use std::{collections::BTreeMap, fmt::Write};
+
+use super::{
+    dynamic::{DynamicStruct, DynamicTagValue},
+    structure::TagStructType,
+};
+
+#[derive(Default, Debug)]
+/// Layout of a structure merged from one or more [`DynamicStruct`]s.
+struct StructLayout {
+    /// GUID of the structure.
+    guid: u128,
+    /// Size of the structure in bytes.
+    size: u64,
+    /// Fields of the structure, keyed by offset.
+    fields: BTreeMap<u64, FieldLayout>,
+}
+
+#[derive(Debug)]
+/// Layout of a single generated field.
+enum FieldLayout {
+    /// `FieldBlock` containing elements of the nested layout.
+    Block(StructLayout),
+    /// `FieldTagResource` containing the nested layout.
+    Resource(StructLayout),
+    /// `FieldData`.
+    Data,
+    /// `FieldReference`.
+    Reference,
+}
+
+impl StructLayout {
+    /// Merges the fields of a dynamic struct into this layout.
+    ///
+    /// `size` is the size of a single element: fields of tag blocks with multiple elements are
+    /// folded onto the first element.
+    fn merge(&mut self, dynamic: &DynamicStruct, size: u64) {
+        self.guid = dynamic.guid;
+        if self.size == 0 {
+            self.size = size;
+        }
+        if size == 0 {
+            return;
+        }
+        for field in &dynamic.fields {
+            let offset = u64::from(field.offset()) % size;
+            match field {
+                DynamicTagValue::Struct { value, .. } => {
+                    let layout = match value.struct_type {
+                        TagStructType::TagBlock => {
+                            let Some(element_size) = block_element_size(dynamic, field, value)
+                            else {
+                                continue;
+                            };
+                            let entry = self
+                                .fields
+                                .entry(offset)
+                                .or_insert_with(|| FieldLayout::Block(StructLayout::default()));
+                            if let FieldLayout::Block(layout) = entry {
+                                layout.merge(value, element_size);
+                            }
+                            continue;
+                        }
+                        TagStructType::Resource | TagStructType::Custom => self
+                            .fields
+                            .entry(offset)
+                            .or_insert_with(|| FieldLayout::Resource(StructLayout::default())),
+                        TagStructType::Literal => {
+                            // Literals do not store a count, so they are generated as a block
+                            // containing a single element spanning the entire data block.
+                            let entry = self
+                                .fields
+                                .entry(offset)
+                                .or_insert_with(|| FieldLayout::Block(StructLayout::default()));
+                            if let FieldLayout::Block(layout) = entry {
+                                layout.merge(value, value.data.len() as u64);
+                            }
+                            continue;
+                        }
+                        TagStructType::MainStruct => continue,
+                    };
+                    if let FieldLayout::Resource(layout) = layout {
+                        layout.merge(value, value.data.len() as u64);
+                    }
+                }
+                DynamicTagValue::Data { .. } => {
+                    self.fields.entry(offset).or_insert(FieldLayout::Data);
+                }
+                DynamicTagValue::Reference { .. } => {
+                    self.fields.entry(offset).or_insert(FieldLayout::Reference);
+                }
+            }
+        }
+    }
+}
+
+/// Calculates the size of a single element of a tag block, using the count stored in the parent.
+fn block_element_size(
+    parent: &DynamicStruct,
+    field: &DynamicTagValue,
+    block: &DynamicStruct,
+) -> Option<u64> {
+    let count = parent.block_count(field)?;
+    if count == 0 {
+        return None;
+    }
+    Some(block.data.len() as u64 / u64::from(count))
+}
+
+/// Generates Rust source for [`TagStructure`](`crate::module::file::TagStructure`) definitions
+/// describing the given tags.
+///
+/// All tags should be of the same tag group, read using
+/// [`read_dynamic`](`crate::module::file::ModuleFileEntry::read_dynamic`).
+///
+/// # Arguments
+///
+/// * `root_name` - Name of the generated root structure. Nested structures are named after it.
+/// * `tags` - Dynamic trees of the tags to generate structures from.
+///
+/// # Examples
+///
+/// ```no_run
+/// use infinite_rs::ModuleFile;
+/// use infinite_rs::tag::codegen::generate_structs;
+///
+/// let mut module = ModuleFile::from_path("C:/XboxGames/Halo Infinite/Content/deploy/any/globals-rtx-new.module").unwrap();
+/// let mut materials = Vec::new();
+/// for index in 0..module.files.len() {
+///     if module.files[index].tag_group == "mat " {
+///         if let Some(tag) = module.read_tag(index as u32).unwrap() {
+///             materials.push(tag.read_dynamic().unwrap());
+///         }
+///     }
+/// }
+/// println!("{}", generate_structs("MaterialTag", &materials));
+/// ```
+#[must_use]
+pub fn generate_structs(root_name: &str, tags: &[DynamicStruct]) -> String {
+    let mut root = StructLayout::default();
+    for tag in tags {
+        root.merge(tag, tag.data.len() as u64);
+    }
+
+    let mut definitions = Vec::new();
+    write_struct(root_name, &root, &mut definitions);
+
+    let mut source = String::from(
+        "use infinite_rs::TagStructure;\n\
+         use infinite_rs::tag::types::common_types::{\n    \
+         FieldBlock, FieldData, FieldReference, FieldTagResource,\n};\n",
+    );
+    for definition in &definitions {
+        source.push('\n');
+        source.push_str(definition);
+    }
+    source
+}
+
+/// Writes the definition of a structure and all of its nested structures.
+fn write_struct(name: &str, layout: &StructLayout, definitions: &mut Vec<String>) {
+    let mut nested = Vec::new();
+    let mut definition = String::new();
+    let _ = writeln!(definition, "/// GUID: {:#034X}", layout.guid);
+    let _ = writeln!(definition, "#[derive(Default, Debug, TagStructure)]");
+    let _ = writeln!(definition, "#[data(size({:#X}))]", layout.size);
+    let _ = writeln!(definition, "pub struct {name} {{");
+    for (offset, field) in &layout.fields {
+        let field_type = match field {
+            FieldLayout::Block(inner) | FieldLayout::Resource(inner) => {
+                let inner_name = format!("{name}{offset:X}");
+                nested.push((inner_name.clone(), inner));
+                if matches!(field, FieldLayout::Block(_)) {
+                    format!("FieldBlock<{inner_name}>")
+                } else {
+                    format!("FieldTagResource<{inner_name}>")
+                }
+            }
+            FieldLayout::Data => String::from("FieldData"),
+            FieldLayout::Reference => String::from("FieldReference"),
+        };
+        let _ = writeln!(definition, "    #[data(offset({offset:#X}))]");
+        let _ = writeln!(definition, "    pub field_{offset:x}: {field_type},");
+    }
+    definition.push_str("}\n");
+    definitions.push(definition);
+
+    for (inner_name, inner) in nested {
+        write_struct(&inner_name, inner, definitions);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tag::dynamic::BLOCK_COUNT_OFFSET;
+
+    #[test]
+    /// Verifies that nested blocks, data and references are placed at their offsets.
+    fn test_generate_structs() {
+        let mut root_data = vec![0; 0x40];
+        root_data[0x20 + BLOCK_COUNT_OFFSET] = 2;
+        let root = DynamicStruct {
+            guid: 1,
+            struct_type: TagStructType::MainStruct,
+            data: root_data,
+            fields: vec![
+                DynamicTagValue::Reference {
+                    offset: 0x10,
+                    dependency_index: 0,
+                },
+                DynamicTagValue::Struct {
+                    offset: 0x20,
+                    value: DynamicStruct {
+                        guid: 2,
+                        struct_type: TagStructType::TagBlock,
+                        data: vec![0; 0x30],
+                        fields: vec![DynamicTagValue::Data {
+                            offset: 0x18 + 0x4,
+                            datablock_index: None,
+                            data: Vec::new(),
+                        }],
+                        ..Default::default()
+                    },
+                },
+            ],
+            ..Default::default()
+        };
+
+        let source = generate_structs("CatTag", &[root]);
+        assert!(source.contains("#[data(size(0x40))]\npub struct CatTag {"));
+        assert!(source.contains("    #[data(offset(0x10))]\n    pub field_10: FieldReference,"));
+        assert!(source.contains("    pub field_20: FieldBlock<CatTag20>,"));
+        assert!(source.contains("#[data(size(0x18))]\npub struct CatTag20 {"));
+        assert!(source.contains("    #[data(offset(0x4))]\n    pub field_4: FieldData,"));
+    }
+}
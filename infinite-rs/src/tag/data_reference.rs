@@ -6,14 +6,15 @@ use std::io::BufRead;
 use crate::Result;
 use crate::common::extensions::Enumerable;
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// Structure that defines a reference to a blob of data inside tag data.
 pub struct TagDataReference {
     /// The index of the tag struct containing the tag field.
     pub parent_struct_index: i32,
     /// Unknown: seems to vary (maybe enum?).
     unknown: i32,
-    /// The index of the tag struct containing the referenced data.
+    /// The index of the data block containing the referenced data.
     /// Can be -1 for null references.
     pub target_index: i32,
     /// The index of the data block containing the tag field.
@@ -1,14 +1,15 @@
 //! Tag datablock specifying the section for tag structs.
 
-use byteorder::{LE, ReadBytesExt};
+use byteorder::{LE, ReadBytesExt, WriteBytesExt};
 use num_enum::TryFromPrimitive;
-use std::io::BufRead;
+use std::io::{BufRead, Write};
 
 use crate::common::errors::TagError;
 use crate::common::extensions::Enumerable;
 use crate::{Result, TagFile};
 
-#[derive(Default, Debug, TryFromPrimitive, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, Copy, TryFromPrimitive, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[repr(u16)]
 /// Location where the data referenced in the tag block is found.
 pub enum TagSectionType {
@@ -23,7 +24,8 @@ pub enum TagSectionType {
     ActualResource,
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// Tag data metadata block containing data on where the binary section is located.
 pub struct TagDataBlock {
     /// The size of the data block entry in bytes.
@@ -48,6 +50,18 @@ impl Enumerable for TagDataBlock {
 }
 
 impl TagDataBlock {
+    /// Writes the data block to the given writer, mirroring [`read`](`Enumerable::read`).
+    ///
+    /// # Errors
+    /// - If the writer fails to write [`ReadError`](`crate::Error::ReadError`)
+    pub(crate) fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32::<LE>(self.entry_size)?;
+        writer.write_u16::<LE>(self.padding)?;
+        writer.write_u16::<LE>(self.section_type as u16)?;
+        writer.write_u64::<LE>(self.offset)?;
+        Ok(())
+    }
+
     pub(crate) fn get_offset(&self, tag_info: &TagFile) -> u64 {
         let section_offset = match self.section_type {
             TagSectionType::TagData | TagSectionType::Header => 0,
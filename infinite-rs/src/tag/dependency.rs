@@ -2,10 +2,11 @@
 
 use byteorder::{LE, ReadBytesExt};
 
-use crate::Result;
 use crate::common::extensions::{BufReaderExt, Enumerable};
+use crate::{Result, TagId};
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// Dependency structure that can be used to search and lazy load for tags inside modules.
 pub struct TagDependency {
     /// 4 byte-long string for tag group, stored as big endian
@@ -20,8 +21,8 @@ pub struct TagDependency {
     pub asset_id: u64,
     /// `MurmurHash3_x86_64` 32 bit hash of tag path.
     /// Referred to in-memory as "global tag id"
-    /// Is set to -1 if file is resource.
-    pub tag_id: i32,
+    /// Is set to [`TagId::NULL`] if file is resource.
+    pub tag_id: TagId,
     /// Index of parent in module.
     pub parent_index: i32,
     /// Tag name of the dependency, located at the position of the [`Self::name_offset`] in the tag string table.
@@ -34,7 +35,7 @@ impl Enumerable for TagDependency {
         self.tag_group = reader.read_fixed_string(4)?.chars().rev().collect(); // Reverse string
         self.name_offset = reader.read_u32::<LE>()?;
         self.asset_id = reader.read_u64::<LE>()?;
-        self.tag_id = reader.read_i32::<LE>()?;
+        self.tag_id = TagId(reader.read_i32::<LE>()?);
         self.parent_index = reader.read_i32::<LE>()?;
         Ok(())
     }
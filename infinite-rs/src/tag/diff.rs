@@ -0,0 +1,316 @@
+//! Comparison of two loaded tags of the same group.
+//!
+//! Both tags are read into their [`DynamicStruct`] tree and walked in parallel. Fields are aligned
+//! by their offset inside the parent structure, so tags from different builds or modules can be
+//! compared as long as their structure definitions match. Every difference carries the path of
+//! the structure it was found in, written as the offsets of the fields leading to it from the
+//! main struct (for example `/0x20/0x18`, with `/` being the main struct itself).
+
+use std::ops::Range;
+
+use super::{
+    dynamic::{DynamicStruct, DynamicTagValue},
+    loader::TagFile,
+};
+use crate::{Result, TagId};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// Single difference found between two tags.
+pub enum TagDifference {
+    /// Structures at the same path have different GUIDs, so their contents are not compared.
+    StructChanged {
+        /// Path of the structure.
+        path: String,
+        /// GUID of the structure in the left tag.
+        left: u128,
+        /// GUID of the structure in the right tag.
+        right: u128,
+    },
+    /// Structure or data has a different size.
+    SizeChanged {
+        /// Path of the structure or data field.
+        path: String,
+        /// Size in bytes in the left tag.
+        left: usize,
+        /// Size in bytes in the right tag.
+        right: usize,
+    },
+    /// Tag block has a different number of elements.
+    BlockCountChanged {
+        /// Path of the tag block.
+        path: String,
+        /// Element count in the left tag.
+        left: u32,
+        /// Element count in the right tag.
+        right: u32,
+    },
+    /// Bytes differ inside a structure or data field.
+    ///
+    /// Only the bytes present in both tags are compared, size changes are reported separately.
+    BytesChanged {
+        /// Path of the structure or data field.
+        path: String,
+        /// Range of differing bytes, relative to the start of the structure or data.
+        range: Range<usize>,
+    },
+    /// Reference points to a different tag.
+    ReferenceChanged {
+        /// Path of the reference field.
+        path: String,
+        /// Tag ID referenced by the left tag, [`TagId::NULL`] for null references.
+        left: TagId,
+        /// Tag ID referenced by the right tag, [`TagId::NULL`] for null references.
+        right: TagId,
+    },
+    /// Field only exists in the right tag, or is of a different kind in each tag.
+    FieldAdded {
+        /// Path of the field.
+        path: String,
+    },
+    /// Field only exists in the left tag, or is of a different kind in each tag.
+    FieldRemoved {
+        /// Path of the field.
+        path: String,
+    },
+}
+
+/// Compares two loaded tags and lists every difference between them.
+///
+/// # Arguments
+///
+/// * `left_tag` - Tag structure of the first tag.
+/// * `left_data` - Bytes of the entire first tag (including header).
+/// * `right_tag` - Tag structure of the second tag.
+/// * `right_data` - Bytes of the entire second tag (including header).
+///
+/// # Errors
+/// - If the main struct definition of either tag is not found [`TagError::MainStructNotFound`](`crate::common::errors::TagError::MainStructNotFound`)
+pub fn diff(
+    left_tag: &TagFile,
+    left_data: &[u8],
+    right_tag: &TagFile,
+    right_data: &[u8],
+) -> Result<Vec<TagDifference>> {
+    let left = DynamicStruct::read(left_tag, left_data)?;
+    let right = DynamicStruct::read(right_tag, right_data)?;
+    let mut differences = Vec::new();
+    diff_struct((left_tag, &left), (right_tag, &right), "", &mut differences);
+    Ok(differences)
+}
+
+/// Compares two structures at the same path, along with all of their fields.
+fn diff_struct(
+    (left_tag, left): (&TagFile, &DynamicStruct),
+    (right_tag, right): (&TagFile, &DynamicStruct),
+    path: &str,
+    differences: &mut Vec<TagDifference>,
+) {
+    let display_path = if path.is_empty() { "/" } else { path };
+    if left.guid != right.guid {
+        differences.push(TagDifference::StructChanged {
+            path: display_path.to_string(),
+            left: left.guid,
+            right: right.guid,
+        });
+        return;
+    }
+    diff_bytes(&left.data, &right.data, display_path, differences);
+
+    let mut left_fields = left.fields.iter().peekable();
+    let mut right_fields = right.fields.iter().peekable();
+    loop {
+        let (left_field, right_field) = match (left_fields.peek(), right_fields.peek()) {
+            (None, None) => break,
+            (Some(l), Some(r)) if l.offset() == r.offset() => {
+                (left_fields.next(), right_fields.next())
+            }
+            (Some(l), Some(r)) if l.offset() < r.offset() => (left_fields.next(), None),
+            (Some(_), None) => (left_fields.next(), None),
+            (Some(_) | None, Some(_)) => (None, right_fields.next()),
+        };
+        let offset = left_field
+            .or(right_field)
+            .map_or(0, DynamicTagValue::offset);
+        let field_path = format!("{path}/{offset:#X}");
+
+        match (left_field, right_field) {
+            (
+                Some(l @ DynamicTagValue::Struct { value: l_value, .. }),
+                Some(r @ DynamicTagValue::Struct { value: r_value, .. }),
+            ) => {
+                if let (Some(l_count), Some(r_count)) = (left.block_count(l), right.block_count(r))
+                    && l_count != r_count
+                {
+                    differences.push(TagDifference::BlockCountChanged {
+                        path: field_path.clone(),
+                        left: l_count,
+                        right: r_count,
+                    });
+                }
+                diff_struct(
+                    (left_tag, l_value),
+                    (right_tag, r_value),
+                    &field_path,
+                    differences,
+                );
+            }
+            (
+                Some(DynamicTagValue::Data { data: l_data, .. }),
+                Some(DynamicTagValue::Data { data: r_data, .. }),
+            ) => diff_bytes(l_data, r_data, &field_path, differences),
+            (
+                Some(DynamicTagValue::Reference {
+                    dependency_index: l_index,
+                    ..
+                }),
+                Some(DynamicTagValue::Reference {
+                    dependency_index: r_index,
+                    ..
+                }),
+            ) => {
+                let l_id = left_tag
+                    .get_dependency(*l_index)
+                    .map_or(TagId::NULL, |d| d.tag_id);
+                let r_id = right_tag
+                    .get_dependency(*r_index)
+                    .map_or(TagId::NULL, |d| d.tag_id);
+                if l_id != r_id {
+                    differences.push(TagDifference::ReferenceChanged {
+                        path: field_path,
+                        left: l_id,
+                        right: r_id,
+                    });
+                }
+            }
+            (l, r) => {
+                if l.is_some() {
+                    differences.push(TagDifference::FieldRemoved {
+                        path: field_path.clone(),
+                    });
+                }
+                if r.is_some() {
+                    differences.push(TagDifference::FieldAdded { path: field_path });
+                }
+            }
+        }
+    }
+}
+
+/// Compares two buffers, reporting size changes and merged ranges of differing bytes.
+fn diff_bytes(left: &[u8], right: &[u8], path: &str, differences: &mut Vec<TagDifference>) {
+    if left.len() != right.len() {
+        differences.push(TagDifference::SizeChanged {
+            path: path.to_string(),
+            left: left.len(),
+            right: right.len(),
+        });
+    }
+    let mut start = None;
+    for (index, (l, r)) in left.iter().zip(right).enumerate() {
+        match (l == r, start) {
+            (false, None) => start = Some(index),
+            (true, Some(range_start)) => {
+                differences.push(TagDifference::BytesChanged {
+                    path: path.to_string(),
+                    range: range_start..index,
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(range_start) = start {
+        differences.push(TagDifference::BytesChanged {
+            path: path.to_string(),
+            range: range_start..left.len().min(right.len()),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tag::dependency::TagDependency;
+    use crate::tag::dynamic::BLOCK_COUNT_OFFSET;
+    use crate::tag::structure::TagStructType;
+
+    /// Builds a main struct with a tag block at 0x20 and a reference at 0x30.
+    fn main_struct(count: u8, block_data: Vec<u8>) -> DynamicStruct {
+        let mut data = vec![0; 0x40];
+        data[0x20 + BLOCK_COUNT_OFFSET] = count;
+        DynamicStruct {
+            guid: 1,
+            struct_type: TagStructType::MainStruct,
+            data,
+            fields: vec![
+                DynamicTagValue::Struct {
+                    offset: 0x20,
+                    value: DynamicStruct {
+                        guid: 2,
+                        struct_type: TagStructType::TagBlock,
+                        data: block_data,
+                        ..Default::default()
+                    },
+                },
+                DynamicTagValue::Reference {
+                    offset: 0x30,
+                    dependency_index: 0,
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    /// Verifies that block counts, bytes and references are compared at aligned paths.
+    fn test_diff_struct() {
+        let tag_with_dependency = |tag_id| TagFile {
+            dependencies: vec![TagDependency {
+                tag_id,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let left_tag = tag_with_dependency(TagId(0x1234));
+        let right_tag = tag_with_dependency(TagId(0x5678));
+        let left = main_struct(1, vec![1, 2, 3, 4]);
+        let right = main_struct(2, vec![1, 9, 9, 4, 5, 6, 7, 8]);
+
+        let mut differences = Vec::new();
+        diff_struct(
+            (&left_tag, &left),
+            (&right_tag, &right),
+            "",
+            &mut differences,
+        );
+        assert_eq!(
+            differences,
+            vec![
+                TagDifference::BytesChanged {
+                    path: String::from("/"),
+                    range: 0x30..0x31,
+                },
+                TagDifference::BlockCountChanged {
+                    path: String::from("/0x20"),
+                    left: 1,
+                    right: 2,
+                },
+                TagDifference::SizeChanged {
+                    path: String::from("/0x20"),
+                    left: 4,
+                    right: 8,
+                },
+                TagDifference::BytesChanged {
+                    path: String::from("/0x20"),
+                    range: 1..3,
+                },
+                TagDifference::ReferenceChanged {
+                    path: String::from("/0x30"),
+                    left: TagId(0x1234),
+                    right: TagId(0x5678),
+                },
+            ]
+        );
+    }
+}
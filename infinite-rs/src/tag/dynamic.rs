@@ -0,0 +1,206 @@
+//! Schema-less reading of tags into a dynamic tree of structures.
+//!
+//! This walks the [`struct_definitions`](`TagFile::struct_definitions`),
+//! [`data_references`](`TagFile::data_references`) and [`tag_references`](`TagFile::tag_references`)
+//! of a tag to reconstruct its hierarchy without a [`TagStructure`](`crate::module::file::TagStructure`)
+//! definition. Field names are not known, but every structure exposes its raw bytes and the
+//! offsets of the fields pointing to other structures or data.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use super::{datablock::TagSectionType, loader::TagFile, structure::TagStructType};
+use crate::Result;
+use crate::common::errors::TagError;
+
+/// Offset of the element count inside a `FieldBlock` field.
+pub(super) const BLOCK_COUNT_OFFSET: usize = 0x10;
+
+#[derive(Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// Contents of a tag struct, read without a structure definition.
+pub struct DynamicStruct {
+    /// GUID of the structure.
+    pub guid: u128,
+    /// What kind of structure this is.
+    pub struct_type: TagStructType,
+    /// Index of the data block containing the structure, [`None`] if it does not point to any data.
+    pub datablock_index: Option<usize>,
+    /// Where the data block is stored.
+    pub section_type: TagSectionType,
+    /// Raw bytes of the data block.
+    /// Empty if the data block lies outside of the loaded tag data (for instance, missing resources).
+    pub data: Vec<u8>,
+    /// Fields inside [`data`](`DynamicStruct::data`) that point to other structures, data or tags,
+    /// sorted by offset.
+    pub fields: Vec<DynamicTagValue>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// Field inside a [`DynamicStruct`] that points outside of its data block.
+pub enum DynamicTagValue {
+    /// Nested structure, such as a tag block or resource.
+    Struct {
+        /// Offset of the field inside the parent data block.
+        offset: u32,
+        /// Contents of the nested structure.
+        value: DynamicStruct,
+    },
+    /// Reference to a blob of data.
+    Data {
+        /// Offset of the field inside the parent data block.
+        offset: u32,
+        /// Index of the data block containing the data, [`None`] for null references.
+        datablock_index: Option<usize>,
+        /// Raw bytes of the referenced data.
+        data: Vec<u8>,
+    },
+    /// Reference to another tag.
+    Reference {
+        /// Offset of the field inside the parent data block.
+        offset: u32,
+        /// Index of the dependency in [`dependencies`](`TagFile::dependencies`), -1 for null references.
+        dependency_index: i32,
+    },
+}
+
+impl DynamicTagValue {
+    /// Offset of the field inside the parent data block.
+    #[must_use]
+    pub fn offset(&self) -> u32 {
+        match self {
+            Self::Struct { offset, .. }
+            | Self::Data { offset, .. }
+            | Self::Reference { offset, .. } => *offset,
+        }
+    }
+}
+
+impl DynamicStruct {
+    /// Reads the entire hierarchy of a tag, starting from its main struct.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag_file` - Tag structure read from the tag.
+    /// * `data` - Bytes of the entire tag file (including header).
+    ///
+    /// # Errors
+    /// - If the main struct definition is not found [`TagError::MainStructNotFound`]
+    pub fn read(tag_file: &TagFile, data: &[u8]) -> Result<Self> {
+        let main_struct = tag_file
+            .struct_definitions
+            .iter()
+            .position(|s| s.struct_type == TagStructType::MainStruct)
+            .ok_or(TagError::MainStructNotFound)?;
+        let tag_data = data
+            .get(tag_file.header.header_size as usize..)
+            .unwrap_or_default();
+        let mut visited = HashSet::new();
+        Ok(Self::read_struct(
+            tag_file,
+            tag_data,
+            main_struct,
+            &mut visited,
+        ))
+    }
+
+    /// Gets the number of elements of a tag block field of this structure.
+    ///
+    /// The count is read from the field inside [`data`](`DynamicStruct::data`). Returns [`None`]
+    /// if the field is not a tag block or lies outside of the data.
+    #[must_use]
+    pub fn block_count(&self, field: &DynamicTagValue) -> Option<u32> {
+        let DynamicTagValue::Struct { offset, value } = field else {
+            return None;
+        };
+        if value.struct_type != TagStructType::TagBlock {
+            return None;
+        }
+        let count_offset = *offset as usize + BLOCK_COUNT_OFFSET;
+        let count = self.data.get(count_offset..count_offset + 4)?;
+        Some(u32::from_le_bytes(count.try_into().ok()?))
+    }
+
+    /// Reads a single struct definition and all of the structures it points to.
+    fn read_struct(
+        tag_file: &TagFile,
+        tag_data: &[u8],
+        struct_index: usize,
+        visited: &mut HashSet<usize>,
+    ) -> Self {
+        let definition = &tag_file.struct_definitions[struct_index];
+        let mut dynamic = Self {
+            guid: definition.guid,
+            struct_type: definition.struct_type,
+            ..Default::default()
+        };
+
+        // Data blocks can only be visited once, to guard against cycles in corrupt tags.
+        let Some(datablock_index) = usize::try_from(definition.target_index)
+            .ok()
+            .filter(|index| *index < tag_file.datablock_definitions.len())
+            .filter(|index| visited.insert(*index))
+        else {
+            return dynamic;
+        };
+        let datablock = &tag_file.datablock_definitions[datablock_index];
+        dynamic.datablock_index = Some(datablock_index);
+        dynamic.section_type = datablock.section_type;
+        dynamic.data = read_datablock(tag_file, tag_data, datablock_index);
+
+        let block = definition.target_index;
+        for (index, child) in tag_file.struct_definitions.iter().enumerate() {
+            if child.field_block == block && index != struct_index {
+                dynamic.fields.push(DynamicTagValue::Struct {
+                    offset: child.field_offset,
+                    value: Self::read_struct(tag_file, tag_data, index, visited),
+                });
+            }
+        }
+        for reference in &tag_file.data_references {
+            if reference.field_block == block {
+                let target = usize::try_from(reference.target_index).ok();
+                dynamic.fields.push(DynamicTagValue::Data {
+                    offset: reference.field_offset,
+                    datablock_index: target,
+                    data: target
+                        .map(|index| read_datablock(tag_file, tag_data, index))
+                        .unwrap_or_default(),
+                });
+            }
+        }
+        for reference in &tag_file.tag_references {
+            if reference.field_block == block {
+                dynamic.fields.push(DynamicTagValue::Reference {
+                    offset: reference.field_offset,
+                    dependency_index: reference.dependency_index,
+                });
+            }
+        }
+        dynamic.fields.sort_by_key(DynamicTagValue::offset);
+        dynamic
+    }
+}
+
+/// Copies the bytes of a data block, returning an empty buffer if it is out of bounds.
+fn read_datablock(tag_file: &TagFile, tag_data: &[u8], datablock_index: usize) -> Vec<u8> {
+    let Some(datablock) = tag_file.datablock_definitions.get(datablock_index) else {
+        return Vec::new();
+    };
+    let start = usize::try_from(datablock.get_offset(tag_file)).unwrap_or(usize::MAX);
+    let end = start.saturating_add(datablock.entry_size as usize);
+    tag_data
+        .get(start..end)
+        .map(<[u8]>::to_vec)
+        .unwrap_or_default()
+}
+
+/// Formats bytes as a lowercase hex string, used when exporting raw data.
+pub(super) fn to_hex(data: &[u8]) -> String {
+    data.iter()
+        .fold(String::with_capacity(data.len() * 2), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
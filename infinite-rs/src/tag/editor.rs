@@ -0,0 +1,499 @@
+//! In-memory editing of tags and serialization back into a valid tag buffer.
+//!
+//! [`TagEditor`] holds the contents of every data block of a tag separately, so blocks can be
+//! patched, replaced or resized freely. When the tag is written back with
+//! [`to_bytes`](`TagEditor::to_bytes`), the data blocks of each section are laid out again in their
+//! original order, and the data block table and section sizes in the header are updated to match.
+//! The resulting buffer can be packed into a module using
+//! [`ModuleWriter`](`crate::module::writer::ModuleWriter`).
+
+use std::io::Cursor;
+
+use super::{
+    datablock::{TagDataBlock, TagSectionType},
+    dynamic::BLOCK_COUNT_OFFSET,
+    header::TagHeader,
+    loader::TagFile,
+    structure::TagStructType,
+};
+use crate::Result;
+use crate::common::errors::TagError;
+
+/// Size in bytes of the tag header.
+const HEADER_SIZE: usize = 0x50;
+/// Size in bytes of a single entry in the dependency table.
+const DEPENDENCY_SIZE: usize = 0x18;
+/// Offset of the data size inside a `FieldData` field.
+const DATA_SIZE_OFFSET: usize = 0x14;
+
+/// Sections stored after the tag header, in order.
+const SECTIONS: [TagSectionType; 3] = [
+    TagSectionType::TagData,
+    TagSectionType::ResourceData,
+    TagSectionType::ActualResource,
+];
+
+#[derive(Debug)]
+/// Editor used to modify the data of a tag and write it back.
+///
+/// Only the contents of data blocks can be modified: the dependency, struct and reference tables
+/// are written back unchanged. The [`checksum`](`crate::tag::header::TagHeader::checksum`) of the
+/// tag is not recomputed, as its algorithm is unknown.
+///
+/// # Examples
+///
+/// ```no_run
+/// use infinite_rs::TagFile;
+/// use infinite_rs::module::header::ModuleVersion;
+/// use infinite_rs::tag::editor::TagEditor;
+///
+/// let data = std::fs::read("cat.material").unwrap();
+/// let tag = TagFile::from_bytes(&data, &ModuleVersion::Season3).unwrap();
+/// let mut editor = TagEditor::new(tag, data).unwrap();
+/// editor.set_bytes(0, 0x10, &1.0f32.to_le_bytes()).unwrap();
+/// std::fs::write("cat_edited.material", editor.to_bytes().unwrap()).unwrap();
+/// ```
+pub struct TagEditor {
+    /// Tag structure of the original buffer.
+    tag: TagFile,
+    /// Entire original tag buffer (including header).
+    original: Vec<u8>,
+    /// Contents of each data block, [`None`] for data blocks stored in the header.
+    contents: Vec<Option<Vec<u8>>>,
+}
+
+impl TagEditor {
+    /// Creates an editor from a tag and the buffer it was read from.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - Tag structure read from `data`.
+    /// * `data` - Bytes of the entire tag file (including header).
+    ///
+    /// # Errors
+    /// - If the tag is truncated [`TagError::Truncated`]
+    /// - If a data block points outside of its section [`TagError::DatablockOutOfBounds`]
+    pub fn new(tag: TagFile, data: Vec<u8>) -> Result<Self> {
+        check_bounds(&tag, &data)?;
+        let contents = tag
+            .datablock_definitions
+            .iter()
+            .map(|datablock| {
+                (datablock.section_type != TagSectionType::Header)
+                    .then(|| data[tag.get_datablock_range(datablock)].to_vec())
+            })
+            .collect();
+        Ok(Self {
+            tag,
+            original: data,
+            contents,
+        })
+    }
+
+    /// Gets the tag structure of the original buffer.
+    ///
+    /// Data block offsets and section sizes in it are not updated by edits.
+    #[must_use]
+    pub fn tag_file(&self) -> &TagFile {
+        &self.tag
+    }
+
+    /// Gets the current contents of a data block.
+    ///
+    /// Returns [`None`] if the data block does not exist or is stored in the header.
+    #[must_use]
+    pub fn datablock(&self, index: usize) -> Option<&[u8]> {
+        self.contents.get(index)?.as_deref()
+    }
+
+    /// Overwrites bytes inside a data block, for instance to set the value of a field.
+    ///
+    /// # Arguments
+    ///
+    /// * `datablock` - Index of the data block containing the field.
+    /// * `offset` - Offset of the field inside the data block.
+    /// * `bytes` - New bytes of the field.
+    ///
+    /// # Errors
+    /// - If the bytes do not fit inside the data block [`TagError::WriteOutOfBounds`]
+    pub fn set_bytes(&mut self, datablock: usize, offset: usize, bytes: &[u8]) -> Result<()> {
+        self.contents
+            .get_mut(datablock)
+            .and_then(Option::as_mut)
+            .and_then(|contents| contents.get_mut(offset..offset.saturating_add(bytes.len())))
+            .ok_or(TagError::WriteOutOfBounds {
+                datablock,
+                offset,
+                length: bytes.len(),
+            })?
+            .copy_from_slice(bytes);
+        Ok(())
+    }
+
+    /// Replaces the blob referenced by a [`FieldData`](`crate::tag::types::common_types::FieldData`)
+    /// field, updating the size stored in the field.
+    ///
+    /// # Arguments
+    ///
+    /// * `field_block` - Index of the data block containing the data field.
+    /// * `field_offset` - Offset of the data field inside that data block.
+    /// * `data` - New contents of the blob.
+    ///
+    /// # Errors
+    /// - If the field is not a non-null data reference [`TagError::FieldNotFound`]
+    /// - If the field lies outside of its data block [`TagError::WriteOutOfBounds`]
+    /// - If the data is larger than 4GB [`TryFromIntError`](`crate::Error::TryFromIntError`)
+    pub fn replace_data(
+        &mut self,
+        field_block: i32,
+        field_offset: u32,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let target = self
+            .tag
+            .find_data_reference(field_block, field_offset)
+            .and_then(|reference| usize::try_from(reference.target_index).ok())
+            .filter(|index| matches!(self.contents.get(*index), Some(Some(_))))
+            .ok_or(TagError::FieldNotFound {
+                field_block,
+                field_offset,
+            })?;
+
+        let size = u32::try_from(data.len())?;
+        self.set_field(
+            field_block,
+            field_offset,
+            DATA_SIZE_OFFSET,
+            &size.to_le_bytes(),
+        )?;
+        self.contents[target] = Some(data);
+        Ok(())
+    }
+
+    /// Resizes the tag block referenced by a [`FieldBlock`](`crate::tag::types::common_types::FieldBlock`)
+    /// field to the given number of elements, updating the count stored in the field.
+    ///
+    /// New elements are zero-filled. Nested blocks of removed elements are left in the tag, but
+    /// are no longer reachable.
+    ///
+    /// # Arguments
+    ///
+    /// * `field_block` - Index of the data block containing the block field.
+    /// * `field_offset` - Offset of the block field inside that data block.
+    /// * `count` - New number of elements.
+    ///
+    /// # Errors
+    /// - If the field is not a non-empty tag block [`TagError::FieldNotFound`]
+    /// - If the field lies outside of its data block [`TagError::WriteOutOfBounds`]
+    pub fn resize_block(&mut self, field_block: i32, field_offset: u32, count: u32) -> Result<()> {
+        let not_found = TagError::FieldNotFound {
+            field_block,
+            field_offset,
+        };
+        let target = self
+            .tag
+            .struct_definitions
+            .iter()
+            .find(|s| {
+                s.struct_type == TagStructType::TagBlock
+                    && s.field_block == field_block
+                    && s.field_offset == field_offset
+            })
+            .and_then(|s| usize::try_from(s.target_index).ok())
+            .filter(|index| matches!(self.contents.get(*index), Some(Some(_))))
+            .ok_or(not_found)?;
+
+        let parent = usize::try_from(field_block).unwrap_or(usize::MAX);
+        let count_offset = field_offset as usize + BLOCK_COUNT_OFFSET;
+        let old_count = self
+            .datablock(parent)
+            .and_then(|data| data.get(count_offset..count_offset + 4))
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u32::from_le_bytes)
+            .filter(|count| *count != 0)
+            .ok_or(TagError::FieldNotFound {
+                field_block,
+                field_offset,
+            })?;
+
+        self.set_field(
+            field_block,
+            field_offset,
+            BLOCK_COUNT_OFFSET,
+            &count.to_le_bytes(),
+        )?;
+        if let Some(contents) = &mut self.contents[target] {
+            let element_size = contents.len() / old_count as usize;
+            contents.resize(element_size * count as usize, 0);
+        }
+        Ok(())
+    }
+
+    /// Serializes the edited tag into a new buffer.
+    ///
+    /// # Errors
+    /// - If a section is larger than 4GB [`TryFromIntError`](`crate::Error::TryFromIntError`)
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let header_size = self.tag.header.header_size as usize;
+        let mut header = self.tag.header.clone();
+        let mut datablocks = self.tag.datablock_definitions.clone();
+        let mut sections = Vec::with_capacity(SECTIONS.len());
+
+        let mut section_start = header_size;
+        for section_type in SECTIONS {
+            let original_size = section_size(&header, section_type) as usize;
+            let original = &self.original[section_start..section_start + original_size];
+            let section = self.layout_section(section_type, original, &mut datablocks)?;
+            let size = u32::try_from(section.len())?;
+            match section_type {
+                TagSectionType::TagData => header.data_size = size,
+                TagSectionType::ResourceData => header.resource_size = size,
+                TagSectionType::ActualResource | TagSectionType::Header => {
+                    header.actual_resource_size = size;
+                }
+            }
+            sections.push(section);
+            section_start += original_size;
+        }
+
+        let mut data = self.original[..header_size].to_vec();
+        let mut writer = Cursor::new(&mut data);
+        header.write(&mut writer)?;
+        writer.set_position((HEADER_SIZE + self.tag.dependencies.len() * DEPENDENCY_SIZE) as u64);
+        for datablock in &datablocks {
+            datablock.write(&mut writer)?;
+        }
+
+        for section in sections {
+            data.extend_from_slice(&section);
+        }
+        Ok(data)
+    }
+
+    /// Overwrites bytes of a field stored in another data block.
+    fn set_field(
+        &mut self,
+        field_block: i32,
+        field_offset: u32,
+        offset: usize,
+        bytes: &[u8],
+    ) -> Result<()> {
+        let datablock = usize::try_from(field_block).map_err(|_| TagError::FieldNotFound {
+            field_block,
+            field_offset,
+        })?;
+        self.set_bytes(datablock, field_offset as usize + offset, bytes)
+    }
+
+    /// Lays out the data blocks of a section in their original order.
+    ///
+    /// Bytes between data blocks and after the last data block are kept from the original
+    /// section. The offsets of `datablocks` are updated to their new positions.
+    fn layout_section(
+        &self,
+        section_type: TagSectionType,
+        original: &[u8],
+        datablocks: &mut [TagDataBlock],
+    ) -> Result<Vec<u8>> {
+        let mut indices = (0..datablocks.len())
+            .filter(|index| datablocks[*index].section_type == section_type)
+            .collect::<Vec<_>>();
+        indices.sort_by_key(|index| datablocks[*index].offset);
+
+        let mut section = Vec::with_capacity(original.len());
+        let mut original_end = 0;
+        for index in indices {
+            let datablock = &mut datablocks[index];
+            let original_offset = usize::try_from(datablock.offset)?;
+            if original_offset > original_end {
+                section.extend_from_slice(&original[original_end..original_offset]);
+            }
+            let contents = self.contents[index].as_deref().unwrap_or_default();
+            datablock.offset = section.len() as u64;
+            datablock.entry_size = u32::try_from(contents.len())?;
+            section.extend_from_slice(contents);
+            original_end = original_end
+                .max(original_offset + self.tag.datablock_definitions[index].entry_size as usize);
+        }
+        if original_end < original.len() {
+            section.extend_from_slice(&original[original_end..]);
+        }
+        Ok(section)
+    }
+}
+
+/// Gets the size of a section described by the tag header.
+fn section_size(header: &TagHeader, section_type: TagSectionType) -> u32 {
+    match section_type {
+        TagSectionType::TagData => header.data_size,
+        TagSectionType::ResourceData => header.resource_size,
+        TagSectionType::ActualResource | TagSectionType::Header => header.actual_resource_size,
+    }
+}
+
+/// Checks that a tag buffer contains every section described by the tag header, and that every
+/// data block lies inside of its section, so the data blocks can be sliced out of the buffer and
+/// each section laid out on its own.
+///
+/// # Errors
+/// - If the buffer is smaller than the sections described by the header [`TagError::Truncated`]
+/// - If a data block points outside of its section [`TagError::DatablockOutOfBounds`]
+fn check_bounds(tag: &TagFile, data: &[u8]) -> Result<()> {
+    let header_size = u64::from(tag.header.header_size);
+    let expected = header_size
+        + u64::from(tag.header.data_size)
+        + u64::from(tag.header.resource_size)
+        + u64::from(tag.header.actual_resource_size);
+    let found = data.len() as u64;
+    if found < expected {
+        return Err(TagError::Truncated { expected, found }.into());
+    }
+
+    for (index, datablock) in tag.datablock_definitions.iter().enumerate() {
+        // Header blocks are not stored in the sections following the header.
+        if datablock.section_type == TagSectionType::Header {
+            continue;
+        }
+        let end = datablock
+            .offset
+            .saturating_add(u64::from(datablock.entry_size));
+        if end > u64::from(section_size(&tag.header, datablock.section_type)) {
+            return Err(TagError::DatablockOutOfBounds(index).into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use crate::module::header::ModuleVersion;
+    use crate::tag::data_reference::TagDataReference;
+    use crate::tag::structure::TagStruct;
+
+    /// Size in bytes of a single entry in the data block table.
+    const DATABLOCK_SIZE: usize = 0x10;
+
+    /// Builds an editor for a tag with a main block containing a block field at 0x0 and a data
+    /// field at 0x18, pointing to data blocks 1 and 2.
+    fn create_editor() -> TagEditor {
+        let mut tag = TagFile::default();
+        tag.header.header_size = u32::try_from(HEADER_SIZE + 3 * DATABLOCK_SIZE).unwrap();
+        tag.header.data_size = 0x40;
+        tag.header.datablock_count = 3;
+        for (offset, entry_size) in [(0, 0x30), (0x30, 0x8), (0x38, 0x4)] {
+            let mut datablock = TagDataBlock::default();
+            datablock.section_type = TagSectionType::TagData;
+            datablock.offset = offset;
+            datablock.entry_size = entry_size;
+            tag.datablock_definitions.push(datablock);
+        }
+        tag.struct_definitions.push(TagStruct {
+            struct_type: TagStructType::TagBlock,
+            target_index: 1,
+            ..Default::default()
+        });
+        let mut reference = TagDataReference::default();
+        reference.target_index = 2;
+        reference.field_offset = 0x18;
+        tag.data_references.push(reference);
+
+        let mut data = vec![0; tag.header.header_size as usize + 0x40];
+        let mut writer = Cursor::new(&mut data);
+        tag.header.write(&mut writer).unwrap();
+        for datablock in &tag.datablock_definitions {
+            datablock.write(&mut writer).unwrap();
+        }
+        // Set the magic and version, which cannot be set outside of the header module.
+        data[..8].copy_from_slice(&[0x75, 0x63, 0x73, 0x68, 27, 0, 0, 0]);
+        tag.header.read(&mut Cursor::new(&data[..])).unwrap();
+        let tag_data = &mut data[tag.header.header_size as usize..];
+        tag_data[BLOCK_COUNT_OFFSET] = 2;
+        tag_data[0x18 + DATA_SIZE_OFFSET] = 4;
+        tag_data[0x30..0x38].copy_from_slice(&[1, 1, 1, 1, 2, 2, 2, 2]);
+        tag_data[0x38..0x3C].copy_from_slice(&[3, 3, 3, 3]);
+        TagEditor::new(tag, data).unwrap()
+    }
+
+    #[test]
+    /// Verifies that replacing data and resizing blocks moves the following data blocks.
+    fn test_edit_round_trip() {
+        let mut editor = create_editor();
+        editor.replace_data(0, 0x18, vec![4; 6]).unwrap();
+        editor.resize_block(0, 0, 3).unwrap();
+        editor.set_bytes(1, 8, &[5]).unwrap();
+        assert!(editor.set_bytes(2, 4, &[0; 4]).is_err());
+
+        let data = editor.to_bytes().unwrap();
+        let tag = TagFile::from_bytes(&data, &ModuleVersion::Season3).unwrap();
+        // The 4 unused bytes at the end of the section are kept.
+        assert_eq!(tag.header.data_size, 0x30 + 0xC + 0x6 + 0x4);
+        assert!(check_bounds(&tag, &data).is_ok());
+
+        let block = tag.get_datablock_range(&tag.datablock_definitions[1]);
+        assert_eq!(data[block], [1, 1, 1, 1, 2, 2, 2, 2, 5, 0, 0, 0]);
+        let blob = tag.get_datablock_range(&tag.datablock_definitions[2]);
+        assert_eq!(data[blob], [4; 6]);
+
+        let main = &data[tag.get_datablock_range(&tag.datablock_definitions[0])];
+        assert_eq!(main[BLOCK_COUNT_OFFSET], 3);
+        assert_eq!(main[0x18 + DATA_SIZE_OFFSET], 6);
+    }
+
+    #[test]
+    /// Verifies that truncated tags and out of bounds data blocks are rejected.
+    fn test_check_bounds() {
+        let mut tag = TagFile::default();
+        tag.header.header_size = 0x10;
+        tag.header.data_size = 0x20;
+        let mut datablock = TagDataBlock::default();
+        datablock.entry_size = 0x10;
+        datablock.section_type = TagSectionType::TagData;
+        datablock.offset = 0x10;
+        tag.datablock_definitions.push(datablock);
+        assert!(check_bounds(&tag, &[0; 0x30]).is_ok());
+        assert!(matches!(
+            check_bounds(&tag, &[0; 0x20]),
+            Err(Error::TagError(TagError::Truncated {
+                expected: 0x30,
+                found: 0x20
+            }))
+        ));
+
+        tag.datablock_definitions[0].offset = 0x18;
+        assert!(matches!(
+            TagEditor::new(tag, vec![0; 0x30]),
+            Err(Error::TagError(TagError::DatablockOutOfBounds(0)))
+        ));
+    }
+
+    #[test]
+    /// Verifies that data blocks running past the end of their section are rejected, even when
+    /// they lie inside of the buffer.
+    fn test_check_bounds_section() {
+        let mut tag = TagFile::default();
+        tag.header.header_size = 0x10;
+        tag.header.data_size = 0x20;
+        tag.header.resource_size = 0x20;
+        tag.header.actual_resource_size = 0x20;
+        let mut datablock = TagDataBlock::default();
+        datablock.entry_size = 0x10;
+        datablock.section_type = TagSectionType::TagData;
+        datablock.offset = 0x18;
+        tag.datablock_definitions.push(datablock);
+        assert!(matches!(
+            check_bounds(&tag, &[0; 0x70]),
+            Err(Error::TagError(TagError::DatablockOutOfBounds(0)))
+        ));
+
+        tag.datablock_definitions[0].section_type = TagSectionType::ResourceData;
+        tag.datablock_definitions[0].offset = 0x10;
+        assert!(check_bounds(&tag, &[0; 0x70]).is_ok());
+        tag.datablock_definitions[0].offset = 0x18;
+        assert!(matches!(
+            TagEditor::new(tag, vec![0; 0x70]),
+            Err(Error::TagError(TagError::DatablockOutOfBounds(0)))
+        ));
+    }
+}
@@ -0,0 +1,607 @@
+//! glTF 2.0 export of render models.
+//!
+//! A [`RenderModelTag`] is exported as a binary glTF (`.glb`) file, containing:
+//! - the skeleton of the model, as a hierarchy of nodes with a skin,
+//! - a mesh per geometry mesh, with a primitive per part, and positions, normals, texture
+//!   coordinates and skin weights decoded from the vertex buffers,
+//! - a node per permutation mesh, named after its region and permutation,
+//! - a placeholder material per material index, as materials are not part of the render model.
+//!
+//! Region, permutation and node names are resolved with the installed
+//! [`StringIdDictionary`](`crate::common::string_id::StringIdDictionary`), and written as
+//! hexadecimal string ids otherwise.
+
+use serde_json::{Value, json};
+use std::io::Write;
+
+use crate::Result;
+use crate::common::errors::TagError;
+use crate::common::string_id::lookup_global;
+use crate::tag::types::common_types::FieldStringId;
+use crate::tag::types::mesh::{DecodedMesh, DecodedMeshPart};
+use crate::tag::types::render_model::{
+    RenderGeometry, RenderMesh, RenderModelNode, RenderModelTag,
+};
+
+/// Magic at the start of a binary glTF file ("glTF").
+const GLB_MAGIC: u32 = 0x4654_6C67;
+/// Type of the JSON chunk of a binary glTF file ("JSON").
+const GLB_CHUNK_JSON: u32 = 0x4E4F_534A;
+/// Type of the binary chunk of a binary glTF file ("BIN\0").
+const GLB_CHUNK_BIN: u32 = 0x004E_4942;
+
+/// `componentType` of accessors.
+const COMPONENT_UNSIGNED_BYTE: u32 = 5121;
+const COMPONENT_UNSIGNED_INT: u32 = 5125;
+const COMPONENT_FLOAT: u32 = 5126;
+
+/// `target` of buffer views.
+const TARGET_ARRAY_BUFFER: u32 = 34962;
+const TARGET_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+impl RenderModelTag {
+    /// Exports the model as a binary glTF (`.glb`) file.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer_data` - Data of the resource files of the tag, read with
+    ///   [`read_buffer_data`](`crate::tag::types::render_geometry::read_buffer_data`).
+    /// * `writer` - Writer receiving the file.
+    ///
+    /// # Errors
+    /// - If the mesh resource of the geometry is not loaded [`TagError::NotLoaded`]
+    /// - If a buffer lies outside of `buffer_data` [`TagError::BufferOutOfBounds`]
+    /// - If a vertex buffer cannot be decoded [`TagError::UnsupportedVertexFormat`]
+    /// - If the document cannot be serialized [`JsonError`](`crate::Error::JsonError`)
+    /// - If the writer fails to write the file [`ReadError`](`crate::Error::ReadError`)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use infinite_rs::ModuleFile;
+    /// use infinite_rs::tag::types::render_geometry::read_buffer_data;
+    /// use infinite_rs::tag::types::render_model::RenderModelTag;
+    ///
+    /// let mut module = ModuleFile::from_path("deploy/any/globals/forge/forge_objects-rtx-new.module").unwrap();
+    /// let index = module.files.iter().position(|file| file.tag_group == "mode").unwrap() as u32;
+    /// let model = module.read_tag(index).unwrap().unwrap().read_metadata::<RenderModelTag>().unwrap();
+    /// let buffer_data = read_buffer_data(&mut module, index).unwrap();
+    /// model.write_glb(&buffer_data, &mut File::create("model.glb").unwrap()).unwrap();
+    /// ```
+    pub fn write_glb<W: Write>(&self, buffer_data: &[u8], writer: &mut W) -> Result<()> {
+        if self.render_geometry.api_resource().is_none() {
+            return Err(TagError::NotLoaded.into());
+        }
+        let mut builder = GltfBuilder::default();
+        let skin = builder.push_skeleton(&self.nodes);
+
+        let mut mesh_indices = vec![None; self.render_geometry.meshes.len()];
+        let mut scene_nodes: Vec<usize> = (0..self.nodes.len())
+            .filter(|&index| self.nodes[index].parent().is_none())
+            .collect();
+        for region in &self.regions {
+            for permutation in &region.permutations {
+                for mesh_index in permutation.mesh_range() {
+                    let Some(mesh) = self.render_geometry.meshes.get(mesh_index) else {
+                        continue;
+                    };
+                    // Meshes shared by several permutations are only exported once.
+                    let gltf_mesh = if let Some(gltf_mesh) = mesh_indices[mesh_index] {
+                        gltf_mesh
+                    } else {
+                        let gltf_mesh = builder.push_mesh(
+                            &self.render_geometry,
+                            mesh,
+                            buffer_data,
+                            skin.is_some(),
+                        )?;
+                        mesh_indices[mesh_index] = Some(gltf_mesh);
+                        gltf_mesh
+                    };
+                    let mut node = json!({
+                        "name": format!("{}:{}", name_of(&region.name), name_of(&permutation.name)),
+                        "mesh": gltf_mesh,
+                    });
+                    if let Some(skin) = skin {
+                        node["skin"] = json!(skin);
+                    }
+                    scene_nodes.push(builder.nodes.len());
+                    builder.nodes.push(node);
+                }
+            }
+        }
+        builder.write_glb(&name_of(&self.name), &scene_nodes, writer)
+    }
+}
+
+/// Resolves the name of a string id, or formats it as hexadecimal.
+fn name_of(string_id: &FieldStringId) -> String {
+    lookup_global(string_id.0).unwrap_or_else(|| format!("{:08X}", string_id.0))
+}
+
+#[derive(Default)]
+/// Objects of the glTF document being built, and the contents of its binary buffer.
+struct GltfBuilder {
+    nodes: Vec<Value>,
+    meshes: Vec<Value>,
+    accessors: Vec<Value>,
+    buffer_views: Vec<Value>,
+    skins: Vec<Value>,
+    /// Number of placeholder materials, one per material index used by the parts.
+    material_count: usize,
+    buffer: Vec<u8>,
+}
+
+impl GltfBuilder {
+    /// Adds an accessor over `data` to the buffer, returning its index.
+    fn push_accessor(
+        &mut self,
+        data: &[u8],
+        component_type: u32,
+        count: usize,
+        accessor_type: &str,
+        target: Option<u32>,
+    ) -> usize {
+        let offset = self.buffer.len();
+        self.buffer.extend_from_slice(data);
+        self.buffer.resize(self.buffer.len().next_multiple_of(4), 0);
+        let mut buffer_view = json!({
+            "buffer": 0,
+            "byteOffset": offset,
+            "byteLength": data.len(),
+        });
+        if let Some(target) = target {
+            buffer_view["target"] = json!(target);
+        }
+        self.buffer_views.push(buffer_view);
+        self.accessors.push(json!({
+            "bufferView": self.buffer_views.len() - 1,
+            "componentType": component_type,
+            "count": count,
+            "type": accessor_type,
+        }));
+        self.accessors.len() - 1
+    }
+
+    /// Adds an accessor of float vectors, returning its index.
+    fn push_floats<const N: usize>(&mut self, values: &[[f32; N]], accessor_type: &str) -> usize {
+        let data: Vec<u8> = values
+            .iter()
+            .flatten()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        self.push_accessor(
+            &data,
+            COMPONENT_FLOAT,
+            values.len(),
+            accessor_type,
+            Some(TARGET_ARRAY_BUFFER),
+        )
+    }
+
+    /// Adds the nodes of the skeleton and a skin using them, returning the index of the skin.
+    ///
+    /// Nodes are added first, so node indices in the glTF document match the model.
+    fn push_skeleton(&mut self, nodes: &[RenderModelNode]) -> Option<usize> {
+        if nodes.is_empty() {
+            return None;
+        }
+        let mut globals: Vec<Transform> = Vec::with_capacity(nodes.len());
+        for (index, node) in nodes.iter().enumerate() {
+            let local = Transform::from_node(node);
+            let global = match node.parent().and_then(|parent| globals.get(parent)) {
+                Some(parent) => parent.then(&local),
+                None => local,
+            };
+            globals.push(global);
+
+            let children: Vec<usize> = (0..nodes.len())
+                .filter(|&child| nodes[child].parent() == Some(index) && child != index)
+                .collect();
+            let mut gltf_node = json!({
+                "name": name_of(&node.name),
+                "translation": local.translation,
+                "rotation": local.rotation,
+                "scale": [local.scale, local.scale, local.scale],
+            });
+            if !children.is_empty() {
+                gltf_node["children"] = json!(children);
+            }
+            self.nodes.push(gltf_node);
+        }
+
+        let inverse_bind_matrices: Vec<[f32; 16]> = globals
+            .iter()
+            .map(|global| global.inverse().matrix())
+            .collect();
+        let data: Vec<u8> = inverse_bind_matrices
+            .iter()
+            .flatten()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        // Inverse bind matrices are not vertex data, so their buffer view has no target.
+        let accessor = self.push_accessor(&data, COMPONENT_FLOAT, nodes.len(), "MAT4", None);
+        self.skins.push(json!({
+            "joints": (0..nodes.len()).collect::<Vec<_>>(),
+            "inverseBindMatrices": accessor,
+            "skeleton": 0,
+        }));
+        Some(self.skins.len() - 1)
+    }
+
+    /// Adds a mesh with a primitive per part, returning its index.
+    fn push_mesh(
+        &mut self,
+        geometry: &RenderGeometry,
+        mesh: &RenderMesh,
+        buffer_data: &[u8],
+        skinned: bool,
+    ) -> Result<usize> {
+        let decoded = geometry.decode_mesh(mesh, buffer_data)?;
+        if decoded.positions.is_empty() {
+            self.meshes.push(json!({ "primitives": [] }));
+            return Ok(self.meshes.len() - 1);
+        }
+
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        for position in &decoded.positions {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(position[axis]);
+                max[axis] = max[axis].max(position[axis]);
+            }
+        }
+        let position_accessor = self.push_floats(&decoded.positions, "VEC3");
+        self.accessors[position_accessor]["min"] = json!(min);
+        self.accessors[position_accessor]["max"] = json!(max);
+        let mut attributes = json!({ "POSITION": position_accessor });
+        if !decoded.normals.is_empty() {
+            attributes["NORMAL"] = json!(self.push_floats(&decoded.normals, "VEC3"));
+        }
+        if !decoded.texcoords.is_empty() {
+            attributes["TEXCOORD_0"] = json!(self.push_floats(&decoded.texcoords, "VEC2"));
+        }
+        if skinned {
+            self.push_skin_weights(mesh, &decoded, &mut attributes);
+        }
+
+        let mut primitives = self.push_parts(&decoded.parts, &attributes);
+        if primitives.is_empty() {
+            // Meshes without an index buffer are drawn as a triangle list.
+            primitives.push(json!({ "attributes": attributes, "mode": 4 }));
+        }
+        self.meshes.push(json!({ "primitives": primitives }));
+        Ok(self.meshes.len() - 1)
+    }
+
+    /// Adds the joints and weights of the vertices of a mesh to its attributes.
+    ///
+    /// Meshes without blend indices follow their rigid node, if they have one.
+    fn push_skin_weights(
+        &mut self,
+        mesh: &RenderMesh,
+        decoded: &DecodedMesh,
+        attributes: &mut Value,
+    ) {
+        let vertex_count = decoded.positions.len();
+        let influences: Vec<([u8; 4], [f32; 4])> = if decoded.blend_indices.is_empty() {
+            match u8::try_from(mesh.rigid_node_index.0) {
+                Ok(node) => vec![([node, 0, 0, 0], [1.0, 0.0, 0.0, 0.0]); vertex_count],
+                Err(_) => return,
+            }
+        } else {
+            decoded
+                .blend_indices
+                .iter()
+                .enumerate()
+                .map(|(index, &joints)| {
+                    let weights = decoded
+                        .blend_weights
+                        .get(index)
+                        .copied()
+                        .unwrap_or([1.0, 0.0, 0.0, 0.0]);
+                    (joints, normalize_weights(weights))
+                })
+                .collect()
+        };
+
+        let joints: Vec<u8> = influences.iter().flat_map(|(joints, _)| *joints).collect();
+        let weights: Vec<[f32; 4]> = influences.iter().map(|(_, weights)| *weights).collect();
+        attributes["JOINTS_0"] = json!(self.push_accessor(
+            &joints,
+            COMPONENT_UNSIGNED_BYTE,
+            vertex_count,
+            "VEC4",
+            Some(TARGET_ARRAY_BUFFER),
+        ));
+        attributes["WEIGHTS_0"] = json!(self.push_floats(&weights, "VEC4"));
+    }
+
+    /// Adds the indices of every part of a mesh, returning a primitive per part.
+    fn push_parts(&mut self, parts: &[DecodedMeshPart], attributes: &Value) -> Vec<Value> {
+        let mut primitives = Vec::new();
+        for part in parts {
+            let indices: Vec<u8> = part
+                .indices
+                .iter()
+                .flat_map(|index| index.to_le_bytes())
+                .collect();
+            let indices_accessor = self.push_accessor(
+                &indices,
+                COMPONENT_UNSIGNED_INT,
+                part.indices.len(),
+                "SCALAR",
+                Some(TARGET_ELEMENT_ARRAY_BUFFER),
+            );
+            let mut primitive = json!({
+                "attributes": attributes,
+                "indices": indices_accessor,
+                "mode": 4,
+            });
+            if let Some(material) = part.material_index {
+                self.material_count = self.material_count.max(material + 1);
+                primitive["material"] = json!(material);
+            }
+            primitives.push(primitive);
+        }
+        primitives
+    }
+
+    /// Writes the document and its buffer as a binary glTF file.
+    fn write_glb<W: Write>(self, name: &str, scene_nodes: &[usize], writer: &mut W) -> Result<()> {
+        let materials: Vec<Value> = (0..self.material_count)
+            .map(|index| json!({ "name": format!("material_{index}") }))
+            .collect();
+        let mut document = json!({
+            "asset": { "version": "2.0", "generator": "infinite-rs" },
+            "scene": 0,
+            "scenes": [{ "name": name, "nodes": scene_nodes }],
+            "nodes": self.nodes,
+            "meshes": self.meshes,
+            "accessors": self.accessors,
+            "bufferViews": self.buffer_views,
+            "buffers": [{ "byteLength": self.buffer.len() }],
+        });
+        if !materials.is_empty() {
+            document["materials"] = json!(materials);
+        }
+        if !self.skins.is_empty() {
+            document["skins"] = json!(self.skins);
+        }
+
+        let mut json = serde_json::to_vec(&document)?;
+        json.resize(json.len().next_multiple_of(4), b' ');
+        let json_length = u32::try_from(json.len())?;
+        let buffer_length = u32::try_from(self.buffer.len())?;
+        let total_length = 12 + 8 + json_length + 8 + buffer_length;
+
+        writer.write_all(&GLB_MAGIC.to_le_bytes())?;
+        writer.write_all(&2u32.to_le_bytes())?;
+        writer.write_all(&total_length.to_le_bytes())?;
+        writer.write_all(&json_length.to_le_bytes())?;
+        writer.write_all(&GLB_CHUNK_JSON.to_le_bytes())?;
+        writer.write_all(&json)?;
+        writer.write_all(&buffer_length.to_le_bytes())?;
+        writer.write_all(&GLB_CHUNK_BIN.to_le_bytes())?;
+        writer.write_all(&self.buffer)?;
+        Ok(())
+    }
+}
+
+/// Scales skin weights so they sum to 1, falling back to the first joint.
+fn normalize_weights(weights: [f32; 4]) -> [f32; 4] {
+    let sum: f32 = weights.iter().sum();
+    if sum > f32::EPSILON {
+        weights.map(|weight| weight / sum)
+    } else {
+        [1.0, 0.0, 0.0, 0.0]
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Transform made of a translation, a rotation quaternion and a uniform scale.
+struct Transform {
+    translation: [f32; 3],
+    rotation: [f32; 4],
+    scale: f32,
+}
+
+impl Transform {
+    /// Transform of a node relative to its parent in the default pose.
+    fn from_node(node: &RenderModelNode) -> Self {
+        let rotation = &node.default_rotation;
+        let rotation = [rotation.x, rotation.y, rotation.z, rotation.w];
+        let length = rotation
+            .iter()
+            .map(|value| value * value)
+            .sum::<f32>()
+            .sqrt();
+        let translation = &node.default_translation;
+        Self {
+            translation: [translation.x, translation.y, translation.z],
+            rotation: if length > f32::EPSILON {
+                rotation.map(|value| value / length)
+            } else {
+                [0.0, 0.0, 0.0, 1.0]
+            },
+            scale: if node.default_scale.0 == 0.0 {
+                1.0
+            } else {
+                node.default_scale.0
+            },
+        }
+    }
+
+    /// Applies `child` after this transform, as for a node and its parent.
+    fn then(&self, child: &Self) -> Self {
+        let rotated = rotate(self.rotation, child.translation);
+        Self {
+            translation: [0, 1, 2].map(|axis| self.translation[axis] + rotated[axis] * self.scale),
+            rotation: multiply(self.rotation, child.rotation),
+            scale: self.scale * child.scale,
+        }
+    }
+
+    /// Inverse of the transform.
+    fn inverse(&self) -> Self {
+        let [x, y, z, w] = self.rotation;
+        let rotation = [-x, -y, -z, w];
+        let scale = 1.0 / self.scale;
+        let rotated = rotate(rotation, self.translation);
+        Self {
+            translation: rotated.map(|value| -value * scale),
+            rotation,
+            scale,
+        }
+    }
+
+    /// Column-major 4x4 matrix of the transform.
+    fn matrix(&self) -> [f32; 16] {
+        let [x, y, z, w] = self.rotation;
+        let scale = self.scale;
+        let [tx, ty, tz] = self.translation;
+        [
+            (1.0 - 2.0 * (y * y + z * z)) * scale,
+            2.0 * (x * y + z * w) * scale,
+            2.0 * (x * z - y * w) * scale,
+            0.0,
+            2.0 * (x * y - z * w) * scale,
+            (1.0 - 2.0 * (x * x + z * z)) * scale,
+            2.0 * (y * z + x * w) * scale,
+            0.0,
+            2.0 * (x * z + y * w) * scale,
+            2.0 * (y * z - x * w) * scale,
+            (1.0 - 2.0 * (x * x + y * y)) * scale,
+            0.0,
+            tx,
+            ty,
+            tz,
+            1.0,
+        ]
+    }
+}
+
+/// Multiplies two quaternions stored as `[x, y, z, w]`.
+fn multiply(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    let [ax, ay, az, aw] = a;
+    let [bx, by, bz, bw] = b;
+    [
+        aw * bx + ax * bw + ay * bz - az * by,
+        aw * by - ax * bz + ay * bw + az * bx,
+        aw * bz + ax * by - ay * bx + az * bw,
+        aw * bw - ax * bx - ay * by - az * bz,
+    ]
+}
+
+/// Rotates a vector by a unit quaternion stored as `[x, y, z, w]`.
+fn rotate(rotation: [f32; 4], vector: [f32; 3]) -> [f32; 3] {
+    let [x, y, z, _] = multiply(
+        multiply(rotation, [vector[0], vector[1], vector[2], 0.0]),
+        [-rotation[0], -rotation[1], -rotation[2], rotation[3]],
+    );
+    [x, y, z]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::array;
+    use std::f32::consts::FRAC_1_SQRT_2;
+
+    use crate::module::file::TagStructure;
+    use crate::tag::types::common_types::{FieldBlock, FieldLongInteger, FieldShortInteger};
+    use crate::tag::types::render_geometry::{
+        IndexBufferType, RasterizerIndexBuffer, RasterizerVertexBuffer, VertexFormat,
+    };
+    use crate::tag::types::render_model::{
+        RenderMeshPart, RenderMeshResourceGroup, RenderModelPermutation, RenderModelRegion,
+    };
+
+    /// Creates a block containing the given elements.
+    fn block<T: TagStructure + Default>(elements: Vec<T>) -> FieldBlock<T> {
+        let mut block = FieldBlock::default();
+        block.size = u32::try_from(elements.len()).unwrap();
+        block.elements = elements;
+        block
+    }
+
+    #[test]
+    /// Verifies that a single triangle is exported as a valid binary glTF file.
+    fn test_write_glb() {
+        let mut buffer_data = Vec::new();
+        for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            buffer_data.extend_from_slice(&value.to_le_bytes());
+        }
+        for index in [0u16, 1, 2] {
+            buffer_data.extend_from_slice(&index.to_le_bytes());
+        }
+
+        let mut positions = RasterizerVertexBuffer::default();
+        positions.format.0 = VertexFormat::RealVector3D.into();
+        positions.stride.0 = 12;
+        positions.count.0 = 3;
+        let mut indices = RasterizerIndexBuffer::default();
+        indices.declaration_type.0 = IndexBufferType::TriangleList;
+        indices.stride.0 = 2;
+        indices.count.0 = 3;
+        indices.offset.0 = 36;
+
+        let mut vertex_buffer_indices = array::from_fn(|_| FieldShortInteger(-1));
+        vertex_buffer_indices[0].0 = 0;
+        let mesh = RenderMesh {
+            parts: block(vec![RenderMeshPart {
+                index_count: FieldLongInteger(3),
+                ..Default::default()
+            }]),
+            vertex_buffer_indices,
+            ..Default::default()
+        };
+
+        let mut model = RenderModelTag::default();
+        model.render_geometry.meshes = block(vec![mesh]);
+        let mut group = RenderMeshResourceGroup::default();
+        group.mesh_resource.data.pc_vertex_buffers = block(vec![positions]);
+        group.mesh_resource.data.pc_index_buffers = block(vec![indices]);
+        model.render_geometry.mesh_resource_groups = block(vec![group]);
+        let mut permutation = RenderModelPermutation::default();
+        permutation.mesh_count.0 = 1;
+        let region = RenderModelRegion {
+            permutations: block(vec![permutation]),
+            ..Default::default()
+        };
+        model.regions = block(vec![region]);
+
+        let mut glb = Vec::new();
+        model.write_glb(&buffer_data, &mut glb).unwrap();
+        assert_eq!(&glb[..4], b"glTF");
+        assert_eq!(
+            u32::from_le_bytes(glb[8..12].try_into().unwrap()) as usize,
+            glb.len()
+        );
+
+        let json_length = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+        let document: Value = serde_json::from_slice(&glb[20..20 + json_length]).unwrap();
+        assert_eq!(document["meshes"][0]["primitives"][0]["material"], 0);
+        assert_eq!(document["accessors"][0]["count"], 3);
+        assert_eq!(document["accessors"][0]["max"], json!([1.0, 1.0, 0.0]));
+        assert_eq!(document["accessors"][1]["count"], 3);
+        assert_eq!(document["nodes"][0]["mesh"], 0);
+    }
+
+    #[test]
+    /// Verifies that inverse bind matrices undo the transform of their node.
+    fn test_transform_inverse() {
+        let half = FRAC_1_SQRT_2;
+        let transform = Transform {
+            translation: [1.0, 2.0, 3.0],
+            rotation: [0.0, 0.0, half, half],
+            scale: 2.0,
+        };
+        let identity = transform.then(&transform.inverse()).matrix();
+        for (index, value) in identity.iter().enumerate() {
+            let expected = if index % 5 == 0 { 1.0 } else { 0.0 };
+            assert!((value - expected).abs() < 1e-5, "{identity:?}");
+        }
+    }
+}
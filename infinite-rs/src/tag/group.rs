@@ -0,0 +1,113 @@
+//! Human-readable names and categories of tag groups.
+//!
+//! Tag groups are identified by a four character code (such as `bitm` or `mat `), stored in
+//! [`ModuleFileEntry::tag_group`](`crate::module::file::ModuleFileEntry::tag_group`). This table
+//! is not exhaustive: groups missing from it return [`None`].
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// Broad category a tag group belongs to.
+pub enum TagGroupCategory {
+    /// Textures, materials, shaders and models.
+    Render,
+    /// Animations and animation graphs.
+    Animation,
+    /// Collision and physics data.
+    Physics,
+    /// Sounds and sound settings.
+    Sound,
+    /// Effects, particles, decals and lights.
+    Effect,
+    /// Game objects placed in the world, such as weapons and vehicles.
+    Object,
+    /// AI characters and their behaviour.
+    Ai,
+    /// Scenarios, structure BSPs and scripts.
+    Level,
+    /// User interface, fonts and strings.
+    Interface,
+    /// Global settings of the game.
+    Globals,
+}
+
+/// Known tag groups, along with their descriptive names and categories.
+const TAG_GROUPS: &[(&str, &str, TagGroupCategory)] = &[
+    ("bitm", "Bitmap", TagGroupCategory::Render),
+    ("mat ", "Material", TagGroupCategory::Render),
+    ("mode", "Render Model", TagGroupCategory::Render),
+    ("hlmt", "Model", TagGroupCategory::Render),
+    ("rmsh", "Render Method Shader", TagGroupCategory::Render),
+    ("jmad", "Model Animation Graph", TagGroupCategory::Animation),
+    ("coll", "Collision Model", TagGroupCategory::Physics),
+    ("phmo", "Physics Model", TagGroupCategory::Physics),
+    ("snd!", "Sound", TagGroupCategory::Sound),
+    ("lsnd", "Looping Sound", TagGroupCategory::Sound),
+    ("snmx", "Sound Mix", TagGroupCategory::Sound),
+    ("ssce", "Sound Scenery", TagGroupCategory::Sound),
+    ("effe", "Effect", TagGroupCategory::Effect),
+    ("prt3", "Particle", TagGroupCategory::Effect),
+    ("pmdf", "Particle Model", TagGroupCategory::Effect),
+    ("decs", "Decal System", TagGroupCategory::Effect),
+    ("lens", "Lens Flare", TagGroupCategory::Effect),
+    ("ligh", "Light", TagGroupCategory::Effect),
+    ("foot", "Material Effects", TagGroupCategory::Effect),
+    ("jpt!", "Damage Effect", TagGroupCategory::Effect),
+    ("sefc", "Area Screen Effect", TagGroupCategory::Effect),
+    ("bipd", "Biped", TagGroupCategory::Object),
+    ("vehi", "Vehicle", TagGroupCategory::Object),
+    ("weap", "Weapon", TagGroupCategory::Object),
+    ("eqip", "Equipment", TagGroupCategory::Object),
+    ("proj", "Projectile", TagGroupCategory::Object),
+    ("scen", "Scenery", TagGroupCategory::Object),
+    ("bloc", "Crate", TagGroupCategory::Object),
+    ("mach", "Device Machine", TagGroupCategory::Object),
+    ("ctrl", "Device Control", TagGroupCategory::Object),
+    ("char", "Character", TagGroupCategory::Ai),
+    ("styl", "Style", TagGroupCategory::Ai),
+    ("scnr", "Scenario", TagGroupCategory::Level),
+    ("sbsp", "Scenario Structure BSP", TagGroupCategory::Level),
+    ("hsc*", "Script", TagGroupCategory::Level),
+    ("levl", "Level", TagGroupCategory::Level),
+    (
+        "unic",
+        "Multilingual Unicode String List",
+        TagGroupCategory::Interface,
+    ),
+    ("matg", "Globals", TagGroupCategory::Globals),
+    ("mulg", "Multiplayer Globals", TagGroupCategory::Globals),
+    ("wind", "Wind", TagGroupCategory::Globals),
+];
+
+/// Gets the descriptive name of a tag group.
+///
+/// # Examples
+///
+/// ```
+/// use infinite_rs::tag::group::tag_group_name;
+///
+/// assert_eq!(tag_group_name("bitm"), Some("Bitmap"));
+/// assert_eq!(tag_group_name("????"), None);
+/// ```
+#[must_use]
+pub fn tag_group_name(tag_group: &str) -> Option<&'static str> {
+    find(tag_group).map(|(_, name, _)| *name)
+}
+
+/// Gets the category of a tag group.
+///
+/// # Examples
+///
+/// ```
+/// use infinite_rs::tag::group::{TagGroupCategory, tag_group_category};
+///
+/// assert_eq!(tag_group_category("mat "), Some(TagGroupCategory::Render));
+/// ```
+#[must_use]
+pub fn tag_group_category(tag_group: &str) -> Option<TagGroupCategory> {
+    find(tag_group).map(|(_, _, category)| *category)
+}
+
+/// Finds the entry of a tag group in the table.
+fn find(tag_group: &str) -> Option<&'static (&'static str, &'static str, TagGroupCategory)> {
+    TAG_GROUPS.iter().find(|(group, _, _)| *group == tag_group)
+}
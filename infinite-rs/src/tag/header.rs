@@ -1,15 +1,17 @@
 //! Tag Header containing info on the layout of the tag file.
 
-use byteorder::{LE, ReadBytesExt};
-use std::io::BufRead;
+use byteorder::{LE, ReadBytesExt, WriteBytesExt};
+use std::io::{BufRead, Write};
 
 use crate::Result;
 use crate::common::errors::TagError;
+use crate::common::options::Warning;
 
 const HEADER_MAGIC: u32 = 0x6873_6375; // "ucsh"
 const HEADER_VERSION: i32 = 27;
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// Tag Header structure containing info on the layout of the tag file.
 pub struct TagHeader {
     /// Has to be "ucsh" (0x68736375)
@@ -72,6 +74,17 @@ impl TagHeader {
     /// - If the version number is not recognized [`TagError::IncorrectVersion`]
     /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
     pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
+        self.read_with_warnings(reader, true, &mut Vec::new())
+    }
+
+    /// Reads the tag header like [`read`](`TagHeader::read`), recording an unrecognized version
+    /// as a [`Warning::IncorrectTagVersion`] instead of failing when `strict` is disabled.
+    pub(crate) fn read_with_warnings<R: BufRead>(
+        &mut self,
+        reader: &mut R,
+        strict: bool,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<()> {
         self.magic = reader.read_u32::<LE>()?;
         if self.magic != HEADER_MAGIC {
             return Err(TagError::IncorrectMagic(self.magic).into());
@@ -79,7 +92,10 @@ impl TagHeader {
 
         self.version = reader.read_i32::<LE>()?;
         if self.version != HEADER_VERSION {
-            return Err(TagError::IncorrectVersion(self.version).into());
+            if strict {
+                return Err(TagError::IncorrectVersion(self.version).into());
+            }
+            warnings.push(Warning::IncorrectTagVersion(self.version));
         }
 
         self.root_struct_guid = reader.read_i64::<LE>()?;
@@ -103,4 +119,33 @@ impl TagHeader {
         self.is_resource = reader.read_u32::<LE>()? != 0;
         Ok(())
     }
+
+    /// Writes the tag header to the given writer, mirroring [`read`](`TagHeader::read`).
+    ///
+    /// # Errors
+    /// - If the writer fails to write [`ReadError`](`crate::Error::ReadError`)
+    pub(crate) fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32::<LE>(self.magic)?;
+        writer.write_i32::<LE>(self.version)?;
+        writer.write_i64::<LE>(self.root_struct_guid)?;
+        writer.write_i64::<LE>(self.checksum)?;
+        writer.write_u32::<LE>(self.dependency_count)?;
+        writer.write_u32::<LE>(self.datablock_count)?;
+        writer.write_u32::<LE>(self.tagstruct_count)?;
+        writer.write_u32::<LE>(self.data_reference_count)?;
+        writer.write_u32::<LE>(self.tag_reference_count)?;
+        writer.write_u32::<LE>(self.string_table_size)?;
+        writer.write_u32::<LE>(self.zoneset_size)?;
+        writer.write_u32::<LE>(self.unknown)?;
+        writer.write_u32::<LE>(self.header_size)?;
+        writer.write_u32::<LE>(self.data_size)?;
+        writer.write_u32::<LE>(self.resource_size)?;
+        writer.write_u32::<LE>(self.actual_resource_size)?;
+        writer.write_u8(self.header_alignment)?;
+        writer.write_u8(self.tag_alignment)?;
+        writer.write_u8(self.resource_alignment)?;
+        writer.write_u8(self.actual_resource_alignment)?;
+        writer.write_u32::<LE>(u32::from(self.is_resource))?;
+        Ok(())
+    }
 }
@@ -0,0 +1,157 @@
+//! JSON export of loaded tags.
+//!
+//! Tags are exported from their [`DynamicStruct`] tree, so no structure definition is needed.
+//! Each structure is written as an object containing its GUID, type, raw bytes (as a hex string)
+//! and the fields pointing to other structures, data or tags. Tag references are resolved
+//! against the [`dependencies`](`TagFile::dependencies`) of the tag.
+
+use serde_json::{Map, Value, json};
+
+use super::{
+    dynamic::{DynamicStruct, DynamicTagValue, to_hex},
+    loader::TagFile,
+};
+use crate::Result;
+
+impl TagFile {
+    /// Exports the entire tag as a nested JSON document.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Bytes of the entire tag file (including header).
+    ///
+    /// # Errors
+    /// - If the main struct definition is not found [`TagError::MainStructNotFound`](`crate::common::errors::TagError::MainStructNotFound`)
+    /// - If the tag dependencies cannot be serialized [`JsonError`](`crate::Error::JsonError`)
+    pub fn to_json(&self, data: &[u8]) -> Result<Value> {
+        let root = DynamicStruct::read(self, data)?;
+        Ok(json!({
+            "root_struct_guid": format!("{:#018X}", self.header.root_struct_guid),
+            "dependencies": serde_json::to_value(&self.dependencies)?,
+            "main_struct": struct_to_json(self, &root),
+        }))
+    }
+}
+
+/// Converts a dynamic structure and all of its children into a JSON object.
+fn struct_to_json(tag_file: &TagFile, dynamic: &DynamicStruct) -> Value {
+    let fields = dynamic
+        .fields
+        .iter()
+        .map(|field| field_to_json(tag_file, field))
+        .collect::<Vec<_>>();
+
+    let mut object = Map::new();
+    object.insert(
+        String::from("guid"),
+        json!(format!("{:#034X}", dynamic.guid)),
+    );
+    object.insert(
+        String::from("struct_type"),
+        json!(format!("{:?}", dynamic.struct_type)),
+    );
+    if dynamic.datablock_index.is_some() {
+        object.insert(
+            String::from("section_type"),
+            json!(format!("{:?}", dynamic.section_type)),
+        );
+    }
+    object.insert(String::from("size"), json!(dynamic.data.len()));
+    object.insert(String::from("data"), json!(to_hex(&dynamic.data)));
+    object.insert(String::from("fields"), Value::Array(fields));
+    Value::Object(object)
+}
+
+/// Converts a single field of a dynamic structure into a JSON object.
+fn field_to_json(tag_file: &TagFile, field: &DynamicTagValue) -> Value {
+    match field {
+        DynamicTagValue::Struct { offset, value } => json!({
+            "offset": offset,
+            "kind": "struct",
+            "value": struct_to_json(tag_file, value),
+        }),
+        DynamicTagValue::Data { offset, data, .. } => json!({
+            "offset": offset,
+            "kind": "data",
+            "size": data.len(),
+            "data": to_hex(data),
+        }),
+        DynamicTagValue::Reference {
+            offset,
+            dependency_index,
+        } => {
+            let tag = tag_file
+                .get_dependency(*dependency_index)
+                .map(|dependency| {
+                    json!({
+                        "tag_group": dependency.tag_group,
+                        "tag_id": dependency.tag_id,
+                        "asset_id": dependency.asset_id,
+                        "name": dependency.name,
+                    })
+                });
+            json!({
+                "offset": offset,
+                "kind": "reference",
+                "tag": tag,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TagId;
+    use crate::tag::dependency::TagDependency;
+    use crate::tag::structure::TagStructType;
+
+    #[test]
+    /// Verifies that nested structures, data and references are exported with their offsets.
+    fn test_struct_to_json() {
+        let tag_file = TagFile {
+            dependencies: vec![TagDependency {
+                tag_group: String::from("bitm"),
+                tag_id: TagId(0x1234),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let root = DynamicStruct {
+            guid: 1,
+            struct_type: TagStructType::MainStruct,
+            datablock_index: Some(0),
+            data: vec![0xCA, 0x7F],
+            fields: vec![
+                DynamicTagValue::Reference {
+                    offset: 0x10,
+                    dependency_index: 0,
+                },
+                DynamicTagValue::Data {
+                    offset: 0x20,
+                    datablock_index: Some(1),
+                    data: vec![1, 2],
+                },
+                DynamicTagValue::Struct {
+                    offset: 0x30,
+                    value: DynamicStruct {
+                        struct_type: TagStructType::TagBlock,
+                        ..Default::default()
+                    },
+                },
+            ],
+            ..Default::default()
+        };
+
+        let json = struct_to_json(&tag_file, &root);
+        assert_eq!(json["struct_type"], "MainStruct");
+        assert_eq!(json["section_type"], "Header");
+        assert_eq!(json["data"], "ca7f");
+        assert_eq!(json["fields"][0]["tag"]["tag_group"], "bitm");
+        assert_eq!(json["fields"][0]["tag"]["tag_id"], 0x1234);
+        assert_eq!(json["fields"][1]["data"], "0102");
+        assert_eq!(json["fields"][2]["offset"], 0x30);
+        assert_eq!(json["fields"][2]["value"]["struct_type"], "TagBlock");
+        assert!(json["fields"][2]["value"].get("section_type").is_none());
+    }
+}
@@ -1,16 +1,33 @@
 //! Main abstraction file for tags.
 
-use std::io::SeekFrom;
+use std::{
+    fs::File,
+    io::{BufReader, Cursor, SeekFrom},
+    ops::Range,
+    path::Path,
+};
 
 use super::{
-    data_reference::TagDataReference, datablock::TagDataBlock, dependency::TagDependency,
-    header::TagHeader, reference::TagReference, structure::TagStruct,
+    data_reference::TagDataReference,
+    datablock::{TagDataBlock, TagSectionType},
+    dependency::TagDependency,
+    header::TagHeader,
+    reference::TagReference,
+    structure::{TagStruct, TagStructType},
 };
 use crate::Result;
+use crate::common::errors::TagError;
 use crate::common::extensions::BufReaderExt;
-use crate::module::header::ModuleVersion;
+use crate::common::hash::murmur3_x86_32;
+use crate::common::options::{
+    BlockBoundsPolicy, MissingResourcePolicy, ParseOptions, StringTableMode, Warning,
+};
+use crate::common::string_id::lookup_global;
+use crate::module::{file::TagStructure, header::ModuleVersion};
+use crate::tag::types::common_types::FieldStringId;
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// Tag structure containing structure of entire tag file.
 pub struct TagFile {
     /// Header containing info on how to read other parts of the file.
@@ -25,10 +42,75 @@ pub struct TagFile {
     pub data_references: Vec<TagDataReference>,
     /// Tags that are referenced by this tag inside the module.
     pub tag_references: Vec<TagReference>,
+    /// Strings stored in the tag string table, in order.
+    /// This only contains values before Season 3.
+    pub string_table: Vec<String>,
+    /// What happens when tag blocks or resources point to data that is not loaded, when reading
+    /// metadata with [`read_metadata`](`TagFile::read_metadata`).
+    /// Set from [`ParseOptions::missing_resources`] when the tag is read.
+    pub missing_resources: MissingResourcePolicy,
+    /// What happens when tag blocks have more elements than fit in their data block.
+    /// Set from [`ParseOptions::block_bounds`] when the tag is read.
+    pub block_bounds: BlockBoundsPolicy,
+    /// Version of the module the tag was read from, used to select version-dependent fields.
+    pub version: ModuleVersion,
+    /// Recoverable issues found while reading the tag, see [`ParseOptions::strict`].
+    pub warnings: Vec<Warning>,
 }
 
 impl TagFile {
+    /// Instantiates a [`TagFile`] from a standalone tag file on disk.
+    ///
+    /// This is intended for "loose" tags that have already been extracted from a module.
+    /// Use [`read_metadata`](`TagFile::read_metadata`) with the contents of the same file to read
+    /// a [`TagStructure`] from it.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - Path to the extracted tag file.
+    /// * `version` - Version of the module the tag was extracted from.
+    ///
+    /// # Errors
+    /// - If the file cannot be opened or read [`ReadError`](`crate::Error::ReadError`)
+    /// - If the tag header is invalid [`TagError`](`crate::common::errors::TagError`)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use infinite_rs::TagFile;
+    /// use infinite_rs::module::header::ModuleVersion;
+    ///
+    /// let tag = TagFile::from_path("cat.material", &ModuleVersion::Season3).unwrap();
+    /// println!("{} datablocks", tag.datablock_definitions.len());
+    /// ```
+    pub fn from_path<T: AsRef<Path>>(file_path: T, version: &ModuleVersion) -> Result<Self> {
+        let mut reader = BufReader::new(File::open(file_path)?);
+        let mut tag = Self::default();
+        tag.read(&mut reader, version)?;
+        Ok(tag)
+    }
+
+    /// Instantiates a [`TagFile`] from a buffer containing an entire tag (including its header).
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Bytes of the entire tag file.
+    /// * `version` - Version of the module the tag was extracted from.
+    ///
+    /// # Errors
+    /// - If the buffer is too small to contain the tag structure [`ReadError`](`crate::Error::ReadError`)
+    /// - If the tag header is invalid [`TagError`](`crate::common::errors::TagError`)
+    pub fn from_bytes(data: &[u8], version: &ModuleVersion) -> Result<Self> {
+        let mut reader = BufReader::new(Cursor::new(data));
+        let mut tag = Self::default();
+        tag.read(&mut reader, version)?;
+        Ok(tag)
+    }
+
     /// Reads the tag file from the given readers implementing [`BufReaderExt`].
+    ///
+    /// This uses the default [`ParseOptions`], see [`read_with_options`](`TagFile::read_with_options`).
+    ///
     /// # Arguments
     ///
     /// * `reader` - A mutable reference to a reader that implements [`BufReaderExt`] from which to read the data.
@@ -37,7 +119,31 @@ impl TagFile {
     /// # Errors
     /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
     pub fn read<R: BufReaderExt>(&mut self, reader: &mut R, version: &ModuleVersion) -> Result<()> {
-        self.header.read(reader)?;
+        self.read_with_options(reader, version, &ParseOptions::default())
+    }
+
+    /// Reads the tag file from the given readers implementing [`BufReaderExt`], using the given options.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A mutable reference to a reader that implements [`BufReaderExt`] from which to read the data.
+    /// * `module_version` - Version of the module being read
+    /// * `options` - Options controlling how the tag is parsed.
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    pub fn read_with_options<R: BufReaderExt>(
+        &mut self,
+        reader: &mut R,
+        version: &ModuleVersion,
+        options: &ParseOptions,
+    ) -> Result<()> {
+        self.missing_resources = options.missing_resources;
+        self.block_bounds = options.block_bounds;
+        self.version = version.clone();
+        self.warnings.clear();
+        self.header
+            .read_with_warnings(reader, options.strict, &mut self.warnings)?;
         self.dependencies =
             reader.read_enumerable::<TagDependency>(u64::from(self.header.dependency_count))?;
 
@@ -55,23 +161,488 @@ impl TagFile {
 
         let string_table_position = reader.stream_position()?;
 
-        // This is only valid before Season 3.
-        if version < &ModuleVersion::Season3 {
+        if self.has_string_table(string_table_position, version, options.string_table) {
+            let string_table_size = self.header.string_table_size as usize;
+            let mut string_table = vec![0; string_table_size];
+            reader.read_exact(&mut string_table)?;
+            // Names are decoded lossily, so that a corrupt string table does not fail the read.
+            self.string_table = string_table
+                .split(|&byte| byte == 0)
+                .filter(|string| !string.is_empty())
+                .map(|string| String::from_utf8_lossy(string).into_owned())
+                .collect();
+
             for dep in &mut self.dependencies {
-                reader.seek(SeekFrom::Start(
-                    string_table_position + u64::from(dep.name_offset),
-                ))?;
-                dep.name = Some(reader.read_null_terminated_string()?);
+                dep.name = Some(string_table_entry(&string_table, dep.name_offset));
             }
             for reference in &mut self.tag_references {
-                reader.seek(SeekFrom::Start(
-                    string_table_position + u64::from(reference.name_offset),
-                ))?;
-                reference.name = Some(reader.read_null_terminated_string()?);
+                reference.name = Some(string_table_entry(&string_table, reference.name_offset));
             }
         }
         // Ensure that tag data starts where it is supposed to.
         reader.seek(SeekFrom::Start(u64::from(self.header.header_size)))?;
         Ok(())
     }
+
+    /// Determines whether the tag contains a string table that should be read.
+    ///
+    /// String tables only exist before Season 3, and some tag groups do not have one in any
+    /// version. These are detected by checking that the header describes a non-empty string
+    /// table that ends before the tag data starts.
+    fn has_string_table(
+        &self,
+        position: u64,
+        version: &ModuleVersion,
+        mode: StringTableMode,
+    ) -> bool {
+        let from_version = version < &ModuleVersion::Season3;
+        let from_header = self.header.string_table_size != 0
+            && position + u64::from(self.header.string_table_size)
+                <= u64::from(self.header.header_size);
+        match mode {
+            StringTableMode::Detect => from_version && from_header,
+            StringTableMode::FromVersion => from_version,
+            StringTableMode::Always => true,
+            StringTableMode::Never => false,
+        }
+    }
+
+    /// Gets the main struct definition of the tag, which is the root of its structure.
+    #[must_use]
+    pub fn main_struct(&self) -> Option<&TagStruct> {
+        self.struct_definitions
+            .iter()
+            .find(|s| s.struct_type == TagStructType::MainStruct)
+    }
+
+    /// Gets a tag dependency from its index in [`dependencies`](`TagFile::dependencies`).
+    ///
+    /// Indices stored inside tags (for instance [`TagReference::dependency_index`]) use -1 to
+    /// represent a null dependency, in which case [`None`] is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the dependency, as stored in the tag.
+    #[must_use]
+    pub fn get_dependency(&self, index: i32) -> Option<&TagDependency> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| self.dependencies.get(index))
+    }
+
+    /// Finds the row of the tag reference table describing a [`FieldReference`](`crate::tag::types::common_types::FieldReference`) field.
+    ///
+    /// # Arguments
+    ///
+    /// * `field_block` - Index of the data block containing the reference field.
+    /// * `field_offset` - Offset of the reference field inside that data block.
+    #[must_use]
+    pub fn find_tag_reference(&self, field_block: i32, field_offset: u32) -> Option<&TagReference> {
+        self.tag_references
+            .iter()
+            .find(|r| r.field_block == field_block && r.field_offset == field_offset)
+    }
+
+    /// Gets the dependency referenced by a row of the tag reference table, if it is not null.
+    #[must_use]
+    pub fn get_reference_dependency(&self, reference: &TagReference) -> Option<&TagDependency> {
+        self.get_dependency(reference.dependency_index)
+    }
+
+    /// Gets a data block from its index in [`datablock_definitions`](`TagFile::datablock_definitions`).
+    ///
+    /// Returns [`None`] for negative (null) or out of range indices.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the data block.
+    #[must_use]
+    pub fn get_datablock(&self, index: i32) -> Option<&TagDataBlock> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| self.datablock_definitions.get(index))
+    }
+
+    /// Gets the range of bytes covered by a data block, relative to the start of the entire tag
+    /// (including header).
+    ///
+    /// The range can be used to slice the buffer passed to [`read_metadata`](`TagFile::read_metadata`).
+    #[must_use]
+    pub fn get_datablock_range(&self, datablock: &TagDataBlock) -> Range<usize> {
+        let start = u64::from(self.header.header_size) + datablock.get_offset(self);
+        let start = usize::try_from(start).unwrap_or(usize::MAX);
+        start..start.saturating_add(datablock.entry_size as usize)
+    }
+
+    /// Finds the row of the data reference table describing a [`FieldData`](`crate::tag::types::common_types::FieldData`) field.
+    ///
+    /// # Arguments
+    ///
+    /// * `field_block` - Index of the data block containing the data field.
+    /// * `field_offset` - Offset of the data field inside that data block.
+    #[must_use]
+    pub fn find_data_reference(
+        &self,
+        field_block: i32,
+        field_offset: u32,
+    ) -> Option<&TagDataReference> {
+        self.data_references
+            .iter()
+            .find(|r| r.field_block == field_block && r.field_offset == field_offset)
+    }
+
+    /// Resolves a [`FieldData`](`crate::tag::types::common_types::FieldData`) field to the data
+    /// block it points to and the range of bytes it covers in the entire tag (including header).
+    ///
+    /// Returns [`None`] if the field is not present in the data reference table or is a null reference.
+    ///
+    /// # Arguments
+    ///
+    /// * `field_block` - Index of the data block containing the data field.
+    /// * `field_offset` - Offset of the data field inside that data block.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use infinite_rs::TagFile;
+    /// use infinite_rs::module::header::ModuleVersion;
+    ///
+    /// let data = std::fs::read("cat.material").unwrap();
+    /// let tag = TagFile::from_bytes(&data, &ModuleVersion::Season3).unwrap();
+    /// if let Some((_, range)) = tag.resolve_data_reference(0, 0x10) {
+    ///     println!("{:?}", &data[range]);
+    /// }
+    /// ```
+    #[must_use]
+    pub fn resolve_data_reference(
+        &self,
+        field_block: i32,
+        field_offset: u32,
+    ) -> Option<(&TagDataBlock, Range<usize>)> {
+        let reference = self.find_data_reference(field_block, field_offset)?;
+        let datablock = self.get_datablock(reference.target_index)?;
+        Some((datablock, self.get_datablock_range(datablock)))
+    }
+
+    /// Checks whether a string id value is stored anywhere in the tag data.
+    ///
+    /// Every data block in the [`TagData`](`TagSectionType::TagData`) and
+    /// [`ResourceData`](`TagSectionType::ResourceData`) sections is scanned for the value, at
+    /// 4 byte aligned offsets. As no structure definition is used, unrelated fields that happen to
+    /// hold the same value are also matched.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Bytes of the entire tag file (including header).
+    /// * `string_id` - `MurmurHash3_x86_32` hash of the string, see [`FieldStringId`].
+    #[must_use]
+    pub fn contains_string_id(&self, data: &[u8], string_id: i32) -> bool {
+        let needle = string_id.to_le_bytes();
+        self.datablock_definitions
+            .iter()
+            .filter(|datablock| {
+                matches!(
+                    datablock.section_type,
+                    TagSectionType::TagData | TagSectionType::ResourceData
+                )
+            })
+            .filter_map(|datablock| data.get(self.get_datablock_range(datablock)))
+            .any(|block| block.chunks_exact(4).any(|value| value == needle))
+    }
+
+    /// Resolves a string id to its string using the tag string table.
+    ///
+    /// Each string in [`string_table`](`TagFile::string_table`) is hashed and compared against the
+    /// string id. Returns [`None`] if the tag has no string table (Season 3 and later), or if the
+    /// string is not present in it.
+    ///
+    /// # Arguments
+    ///
+    /// * `string_id` - String id field to resolve.
+    #[must_use]
+    pub fn resolve_string_id(&self, string_id: &FieldStringId) -> Option<&str> {
+        #[allow(clippy::cast_sign_loss)]
+        let hash = string_id.0 as u32;
+        self.string_table
+            .iter()
+            .find(|string| murmur3_x86_32(string.as_bytes(), 0) == hash)
+            .map(String::as_str)
+    }
+
+    /// Gets a printable name for a string id, falling back to the raw hash if it cannot be resolved.
+    ///
+    /// See [`resolve_string_id`](`TagFile::resolve_string_id`). If the tag has no string table,
+    /// the globally installed [`StringIdDictionary`](`crate::common::string_id::StringIdDictionary`)
+    /// is used instead.
+    #[must_use]
+    pub fn string_id_name(&self, string_id: &FieldStringId) -> String {
+        self.resolve_string_id(string_id)
+            .map(ToString::to_string)
+            .or_else(|| lookup_global(string_id.0))
+            .unwrap_or_else(|| format!("{:#010X}", string_id.0))
+    }
+
+    /// Reads a specified structure implementing [`TagStructure`] from the tag data.
+    ///
+    /// This looks for the main struct definition of the tag, then gets the referenced data block
+    /// and reads the initial contents of the struct from it. Field block definitions are then
+    /// loaded recursively.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Bytes of the entire tag file this [`TagFile`] was read from (including header).
+    ///
+    /// # Generic Arguments
+    ///
+    /// * `T` - The type of the struct implementing [`TagStructure`] to read the data into.
+    ///
+    /// # Errors
+    /// - If the main struct definition is not found [`TagError::MainStructNotFound`]
+    /// - If `T` declares a GUID that does not match the main struct [`TagError::GuidMismatch`]
+    /// - If a field lies past the end of its data block [`TagError::FieldOutOfBounds`]
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use infinite_rs::TagFile;
+    /// use infinite_rs::module::header::ModuleVersion;
+    /// use infinite_rs::tag::types::common_types::AnyTag;
+    /// use infinite_rs_derive::TagStructure;
+    ///
+    /// #[derive(Default, TagStructure)]
+    /// #[data(size(0x88))]
+    /// struct MaterialTag {
+    ///     #[data(offset(0x00))]
+    ///     any_tag: AnyTag,
+    /// }
+    ///
+    /// let data = std::fs::read("cat.material").unwrap();
+    /// let tag = TagFile::from_bytes(&data, &ModuleVersion::Season3).unwrap();
+    /// let material = tag.read_metadata::<MaterialTag>(&data).unwrap();
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(bytes = data.len()), err)
+    )]
+    pub fn read_metadata<T: Default + TagStructure>(&self, data: &[u8]) -> Result<T> {
+        let mut struct_type = T::default();
+        let main_struct = self.main_struct().ok_or(TagError::MainStructNotFound)?;
+        if let Some(expected) = struct_type.guid().filter(|&guid| guid != main_struct.guid) {
+            return Err(TagError::GuidMismatch {
+                expected,
+                found: main_struct.guid,
+            }
+            .into());
+        }
+
+        let main_block = self
+            .datablock_definitions
+            .get(usize::try_from(main_struct.target_index)?)
+            .ok_or(TagError::MainStructNotFound)?;
+        let tag_data = data
+            .get(self.header.header_size as usize..)
+            .unwrap_or_default();
+        let mut reader = BufReader::new(Cursor::new(tag_data));
+        // The main struct is read from its own data block, so that optional fields past the end
+        // of the block are left empty instead of being read from the next block.
+        struct_type.read(
+            &mut reader.sub_reader(main_block.offset, u64::from(main_block.entry_size))?,
+            &self.version,
+        )?;
+        struct_type.load_field_blocks(main_struct.target_index, 0, 0, &mut reader, self)?;
+        Ok(struct_type)
+    }
+}
+
+/// Gets the null-terminated string starting at `offset` in a string table.
+///
+/// Invalid UTF-8 sequences are replaced with `U+FFFD`, and a string without a null terminator
+/// ends at the end of the table. Offsets past the end of the table give an empty string.
+fn string_table_entry(string_table: &[u8], offset: u32) -> String {
+    let string = string_table.get(offset as usize..).unwrap_or_default();
+    let length = string
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(string.len());
+    String::from_utf8_lossy(&string[..length]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use crate::tag::types::common_types::{FieldBlock, FieldFixedArray, FieldReal};
+
+    #[test]
+    /// Verifies that string table entries with invalid UTF-8 are decoded lossily.
+    fn test_string_table_entry() {
+        let string_table = b"first\0bad\xFFname\0unterminated";
+        assert_eq!(string_table_entry(string_table, 0), "first");
+        assert_eq!(string_table_entry(string_table, 6), "bad\u{FFFD}name");
+        assert_eq!(string_table_entry(string_table, 15), "unterminated");
+        assert_eq!(string_table_entry(string_table, 100), "");
+    }
+
+    #[test]
+    /// Verifies that unrecognized tag versions are recorded as warnings when not strict.
+    fn test_lenient_tag_version() {
+        let mut data = vec![0; 0x100];
+        data[..4].copy_from_slice(&0x6873_6375_u32.to_le_bytes());
+        data[4..8].copy_from_slice(&26_i32.to_le_bytes());
+
+        let mut tag = TagFile::default();
+        let mut reader = BufReader::new(Cursor::new(&data));
+        let options = ParseOptions::default();
+        assert!(matches!(
+            tag.read_with_options(&mut reader, &ModuleVersion::Season3, &options),
+            Err(Error::TagError(TagError::IncorrectVersion(26)))
+        ));
+
+        let mut reader = BufReader::new(Cursor::new(&data));
+        let options = ParseOptions {
+            strict: false,
+            ..Default::default()
+        };
+        tag.read_with_options(&mut reader, &ModuleVersion::Season3, &options)
+            .unwrap();
+        assert_eq!(tag.warnings, [Warning::IncorrectTagVersion(26)]);
+    }
+
+    #[test]
+    /// Verifies that data fields resolve to the byte range of their target data block.
+    fn test_resolve_data_reference() {
+        let mut tag = TagFile::default();
+        tag.header.header_size = 0x10;
+        tag.header.data_size = 0x20;
+        let mut datablock = TagDataBlock::default();
+        datablock.entry_size = 0x8;
+        datablock.section_type = TagSectionType::ResourceData;
+        datablock.offset = 0x4;
+        tag.datablock_definitions.push(datablock);
+        let mut reference = TagDataReference::default();
+        reference.field_block = 1;
+        reference.field_offset = 0x18;
+        tag.data_references.push(reference);
+
+        let (_, range) = tag.resolve_data_reference(1, 0x18).unwrap();
+        assert_eq!(range, 0x34..0x3C);
+        assert!(tag.resolve_data_reference(1, 0x1C).is_none());
+
+        tag.data_references[0].target_index = -1;
+        assert!(tag.resolve_data_reference(1, 0x18).is_none());
+    }
+
+    #[test]
+    /// Verifies that string ids are only matched at aligned offsets inside tag data.
+    fn test_contains_string_id() {
+        let mut tag = TagFile::default();
+        tag.header.header_size = 0x10;
+        let mut datablock = TagDataBlock::default();
+        datablock.entry_size = 0x10;
+        datablock.section_type = TagSectionType::TagData;
+        tag.datablock_definitions.push(datablock);
+
+        let mut data = vec![0; 0x20];
+        data[0x18..0x1C].copy_from_slice(&0x1234_5678_i32.to_le_bytes());
+        data[0x13..0x17].copy_from_slice(&0x0BAD_F00D_i32.to_le_bytes());
+        assert!(tag.contains_string_id(&data, 0x1234_5678));
+        assert!(!tag.contains_string_id(&data, 0x0BAD_F00D));
+    }
+
+    #[derive(Default, Debug, infinite_rs_derive::TagStructure)]
+    #[data(size(0x8))]
+    struct Wind {
+        #[data(offset(0x00))]
+        speed: FieldReal,
+        #[data(offset(0x04))]
+        gust_speed: Option<FieldReal>,
+    }
+
+    #[derive(Default, Debug, infinite_rs_derive::TagStructure)]
+    #[data(size(0x14))]
+    struct Weather {
+        #[data(offset(0x00))]
+        winds: FieldBlock<Wind>,
+    }
+
+    #[derive(Default, Debug, infinite_rs_derive::TagStructure)]
+    #[data(size(0x4))]
+    struct Key {
+        #[data(offset(0x00))]
+        value: FieldReal,
+    }
+
+    #[derive(Default, Debug, infinite_rs_derive::TagStructure)]
+    #[data(size(0x8))]
+    struct Curve {
+        #[data(offset(0x00))]
+        keys: FieldFixedArray<Key, 2>,
+    }
+
+    #[derive(Default, Debug, infinite_rs_derive::TagStructure)]
+    #[data(size(0x14))]
+    struct Curves {
+        #[data(offset(0x00))]
+        curves: FieldBlock<Curve>,
+    }
+
+    /// Builds a tag whose main struct has a block field at 0x0, pointing to a single element in a
+    /// data block of 4 bytes holding `1.0`, followed by a data block holding `2.0`.
+    fn create_short_block_tag() -> (TagFile, Vec<u8>) {
+        let mut tag = TagFile::default();
+        for (offset, entry_size) in [(0x0, 0x14), (0x14, 0x4), (0x18, 0x4)] {
+            let mut datablock = TagDataBlock::default();
+            datablock.section_type = TagSectionType::TagData;
+            datablock.offset = offset;
+            datablock.entry_size = entry_size;
+            tag.datablock_definitions.push(datablock);
+        }
+        tag.struct_definitions.push(TagStruct {
+            struct_type: TagStructType::MainStruct,
+            target_index: 0,
+            field_block: -1,
+            ..Default::default()
+        });
+        tag.struct_definitions.push(TagStruct {
+            struct_type: TagStructType::TagBlock,
+            target_index: 1,
+            field_block: 0,
+            field_offset: 0,
+            ..Default::default()
+        });
+
+        let mut data = vec![0; 0x1C];
+        data[0x10..0x14].copy_from_slice(&1_u32.to_le_bytes());
+        data[0x14..0x18].copy_from_slice(&1.0_f32.to_le_bytes());
+        data[0x18..0x1C].copy_from_slice(&2.0_f32.to_le_bytes());
+        (tag, data)
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    /// Verifies that block elements are read from their own data block, so that optional fields
+    /// past its end are left empty instead of being read from the next data block.
+    fn test_read_short_block_element() {
+        let (tag, data) = create_short_block_tag();
+        let weather = tag.read_metadata::<Weather>(&data).unwrap();
+        assert_eq!(weather.winds.len(), 1);
+        assert_eq!(weather.winds[0].speed.0, 1.0);
+        assert!(weather.winds[0].gust_speed.is_none());
+    }
+
+    #[test]
+    /// Verifies that arrays nested in block elements are checked against the data block of the
+    /// elements, instead of the entire tag.
+    fn test_read_short_nested_array() {
+        let (tag, data) = create_short_block_tag();
+        assert!(matches!(
+            tag.read_metadata::<Curves>(&data),
+            Err(Error::TagError(TagError::FieldOutOfBounds {
+                field: "value",
+                offset: 0x4,
+                limit: 0x4
+            }))
+        ));
+    }
 }
@@ -1,10 +1,24 @@
 //! Main Interface for reading tag files.
 
+#[cfg(feature = "codegen")]
+pub mod codegen;
 pub mod data_reference;
 pub mod datablock;
 pub mod dependency;
+pub mod diff;
+pub mod dynamic;
+pub mod editor;
+#[cfg(feature = "gltf")]
+pub mod gltf;
+pub mod group;
 pub mod header;
+#[cfg(feature = "json")]
+pub mod json;
 pub mod loader;
+pub mod provenance;
 pub mod reference;
+pub mod registry;
 pub mod structure;
 pub mod types;
+pub mod validation;
+pub mod xml;
@@ -0,0 +1,86 @@
+//! Reports of where each field of a [`TagStructure`] was read from.
+//!
+//! When a structure definition is written for another build of the game, fields end up being read
+//! from the wrong offsets. The provenance report lists the absolute offset of every field in the
+//! tag buffer, so values can be compared against a hex dump of the tag.
+
+use super::loader::TagFile;
+use crate::Result;
+use crate::common::errors::TagError;
+use crate::module::file::{FieldDescriptor, TagStructure};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// Location a single field of the main structure was read from.
+pub struct FieldProvenance {
+    /// Name of the field in the structure.
+    pub name: &'static str,
+    /// Offset of the field inside the structure, as declared by `#[data(offset())]`.
+    pub offset: u64,
+    /// Index of the data block the field was read from.
+    pub datablock_index: usize,
+    /// Offset of the field from the start of the entire tag buffer (including header).
+    pub absolute_offset: u64,
+    /// Index of the data block the field points to, if it is a tag block, resource or data field.
+    pub target_datablock: Option<usize>,
+}
+
+impl TagFile {
+    /// Reads a structure implementing [`TagStructure`] from the tag data, along with the location
+    /// each of its fields was read from.
+    ///
+    /// See [`read_metadata`](`TagFile::read_metadata`). Fields are sorted by offset.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Bytes of the entire tag file (including header).
+    ///
+    /// # Errors
+    /// - If the main struct definition is not found [`TagError::MainStructNotFound`]
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    pub fn read_metadata_with_provenance<T: Default + TagStructure>(
+        &self,
+        data: &[u8],
+    ) -> Result<(T, Vec<FieldProvenance>)> {
+        let structure = self.read_metadata::<T>(data)?;
+
+        let main_struct = self.main_struct().ok_or(TagError::MainStructNotFound)?;
+        let main_index = main_struct.target_index;
+        let datablock_index = usize::try_from(main_index)?;
+        let main_block = self
+            .datablock_definitions
+            .get(datablock_index)
+            .ok_or(TagError::MainStructNotFound)?;
+        let base = u64::from(self.header.header_size) + main_block.offset;
+
+        let mut provenance = structure
+            .fields()
+            .iter()
+            .map(|&FieldDescriptor { name, offset, .. }| {
+                let is_field = |field_block: i32, field_offset: u32| {
+                    field_block == main_index && u64::from(field_offset) == offset
+                };
+                let target = self
+                    .struct_definitions
+                    .iter()
+                    .find(|s| is_field(s.field_block, s.field_offset))
+                    .map(|s| s.target_index)
+                    .or_else(|| {
+                        self.data_references
+                            .iter()
+                            .find(|r| is_field(r.field_block, r.field_offset))
+                            .map(|r| r.target_index)
+                    });
+                FieldProvenance {
+                    name,
+                    offset,
+                    datablock_index,
+                    absolute_offset: base + offset,
+                    target_datablock: target.and_then(|index| usize::try_from(index).ok()),
+                }
+            })
+            .collect::<Vec<_>>();
+        provenance.sort_by_key(|field| (field.offset, field.name));
+        Ok((structure, provenance))
+    }
+}
@@ -6,13 +6,13 @@ use std::io::BufRead;
 use crate::Result;
 use crate::common::extensions::Enumerable;
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// Structure that defines a reference to a tag.
 pub struct TagReference {
     /// The index of the data block containing the tag field.
     pub field_block: i32,
-    /// The offset of the tag data block containing the referenced data.
-    /// Can be -1 for null references.
+    /// The offset of the tag field inside the data block.
     pub field_offset: u32,
     /// The offset of the tag file name inside the tag string table.
     pub(crate) name_offset: u32,
@@ -0,0 +1,205 @@
+//! Registry associating [`TagStructure`] types with the GUIDs of the tag structs they describe,
+//! or with the tag groups they are read from.
+
+use std::{
+    any::{Any, TypeId, type_name},
+    collections::HashMap,
+};
+
+use super::loader::TagFile;
+use crate::Result;
+use crate::common::errors::TagError;
+use crate::module::file::TagStructure;
+
+/// Function reading a registered type from a tag and boxing it.
+type ReadFn = fn(&TagFile, &[u8]) -> Result<Box<dyn Any>>;
+
+#[derive(Debug)]
+/// Type registered for a struct GUID.
+struct RegisteredStruct {
+    /// [`TypeId`] of the registered type.
+    type_id: TypeId,
+    /// Name of the registered type, used for debugging.
+    type_name: &'static str,
+    /// Reads the registered type from a tag.
+    read: ReadFn,
+}
+
+#[derive(Default, Debug)]
+/// Registry of [`TagStructure`] types identified by the GUID of the struct they describe.
+///
+/// Every tag struct definition has a [`guid`](`crate::tag::structure::TagStruct::guid`) that
+/// uniquely identifies its layout. Registering types by GUID allows reads to be verified against the
+/// actual layout of the tag, or dispatched to the correct type automatically.
+///
+/// # Examples
+///
+/// ```no_run
+/// use infinite_rs::tag::registry::StructRegistry;
+/// use infinite_rs::tag::types::common_types::AnyTag;
+/// use infinite_rs::{ModuleFile, TagStructure};
+///
+/// #[derive(Default, Debug, TagStructure)]
+/// #[data(size(0x88))]
+/// struct MaterialTag {
+///     #[data(offset(0x00))]
+///     any_tag: AnyTag,
+/// }
+///
+/// let mut registry = StructRegistry::new();
+/// registry.register::<MaterialTag>(0x1234_5678_9ABC_DEF0_1234_5678_9ABC_DEF0);
+///
+/// let mut module = ModuleFile::from_path("C:/XboxGames/Halo Infinite/Content/deploy/any/globals-rtx-new.module").unwrap();
+/// if let Some(tag) = module.read_tag(0).unwrap() {
+///     // Fails with `TagError::GuidMismatch` if the tag is not a material.
+///     let material = tag.read_metadata_verified::<MaterialTag>(&registry).unwrap();
+/// }
+/// ```
+pub struct StructRegistry {
+    /// Registered types, keyed by struct GUID.
+    structs: HashMap<u128, RegisteredStruct>,
+    /// Registered types, keyed by tag group.
+    groups: HashMap<String, RegisteredStruct>,
+}
+
+impl StructRegistry {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a [`TagStructure`] type for the given struct GUID.
+    ///
+    /// Registering another type with the same GUID replaces the previous one.
+    pub fn register<T: Default + TagStructure + 'static>(&mut self, guid: u128) {
+        self.structs.insert(
+            guid,
+            RegisteredStruct {
+                type_id: TypeId::of::<T>(),
+                type_name: type_name::<T>(),
+                read: |tag_file, data| Ok(Box::new(tag_file.read_metadata::<T>(data)?)),
+            },
+        );
+    }
+
+    /// Registers a [`TagStructure`] type for the given tag group (for example `"mat "`).
+    ///
+    /// Unlike [`register`](`StructRegistry::register`), no GUID is needed, but reads are not
+    /// verified against the layout of the tag. Registering another type with the same group
+    /// replaces the previous one.
+    pub fn register_group<T: Default + TagStructure + 'static>(&mut self, tag_group: &str) {
+        self.groups.insert(
+            tag_group.to_string(),
+            RegisteredStruct {
+                type_id: TypeId::of::<T>(),
+                type_name: type_name::<T>(),
+                read: |tag_file, data| Ok(Box::new(tag_file.read_metadata::<T>(data)?)),
+            },
+        );
+    }
+
+    /// Gets the name of the type registered for a tag group, if any.
+    #[must_use]
+    pub fn type_name_of_group(&self, tag_group: &str) -> Option<&'static str> {
+        self.groups
+            .get(tag_group)
+            .map(|registered| registered.type_name)
+    }
+
+    /// Gets the GUID a type has been registered with, if any.
+    #[must_use]
+    pub fn guid_of<T: 'static>(&self) -> Option<u128> {
+        self.structs
+            .iter()
+            .find(|(_, registered)| registered.type_id == TypeId::of::<T>())
+            .map(|(guid, _)| *guid)
+    }
+
+    /// Gets the name of the type registered for a GUID, if any.
+    #[must_use]
+    pub fn type_name_of(&self, guid: u128) -> Option<&'static str> {
+        self.structs
+            .get(&guid)
+            .map(|registered| registered.type_name)
+    }
+
+    /// Reads a type after verifying that it was registered for the main struct GUID of the tag.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag_file` - Tag structure read from the tag.
+    /// * `data` - Bytes of the entire tag file (including header).
+    ///
+    /// # Errors
+    /// - If `T` has not been registered [`TagError::UnregisteredStruct`]
+    /// - If the GUID of `T` does not match the main struct of the tag [`TagError::GuidMismatch`]
+    /// - If reading the structure fails, see [`TagFile::read_metadata`]
+    pub fn read_verified<T: Default + TagStructure + 'static>(
+        &self,
+        tag_file: &TagFile,
+        data: &[u8],
+    ) -> Result<T> {
+        let found = tag_file
+            .main_struct()
+            .ok_or(TagError::MainStructNotFound)?
+            .guid;
+        let expected = self
+            .guid_of::<T>()
+            .ok_or(TagError::UnregisteredStruct(found))?;
+        if expected != found {
+            return Err(TagError::GuidMismatch { expected, found }.into());
+        }
+        tag_file.read_metadata::<T>(data)
+    }
+
+    /// Reads the type registered for the main struct GUID of the tag.
+    ///
+    /// The result can be downcast to the concrete type using [`Box::downcast`].
+    ///
+    /// # Arguments
+    ///
+    /// * `tag_file` - Tag structure read from the tag.
+    /// * `data` - Bytes of the entire tag file (including header).
+    ///
+    /// # Errors
+    /// - If no type has been registered for the GUID [`TagError::UnregisteredStruct`]
+    /// - If reading the structure fails, see [`TagFile::read_metadata`]
+    pub fn read(&self, tag_file: &TagFile, data: &[u8]) -> Result<Box<dyn Any>> {
+        let guid = tag_file
+            .main_struct()
+            .ok_or(TagError::MainStructNotFound)?
+            .guid;
+        let registered = self
+            .structs
+            .get(&guid)
+            .ok_or(TagError::UnregisteredStruct(guid))?;
+        (registered.read)(tag_file, data)
+    }
+
+    /// Reads the type registered for a tag group.
+    ///
+    /// The result can be downcast to the concrete type using [`Box::downcast`].
+    ///
+    /// # Arguments
+    ///
+    /// * `tag_group` - Tag group of the tag, such as [`ModuleFileEntry::tag_group`](`crate::module::file::ModuleFileEntry::tag_group`).
+    /// * `tag_file` - Tag structure read from the tag.
+    /// * `data` - Bytes of the entire tag file (including header).
+    ///
+    /// # Errors
+    /// - If no type has been registered for the group [`TagError::UnregisteredGroup`]
+    /// - If reading the structure fails, see [`TagFile::read_metadata`]
+    pub fn read_group(
+        &self,
+        tag_group: &str,
+        tag_file: &TagFile,
+        data: &[u8],
+    ) -> Result<Box<dyn Any>> {
+        let registered = self
+            .groups
+            .get(tag_group)
+            .ok_or_else(|| TagError::UnregisteredGroup(tag_group.to_string()))?;
+        (registered.read)(tag_file, data)
+    }
+}
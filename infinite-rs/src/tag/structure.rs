@@ -8,7 +8,8 @@ use crate::Result;
 use crate::common::errors::TagError;
 use crate::common::extensions::Enumerable;
 
-#[derive(Default, Debug, TryFromPrimitive, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, Copy, TryFromPrimitive, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[repr(u16)]
 /// Enum defining what the tag struct is pointing to.
 pub enum TagStructType {
@@ -21,11 +22,14 @@ pub enum TagStructType {
     Resource,
     /// Reference to "external" resource.
     Custom,
-    /// Unknown
+    /// Inline literal data, stored in its own data block.
+    /// Unlike tag blocks, the field referring to it does not store an element count, so the
+    /// number of elements is derived from the size of the data block.
     Literal,
 }
 
-#[derive(Default, Debug, TryFromPrimitive, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, Copy, TryFromPrimitive, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[repr(u16)]
 /// Enum defining where teh data in the tag struct is pointing towards in a "Custom" tag structure.
 pub enum TagStructLocation {
@@ -35,7 +39,8 @@ pub enum TagStructLocation {
     Debug,
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// Structure defining the hierarchical order of info in tags.
 pub struct TagStruct {
     /// GUID of the structure referenced.
@@ -0,0 +1,636 @@
+//! Bitmap (`bitm`) tags, describing textures and the location of their pixel data.
+//!
+//! Each bitmap of a tag stores its smallest mipmaps inline in the
+//! [`pixels`](`BitmapDataResource::pixels`) of its resource, while larger mipmaps are streamed
+//! from the resource files of the tag, which can be stored in the HD1 module. The
+//! [`streaming_data`](`BitmapDataResource::streaming_data`) of the resource lists where each
+//! mipmap is stored, from the largest to the smallest.
+//!
+//! Only the fields required to locate and decode the pixel data are exposed. The pixel data of a
+//! bitmap is assembled with [`BitmapData::read_texels`], and can be exported as a DDS file with
+//! [`Surface::write_dds`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::fs::File;
+//! use infinite_rs::ModuleFile;
+//! use infinite_rs::tag::types::bitmap::BitmapTag;
+//!
+//! let mut module = ModuleFile::from_path("deploy/any/globals/forge/forge_objects-rtx-new.module").unwrap();
+//! let index = module.files.iter().position(|file| file.tag_group == "bitm").unwrap() as u32;
+//! let tag = module.read_tag(index).unwrap().unwrap();
+//! let bitmap_tag = tag.read_metadata::<BitmapTag>().unwrap();
+//! for (i, bitmap) in bitmap_tag.bitmaps.iter().enumerate() {
+//!     let texels = bitmap.read_texels(&mut module, index).unwrap();
+//!     let surface = bitmap.surface().without_mips(texels.first_mip);
+//!     surface.write_dds(&mut File::create(format!("{i}.dds")).unwrap(), &texels.data).unwrap();
+//! }
+//! ```
+
+use byteorder::{LE, WriteBytesExt};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use std::io::Write;
+
+use super::common_types::{
+    AnyTag, FieldBlock, FieldCharEnum, FieldCharInteger, FieldData, FieldLongInteger,
+    FieldShortEnum, FieldShortInteger, FieldTagResource,
+};
+use super::pixel_format::DxgiFormat;
+use crate::ModuleFile;
+use crate::Result;
+use crate::common::errors::TagError;
+use infinite_rs_derive::TagStructure;
+
+/// Magic at the start of a DDS file ("DDS ").
+const DDS_MAGIC: u32 = 0x2053_4444;
+/// "DX10" four character code, indicating that the extended header follows.
+const DDS_FOURCC_DX10: u32 = 0x3031_5844;
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive, IntoPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[repr(u8)]
+/// Shape of a bitmap.
+pub enum BitmapType {
+    #[default]
+    Texture2D,
+    Texture3D,
+    CubeMap,
+    Array,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive, IntoPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[repr(u16)]
+/// Format of the pixels of a bitmap, as stored in the tag.
+///
+/// Names follow the engine, so channel orders are written from the most significant bits.
+pub enum BitmapFormat {
+    #[default]
+    A8,
+    Y8,
+    AY8,
+    A8Y8,
+    Unused4,
+    Unused5,
+    R5G6B5,
+    Unused7,
+    A1R5G5B5,
+    A4R4G4B4,
+    X8R8G8B8,
+    A8R8G8B8,
+    Unused12,
+    Unused13,
+    Dxt1,
+    Dxt3,
+    Dxt5,
+    A4R4G4B4Font,
+    Unused18,
+    Unused19,
+    SoftwareRgbFp32,
+    Unused21,
+    V8U8,
+    G8B8,
+    AbgrFp32,
+    AbgrFp16,
+    Mono16F,
+    Red16F,
+    Q8W8V8U8,
+    A2R10G10B10,
+    A16B16G16R16,
+    V16U16,
+    L16,
+    R16G16,
+    SignedR16G16B16A16,
+    Dxt3A,
+    Dxt5A,
+    Dxt3A1111,
+    Dxn,
+    Ctx1,
+    Dxt3AAlpha,
+    Dxt3AMono,
+    Dxt5AAlpha,
+    Dxt5AMono,
+    DxnMonoAlpha,
+    Bc6hUf16,
+    Bc6hSf16,
+    Bc7Unorm,
+}
+
+impl BitmapFormat {
+    /// Whether the format is block compressed, storing blocks of 4x4 pixels.
+    #[must_use]
+    pub fn is_block_compressed(self) -> bool {
+        matches!(
+            self,
+            Self::Dxt1
+                | Self::Dxt3
+                | Self::Dxt5
+                | Self::Dxt3A
+                | Self::Dxt5A
+                | Self::Dxt3A1111
+                | Self::Dxn
+                | Self::Ctx1
+                | Self::Dxt3AAlpha
+                | Self::Dxt3AMono
+                | Self::Dxt5AAlpha
+                | Self::Dxt5AMono
+                | Self::DxnMonoAlpha
+                | Self::Bc6hUf16
+                | Self::Bc6hSf16
+                | Self::Bc7Unorm
+        )
+    }
+
+    /// Size in bytes of a single pixel, or of a block of 4x4 pixels for
+    /// [block compressed](`BitmapFormat::is_block_compressed`) formats.
+    ///
+    /// Returns [`None`] for unused formats.
+    #[must_use]
+    pub fn element_size(self) -> Option<u32> {
+        Some(match self {
+            Self::A8 | Self::Y8 | Self::AY8 => 1,
+            Self::A8Y8
+            | Self::R5G6B5
+            | Self::A1R5G5B5
+            | Self::A4R4G4B4
+            | Self::A4R4G4B4Font
+            | Self::V8U8
+            | Self::G8B8
+            | Self::Mono16F
+            | Self::Red16F
+            | Self::L16 => 2,
+            Self::X8R8G8B8
+            | Self::A8R8G8B8
+            | Self::Q8W8V8U8
+            | Self::A2R10G10B10
+            | Self::V16U16
+            | Self::R16G16 => 4,
+            Self::AbgrFp16
+            | Self::A16B16G16R16
+            | Self::SignedR16G16B16A16
+            | Self::Dxt1
+            | Self::Dxt3A
+            | Self::Dxt5A
+            | Self::Dxt3A1111
+            | Self::Ctx1
+            | Self::Dxt3AAlpha
+            | Self::Dxt3AMono
+            | Self::Dxt5AAlpha
+            | Self::Dxt5AMono => 8,
+            Self::SoftwareRgbFp32 => 12,
+            Self::AbgrFp32
+            | Self::Dxt3
+            | Self::Dxt5
+            | Self::Dxn
+            | Self::DxnMonoAlpha
+            | Self::Bc6hUf16
+            | Self::Bc6hSf16
+            | Self::Bc7Unorm => 16,
+            Self::Unused4
+            | Self::Unused5
+            | Self::Unused7
+            | Self::Unused12
+            | Self::Unused13
+            | Self::Unused18
+            | Self::Unused19
+            | Self::Unused21 => return None,
+        })
+    }
+
+    /// DXGI format the bitmap is converted to when uploaded, also used to store it in a DDS file.
+    ///
+    /// Returns [`None`] for formats that have no DXGI equivalent, which were only used by
+    /// previous console generations.
+    #[must_use]
+    pub fn dxgi_format(self) -> Option<DxgiFormat> {
+        Some(match self {
+            Self::AbgrFp32 => DxgiFormat::R32G32B32A32Float,
+            Self::SoftwareRgbFp32 => DxgiFormat::R32G32B32Float,
+            Self::AbgrFp16 => DxgiFormat::R16G16B16A16Float,
+            Self::A16B16G16R16 => DxgiFormat::R16G16B16A16Unorm,
+            Self::SignedR16G16B16A16 => DxgiFormat::R16G16B16A16Snorm,
+            Self::A2R10G10B10 => DxgiFormat::R10G10B10A2Unorm,
+            Self::Q8W8V8U8 => DxgiFormat::R8G8B8A8Snorm,
+            Self::R16G16 => DxgiFormat::R16G16Unorm,
+            Self::V16U16 => DxgiFormat::R16G16Snorm,
+            Self::A8Y8 | Self::G8B8 => DxgiFormat::R8G8Unorm,
+            Self::V8U8 => DxgiFormat::R8G8Snorm,
+            Self::Mono16F | Self::Red16F => DxgiFormat::R16Float,
+            Self::L16 => DxgiFormat::R16Unorm,
+            Self::Y8 | Self::AY8 => DxgiFormat::R8Unorm,
+            Self::A8 => DxgiFormat::A8Unorm,
+            Self::Dxt1 => DxgiFormat::Bc1Unorm,
+            Self::Dxt3 => DxgiFormat::Bc2Unorm,
+            Self::Dxt5 => DxgiFormat::Bc3Unorm,
+            Self::Dxt5A | Self::Dxt5AAlpha | Self::Dxt5AMono => DxgiFormat::Bc4Unorm,
+            Self::Dxn => DxgiFormat::Bc5Unorm,
+            Self::R5G6B5 => DxgiFormat::B5G6R5Unorm,
+            Self::A1R5G5B5 => DxgiFormat::B5G5R5A1Unorm,
+            Self::A8R8G8B8 => DxgiFormat::B8G8R8A8Unorm,
+            Self::X8R8G8B8 => DxgiFormat::B8G8R8X8Unorm,
+            Self::Bc6hUf16 => DxgiFormat::Bc6hUf16,
+            Self::Bc6hSf16 => DxgiFormat::Bc6hSf16,
+            Self::Bc7Unorm => DxgiFormat::Bc7Unorm,
+            Self::A4R4G4B4 => DxgiFormat::B4G4R4A4Unorm,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x0C), write)]
+/// Location of a single mipmap of a bitmap (`bitmap_texture_interop_resource_streaming_data`).
+pub struct BitmapStreamingData {
+    /// Offset in bytes of the mipmap inside its chunk.
+    #[data(offset(0x00))]
+    pub offset: FieldLongInteger,
+    /// Size in bytes of the mipmap.
+    #[data(offset(0x04))]
+    pub size: FieldLongInteger,
+    /// Index of the resource file containing the mipmap, among the resource files of the tag.
+    /// A negative index refers to the inline [`pixels`](`BitmapDataResource::pixels`).
+    #[data(offset(0x08))]
+    pub chunk: FieldShortInteger,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x34), write)]
+/// Resource containing the pixel data of a bitmap (`bitmap_texture_interop_resource`).
+///
+/// Meant to be used as the type parameter of a
+/// [`FieldTagResource`](`super::common_types::FieldTagResource`).
+pub struct BitmapDataResource {
+    /// Inline pixel data, containing the mipmaps that are not streamed.
+    #[data(offset(0x00))]
+    pub pixels: FieldData,
+    /// Format used by the graphics API.
+    #[data(offset(0x18))]
+    pub hardware_format: FieldLongInteger,
+    /// Layout of the pixels in memory.
+    #[data(offset(0x1C))]
+    pub tiling_mode: FieldCharInteger,
+    /// Location of each mipmap, from the largest to the smallest.
+    /// Empty if the whole mipmap chain is stored in [`pixels`](`BitmapDataResource::pixels`).
+    #[data(offset(0x20))]
+    pub streaming_data: FieldBlock<BitmapStreamingData>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x30), write)]
+/// Description of a single bitmap of a tag (`bitmap_data`).
+pub struct BitmapData {
+    /// Width in pixels of the largest mipmap.
+    #[data(offset(0x00))]
+    pub width: FieldShortInteger,
+    /// Height in pixels of the largest mipmap.
+    #[data(offset(0x02))]
+    pub height: FieldShortInteger,
+    /// Depth in pixels of 3D textures, or number of layers of texture arrays.
+    #[data(offset(0x04))]
+    pub depth: FieldCharInteger,
+    /// Shape of the bitmap.
+    #[data(offset(0x06))]
+    pub bitmap_type: FieldCharEnum<BitmapType>,
+    /// Format of the pixels.
+    #[data(offset(0x08))]
+    pub format: FieldShortEnum<BitmapFormat>,
+    /// Number of mipmaps below the largest one.
+    #[data(offset(0x10))]
+    pub mipmap_count: FieldCharInteger,
+    /// Resource containing the pixel data.
+    #[data(offset(0x20))]
+    pub bitmap_resource_handle: FieldTagResource<BitmapDataResource>,
+}
+
+/// Pixel data of a bitmap, read by [`BitmapData::read_texels`].
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Texels {
+    /// Pixel data of every available mipmap, from the largest to the smallest.
+    pub data: Vec<u8>,
+    /// Number of the largest mipmaps that are not available, because their resource files are
+    /// stored in an HD1 module that is not present.
+    pub first_mip: u32,
+}
+
+impl BitmapData {
+    /// Describes the layout of the pixel data of the bitmap.
+    #[must_use]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn surface(&self) -> Surface {
+        let layers = u32::from(self.depth.0 as u8).max(1);
+        let (depth, layers) = match self.bitmap_type.0 {
+            BitmapType::Texture2D => (1, 1),
+            BitmapType::Texture3D => (layers, 1),
+            BitmapType::CubeMap => (1, 6),
+            BitmapType::Array => (1, layers),
+        };
+        Surface {
+            width: u32::from(self.width.0 as u16),
+            height: u32::from(self.height.0 as u16),
+            depth,
+            layers,
+            mip_levels: u32::from(self.mipmap_count.0 as u8) + 1,
+            bitmap_type: self.bitmap_type.0,
+            format: self.format.0,
+        }
+    }
+
+    /// Reads the pixel data of the bitmap, from its inline pixels and the resource files of the
+    /// tag it was read from.
+    ///
+    /// Resource files stored in a missing HD1 module are skipped, which is only possible for the
+    /// largest mipmaps. The number of skipped mipmaps is returned in
+    /// [`first_mip`](`Texels::first_mip`).
+    ///
+    /// # Arguments
+    ///
+    /// * `module` - Module containing the tag.
+    /// * `index` - Index of the tag in the module.
+    ///
+    /// # Errors
+    /// - If a resource file that is not one of the largest mipmaps is missing [`TagError::NotLoaded`]
+    /// - If a mipmap lies outside of its chunk [`TagError::BitmapDataTooSmall`]
+    /// - If a resource file cannot be read [`ReadError`](`crate::Error::ReadError`)
+    pub fn read_texels(&self, module: &mut ModuleFile, index: u32) -> Result<Texels> {
+        let resource = &self.bitmap_resource_handle.data;
+        let pixels = &resource.pixels.data;
+        if resource.streaming_data.is_empty() {
+            return Ok(Texels {
+                data: pixels.clone(),
+                first_mip: 0,
+            });
+        }
+
+        let resources = module.resources_of(index).to_vec();
+        let mut texels = Texels::default();
+        for streaming_data in &resource.streaming_data {
+            let chunk = match usize::try_from(streaming_data.chunk.0) {
+                Ok(chunk) => {
+                    let file_index = *resources.get(chunk).ok_or(TagError::NotLoaded)?;
+                    match module.read_tag(file_index)? {
+                        Some(file) => file.get_raw_data(false)?,
+                        None if texels.data.is_empty() => {
+                            texels.first_mip += 1;
+                            continue;
+                        }
+                        None => return Err(TagError::NotLoaded.into()),
+                    }
+                }
+                Err(_) => pixels.clone(),
+            };
+            texels.data.extend_from_slice(streaming_data.slice(&chunk)?);
+        }
+        Ok(texels)
+    }
+}
+
+impl BitmapStreamingData {
+    /// Gets the mipmap from the data of its chunk.
+    #[allow(clippy::cast_sign_loss)]
+    fn slice<'a>(&self, chunk: &'a [u8]) -> Result<&'a [u8]> {
+        let start = self.offset.0 as u32 as usize;
+        let end = start + self.size.0 as u32 as usize;
+        chunk.get(start..end).ok_or_else(|| {
+            TagError::BitmapDataTooSmall {
+                expected: end as u64,
+                found: chunk.len() as u64,
+            }
+            .into()
+        })
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0xD4), write)]
+/// Root structure of bitmap tags (`bitmap`).
+pub struct BitmapTag {
+    /// Runtime information of the tag.
+    #[data(offset(0x00))]
+    pub any_tag: AnyTag,
+    /// Bitmaps of the tag, such as the frames of a sprite sheet.
+    #[data(offset(0xC0))]
+    pub bitmaps: FieldBlock<BitmapData>,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// Layout of the pixel data of a bitmap, see [`BitmapData::surface`].
+///
+/// Pixel data is laid out like in DDS files: each layer (cube face or array slice) is stored in
+/// turn, with its mipmaps from the largest to the smallest. 3D textures store every depth slice
+/// of a mipmap before the next mipmap.
+pub struct Surface {
+    /// Width in pixels of the largest mipmap.
+    pub width: u32,
+    /// Height in pixels of the largest mipmap.
+    pub height: u32,
+    /// Depth in pixels of the largest mipmap, 1 for anything but 3D textures.
+    pub depth: u32,
+    /// Number of cube faces or array slices, 1 for 2D and 3D textures.
+    pub layers: u32,
+    /// Number of mipmaps, including the largest one.
+    pub mip_levels: u32,
+    /// Shape of the bitmap.
+    pub bitmap_type: BitmapType,
+    /// Format of the pixels.
+    pub format: BitmapFormat,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+/// A single mipmap of a [`Surface`].
+pub struct MipLevel {
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+    /// Depth in pixels.
+    pub depth: u32,
+    /// Offset in bytes of the mipmap from the start of its layer.
+    pub offset: u64,
+    /// Size in bytes of the mipmap.
+    pub size: u64,
+}
+
+impl Surface {
+    /// Removes the `count` largest mipmaps, such as the ones missing from
+    /// [`Texels::first_mip`]. At least one mipmap is kept.
+    #[must_use]
+    pub fn without_mips(self, count: u32) -> Self {
+        let count = count.min(self.mip_levels.saturating_sub(1));
+        Self {
+            width: (self.width >> count).max(1),
+            height: (self.height >> count).max(1),
+            depth: (self.depth >> count).max(1),
+            mip_levels: self.mip_levels - count,
+            ..self
+        }
+    }
+
+    /// Gets the size and location of every mipmap of a layer, from the largest to the smallest.
+    #[must_use]
+    pub fn mip_chain(&self) -> Vec<MipLevel> {
+        let element_size = u64::from(self.format.element_size().unwrap_or_default());
+        let block_dimension = if self.format.is_block_compressed() {
+            4
+        } else {
+            1
+        };
+        let mut offset = 0;
+        (0..self.mip_levels.max(1))
+            .map(|level| {
+                let width = (self.width >> level).max(1);
+                let height = (self.height >> level).max(1);
+                let depth = (self.depth >> level).max(1);
+                let size = u64::from(width.div_ceil(block_dimension))
+                    * u64::from(height.div_ceil(block_dimension))
+                    * u64::from(depth)
+                    * element_size;
+                let mip = MipLevel {
+                    width,
+                    height,
+                    depth,
+                    offset,
+                    size,
+                };
+                offset += size;
+                mip
+            })
+            .collect()
+    }
+
+    /// Size in bytes of the pixel data of every layer and mipmap.
+    #[must_use]
+    pub fn data_size(&self) -> u64 {
+        let layer_size: u64 = self.mip_chain().iter().map(|mip| mip.size).sum();
+        layer_size * u64::from(self.layers.max(1))
+    }
+
+    /// Converts the largest mipmap of the first layer to RGBA8, see
+    /// [`DxgiFormat::decode_rgba8`]. For 3D textures, only the first depth slice is converted.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Pixel data laid out as described by the surface, such as [`Texels::data`].
+    ///
+    /// # Errors
+    /// - If the format cannot be converted [`TagError::UnsupportedBitmapFormat`] or [`TagError::UnsupportedPixelFormat`]
+    /// - If `data` is smaller than the largest mipmap [`TagError::BitmapDataTooSmall`]
+    #[cfg(feature = "bcn")]
+    pub fn decode_rgba8(&self, data: &[u8]) -> Result<Vec<u8>> {
+        self.format
+            .dxgi_format()
+            .ok_or(TagError::UnsupportedBitmapFormat(self.format))?
+            .decode_rgba8(self.width, self.height, data)
+    }
+
+    /// Writes the pixel data as a DDS file, using the DX10 extended header.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Writer receiving the DDS file.
+    /// * `data` - Pixel data laid out as described by the surface, such as [`Texels::data`].
+    ///
+    /// # Errors
+    /// - If the format cannot be stored in a DDS file [`TagError::UnsupportedBitmapFormat`]
+    /// - If `data` is smaller than [`data_size`](`Surface::data_size`) [`TagError::BitmapDataTooSmall`]
+    /// - If the writer fails to write the file [`ReadError`](`crate::Error::ReadError`)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use infinite_rs::tag::types::bitmap::{BitmapFormat, BitmapType, Surface};
+    ///
+    /// let surface = Surface {
+    ///     width: 8,
+    ///     height: 8,
+    ///     depth: 1,
+    ///     layers: 1,
+    ///     mip_levels: 2,
+    ///     bitmap_type: BitmapType::Texture2D,
+    ///     format: BitmapFormat::Dxt1,
+    /// };
+    /// assert_eq!(surface.data_size(), 0x20 + 0x8);
+    ///
+    /// let mut dds = Vec::new();
+    /// surface.write_dds(&mut dds, &[0; 0x28]).unwrap();
+    /// assert_eq!(&dds[..4], b"DDS ");
+    /// assert_eq!(dds.len(), 4 + 124 + 20 + 0x28);
+    /// ```
+    pub fn write_dds<W: Write>(&self, writer: &mut W, data: &[u8]) -> Result<()> {
+        let dxgi_format = self
+            .format
+            .dxgi_format()
+            .ok_or(TagError::UnsupportedBitmapFormat(self.format))?;
+        let size = self.data_size();
+        let data = usize::try_from(size)
+            .ok()
+            .and_then(|size| data.get(..size))
+            .ok_or(TagError::BitmapDataTooSmall {
+                expected: size,
+                found: data.len() as u64,
+            })?;
+
+        let mip_levels = self.mip_levels.max(1);
+        let element_size = self.format.element_size().unwrap_or_default();
+        // CAPS | HEIGHT | WIDTH | PIXELFORMAT
+        let mut flags = 0x1 | 0x2 | 0x4 | 0x1000;
+        let pitch_or_linear_size = if self.format.is_block_compressed() {
+            flags |= 0x8_0000; // LINEARSIZE
+            self.mip_chain()[0].size.try_into().unwrap_or(u32::MAX)
+        } else {
+            flags |= 0x8; // PITCH
+            self.width * element_size
+        };
+        let mut caps = 0x1000; // TEXTURE
+        let mut caps2 = 0;
+        if mip_levels > 1 {
+            flags |= 0x2_0000; // MIPMAPCOUNT
+            caps |= 0x8 | 0x40_0000; // COMPLEX | MIPMAP
+        }
+        let (dimension, misc_flags, array_size) = match self.bitmap_type {
+            BitmapType::Texture3D => {
+                flags |= 0x80_0000; // DEPTH
+                caps |= 0x8;
+                caps2 |= 0x20_0000; // VOLUME
+                (4, 0, 1)
+            }
+            BitmapType::CubeMap => {
+                caps |= 0x8;
+                caps2 |= 0x200 | 0xFC00; // CUBEMAP | every face
+                (3, 0x4, 1) // TEXTURECUBE
+            }
+            BitmapType::Texture2D | BitmapType::Array => (3, 0, self.layers.max(1)),
+        };
+
+        writer.write_u32::<LE>(DDS_MAGIC)?;
+        writer.write_u32::<LE>(124)?;
+        writer.write_u32::<LE>(flags)?;
+        writer.write_u32::<LE>(self.height)?;
+        writer.write_u32::<LE>(self.width)?;
+        writer.write_u32::<LE>(pitch_or_linear_size)?;
+        writer.write_u32::<LE>(self.depth.max(1))?;
+        writer.write_u32::<LE>(mip_levels)?;
+        writer.write_all(&[0; 11 * 4])?;
+        // Pixel format, pointing to the DX10 header.
+        writer.write_u32::<LE>(32)?;
+        writer.write_u32::<LE>(0x4)?; // FOURCC
+        writer.write_u32::<LE>(DDS_FOURCC_DX10)?;
+        writer.write_all(&[0; 5 * 4])?;
+        writer.write_u32::<LE>(caps)?;
+        writer.write_u32::<LE>(caps2)?;
+        writer.write_all(&[0; 3 * 4])?;
+        // DX10 header.
+        writer.write_u32::<LE>(dxgi_format.into())?;
+        writer.write_u32::<LE>(dimension)?;
+        writer.write_u32::<LE>(misc_flags)?;
+        writer.write_u32::<LE>(array_size)?;
+        writer.write_u32::<LE>(0)?;
+        writer.write_all(data)?;
+        Ok(())
+    }
+}
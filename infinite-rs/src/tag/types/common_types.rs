@@ -1,20 +1,39 @@
 //! Types used by the game to construct a tag.
 
-use byteorder::{LE, ReadBytesExt};
+use byteorder::{ReadBytesExt, WriteBytesExt};
 use num_enum::TryFromPrimitive;
 use std::{
-    fmt::Debug,
-    io::{BufRead, Seek, SeekFrom},
+    array,
+    f32::consts::{PI, TAU},
+    fmt::{self, Debug, Display},
+    io::{BufReader, Cursor, Read, Seek, SeekFrom, Write},
+    ops::{Deref, Range},
+    slice::{Iter, IterMut},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+    vec::IntoIter,
 };
 
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+
 use crate::{
-    Result, TagFile,
+    Result, TagFile, TagId,
     common::errors::{Error, TagError},
+    common::options::{BlockBoundsPolicy, MissingResourcePolicy},
+    common::string_id::lookup_global,
     tag::{datablock::TagSectionType, structure::TagStructType},
 };
-use crate::{common::extensions::BufReaderExt, module::file::TagStructure};
+use crate::{
+    common::extensions::{BufReaderExt, WriterExt},
+    module::{
+        collection::{ModuleCollection, TagHandle},
+        file::TagStructure,
+        header::ModuleVersion,
+    },
+};
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _0: 32 Byte strings that usually store some sort of short name.
 pub struct FieldString(pub String);
 
@@ -23,9 +42,15 @@ impl FieldString {
         self.0 = reader.read_fixed_string(32)?;
         Ok(())
     }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        write_fixed_string(writer, &self.0, 32)?;
+        Ok(())
+    }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _1: 256 byte long string usually used to store paths.
 pub struct FieldLongString(pub String);
 
@@ -34,144 +59,338 @@ impl FieldLongString {
         self.0 = reader.read_fixed_string(256)?;
         Ok(())
     }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        write_fixed_string(writer, &self.0, 256)?;
+        Ok(())
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// UTF-16 string of `N` code units, used by some resources and localization data.
+///
+/// Like [`FieldString`], shorter strings are padded with null characters.
+pub struct FieldWideString<const N: usize>(pub String);
+
+impl<const N: usize> FieldWideString<N> {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_utf16_string(N)?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        let mut units: Vec<u16> = self.0.encode_utf16().take(N).collect();
+        units.resize(N, 0);
+        for unit in units {
+            writer.write_u16_ordered(unit)?;
+        }
+        Ok(())
+    }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _2: 32 bit unsigned integer containing a `MurmurHash3_x86_64` 32 bit value.
+///
+/// When a [`StringIdDictionary`](`crate::common::string_id::StringIdDictionary`) is installed,
+/// [`Debug`] formatting shows the string instead of the hash if it is present in the dictionary.
 pub struct FieldStringId(pub i32);
 
+impl Debug for FieldStringId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match lookup_global(self.0) {
+            Some(string) => f.debug_tuple("FieldStringId").field(&string).finish(),
+            None => f.debug_tuple("FieldStringId").field(&self.0).finish(),
+        }
+    }
+}
+
 impl FieldStringId {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_i32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_i32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i32_ordered(self.0)?;
+        Ok(())
+    }
+}
+
+#[derive(Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _3: Legacy string id, stored the same way as [`FieldStringId`].
+///
+/// Still used by some older tag groups. Can be converted into a [`FieldStringId`] to be resolved.
+pub struct FieldOldStringId(pub i32);
+
+impl Debug for FieldOldStringId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match lookup_global(self.0) {
+            Some(string) => f.debug_tuple("FieldOldStringId").field(&string).finish(),
+            None => f.debug_tuple("FieldOldStringId").field(&self.0).finish(),
+        }
+    }
+}
+
+impl FieldOldStringId {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_i32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i32_ordered(self.0)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+impl From<FieldOldStringId> for FieldStringId {
+    fn from(value: FieldOldStringId) -> Self {
+        Self(value.0)
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _4: Signed integer type "char" in C.
 pub struct FieldCharInteger(pub i8);
 
 impl FieldCharInteger {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
         self.0 = reader.read_i8()?;
         Ok(())
     }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i8(self.0)?;
+        Ok(())
+    }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _5: Signed integer type "short" in C.
 pub struct FieldShortInteger(pub i16);
 
 impl FieldShortInteger {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_i16::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_i16_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i16_ordered(self.0)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _6: Signed integer type "long" in C.
 pub struct FieldLongInteger(pub i32);
 
 impl FieldLongInteger {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_i32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_i32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i32_ordered(self.0)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _7: Signed integer type "__int64 (long long)" in C.
 pub struct FieldInt64Integer(pub i64);
 
 impl FieldInt64Integer {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_i64::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_i64_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i64_ordered(self.0)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
-/// _8: IEE 754 floating point number that stores an angle.
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _8: IEE 754 floating point number that stores an angle, in radians.
 pub struct FieldAngle(pub f32);
 
 impl FieldAngle {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_f32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_f32_ordered()?;
         Ok(())
     }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32_ordered(self.0)?;
+        Ok(())
+    }
+
+    /// Creates an angle from degrees.
+    #[must_use]
+    pub fn from_degrees(degrees: f32) -> Self {
+        Self(degrees.to_radians())
+    }
+
+    /// Angle in degrees, as angles are stored in radians.
+    #[must_use]
+    pub fn to_degrees(&self) -> f32 {
+        self.0.to_degrees()
+    }
+
+    /// Wraps the angle to the range `-PI..PI`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use infinite_rs::tag::types::common_types::FieldAngle;
+    ///
+    /// let angle = FieldAngle::from_degrees(270.0).normalized();
+    /// assert!((angle.to_degrees() + 90.0).abs() < 1e-4);
+    /// ```
+    #[must_use]
+    pub fn normalized(&self) -> Self {
+        Self(normalize_angle(self.0))
+    }
+}
+
+/// Writes a string into a fixed number of bytes, padded with zeros.
+fn write_fixed_string<W: Write>(writer: &mut W, string: &str, length: usize) -> Result<()> {
+    let mut buffer = vec![0; length];
+    let bytes = &string.as_bytes()[..string.len().min(length)];
+    buffer[..bytes.len()].copy_from_slice(bytes);
+    writer.write_all(&buffer)?;
+    Ok(())
+}
+
+/// Wraps an angle in radians to the range `-PI..PI`.
+fn normalize_angle(radians: f32) -> f32 {
+    (radians + PI).rem_euclid(TAU) - PI
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _A: An unsigned "char" value in C used to calculate enums.
 pub struct FieldCharEnum<T: num_enum::TryFromPrimitive<Primitive = u8>>(pub T);
 
 impl<T: TryFromPrimitive<Primitive = u8>> FieldCharEnum<T> {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
         self.0 = T::try_from_primitive(reader.read_u8()?)
             .map_err(|_| Error::TagError(TagError::NumEnumError))?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+impl<T: TryFromPrimitive<Primitive = u8> + Clone + Into<u8>> FieldCharEnum<T> {
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u8(self.0.clone().into())?;
+        Ok(())
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _B: An unsigned "short" value in C used to calculate enums.
 pub struct FieldShortEnum<T: num_enum::TryFromPrimitive<Primitive = u16>>(pub T);
 
 impl<T: TryFromPrimitive<Primitive = u16>> FieldShortEnum<T> {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = T::try_from_primitive(reader.read_u16::<LE>()?)
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = T::try_from_primitive(reader.read_u16_ordered()?)
             .map_err(|_| Error::TagError(TagError::NumEnumError))?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+impl<T: TryFromPrimitive<Primitive = u16> + Clone + Into<u16>> FieldShortEnum<T> {
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u16_ordered(self.0.clone().into())?;
+        Ok(())
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _C: An unsigned "long" value in C used to calculate enums.
 pub struct FieldLongEnum<T: num_enum::TryFromPrimitive<Primitive = u32>>(pub T);
 
 impl<T: num_enum::TryFromPrimitive<Primitive = u32>> FieldLongEnum<T> {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = T::try_from_primitive(reader.read_u32::<LE>()?)
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = T::try_from_primitive(reader.read_u32_ordered()?)
             .map_err(|_| Error::TagError(TagError::NumEnumError))?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+impl<T: TryFromPrimitive<Primitive = u32> + Clone + Into<u32>> FieldLongEnum<T> {
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32_ordered(self.0.clone().into())?;
+        Ok(())
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _D: An unsigned "long" value in C used to calculate bitflags.
 pub struct FieldLongFlags<T: bitflags::Flags<Bits = u32>>(pub T);
 
 impl<T: bitflags::Flags<Bits = u32>> FieldLongFlags<T> {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = T::from_bits_truncate(reader.read_u32::<LE>()?);
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = T::from_bits_truncate(reader.read_u32_ordered()?);
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32_ordered(self.0.bits())?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _E: An unsigned "word (short)" value in C used to calculate bitflags.
 pub struct FieldWordFlags<T: bitflags::Flags<Bits = u16>>(pub T);
 
 impl<T: bitflags::Flags<Bits = u16>> FieldWordFlags<T> {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = T::from_bits_truncate(reader.read_u16::<LE>()?);
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = T::from_bits_truncate(reader.read_u16_ordered()?);
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u16_ordered(self.0.bits())?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _F: An unsigned "byte (char)" value in C used to calculate bitflags.
 pub struct FieldByteFlags<T: bitflags::Flags<Bits = u8>>(pub T);
 
 impl<T: bitflags::Flags<Bits = u8>> FieldByteFlags<T> {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
         self.0 = T::from_bits_truncate(reader.read_u8()?);
         Ok(())
     }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u8(self.0.bits())?;
+        Ok(())
+    }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _10: X and Y coordinates of a point in 2D.
 pub struct FieldPoint2D {
     pub x: u16,
@@ -179,29 +398,61 @@ pub struct FieldPoint2D {
 }
 
 impl FieldPoint2D {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.x = reader.read_u16::<LE>()?;
-        self.y = reader.read_u16::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.x = reader.read_u16_ordered()?;
+        self.y = reader.read_u16_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u16_ordered(self.x)?;
+        writer.write_u16_ordered(self.y)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
-/// _11:  X and Y coordinates of a rectangle in 2D.
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _11: Edges of a rectangle in 2D, stored as four signed shorts in C (i16).
 pub struct FieldRectangle2D {
-    pub x: u16,
-    pub y: u16,
+    pub top: i16,
+    pub left: i16,
+    pub bottom: i16,
+    pub right: i16,
 }
 
 impl FieldRectangle2D {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.x = reader.read_u16::<LE>()?;
-        self.y = reader.read_u16::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.top = reader.read_i16_ordered()?;
+        self.left = reader.read_i16_ordered()?;
+        self.bottom = reader.read_i16_ordered()?;
+        self.right = reader.read_i16_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i16_ordered(self.top)?;
+        writer.write_i16_ordered(self.left)?;
+        writer.write_i16_ordered(self.bottom)?;
+        writer.write_i16_ordered(self.right)?;
         Ok(())
     }
+
+    /// Horizontal size of the rectangle.
+    #[must_use]
+    pub fn width(&self) -> i32 {
+        i32::from(self.right) - i32::from(self.left)
+    }
+
+    /// Vertical size of the rectangle.
+    #[must_use]
+    pub fn height(&self) -> i32 {
+        i32::from(self.bottom) - i32::from(self.top)
+    }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _12: RGBA values of a color represented in u8.
 /// Alpha value is unused.
 pub struct FieldRGBColor {
@@ -212,16 +463,25 @@ pub struct FieldRGBColor {
 }
 
 impl FieldRGBColor {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
         self.r = reader.read_u8()?;
         self.g = reader.read_u8()?;
         self.b = reader.read_u8()?;
         self.a = reader.read_u8()?;
         Ok(())
     }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u8(self.r)?;
+        writer.write_u8(self.g)?;
+        writer.write_u8(self.b)?;
+        writer.write_u8(self.a)?;
+        Ok(())
+    }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _13: RGBA values of a color represented in u8.
 pub struct FieldARGBColor {
     pub r: u8,
@@ -231,38 +491,59 @@ pub struct FieldARGBColor {
 }
 
 impl FieldARGBColor {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
         self.r = reader.read_u8()?;
         self.g = reader.read_u8()?;
         self.b = reader.read_u8()?;
         self.a = reader.read_u8()?;
         Ok(())
     }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u8(self.r)?;
+        writer.write_u8(self.g)?;
+        writer.write_u8(self.b)?;
+        writer.write_u8(self.a)?;
+        Ok(())
+    }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _14: Real number represented as a float.
 pub struct FieldReal(pub f32);
 
 impl FieldReal {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_f32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_f32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32_ordered(self.0)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _15: Real "fraction" value represented as a float.
 pub struct FieldRealFraction(pub f32);
 
 impl FieldRealFraction {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_f32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_f32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32_ordered(self.0)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _16: X and Y coordinates of point in 2D stored as two floats.
 pub struct FieldRealPoint2D {
     pub x: f32,
@@ -270,14 +551,21 @@ pub struct FieldRealPoint2D {
 }
 
 impl FieldRealPoint2D {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.x = reader.read_f32::<LE>()?;
-        self.y = reader.read_f32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.x = reader.read_f32_ordered()?;
+        self.y = reader.read_f32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32_ordered(self.x)?;
+        writer.write_f32_ordered(self.y)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _17: X, Y and Z coordinates of point in 3D stored as three floats.
 pub struct FieldRealPoint3D {
     pub x: f32,
@@ -286,15 +574,23 @@ pub struct FieldRealPoint3D {
 }
 
 impl FieldRealPoint3D {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.x = reader.read_f32::<LE>()?;
-        self.y = reader.read_f32::<LE>()?;
-        self.z = reader.read_f32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.x = reader.read_f32_ordered()?;
+        self.y = reader.read_f32_ordered()?;
+        self.z = reader.read_f32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32_ordered(self.x)?;
+        writer.write_f32_ordered(self.y)?;
+        writer.write_f32_ordered(self.z)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _18: X and Y coordinates of a vector in 2D stored as two floats.
 pub struct FieldRealVector2D {
     pub x: f32,
@@ -302,14 +598,21 @@ pub struct FieldRealVector2D {
 }
 
 impl FieldRealVector2D {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.x = reader.read_f32::<LE>()?;
-        self.y = reader.read_f32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.x = reader.read_f32_ordered()?;
+        self.y = reader.read_f32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32_ordered(self.x)?;
+        writer.write_f32_ordered(self.y)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _19: X, Y and Z coordinates of a vector in 3D stored as three floats.
 pub struct FieldRealVector3D {
     pub x: f32,
@@ -318,15 +621,23 @@ pub struct FieldRealVector3D {
 }
 
 impl FieldRealVector3D {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.x = reader.read_f32::<LE>()?;
-        self.y = reader.read_f32::<LE>()?;
-        self.z = reader.read_f32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.x = reader.read_f32_ordered()?;
+        self.y = reader.read_f32_ordered()?;
+        self.z = reader.read_f32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32_ordered(self.x)?;
+        writer.write_f32_ordered(self.y)?;
+        writer.write_f32_ordered(self.z)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _1A: X, Y, Z and W values of a quaternion stored as four floats.
 /// Used for rotation math.
 pub struct FieldRealQuaternion {
@@ -337,48 +648,130 @@ pub struct FieldRealQuaternion {
 }
 
 impl FieldRealQuaternion {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.x = reader.read_f32::<LE>()?;
-        self.y = reader.read_f32::<LE>()?;
-        self.z = reader.read_f32::<LE>()?;
-        self.w = reader.read_f32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.x = reader.read_f32_ordered()?;
+        self.y = reader.read_f32_ordered()?;
+        self.z = reader.read_f32_ordered()?;
+        self.w = reader.read_f32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32_ordered(self.x)?;
+        writer.write_f32_ordered(self.y)?;
+        writer.write_f32_ordered(self.z)?;
+        writer.write_f32_ordered(self.w)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
-/// _1B: X and Y coordinates of a eular angle in 2D stored as two floats.
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _1B: X and Y coordinates of a euler angle in 2D stored as two floats, in radians.
 pub struct FieldRealEulerAngles2D {
     pub x: f32,
     pub y: f32,
 }
 
 impl FieldRealEulerAngles2D {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.x = reader.read_f32::<LE>()?;
-        self.y = reader.read_f32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.x = reader.read_f32_ordered()?;
+        self.y = reader.read_f32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32_ordered(self.x)?;
+        writer.write_f32_ordered(self.y)?;
         Ok(())
     }
+
+    /// Creates angles from degrees.
+    #[must_use]
+    pub fn from_degrees(x: f32, y: f32) -> Self {
+        Self {
+            x: x.to_radians(),
+            y: y.to_radians(),
+        }
+    }
+
+    /// Angles in degrees, as angles are stored in radians.
+    #[must_use]
+    pub fn to_degrees(&self) -> [f32; 2] {
+        [self.x.to_degrees(), self.y.to_degrees()]
+    }
+
+    /// Wraps each angle to the range `-PI..PI`.
+    #[must_use]
+    pub fn normalized(&self) -> Self {
+        Self {
+            x: normalize_angle(self.x),
+            y: normalize_angle(self.y),
+        }
+    }
 }
 
-#[derive(Default, Debug)]
-/// _1C: X, Y and Z coordinates of a eular angle in 3D stored as two floats.
-pub struct FieldRealEularAngles3D {
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _1C: X, Y and Z coordinates of a euler angle in 3D stored as three floats, in radians.
+pub struct FieldRealEulerAngles3D {
     pub x: f32,
     pub y: f32,
     pub z: f32,
 }
 
-impl FieldRealEularAngles3D {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.x = reader.read_f32::<LE>()?;
-        self.y = reader.read_f32::<LE>()?;
-        self.z = reader.read_f32::<LE>()?;
+/// Misspelled name of [`FieldRealEulerAngles3D`].
+#[deprecated(note = "renamed to `FieldRealEulerAngles3D`")]
+pub type FieldRealEularAngles3D = FieldRealEulerAngles3D;
+
+impl FieldRealEulerAngles3D {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.x = reader.read_f32_ordered()?;
+        self.y = reader.read_f32_ordered()?;
+        self.z = reader.read_f32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32_ordered(self.x)?;
+        writer.write_f32_ordered(self.y)?;
+        writer.write_f32_ordered(self.z)?;
         Ok(())
     }
+
+    /// Creates angles from degrees.
+    #[must_use]
+    pub fn from_degrees(x: f32, y: f32, z: f32) -> Self {
+        Self {
+            x: x.to_radians(),
+            y: y.to_radians(),
+            z: z.to_radians(),
+        }
+    }
+
+    /// Angles in degrees, as angles are stored in radians.
+    #[must_use]
+    pub fn to_degrees(&self) -> [f32; 3] {
+        [
+            self.x.to_degrees(),
+            self.y.to_degrees(),
+            self.z.to_degrees(),
+        ]
+    }
+
+    /// Wraps each angle to the range `-PI..PI`.
+    #[must_use]
+    pub fn normalized(&self) -> Self {
+        Self {
+            x: normalize_angle(self.x),
+            y: normalize_angle(self.y),
+            z: normalize_angle(self.z),
+        }
+    }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _1D: X, Y and D values of a plane in 2D stored as three floats.
 pub struct FieldRealPlane2D {
     pub x: f32,
@@ -387,15 +780,23 @@ pub struct FieldRealPlane2D {
 }
 
 impl FieldRealPlane2D {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.x = reader.read_f32::<LE>()?;
-        self.y = reader.read_f32::<LE>()?;
-        self.d = reader.read_f32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.x = reader.read_f32_ordered()?;
+        self.y = reader.read_f32_ordered()?;
+        self.d = reader.read_f32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32_ordered(self.x)?;
+        writer.write_f32_ordered(self.y)?;
+        writer.write_f32_ordered(self.d)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _1E: X, Y, Z and D values of a plane in 3D stored as four floats.
 pub struct FieldRealPlane3D {
     pub x: f32,
@@ -405,16 +806,25 @@ pub struct FieldRealPlane3D {
 }
 
 impl FieldRealPlane3D {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.x = reader.read_f32::<LE>()?;
-        self.y = reader.read_f32::<LE>()?;
-        self.z = reader.read_f32::<LE>()?;
-        self.d = reader.read_f32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.x = reader.read_f32_ordered()?;
+        self.y = reader.read_f32_ordered()?;
+        self.z = reader.read_f32_ordered()?;
+        self.d = reader.read_f32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32_ordered(self.x)?;
+        writer.write_f32_ordered(self.y)?;
+        writer.write_f32_ordered(self.z)?;
+        writer.write_f32_ordered(self.d)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _1F: RGB values of a color stored as three floats.
 pub struct FieldRealRGBColor {
     pub r: f32,
@@ -423,15 +833,23 @@ pub struct FieldRealRGBColor {
 }
 
 impl FieldRealRGBColor {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.r = reader.read_f32::<LE>()?;
-        self.g = reader.read_f32::<LE>()?;
-        self.b = reader.read_f32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.r = reader.read_f32_ordered()?;
+        self.g = reader.read_f32_ordered()?;
+        self.b = reader.read_f32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32_ordered(self.r)?;
+        writer.write_f32_ordered(self.g)?;
+        writer.write_f32_ordered(self.b)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _20: RGBA values of a color stored as four floats.
 pub struct FieldRealARGBColor {
     pub a: f32,
@@ -441,70 +859,202 @@ pub struct FieldRealARGBColor {
 }
 
 impl FieldRealARGBColor {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.a = reader.read_f32::<LE>()?;
-        self.r = reader.read_f32::<LE>()?;
-        self.g = reader.read_f32::<LE>()?;
-        self.b = reader.read_f32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.a = reader.read_f32_ordered()?;
+        self.r = reader.read_f32_ordered()?;
+        self.g = reader.read_f32_ordered()?;
+        self.b = reader.read_f32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32_ordered(self.a)?;
+        writer.write_f32_ordered(self.r)?;
+        writer.write_f32_ordered(self.g)?;
+        writer.write_f32_ordered(self.b)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
-/// _21: HSV values of a color stored as a single float.
-/// Unknown how the actual color is calculated
-pub struct FieldRealHSVColor(f32);
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _21: Hue, saturation and value of a color stored as three floats.
+///
+/// All components are normalized to the range `0.0..=1.0`, including hue, where `1.0`
+/// corresponds to 360 degrees.
+pub struct FieldRealHSVColor {
+    pub h: f32,
+    pub s: f32,
+    pub v: f32,
+}
 
 impl FieldRealHSVColor {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_f32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.h = reader.read_f32_ordered()?;
+        self.s = reader.read_f32_ordered()?;
+        self.v = reader.read_f32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32_ordered(self.h)?;
+        writer.write_f32_ordered(self.s)?;
+        writer.write_f32_ordered(self.v)?;
         Ok(())
     }
+
+    /// Converts the color to RGB.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use infinite_rs::tag::types::common_types::FieldRealHSVColor;
+    ///
+    /// let color = FieldRealHSVColor { h: 1.0 / 3.0, s: 1.0, v: 1.0 }.to_rgb();
+    /// assert!(color.r.abs() < 1e-6 && (color.g - 1.0).abs() < 1e-6 && color.b.abs() < 1e-6);
+    /// ```
+    #[must_use]
+    pub fn to_rgb(&self) -> FieldRealRGBColor {
+        let [r, g, b] = hsv_to_rgb(self.h, self.s, self.v);
+        FieldRealRGBColor { r, g, b }
+    }
 }
 
-#[derive(Default, Debug)]
-/// _22: AHSV values of a color stored as a single float.
-/// Unknown how the actual color is calculated
-pub struct FieldRealAHSVColor(f32);
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _22: Alpha, hue, saturation and value of a color stored as four floats.
+///
+/// Components are normalized the same way as [`FieldRealHSVColor`].
+pub struct FieldRealAHSVColor {
+    pub a: f32,
+    pub h: f32,
+    pub s: f32,
+    pub v: f32,
+}
 
 impl FieldRealAHSVColor {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_f32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.a = reader.read_f32_ordered()?;
+        self.h = reader.read_f32_ordered()?;
+        self.s = reader.read_f32_ordered()?;
+        self.v = reader.read_f32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32_ordered(self.a)?;
+        writer.write_f32_ordered(self.h)?;
+        writer.write_f32_ordered(self.s)?;
+        writer.write_f32_ordered(self.v)?;
         Ok(())
     }
+
+    /// Converts the color to RGB, discarding alpha.
+    #[must_use]
+    pub fn to_rgb(&self) -> FieldRealRGBColor {
+        let [r, g, b] = hsv_to_rgb(self.h, self.s, self.v);
+        FieldRealRGBColor { r, g, b }
+    }
+
+    /// Converts the color to ARGB.
+    #[must_use]
+    pub fn to_argb(&self) -> FieldRealARGBColor {
+        let [r, g, b] = hsv_to_rgb(self.h, self.s, self.v);
+        FieldRealARGBColor { a: self.a, r, g, b }
+    }
 }
 
-#[derive(Default, Debug)]
-/// _23: Minimum and Maximum bounds stored as two unsigned shorts in C (u16).
+/// Converts normalized hue, saturation and value to red, green and blue.
+///
+/// Hue wraps around, so values outside of `0.0..1.0` are still valid.
+fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> [f32; 3] {
+    let saturation = saturation.clamp(0.0, 1.0);
+    let sector = hue.rem_euclid(1.0) * 6.0;
+    let fraction = sector.fract();
+    let min = value * (1.0 - saturation);
+    let falling = value * (1.0 - saturation * fraction);
+    let rising = value * (1.0 - saturation * (1.0 - fraction));
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    match sector as u8 {
+        0 => [value, rising, min],
+        1 => [falling, value, min],
+        2 => [min, value, rising],
+        3 => [min, falling, value],
+        4 => [rising, min, value],
+        _ => [value, min, falling],
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _23: Minimum and Maximum bounds stored as two signed shorts in C (i16).
 pub struct FieldShortBounds {
-    pub min: u16,
-    pub max: u16,
+    pub min: i16,
+    pub max: i16,
 }
 
 impl FieldShortBounds {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.min = reader.read_u16::<LE>()?;
-        self.max = reader.read_u16::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.min = reader.read_i16_ordered()?;
+        self.max = reader.read_i16_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i16_ordered(self.min)?;
+        writer.write_i16_ordered(self.max)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
-/// _24: Minimum and Maximum angles stored as two floats.
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _24: Minimum and Maximum angles stored as two floats, in radians.
 pub struct FieldAngleBounds {
     pub min: f32,
     pub max: f32,
 }
 
 impl FieldAngleBounds {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.min = reader.read_f32::<LE>()?;
-        self.max = reader.read_f32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.min = reader.read_f32_ordered()?;
+        self.max = reader.read_f32_ordered()?;
         Ok(())
     }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32_ordered(self.min)?;
+        writer.write_f32_ordered(self.max)?;
+        Ok(())
+    }
+
+    /// Creates bounds from degrees.
+    #[must_use]
+    pub fn from_degrees(min: f32, max: f32) -> Self {
+        Self {
+            min: min.to_radians(),
+            max: max.to_radians(),
+        }
+    }
+
+    /// Minimum and maximum angles in degrees, as angles are stored in radians.
+    #[must_use]
+    pub fn to_degrees(&self) -> [f32; 2] {
+        [self.min.to_degrees(), self.max.to_degrees()]
+    }
+
+    /// Wraps both angles to the range `-PI..PI`.
+    #[must_use]
+    pub fn normalized(&self) -> Self {
+        Self {
+            min: normalize_angle(self.min),
+            max: normalize_angle(self.max),
+        }
+    }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _25: Minimum and Maximum real values stored as two floats.
 pub struct FieldRealBounds {
     pub min: f32,
@@ -512,14 +1062,21 @@ pub struct FieldRealBounds {
 }
 
 impl FieldRealBounds {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.min = reader.read_f32::<LE>()?;
-        self.max = reader.read_f32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.min = reader.read_f32_ordered()?;
+        self.max = reader.read_f32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32_ordered(self.min)?;
+        writer.write_f32_ordered(self.max)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _26: Minimum and Maximum real fraction values stored as two floats.
 pub struct FieldRealFractionBounds {
     pub min: f32,
@@ -527,114 +1084,266 @@ pub struct FieldRealFractionBounds {
 }
 
 impl FieldRealFractionBounds {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.min = reader.read_f32::<LE>()?;
-        self.max = reader.read_f32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.min = reader.read_f32_ordered()?;
+        self.max = reader.read_f32_ordered()?;
         Ok(())
     }
-}
-
-#[derive(Default, Debug)]
-/// _29: Long block flags, stored a 32-bit unsigned integer.
-pub struct FieldLongBlockFlags(pub u32);
 
-impl FieldLongBlockFlags {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_u32::<LE>()?;
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32_ordered(self.min)?;
+        writer.write_f32_ordered(self.max)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
-/// _2A: Word block flags, stored a 32-bit unsigned integer.
-pub struct FieldWordBlockFlags(pub u32);
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _27: Minimum and Maximum bounds stored as two signed chars in C (i8).
+pub struct FieldCharBounds {
+    pub min: i8,
+    pub max: i8,
+}
 
-impl FieldWordBlockFlags {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_u32::<LE>()?;
+impl FieldCharBounds {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.min = reader.read_i8()?;
+        self.max = reader.read_i8()?;
         Ok(())
     }
-}
-
-#[derive(Default, Debug)]
-/// _2B: Byte block flags, stored a 32-bit unsigned integer.
-pub struct FieldByteBlockFlags(pub u32);
 
-impl FieldByteBlockFlags {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_u32::<LE>()?;
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i8(self.min)?;
+        writer.write_i8(self.max)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
-/// _2C: Char block index, stores an 8-bit signed integer.
-pub struct FieldCharBlockIndex(pub i8);
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _28: Minimum and Maximum bounds stored as two signed longs in C (i32).
+pub struct FieldLongBounds {
+    pub min: i32,
+    pub max: i32,
+}
 
-impl FieldCharBlockIndex {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_i8()?;
+impl FieldLongBounds {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.min = reader.read_i32_ordered()?;
+        self.max = reader.read_i32_ordered()?;
         Ok(())
     }
-}
-
-#[derive(Default, Debug)]
-/// _2D: Custom char block index, stores an 8-bit signed integer.
-pub struct FieldCustomCharBlockIndex(pub i8);
 
-impl FieldCustomCharBlockIndex {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_i8()?;
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i32_ordered(self.min)?;
+        writer.write_i32_ordered(self.max)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
-/// _2E: Short block index, stores a 16-bit signed integer.
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _29: Long block flags, stored a 32-bit unsigned integer.
+pub struct FieldLongBlockFlags(pub u32);
+
+impl FieldLongBlockFlags {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_u32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32_ordered(self.0)?;
+        Ok(())
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _2A: Word block flags, stored a 16-bit unsigned integer.
+///
+/// Versions before 0.14 read a 32-bit integer. Structures that relied on this should use
+/// [`FieldLongBlockFlags`] instead.
+pub struct FieldWordBlockFlags(pub u16);
+
+impl FieldWordBlockFlags {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_u16_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u16_ordered(self.0)?;
+        Ok(())
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _2B: Byte block flags, stored an 8-bit unsigned integer.
+///
+/// Versions before 0.14 read a 32-bit integer. Structures that relied on this should use
+/// [`FieldLongBlockFlags`] instead.
+pub struct FieldByteBlockFlags(pub u8);
+
+impl FieldByteBlockFlags {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_u8()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u8(self.0)?;
+        Ok(())
+    }
+}
+
+impl From<FieldWordBlockFlags> for FieldLongBlockFlags {
+    fn from(value: FieldWordBlockFlags) -> Self {
+        Self(u32::from(value.0))
+    }
+}
+
+impl From<FieldByteBlockFlags> for FieldLongBlockFlags {
+    fn from(value: FieldByteBlockFlags) -> Self {
+        Self(u32::from(value.0))
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _2C: Char block index, stores an 8-bit signed integer.
+pub struct FieldCharBlockIndex(pub i8);
+
+impl FieldCharBlockIndex {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_i8()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i8(self.0)?;
+        Ok(())
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _2D: Custom char block index, stores an 8-bit signed integer.
+pub struct FieldCustomCharBlockIndex(pub i8);
+
+impl FieldCustomCharBlockIndex {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_i8()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i8(self.0)?;
+        Ok(())
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _2E: Short block index, stores a 16-bit signed integer.
 pub struct FieldShortBlockIndex(pub i16);
 
 impl FieldShortBlockIndex {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_i16::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_i16_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i16_ordered(self.0)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _2F: Custom short block index, stores a 16-bit signed integer.
 pub struct FieldCustomShortBlockIndex(pub i16);
 
 impl FieldCustomShortBlockIndex {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_i16::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_i16_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i16_ordered(self.0)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _30: Long block index, stores a 32-bit signed integer.
 pub struct FieldLongBlockIndex(pub i32);
 
 impl FieldLongBlockIndex {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_i32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_i32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i32_ordered(self.0)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _31: Custom long block index, stores a 32-bit signed integer.
 pub struct FieldCustomLongBlockIndex(pub i32);
 
 impl FieldCustomLongBlockIndex {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_i32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_i32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i32_ordered(self.0)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _34: Padding field, no data stored.
+///
+/// When used in a [`TagStructure`], the length of the padding is given by the `length` attribute.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::{BufReader, Cursor};
+/// use infinite_rs::module::file::TagStructure;
+/// use infinite_rs::module::header::ModuleVersion;
+/// use infinite_rs::tag::types::common_types::{FieldLongInteger, FieldPad};
+/// use infinite_rs_derive::TagStructure;
+///
+/// #[derive(Default, TagStructure)]
+/// #[data(size(0x10))]
+/// struct PaddedStruct {
+///     #[data(offset(0x00), length(0x0C))]
+///     padding: FieldPad,
+///     #[data(offset(0x0C))]
+///     value: FieldLongInteger,
+/// }
+///
+/// let mut data = [0_u8; 0x10];
+/// data[0x0C] = 7;
+/// let mut padded = PaddedStruct::default();
+/// padded
+///     .read(&mut BufReader::new(Cursor::new(data)), &ModuleVersion::default())
+///     .unwrap();
+/// assert_eq!(padded.value.0, 7);
+/// ```
 pub struct FieldPad;
 
 impl FieldPad {
@@ -642,66 +1351,210 @@ impl FieldPad {
         reader.seek_relative(i64::from(length))?;
         Ok(())
     }
+
+    /// Writes `length` zero bytes.
+    pub fn write<W: WriterExt>(&self, writer: &mut W, length: u8) -> Result<()> {
+        writer.write_all(&vec![0; usize::from(length)])?;
+        Ok(())
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _36: Explanation shown in the tag editor, no data stored.
+///
+/// Only present in layouts so that field types map one-to-one with other tools.
+pub struct FieldExplanation;
+
+impl FieldExplanation {
+    pub fn read<R: BufReaderExt>(&mut self, _reader: &mut R) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, _writer: &mut W) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _37: Custom editor control, no data stored.
+///
+/// Only present in layouts so that field types map one-to-one with other tools.
+pub struct FieldCustom;
+
+impl FieldCustom {
+    pub fn read<R: BufReaderExt>(&mut self, _reader: &mut R) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, _writer: &mut W) -> Result<()> {
+        Ok(())
+    }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _3C: Byte integer field, stores an 8-bit unsigned integer.
 pub struct FieldByteInteger(pub u8);
 
 impl FieldByteInteger {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
         self.0 = reader.read_u8()?;
         Ok(())
     }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u8(self.0)?;
+        Ok(())
+    }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _3D: Word integer field, stores a 16-bit unsigned integer.
 pub struct FieldWordInteger(pub u16);
 
 impl FieldWordInteger {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_u16::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_u16_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u16_ordered(self.0)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _3E: Dword integer field, stores a 32-bit unsigned integer.
 pub struct FieldDwordInteger(pub u32);
 
 impl FieldDwordInteger {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_u32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_u32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32_ordered(self.0)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _3F: Qword integer field, stores a 64-bit unsigned integer.
 pub struct FieldQwordInteger(pub u64);
 
 impl FieldQwordInteger {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.0 = reader.read_u64::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_u64_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u64_ordered(self.0)?;
+        Ok(())
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _3F: Timestamp stored in a qword field.
+///
+/// Tags store timestamps either as a Windows `FILETIME` (100 nanosecond intervals since
+/// 1601-01-01) or as Unix time (seconds since 1970-01-01). The raw value does not indicate which,
+/// so a conversion is provided for each.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use infinite_rs::tag::types::common_types::FieldTimestamp;
+///
+/// let timestamp = FieldTimestamp(116_444_736_000_000_000 + 10_000_000);
+/// assert_eq!(timestamp.filetime_to_system_time(), Some(UNIX_EPOCH + Duration::from_secs(1)));
+/// assert_eq!(FieldTimestamp(1).unix_to_system_time(), Some(UNIX_EPOCH + Duration::from_secs(1)));
+/// ```
+pub struct FieldTimestamp(pub u64);
+
+/// Number of 100 nanosecond intervals between the `FILETIME` epoch and the Unix epoch.
+const FILETIME_UNIX_OFFSET: u64 = 116_444_736_000_000_000;
+
+impl FieldTimestamp {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.0 = reader.read_u64_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u64_ordered(self.0)?;
         Ok(())
     }
+
+    /// Interprets the value as a Windows `FILETIME`.
+    ///
+    /// Returns [`None`] if the time is before the Unix epoch or cannot be represented.
+    #[must_use]
+    pub fn filetime_to_system_time(&self) -> Option<SystemTime> {
+        let intervals = self.0.checked_sub(FILETIME_UNIX_OFFSET)?;
+        let duration = Duration::new(
+            intervals / 10_000_000,
+            u32::try_from(intervals % 10_000_000).ok()? * 100,
+        );
+        UNIX_EPOCH.checked_add(duration)
+    }
+
+    /// Interprets the value as Unix time in seconds.
+    ///
+    /// Returns [`None`] if the time cannot be represented.
+    #[must_use]
+    pub fn unix_to_system_time(&self) -> Option<SystemTime> {
+        UNIX_EPOCH.checked_add(Duration::from_secs(self.0))
+    }
+
+    /// Interprets the value as a Windows `FILETIME`, see
+    /// [`filetime_to_system_time`](`FieldTimestamp::filetime_to_system_time`).
+    #[cfg(feature = "chrono")]
+    #[must_use]
+    pub fn filetime_to_datetime(&self) -> Option<DateTime<Utc>> {
+        self.filetime_to_system_time().map(DateTime::from)
+    }
+
+    /// Interprets the value as Unix time in seconds, see
+    /// [`unix_to_system_time`](`FieldTimestamp::unix_to_system_time`).
+    #[cfg(feature = "chrono")]
+    #[must_use]
+    pub fn unix_to_datetime(&self) -> Option<DateTime<Utc>> {
+        self.unix_to_system_time().map(DateTime::from)
+    }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _39: Array of structures stored in sequence.
 pub struct FieldArray<T: TagStructure + Default> {
     pub elements: Vec<T>,
 }
 
 impl<T: TagStructure + Default> FieldArray<T> {
-    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R, size: u64) -> Result<()> {
-        for _ in 0..size {
-            let mut element = T::default();
-            element.read(reader)?;
-            self.elements.push(element);
-        }
-        Ok(())
+    pub fn read<R: BufReaderExt>(
+        &mut self,
+        reader: &mut R,
+        size: u64,
+        version: &ModuleVersion,
+    ) -> Result<()> {
+        let start = self.elements.len();
+        self.elements
+            .resize_with(start + usize::try_from(size)?, T::default);
+        read_elements(&mut self.elements[start..], reader, version)
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W, version: &ModuleVersion) -> Result<()> {
+        write_elements(&self.elements, writer, version)
     }
 
     pub fn load_blocks<R: BufReaderExt>(
@@ -711,32 +1564,252 @@ impl<T: TagStructure + Default> FieldArray<T> {
         adjusted_base: u64,
         tag_file: &TagFile,
     ) -> Result<()> {
-        for element in &mut self.elements {
-            element.load_field_blocks(source_index, 0, adjusted_base, reader, tag_file)?;
+        load_element_blocks(
+            &mut self.elements,
+            reader,
+            source_index,
+            adjusted_base,
+            tag_file,
+        )
+    }
+}
+
+/// Reads the elements of an array stored in sequence, shared by [`FieldArray`] and
+/// [`FieldFixedArray`].
+fn read_elements<T: TagStructure, R: BufReaderExt>(
+    elements: &mut [T],
+    reader: &mut R,
+    version: &ModuleVersion,
+) -> Result<()> {
+    for element in elements {
+        element.read(reader, version)?;
+    }
+    Ok(())
+}
+
+/// Writes the elements of an array stored in sequence.
+fn write_elements<T: TagStructure, W: WriterExt>(
+    elements: &[T],
+    writer: &mut W,
+    version: &ModuleVersion,
+) -> Result<()> {
+    for element in elements {
+        element.write(writer, version)?;
+    }
+    Ok(())
+}
+
+/// Loads the tag blocks of the elements of an array, each element starting right after the
+/// previous one.
+fn load_element_blocks<T: TagStructure, R: BufReaderExt>(
+    elements: &mut [T],
+    reader: &mut R,
+    source_index: i32,
+    adjusted_base: u64,
+    tag_file: &TagFile,
+) -> Result<()> {
+    for (idx, element) in elements.iter_mut().enumerate() {
+        let element_base = adjusted_base + element.size() * idx as u64;
+        element.load_field_blocks(source_index, 0, element_base, reader, tag_file)?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// _39: Array of structures stored in sequence, whose length is part of the type.
+///
+/// Unlike [`FieldArray`], no `count` attribute is needed when used in a [`TagStructure`].
+///
+/// # Examples
+///
+/// ```rust
+/// use infinite_rs::tag::types::common_types::FieldFixedArray;
+/// # use infinite_rs::tag::types::common_types::FieldLongInteger;
+/// # use infinite_rs_derive::TagStructure;
+/// # #[derive(Default, TagStructure)]
+/// # #[data(size(0x4))]
+/// # struct Element {
+/// #     #[data(offset(0x00))]
+/// #     value: FieldLongInteger,
+/// # }
+///
+/// let array = FieldFixedArray::<Element, 4>::default();
+/// assert_eq!(array.len(), 4);
+/// ```
+pub struct FieldFixedArray<T: TagStructure + Default, const N: usize> {
+    pub elements: [T; N],
+}
+
+impl<T: TagStructure + Default, const N: usize> Default for FieldFixedArray<T, N> {
+    fn default() -> Self {
+        Self {
+            elements: array::from_fn(|_| T::default()),
         }
-        Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[cfg(feature = "serde")]
+#[allow(clippy::absolute_paths)]
+/// Serialized as a sequence, as `serde` only implements arrays of up to 32 elements.
+impl<T: TagStructure + Default + serde::Serialize, const N: usize> serde::Serialize
+    for FieldFixedArray<T, N>
+{
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        self.elements.as_slice().serialize(serializer)
+    }
+}
+
+impl<T: TagStructure + Default, const N: usize> FieldFixedArray<T, N> {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R, version: &ModuleVersion) -> Result<()> {
+        read_elements(&mut self.elements, reader, version)
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W, version: &ModuleVersion) -> Result<()> {
+        write_elements(&self.elements, writer, version)
+    }
+
+    pub fn load_blocks<R: BufReaderExt>(
+        &mut self,
+        reader: &mut R,
+        source_index: i32,
+        adjusted_base: u64,
+        tag_file: &TagFile,
+    ) -> Result<()> {
+        load_element_blocks(
+            &mut self.elements,
+            reader,
+            source_index,
+            adjusted_base,
+            tag_file,
+        )
+    }
+}
+
+impl<T: TagStructure + Default, const N: usize> Deref for FieldFixedArray<T, N> {
+    type Target = [T; N];
+
+    fn deref(&self) -> &Self::Target {
+        &self.elements
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _40: Tag block, stores the size of an array.
+///
+/// If the field points to a [`Literal`](`TagStructType::Literal`) struct, the elements are stored
+/// inline in its data block, and [`size`](`FieldBlock::size`) is derived from the size of that
+/// data block.
+///
+/// The block dereferences to a slice of its elements, and can be iterated over directly.
 pub struct FieldBlock<T: TagStructure> {
+    #[cfg_attr(feature = "serde", serde(skip))]
     field_offset: u64,
+    #[cfg_attr(feature = "serde", serde(skip))]
     type_info: u64, // uintptr at runtime
-    unknown: u64,   // uintptr at runtime
+    #[cfg_attr(feature = "serde", serde(skip))]
+    unknown: u64, // uintptr at runtime
     pub size: u32,
     pub elements: Vec<T>,
+    /// Whether the elements point to data that is not loaded, see [`MissingResourcePolicy`].
+    pub is_missing: bool,
+}
+
+/// Runtime pointers and the offset the field was read from are not compared.
+impl<T: TagStructure + PartialEq> PartialEq for FieldBlock<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size
+            && self.elements == other.elements
+            && self.is_missing == other.is_missing
+    }
+}
+
+impl<T: TagStructure> FieldBlock<T> {
+    /// Number of elements read from the block.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Whether there are no elements read from the block.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+}
+
+impl<T: TagStructure> Deref for FieldBlock<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        &self.elements
+    }
+}
+
+impl<T: TagStructure> IntoIterator for FieldBlock<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.into_iter()
+    }
+}
+
+impl<'a, T: TagStructure> IntoIterator for &'a FieldBlock<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.iter()
+    }
+}
+
+impl<'a, T: TagStructure> IntoIterator for &'a mut FieldBlock<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.iter_mut()
+    }
 }
 
 impl<T: TagStructure + Debug + Default> FieldBlock<T> {
     pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
-        self.field_offset = reader.stream_position()?;
-        self.type_info = reader.read_u64::<LE>()?;
-        self.unknown = reader.read_u64::<LE>()?;
-        self.size = reader.read_u32::<LE>()?;
+        self.field_offset = reader.window_offset() + reader.stream_position()?;
+        self.type_info = reader.read_u64_ordered()?;
+        self.unknown = reader.read_u64_ordered()?;
+        self.size = reader.read_u32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u64_ordered(self.type_info)?;
+        writer.write_u64_ordered(self.unknown)?;
+        writer.write_u32_ordered(self.size)?;
         Ok(())
     }
 
+    /// Offset of the field from the start of tag data, recorded when it was read.
+    #[must_use]
+    pub fn field_offset(&self) -> u64 {
+        self.field_offset
+    }
+
+    /// Runtime pointer to the type information of the field, as stored in the tag.
+    #[must_use]
+    pub fn type_info(&self) -> u64 {
+        self.type_info
+    }
+
+    /// Unknown runtime pointer stored in the field.
+    #[must_use]
+    pub fn unknown(&self) -> u64 {
+        self.unknown
+    }
+
     #[inline(never)]
     pub fn load_blocks<R: BufReaderExt>(
         &mut self,
@@ -745,105 +1818,537 @@ impl<T: TagStructure + Debug + Default> FieldBlock<T> {
         reader: &mut R,
         tag_file: &TagFile,
     ) -> Result<()> {
+        let Some(location) = find_block_location(current_block, collection_offset, tag_file) else {
+            return Ok(());
+        };
+        let size = T::default().size();
+        self.size = location.element_count(self.size, size);
+        self.size = location.checked_count(self.size, size, tag_file.block_bounds)?;
         // Empty blocks may cause issues.
         if self.size == 0 {
             return Ok(());
         }
-        let structs = &tag_file.struct_definitions;
-        let blocks = &tag_file.datablock_definitions;
-
-        // This is the "root" of the tag block, pointing to where the metadata for it is stored.
-        // If target index is -1, it's a resource block, which we don't want right now.
-        let block_root = structs.iter().enumerate().find(|(_, s)| {
-            s.field_block == current_block
-                && u64::from(s.field_offset) == collection_offset
-                && s.target_index != -1
-        });
-
-        if let Some(block_struct) = block_root {
+        let BlockLocation {
+            target_index,
+            offset,
+            block_size,
+            ..
+        } = location;
+
+        let length = (size * u64::from(self.size)).min(u64::from(block_size));
+        if is_missing(reader, offset, length)? {
             #[allow(clippy::cast_sign_loss)]
-            let Some(block) = blocks.get(block_struct.1.target_index as usize) else {
-                return Ok(());
-            };
+            match tag_file.missing_resources {
+                MissingResourcePolicy::Error => {
+                    return Err(TagError::DatablockOutOfBounds(target_index as usize).into());
+                }
+                MissingResourcePolicy::Skip => {}
+                MissingResourcePolicy::ZeroFill => {
+                    for _ in 0..self.size {
+                        let mut object = T::default();
+                        read_zeroed(&mut object, &tag_file.version)?;
+                        self.elements.push(object);
+                    }
+                }
+            }
+            self.is_missing = true;
+            return Ok(());
+        }
 
-            let mut offset = block.offset;
+        // We first read the object itself without any of its children, from its own data block so
+        // that fields past the end of the block are not read from the next one.
+        let mut block = reader.sub_reader(offset, u64::from(block_size))?;
+        for _ in 0..self.size {
+            let mut object = T::default();
+            object.read(&mut block, &tag_file.version)?;
+            self.elements.push(object);
+        }
+        drop(block);
 
-            // HACK: Calculate offset using other blocks.
-            let tagdata_size = blocks
-                .iter()
-                .filter(|x| x.section_type == TagSectionType::TagData)
-                .map(|x| x.entry_size)
-                .sum::<u32>();
+        // We then read the children, with the adjusted size parameter depending on the size.
+        for (idx, element) in self.elements.iter_mut().enumerate() {
+            let adjusted_base = size * idx as u64;
+            element.load_field_blocks(target_index, idx, adjusted_base, reader, tag_file)?;
+        }
+        Ok(())
+    }
+}
 
-            if block.section_type == TagSectionType::ResourceData {
-                offset = block.offset + u64::from(tagdata_size);
+/// Checks whether `length` bytes starting at `offset` lie outside of the data being read.
+fn is_missing<R: BufReaderExt>(reader: &mut R, offset: u64, length: u64) -> Result<bool> {
+    Ok(offset + length > reader.stream_length()?)
+}
+
+/// Reads a structure from zeroed data, used for [`MissingResourcePolicy::ZeroFill`].
+fn read_zeroed<T: TagStructure>(object: &mut T, version: &ModuleVersion) -> Result<()> {
+    let size = usize::try_from(object.size())?;
+    let mut reader = BufReader::new(Cursor::new(vec![0u8; size]));
+    object.read(&mut reader, version)
+}
+
+/// Location of the elements of a tag block field.
+struct BlockLocation {
+    /// Index of the data block containing the elements.
+    target_index: i32,
+    /// Offset of the elements from the start of tag data.
+    offset: u64,
+    /// Size of the data block, if the field points to a [`Literal`](`TagStructType::Literal`)
+    /// struct, which does not store an element count.
+    literal_size: Option<u32>,
+    /// Size of the data block.
+    block_size: u32,
+}
+
+impl BlockLocation {
+    /// Number of elements stored at this location.
+    ///
+    /// Literal structs store their data inline, so the count is derived from the size of the data
+    /// block instead of the count stored in the field. A data block shorter than the struct holds
+    /// a single element, see [`checked_count`](`BlockLocation::checked_count`).
+    fn element_count(&self, field_count: u32, element_size: u64) -> u32 {
+        match self.literal_size {
+            Some(literal_size) if u64::from(literal_size) < element_size => {
+                u32::from(literal_size != 0)
             }
-            let size = T::default().size();
-
-            // We first read the object itself without any of its children
-            reader.seek(SeekFrom::Start(offset))?;
-            for _ in 0..self.size {
-                let mut object = T::default();
-                object.read(reader)?;
-                self.elements.push(object);
+            Some(literal_size) if element_size != 0 => {
+                u32::try_from(u64::from(literal_size) / element_size).unwrap_or(u32::MAX)
             }
+            Some(_) => 0,
+            None => field_count,
+        }
+    }
 
-            // We then read the children, with the adjusted size parameter depending on the size.
-            for (idx, element) in self.elements.iter_mut().enumerate() {
-                let adjusted_base = size * idx as u64;
-                element.load_field_blocks(
-                    block_struct.1.target_index,
-                    idx,
-                    adjusted_base,
-                    reader,
-                    tag_file,
-                )?;
+    /// Checks that `count` elements fit in the data block, applying `policy` if they do not.
+    ///
+    /// A single element may be longer than the data block, as builds where the struct was smaller
+    /// store shorter elements. It is read with its `Option` fields past the end of the block left
+    /// empty.
+    fn checked_count(
+        &self,
+        count: u32,
+        element_size: u64,
+        policy: BlockBoundsPolicy,
+    ) -> Result<u32> {
+        let required = u64::from(count) * element_size;
+        if required <= u64::from(self.block_size) || (count == 1 && self.block_size != 0) {
+            return Ok(count);
+        }
+        match policy {
+            #[allow(clippy::cast_sign_loss)]
+            BlockBoundsPolicy::Error => Err(TagError::BlockOutOfBounds {
+                datablock: self.target_index as usize,
+                required,
+                available: self.block_size,
             }
+            .into()),
+            BlockBoundsPolicy::Clamp => {
+                Ok(u32::try_from(u64::from(self.block_size) / element_size).unwrap_or(u32::MAX))
+            }
+        }
+    }
+}
+
+/// Finds the data block containing the elements of a tag block field.
+///
+/// Returns [`None`] if the field does not point to any data block.
+fn find_block_location(
+    current_block: i32,
+    collection_offset: u64,
+    tag_file: &TagFile,
+) -> Option<BlockLocation> {
+    let structs = &tag_file.struct_definitions;
+    let blocks = &tag_file.datablock_definitions;
+
+    // This is the "root" of the tag block, pointing to where the metadata for it is stored.
+    // If target index is -1, it's a resource block, which we don't want right now.
+    let block_struct = structs.iter().find(|s| {
+        s.field_block == current_block
+            && u64::from(s.field_offset) == collection_offset
+            && s.target_index != -1
+    })?;
+
+    #[allow(clippy::cast_sign_loss)]
+    let block = blocks.get(block_struct.target_index as usize)?;
+
+    let mut offset = block.offset;
+
+    // HACK: Calculate offset using other blocks.
+    let tagdata_size = blocks
+        .iter()
+        .filter(|x| x.section_type == TagSectionType::TagData)
+        .map(|x| x.entry_size)
+        .sum::<u32>();
+
+    if block.section_type == TagSectionType::ResourceData {
+        offset = block.offset + u64::from(tagdata_size);
+    }
+    Some(BlockLocation {
+        target_index: block_struct.target_index,
+        offset,
+        literal_size: (block_struct.struct_type == TagStructType::Literal)
+            .then_some(block.entry_size),
+        block_size: block.entry_size,
+    })
+}
+
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _40: Tag block whose elements are only read when accessed.
+///
+/// When reading a structure, only the location of the elements is recorded. Elements can then be
+/// read all at once with [`load`](`LazyFieldBlock::load`), or one at a time with
+/// [`iter_lazy`](`LazyFieldBlock::iter_lazy`). This avoids parsing large blocks that are not needed.
+///
+/// Like [`FieldBlock`], the block dereferences to a slice of the elements that have been loaded.
+pub struct LazyFieldBlock<T: TagStructure> {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    type_info: u64, // uintptr at runtime
+    #[cfg_attr(feature = "serde", serde(skip))]
+    unknown: u64, // uintptr at runtime
+    /// Number of elements in the block.
+    pub size: u32,
+    /// Index of the data block containing the elements, [`None`] if it has not been located.
+    pub datablock_index: Option<usize>,
+    /// Offset of the elements from the start of tag data.
+    pub data_offset: u64,
+    /// Elements of the block, only populated after calling [`load`](`LazyFieldBlock::load`).
+    pub elements: Vec<T>,
+}
+
+/// Runtime pointers and the location of the elements are not compared.
+impl<T: TagStructure + PartialEq> PartialEq for LazyFieldBlock<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size && self.elements == other.elements
+    }
+}
+
+impl<T: TagStructure> LazyFieldBlock<T> {
+    /// Number of elements that have been loaded.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Whether there are no elements that have been loaded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+}
+
+impl<T: TagStructure> Deref for LazyFieldBlock<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        &self.elements
+    }
+}
+
+impl<T: TagStructure> IntoIterator for LazyFieldBlock<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.into_iter()
+    }
+}
+
+impl<'a, T: TagStructure> IntoIterator for &'a LazyFieldBlock<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.iter()
+    }
+}
+
+impl<'a, T: TagStructure> IntoIterator for &'a mut LazyFieldBlock<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.iter_mut()
+    }
+}
+
+impl<T: TagStructure + Debug + Default> LazyFieldBlock<T> {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.type_info = reader.read_u64_ordered()?;
+        self.unknown = reader.read_u64_ordered()?;
+        self.size = reader.read_u32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u64_ordered(self.type_info)?;
+        writer.write_u64_ordered(self.unknown)?;
+        writer.write_u32_ordered(self.size)?;
+        Ok(())
+    }
+
+    /// Runtime pointer to the type information of the field, as stored in the tag.
+    #[must_use]
+    pub fn type_info(&self) -> u64 {
+        self.type_info
+    }
+
+    /// Unknown runtime pointer stored in the field.
+    #[must_use]
+    pub fn unknown(&self) -> u64 {
+        self.unknown
+    }
+
+    /// Records the location of the elements without reading them.
+    pub fn load_blocks<R: BufReaderExt>(
+        &mut self,
+        current_block: i32,
+        collection_offset: u64,
+        _reader: &mut R,
+        tag_file: &TagFile,
+    ) -> Result<()> {
+        let Some(location) = find_block_location(current_block, collection_offset, tag_file) else {
+            return Ok(());
+        };
+        let size = T::default().size();
+        self.size = location.element_count(self.size, size);
+        self.size = location.checked_count(self.size, size, tag_file.block_bounds)?;
+        if self.size != 0 {
+            self.datablock_index = Some(usize::try_from(location.target_index)?);
+            self.data_offset = location.offset;
         }
         Ok(())
     }
+
+    /// Whether the elements have been read into [`elements`](`LazyFieldBlock::elements`).
+    #[must_use]
+    pub fn is_loaded(&self) -> bool {
+        self.elements.len() == self.size as usize
+    }
+
+    /// Reads all elements of the block, including their children, into
+    /// [`elements`](`LazyFieldBlock::elements`).
+    ///
+    /// # Arguments
+    ///
+    /// * `tag_file` - Tag structure the block was read from.
+    /// * `data` - Bytes of the entire tag file (including header).
+    ///
+    /// # Errors
+    /// - If the reader fails to read the exact number of bytes [`ReadError`](`crate::Error::ReadError`)
+    pub fn load(&mut self, tag_file: &TagFile, data: &[u8]) -> Result<()> {
+        if self.is_loaded() {
+            return Ok(());
+        }
+        self.elements = self.iter_lazy(tag_file, data).collect::<Result<Vec<_>>>()?;
+        Ok(())
+    }
+
+    /// Returns an iterator reading each element of the block (including its children) when it is
+    /// accessed. Elements are not stored in [`elements`](`LazyFieldBlock::elements`).
+    ///
+    /// # Arguments
+    ///
+    /// * `tag_file` - Tag structure the block was read from.
+    /// * `data` - Bytes of the entire tag file (including header).
+    pub fn iter_lazy<'a>(
+        &'a self,
+        tag_file: &'a TagFile,
+        data: &'a [u8],
+    ) -> impl Iterator<Item = Result<T>> + 'a {
+        let tag_data = data
+            .get(tag_file.header.header_size as usize..)
+            .unwrap_or_default();
+        let mut reader = BufReader::new(Cursor::new(tag_data));
+        let datablock = self
+            .datablock_index
+            .and_then(|index| tag_file.datablock_definitions.get(index));
+        let count = if datablock.is_some() {
+            self.size as usize
+        } else {
+            0
+        };
+        let block_size = datablock.map_or(0, |datablock| u64::from(datablock.entry_size));
+        (0..count).map(move |idx| {
+            let mut element = T::default();
+            let adjusted_base = element.size() * idx as u64;
+            // Elements are read from their own data block, see `FieldBlock::load_blocks`.
+            let mut block = reader.sub_reader(self.data_offset, block_size)?;
+            block.seek(SeekFrom::Start(adjusted_base))?;
+            element.read(&mut block, &tag_file.version)?;
+            drop(block);
+            #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+            let target_index = self.datablock_index.unwrap_or_default() as i32;
+            element.load_field_blocks(target_index, idx, adjusted_base, &mut reader, tag_file)?;
+            Ok(element)
+        })
+    }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _41: Reference to an external tag.
 pub struct FieldReference {
+    #[cfg_attr(feature = "serde", serde(skip))]
     type_info: u64, // uintptr at runtime
-    pub global_id: i32,
+    pub global_id: TagId,
     pub asset_id: u64,
     pub group: String,
     local_handle: i32,
 }
 
+/// Runtime pointers and handles are not compared.
+impl PartialEq for FieldReference {
+    fn eq(&self, other: &Self) -> bool {
+        self.global_id == other.global_id
+            && self.asset_id == other.asset_id
+            && self.group == other.group
+    }
+}
+
 impl FieldReference {
     pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
-        self.type_info = reader.read_u64::<LE>()?;
-        self.global_id = reader.read_i32::<LE>()?;
-        self.asset_id = reader.read_u64::<LE>()?;
+        self.type_info = reader.read_u64_ordered()?;
+        self.global_id = TagId(reader.read_i32_ordered()?);
+        self.asset_id = reader.read_u64_ordered()?;
         self.group = reader.read_fixed_string(4)?.chars().rev().collect(); // reverse string
-        self.local_handle = reader.read_i32::<LE>()?;
+        self.local_handle = reader.read_i32_ordered()?;
         Ok(())
     }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u64_ordered(self.type_info)?;
+        writer.write_i32_ordered(self.global_id.0)?;
+        writer.write_u64_ordered(self.asset_id)?;
+        if self.group.is_empty() {
+            writer.write_all(&[0xFF; 4])?;
+        } else {
+            let group: String = self.group.chars().rev().collect();
+            write_fixed_string(writer, &group, 4)?;
+        }
+        writer.write_i32_ordered(self.local_handle)?;
+        Ok(())
+    }
+
+    /// Runtime pointer to the type information of the field, as stored in the tag.
+    #[must_use]
+    pub fn type_info(&self) -> u64 {
+        self.type_info
+    }
+
+    /// Runtime handle of the referenced tag, as stored in the tag.
+    #[must_use]
+    pub fn local_handle(&self) -> i32 {
+        self.local_handle
+    }
+
+    /// Finds the referenced tag in a collection of modules, and loads it.
+    ///
+    /// The tag is located using [`global_id`](`FieldReference::global_id`), as modules do not
+    /// store the asset ID of their tags.
+    ///
+    /// # Returns
+    ///
+    /// Returns the handle of the loaded tag, or [`None`] if the reference is null, the tag is not
+    /// present in any module, or it cannot be read (see [`ModuleFile::read_tag`](`crate::ModuleFile::read_tag`)).
+    ///
+    /// # Errors
+    /// - If reading the tag fails, see [`ModuleFile::read_tag`](`crate::ModuleFile::read_tag`)
+    pub fn resolve(&self, collection: &mut ModuleCollection) -> Result<Option<TagHandle>> {
+        let Some(handle) = collection.find_tag(self.global_id) else {
+            return Ok(None);
+        };
+        Ok(collection.read_tag(handle)?.map(|_| handle))
+    }
+
+    /// Describes the reference like [`Display`], followed by the name of the referenced tag if it
+    /// is found in a collection of modules.
+    ///
+    /// The tag is not loaded, only looked up with
+    /// [`find_tag`](`ModuleCollection::find_tag`).
+    #[must_use]
+    pub fn describe(&self, collection: &ModuleCollection) -> String {
+        collection
+            .find_tag(self.global_id)
+            .and_then(|handle| collection.get(handle))
+            .map_or_else(
+                || self.to_string(),
+                |file| format!("{self} ({})", file.tag_name),
+            )
+    }
 }
 
-#[derive(Default, Debug)]
+/// Displays the reference as `group:global_id`, or `none` for null references.
+///
+/// # Examples
+///
+/// ```
+/// use infinite_rs::TagId;
+/// use infinite_rs::tag::types::common_types::FieldReference;
+///
+/// let mut reference = FieldReference::default();
+/// reference.group = String::from("mat ");
+/// reference.global_id = TagId(0x1234);
+/// assert_eq!(reference.to_string(), "mat :0x00001234");
+/// ```
+impl Display for FieldReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.global_id.is_null() {
+            write!(f, "none")
+        } else {
+            write!(f, "{}:{}", self.group, self.global_id)
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _42: "External" resource inside tag.
 pub struct FieldData {
+    #[cfg_attr(feature = "serde", serde(skip))]
     data_pointer: u64, // uintptr at runtime
-    type_info: u64,    // uintptr at runtime
+    #[cfg_attr(feature = "serde", serde(skip))]
+    type_info: u64, // uintptr at runtime
     pub unknown: u32,
     pub size: u32,
     pub data: Vec<u8>,
 }
 
+/// Runtime pointers are not compared.
+impl PartialEq for FieldData {
+    fn eq(&self, other: &Self) -> bool {
+        self.unknown == other.unknown && self.size == other.size && self.data == other.data
+    }
+}
+
 impl FieldData {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.data_pointer = reader.read_u64::<LE>()?;
-        self.type_info = reader.read_u64::<LE>()?;
-        self.unknown = reader.read_u32::<LE>()?;
-        self.size = reader.read_u32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.data_pointer = reader.read_u64_ordered()?;
+        self.type_info = reader.read_u64_ordered()?;
+        self.unknown = reader.read_u32_ordered()?;
+        self.size = reader.read_u32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u64_ordered(self.data_pointer)?;
+        writer.write_u64_ordered(self.type_info)?;
+        writer.write_u32_ordered(self.unknown)?;
+        writer.write_u32_ordered(self.size)?;
         Ok(())
     }
 
+    /// Runtime pointer to the data, as stored in the tag.
+    #[must_use]
+    pub fn data_pointer(&self) -> u64 {
+        self.data_pointer
+    }
+
+    /// Runtime pointer to the type information of the field, as stored in the tag.
+    #[must_use]
+    pub fn type_info(&self) -> u64 {
+        self.type_info
+    }
+
     pub fn load_data<R: BufReaderExt>(
         &mut self,
         reader: &mut R,
@@ -861,12 +2366,11 @@ impl FieldData {
                 let datablock = &tag_file
                     .datablock_definitions
                     .get(usize::try_from(reference.target_index)?);
-                let position = reader.stream_position()?;
                 if let Some(datablock) = datablock {
-                    reader.seek(SeekFrom::Start(datablock.get_offset(tag_file)))?;
+                    let mut data =
+                        reader.sub_reader(datablock.get_offset(tag_file), u64::from(self.size))?;
                     let mut buf = vec![0; self.size as usize];
-                    reader.read_exact(&mut buf)?;
-                    reader.seek(SeekFrom::Start(position))?;
+                    data.read_exact(&mut buf)?;
                     self.data = buf;
                 }
             }
@@ -876,25 +2380,191 @@ impl FieldData {
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _42: "External" resource inside tag, whose bytes are not copied when reading.
+///
+/// When reading a structure, only the location of the data inside the tag buffer is recorded.
+/// The data can then be borrowed with [`as_slice`](`LazyFieldData::as_slice`), or streamed with
+/// [`as_reader`](`LazyFieldData::as_reader`) and [`read_into`](`LazyFieldData::read_into`), which
+/// avoids duplicating large payloads such as scripts or havok data.
+pub struct LazyFieldData {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    data_pointer: u64, // uintptr at runtime
+    #[cfg_attr(feature = "serde", serde(skip))]
+    type_info: u64, // uintptr at runtime
+    pub unknown: u32,
+    pub size: u32,
+    /// Index of the data block containing the data, [`None`] for null references.
+    pub datablock_index: Option<usize>,
+    /// Range of the data in the entire tag buffer (including header).
+    pub range: Range<usize>,
+}
+
+/// Runtime pointers are not compared.
+impl PartialEq for LazyFieldData {
+    fn eq(&self, other: &Self) -> bool {
+        self.unknown == other.unknown
+            && self.size == other.size
+            && self.datablock_index == other.datablock_index
+            && self.range == other.range
+    }
+}
+
+impl LazyFieldData {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.data_pointer = reader.read_u64_ordered()?;
+        self.type_info = reader.read_u64_ordered()?;
+        self.unknown = reader.read_u32_ordered()?;
+        self.size = reader.read_u32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u64_ordered(self.data_pointer)?;
+        writer.write_u64_ordered(self.type_info)?;
+        writer.write_u32_ordered(self.unknown)?;
+        writer.write_u32_ordered(self.size)?;
+        Ok(())
+    }
+
+    /// Runtime pointer to the data, as stored in the tag.
+    #[must_use]
+    pub fn data_pointer(&self) -> u64 {
+        self.data_pointer
+    }
+
+    /// Runtime pointer to the type information of the field, as stored in the tag.
+    #[must_use]
+    pub fn type_info(&self) -> u64 {
+        self.type_info
+    }
+
+    /// Records the location of the data without reading it.
+    pub fn load_data<R: BufReaderExt>(
+        &mut self,
+        _reader: &mut R,
+        parent_index: i32,
+        parent_struct_index: usize,
+        tag_file: &TagFile,
+    ) -> Result<()> {
+        let reference = tag_file
+            .data_references
+            .iter()
+            .filter(|x| x.field_block == parent_index)
+            .nth(parent_struct_index);
+        if let Some(reference) = reference
+            && let Some(datablock) = tag_file.get_datablock(reference.target_index)
+        {
+            let start = tag_file.get_datablock_range(datablock).start;
+            self.datablock_index = Some(usize::try_from(reference.target_index)?);
+            self.range = start..start.saturating_add(self.size as usize);
+        }
+        Ok(())
+    }
+
+    /// Borrows the data from the tag buffer. Null references return an empty slice.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Bytes of the entire tag file (including header).
+    ///
+    /// # Errors
+    /// - If the data lies outside of the tag buffer [`TagError::DatablockOutOfBounds`]
+    pub fn as_slice<'a>(&self, data: &'a [u8]) -> Result<&'a [u8]> {
+        let Some(datablock_index) = self.datablock_index else {
+            return Ok(&[]);
+        };
+        data.get(self.range.clone())
+            .ok_or_else(|| TagError::DatablockOutOfBounds(datablock_index).into())
+    }
+
+    /// Creates a reader over the data in the tag buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Bytes of the entire tag file (including header).
+    ///
+    /// # Errors
+    /// - If the data lies outside of the tag buffer [`TagError::DatablockOutOfBounds`]
+    pub fn as_reader<'a>(&self, data: &'a [u8]) -> Result<Cursor<&'a [u8]>> {
+        Ok(Cursor::new(self.as_slice(data)?))
+    }
+
+    /// Writes the data from the tag buffer into a writer, returning the number of bytes written.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Bytes of the entire tag file (including header).
+    /// * `writer` - Writer to copy the data into.
+    ///
+    /// # Errors
+    /// - If the data lies outside of the tag buffer [`TagError::DatablockOutOfBounds`]
+    /// - If the writer fails to write the data [`ReadError`](`crate::Error::ReadError`)
+    pub fn read_into<W: Write>(&self, data: &[u8], writer: &mut W) -> Result<u64> {
+        let slice = self.as_slice(data)?;
+        writer.write_all(slice)?;
+        Ok(slice.len() as u64)
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// _43: Reference to tag resource.
 pub struct FieldTagResource<T: TagStructure> {
+    #[cfg_attr(feature = "serde", serde(skip))]
     block: u64, // uintptr at runtime
     handle: u32,
     pub resource_index: u32,
     pub data: T,
+    /// Whether the resource points to data that is not loaded, see [`MissingResourcePolicy`].
+    pub is_missing: bool,
+}
+
+/// Runtime pointers and handles are not compared.
+impl<T: TagStructure + PartialEq> PartialEq for FieldTagResource<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.resource_index == other.resource_index
+            && self.data == other.data
+            && self.is_missing == other.is_missing
+    }
 }
 
 impl<T: TagStructure + Debug> FieldTagResource<T> {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.block = reader.read_u64::<LE>()?;
-        self.handle = reader.read_u32::<LE>()?;
-        self.resource_index = reader.read_u32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.block = reader.read_u64_ordered()?;
+        self.handle = reader.read_u32_ordered()?;
+        self.resource_index = reader.read_u32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u64_ordered(self.block)?;
+        writer.write_u32_ordered(self.handle)?;
+        writer.write_u32_ordered(self.resource_index)?;
         Ok(())
     }
 
+    /// Runtime pointer to the resource block, as stored in the tag.
+    #[must_use]
+    pub fn block(&self) -> u64 {
+        self.block
+    }
+
+    /// Runtime handle of the resource, as stored in the tag.
+    #[must_use]
+    pub fn handle(&self) -> u32 {
+        self.handle
+    }
+
+    /// Reads the resource structure pointed to by this field.
+    ///
+    /// The resource is located using the data block containing the field (`current_block`) and the
+    /// offset of the field inside it, so tags with several resource fields, or resources inside
+    /// blocks and arrays, each read their own resource.
     pub fn load_resource<R: BufReaderExt>(
         &mut self,
+        current_block: i32,
         adjusted_base: u64,
         reader: &mut R,
         tag_file: &TagFile,
@@ -904,7 +2574,9 @@ impl<T: TagStructure + Debug> FieldTagResource<T> {
             .iter()
             .enumerate()
             .find(|(_, s)| {
-                s.struct_type == TagStructType::Custom && u64::from(s.field_offset) == adjusted_base
+                s.struct_type == TagStructType::Custom
+                    && s.field_block == current_block
+                    && u64::from(s.field_offset) == adjusted_base
             });
         if let Some(resource) = resource {
             let datablock = &tag_file
@@ -913,8 +2585,27 @@ impl<T: TagStructure + Debug> FieldTagResource<T> {
             let position = reader.stream_position()?;
             if let Some(datablock) = datablock {
                 let datablock_location = datablock.get_offset(tag_file);
-                reader.seek(SeekFrom::Start(datablock_location))?;
-                self.data.read(reader)?;
+                if is_missing(reader, datablock_location, self.data.size())? {
+                    #[allow(clippy::cast_sign_loss)]
+                    match tag_file.missing_resources {
+                        MissingResourcePolicy::Error => {
+                            return Err(TagError::DatablockOutOfBounds(
+                                resource.1.target_index as usize,
+                            )
+                            .into());
+                        }
+                        MissingResourcePolicy::Skip => {}
+                        MissingResourcePolicy::ZeroFill => {
+                            read_zeroed(&mut self.data, &tag_file.version)?;
+                        }
+                    }
+                    self.is_missing = true;
+                    return Ok(());
+                }
+                self.data.read(
+                    &mut reader.sub_reader(datablock_location, u64::from(datablock.entry_size))?,
+                    &tag_file.version,
+                )?;
                 self.data.load_field_blocks(
                     resource.1.target_index,
                     resource.0,
@@ -929,22 +2620,73 @@ impl<T: TagStructure + Debug> FieldTagResource<T> {
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// _44: Handle to a graphics API object (such as a D3D buffer), only valid at runtime.
+pub struct FieldApiInterop {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    descriptor: u64, // uintptr at runtime
+    #[cfg_attr(feature = "serde", serde(skip))]
+    address: u64, // uintptr at runtime
+}
+
+/// Runtime pointers are not compared.
+impl PartialEq for FieldApiInterop {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl FieldApiInterop {
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.descriptor = reader.read_u64_ordered()?;
+        self.address = reader.read_u64_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u64_ordered(self.descriptor)?;
+        writer.write_u64_ordered(self.address)?;
+        Ok(())
+    }
+
+    /// Runtime descriptor of the API object, as stored in the tag.
+    #[must_use]
+    pub fn descriptor(&self) -> u64 {
+        self.descriptor
+    }
+
+    /// Runtime address of the API object, as stored in the tag.
+    #[must_use]
+    pub fn address(&self) -> u64 {
+        self.address
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// "Internal struct" of `AnyTag` field.
 pub struct AnyTagGuts {
-    pub tag_id: i32,
+    pub tag_id: TagId,
     pub local_tag_handle: i32,
 }
 
 impl AnyTagGuts {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.tag_id = reader.read_i32::<LE>()?;
-        self.local_tag_handle = reader.read_i32::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.tag_id = TagId(reader.read_i32_ordered()?);
+        self.local_tag_handle = reader.read_i32_ordered()?;
+        Ok(())
+    }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i32_ordered(self.tag_id.0)?;
+        writer.write_i32_ordered(self.local_tag_handle)?;
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// `AnyTag` is present in all non-resource tags.
 /// Is used at runtime to calculate locations of tags in memory.
 pub struct AnyTag {
@@ -952,10 +2694,29 @@ pub struct AnyTag {
     pub internal_struct: AnyTagGuts,
 }
 
+/// The runtime vtable pointer is not compared.
+impl PartialEq for AnyTag {
+    fn eq(&self, other: &Self) -> bool {
+        self.internal_struct == other.internal_struct
+    }
+}
+
 impl AnyTag {
-    pub fn read<R: BufRead>(&mut self, reader: &mut R) -> Result<()> {
-        self.vtable_space = reader.read_u64::<LE>()?;
+    pub fn read<R: BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.vtable_space = reader.read_u64_ordered()?;
         self.internal_struct.read(reader)?;
         Ok(())
     }
+
+    pub fn write<W: WriterExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u64_ordered(self.vtable_space)?;
+        self.internal_struct.write(writer)?;
+        Ok(())
+    }
+
+    /// Space reserved for the runtime vtable pointer of the tag.
+    #[must_use]
+    pub fn vtable_space(&self) -> u64 {
+        self.vtable_space
+    }
 }
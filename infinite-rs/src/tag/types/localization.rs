@@ -0,0 +1,289 @@
+//! Unicode string list (`unic`) tags, containing the localized text of the user interface.
+//!
+//! Each string of a tag is identified by a string id, and has an offset per [`Language`] into the
+//! [`string_data`](`UnicodeStringListTag::string_data`) of the tag, where its text is stored as
+//! null-terminated UTF-8. A [`StringCatalog`] gathers the strings of any number of tags, so that
+//! they can be looked up by string id and language.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use infinite_rs::ModuleFile;
+//! use infinite_rs::tag::types::localization::{Language, StringCatalog, UnicodeStringListTag};
+//!
+//! let mut module = ModuleFile::from_path("deploy/any/globals/globals-rtx-new.module").unwrap();
+//! let mut catalog = StringCatalog::new();
+//! for index in 0..module.files.len() as u32 {
+//!     if module.files[index as usize].tag_group == "unic" {
+//!         let tag = module.read_tag(index).unwrap().unwrap();
+//!         catalog.extend_from_tag(&tag.read_metadata::<UnicodeStringListTag>().unwrap()).unwrap();
+//!     }
+//! }
+//! println!("{:?}", catalog.get_by_name("play", Language::EnUs));
+//! ```
+
+use std::collections::HashMap;
+
+use super::common_types::{AnyTag, FieldBlock, FieldData, FieldLongInteger, FieldStringId};
+use crate::Result;
+use crate::common::errors::TagError;
+use crate::common::hash::murmur3_x86_32;
+use infinite_rs_derive::TagStructure;
+
+/// Number of languages strings are localized in.
+pub const LANGUAGE_COUNT: usize = 17;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[repr(u8)]
+/// Language of a localized string, in the order of the offsets of a [`UnicodeStringReference`].
+pub enum Language {
+    /// English (United States).
+    EnUs,
+    /// Japanese.
+    Japanese,
+    /// German.
+    German,
+    /// French.
+    French,
+    /// Spanish (Spain).
+    Spanish,
+    /// Spanish (Mexico).
+    MexicanSpanish,
+    /// Italian.
+    Italian,
+    /// Korean.
+    Korean,
+    /// Chinese (Traditional).
+    ChineseTraditional,
+    /// Chinese (Simplified).
+    ChineseSimplified,
+    /// Portuguese (Brazil).
+    Portuguese,
+    /// Polish.
+    Polish,
+    /// Russian.
+    Russian,
+    /// Danish.
+    Danish,
+    /// Finnish.
+    Finnish,
+    /// Dutch.
+    Dutch,
+    /// Norwegian.
+    Norwegian,
+}
+
+impl Language {
+    /// Every language, in order.
+    pub const ALL: [Self; LANGUAGE_COUNT] = [
+        Self::EnUs,
+        Self::Japanese,
+        Self::German,
+        Self::French,
+        Self::Spanish,
+        Self::MexicanSpanish,
+        Self::Italian,
+        Self::Korean,
+        Self::ChineseTraditional,
+        Self::ChineseSimplified,
+        Self::Portuguese,
+        Self::Polish,
+        Self::Russian,
+        Self::Danish,
+        Self::Finnish,
+        Self::Dutch,
+        Self::Norwegian,
+    ];
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x48), write)]
+/// Localized string of a unicode string list (`string_reference`).
+pub struct UnicodeStringReference {
+    /// String id identifying the string.
+    #[data(offset(0x00))]
+    pub string_id: FieldStringId,
+    /// Offset of the text of each [`Language`] in the string data of the tag, or -1.
+    #[data(offset(0x04))]
+    pub offsets: [FieldLongInteger; LANGUAGE_COUNT],
+}
+
+impl UnicodeStringReference {
+    /// Gets the offset of the text in the given language, if the string is localized in it.
+    #[must_use]
+    pub fn offset(&self, language: Language) -> Option<usize> {
+        usize::try_from(self.offsets[language as usize].0).ok()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x3C), write)]
+/// Root structure of unicode string list tags (`multilingual_unicode_string_list`).
+pub struct UnicodeStringListTag {
+    /// Runtime information of the tag.
+    #[data(offset(0x00))]
+    pub any_tag: AnyTag,
+    /// Strings of the tag.
+    #[data(offset(0x10))]
+    pub strings: FieldBlock<UnicodeStringReference>,
+    /// Null-terminated UTF-8 text of every string, in every language.
+    #[data(offset(0x24))]
+    pub string_data: FieldData,
+}
+
+impl UnicodeStringListTag {
+    /// Gets the text of a string in the given language, if it is localized in it.
+    ///
+    /// # Errors
+    /// - If the offset of the text is outside of the string data [`TagError::BufferOutOfBounds`]
+    pub fn text(
+        &self,
+        string: &UnicodeStringReference,
+        language: Language,
+    ) -> Result<Option<String>> {
+        let Some(offset) = string.offset(language) else {
+            return Ok(None);
+        };
+        let data = self
+            .string_data
+            .data
+            .get(offset..)
+            .filter(|data| !data.is_empty())
+            .ok_or(TagError::BufferOutOfBounds {
+                offset: offset as u64,
+                size: 1,
+                available: self.string_data.data.len() as u64,
+            })?;
+        let end = data
+            .iter()
+            .position(|&byte| byte == 0)
+            .unwrap_or(data.len());
+        Ok(Some(String::from_utf8_lossy(&data[..end]).into_owned()))
+    }
+}
+
+/// Text of a string in every language, [`None`] where it is not localized.
+type LocalizedText = [Option<String>; LANGUAGE_COUNT];
+
+#[derive(Default, Debug, Clone)]
+/// Localized strings of any number of unicode string list tags, keyed by string id.
+///
+/// # Examples
+///
+/// ```
+/// use infinite_rs::tag::types::localization::{Language, StringCatalog};
+///
+/// let mut catalog = StringCatalog::new();
+/// let string_id = catalog.insert("play", Language::EnUs, "Play");
+/// assert_eq!(catalog.get(string_id, Language::EnUs), Some("Play"));
+/// assert_eq!(catalog.get_by_name("play", Language::EnUs), Some("Play"));
+/// assert_eq!(catalog.get(string_id, Language::French), None);
+/// ```
+pub struct StringCatalog {
+    /// Text of every string, keyed by string id.
+    strings: HashMap<i32, LocalizedText>,
+}
+
+impl StringCatalog {
+    /// Creates an empty catalog.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a catalog from the strings of a tag.
+    ///
+    /// # Errors
+    /// - If the offset of a string is outside of the string data [`TagError::BufferOutOfBounds`]
+    pub fn from_tag(tag: &UnicodeStringListTag) -> Result<Self> {
+        let mut catalog = Self::new();
+        catalog.extend_from_tag(tag)?;
+        Ok(catalog)
+    }
+
+    /// Adds the strings of a tag to the catalog. Strings already in the catalog are replaced in
+    /// the languages the tag localizes them in.
+    ///
+    /// # Errors
+    /// - If the offset of a string is outside of the string data [`TagError::BufferOutOfBounds`]
+    pub fn extend_from_tag(&mut self, tag: &UnicodeStringListTag) -> Result<()> {
+        for string in &tag.strings {
+            let entry = self.strings.entry(string.string_id.0).or_default();
+            for language in Language::ALL {
+                if let Some(text) = tag.text(string, language)? {
+                    entry[language as usize] = Some(text);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Hashes a name and adds its text in the given language, returning its string id.
+    pub fn insert(&mut self, name: &str, language: Language, text: &str) -> i32 {
+        #[allow(clippy::cast_possible_wrap)]
+        let string_id = murmur3_x86_32(name.as_bytes(), 0) as i32;
+        self.strings.entry(string_id).or_default()[language as usize] = Some(text.to_string());
+        string_id
+    }
+
+    /// Gets the text of a string in the given language.
+    #[must_use]
+    pub fn get(&self, string_id: i32, language: Language) -> Option<&str> {
+        self.strings.get(&string_id)?[language as usize].as_deref()
+    }
+
+    /// Gets the text of a string from its name, which is hashed into its string id.
+    #[must_use]
+    pub fn get_by_name(&self, name: &str, language: Language) -> Option<&str> {
+        #[allow(clippy::cast_possible_wrap)]
+        self.get(murmur3_x86_32(name.as_bytes(), 0) as i32, language)
+    }
+
+    /// Number of strings in the catalog.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Whether the catalog contains no strings.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Verifies that strings of a tag are decoded per language, and that missing text is skipped.
+    fn test_extend_from_tag() {
+        let mut reference = UnicodeStringReference {
+            string_id: FieldStringId(7),
+            offsets: Default::default(),
+        };
+        for offset in &mut reference.offsets {
+            offset.0 = -1;
+        }
+        reference.offsets[Language::EnUs as usize] = FieldLongInteger(0);
+        reference.offsets[Language::French as usize] = FieldLongInteger(5);
+
+        let mut tag = UnicodeStringListTag::default();
+        tag.strings.size = 1;
+        tag.strings.elements = vec![reference];
+        tag.string_data.data = b"Play\0Jouer\0".to_vec();
+
+        let catalog = StringCatalog::from_tag(&tag).unwrap();
+        assert_eq!(catalog.len(), 1);
+        assert_eq!(catalog.get(7, Language::EnUs), Some("Play"));
+        assert_eq!(catalog.get(7, Language::French), Some("Jouer"));
+        assert_eq!(catalog.get(7, Language::German), None);
+
+        tag.strings.elements[0].offsets[Language::German as usize] = FieldLongInteger(64);
+        assert!(StringCatalog::from_tag(&tag).is_err());
+    }
+}
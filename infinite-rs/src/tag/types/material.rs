@@ -0,0 +1,414 @@
+//! Material (`mat `) tags, and resolution of their styles into a flattened description.
+//!
+//! A material lists the textures passed to its shader as parameters, and can be layered with
+//! coatings through its style info. Each style info points to a material style (`mwsy`) tag,
+//! whose regions list the swatches of each layer by name. The swatches themselves are found in
+//! the material palette (`mwpl`) of the style, which points to the material swatch (`mwsw`) tags
+//! containing the textures of each layer.
+//!
+//! These tags are usually stored in different modules, so [`MaterialTag::resolve`] follows the
+//! references through a [`ModuleCollection`] and returns a [`MaterialDescription`] listing the
+//! textures and layers of the material.
+//!
+//! Only the fields required to resolve the textures of a material are exposed.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use infinite_rs::module::collection::ModuleCollection;
+//! use infinite_rs::tag::types::material::MaterialTag;
+//!
+//! let mut modules = ModuleCollection::from_paths([
+//!     "deploy/any/globals/forge/forge_objects-rtx-new.module",
+//!     "deploy/any/globals/globals-rtx-new.module",
+//! ])
+//! .unwrap();
+//! let index = modules.modules[0].files.iter().position(|file| file.tag_group == "mat ").unwrap();
+//! let tag = modules.modules[0].read_tag(index as u32).unwrap().unwrap();
+//! let material = tag.read_metadata::<MaterialTag>().unwrap();
+//! let description = material.resolve(&mut modules).unwrap();
+//! for texture in &description.textures {
+//!     println!("{:?}: {}", texture.parameter, texture.bitmap);
+//! }
+//! for layer in &description.layers {
+//!     println!("{:?}: {:?}", layer.swatch_name, layer.color_gradient_map);
+//! }
+//! ```
+
+use super::common_types::{
+    AnyTag, FieldBlock, FieldCharInteger, FieldLongInteger, FieldReference, FieldStringId,
+};
+use crate::Result;
+use crate::TagId;
+use crate::module::{collection::ModuleCollection, file::TagStructure};
+use infinite_rs_derive::TagStructure;
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x74), write)]
+/// Parameter passed to the shader of a material (`material_parameter`).
+pub struct MaterialParameter {
+    /// Name of the parameter in the shader.
+    #[data(offset(0x00))]
+    pub parameter_name: FieldStringId,
+    /// Type of the parameter, such as bitmap, real or color.
+    #[data(offset(0x04))]
+    pub parameter_type: FieldLongInteger,
+    /// Bitmap bound to the parameter, null if the parameter is not a bitmap.
+    #[data(offset(0x08))]
+    pub bitmap: FieldReference,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x5C), write)]
+/// Coating applied to a material (`material_style_info`).
+pub struct MaterialStyleInfo {
+    /// Material style (`mwsy`) tag of the coating.
+    #[data(offset(0x00))]
+    pub material_style: FieldReference,
+    /// Region of the material style applied to the material.
+    #[data(offset(0x1C))]
+    pub region_name: FieldStringId,
+    /// Number of layers of the region used by the material.
+    #[data(offset(0x3C))]
+    pub supported_layers: FieldCharInteger,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x88), write)]
+/// Root structure of material tags (`material`).
+pub struct MaterialTag {
+    /// Runtime information of the tag.
+    #[data(offset(0x00))]
+    pub any_tag: AnyTag,
+    /// Shader (`msh `) tag rendering the material.
+    #[data(offset(0x10))]
+    pub material_shader: FieldReference,
+    /// Parameters passed to the shader.
+    #[data(offset(0x2C))]
+    pub material_parameters: FieldBlock<MaterialParameter>,
+    /// Coatings applied to the material.
+    #[data(offset(0x6C))]
+    pub style_info: FieldBlock<MaterialStyleInfo>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x4C), write)]
+/// Layer of a material style region (`material_style_layer`).
+pub struct MaterialStyleLayer {
+    /// Name of the swatch of the layer in the palette of the style.
+    #[data(offset(0x00))]
+    pub swatch_name: FieldStringId,
+    /// Color variant of the swatch, or 0 for its default colors.
+    #[data(offset(0x04))]
+    pub color_variant: FieldStringId,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x18), write)]
+/// Region of a material style (`material_style_region`).
+pub struct MaterialStyleRegion {
+    /// Name of the region, matched against [`MaterialStyleInfo::region_name`].
+    #[data(offset(0x00))]
+    pub name: FieldStringId,
+    /// Layers of the region, from the bottom to the top.
+    #[data(offset(0x04))]
+    pub layers: FieldBlock<MaterialStyleLayer>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x40), write)]
+/// Root structure of material style tags (`material_styles`).
+pub struct MaterialStyleTag {
+    /// Runtime information of the tag.
+    #[data(offset(0x00))]
+    pub any_tag: AnyTag,
+    /// Material palette (`mwpl`) tag containing the swatches of the style.
+    #[data(offset(0x10))]
+    pub palette: FieldReference,
+    /// Regions of the style.
+    #[data(offset(0x2C))]
+    pub regions: FieldBlock<MaterialStyleRegion>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x24), write)]
+/// Swatch of a material palette (`material_palette_swatch`).
+pub struct MaterialPaletteSwatch {
+    /// Name of the swatch, matched against [`MaterialStyleLayer::swatch_name`].
+    #[data(offset(0x00))]
+    pub name: FieldStringId,
+    /// Color variant of the swatch, matched against [`MaterialStyleLayer::color_variant`].
+    #[data(offset(0x04))]
+    pub color_variant: FieldStringId,
+    /// Material swatch (`mwsw`) tag containing the textures of the swatch.
+    #[data(offset(0x08))]
+    pub swatch: FieldReference,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x24), write)]
+/// Root structure of material palette tags (`material_palette`).
+pub struct MaterialPaletteTag {
+    /// Runtime information of the tag.
+    #[data(offset(0x00))]
+    pub any_tag: AnyTag,
+    /// Swatches of the palette.
+    #[data(offset(0x10))]
+    pub swatches: FieldBlock<MaterialPaletteSwatch>,
+}
+
+impl MaterialPaletteTag {
+    /// Finds the swatch used by a layer, preferring the color variant of the layer and falling
+    /// back to any swatch with the same name.
+    #[must_use]
+    pub fn swatch(&self, layer: &MaterialStyleLayer) -> Option<&MaterialPaletteSwatch> {
+        let mut swatches = self
+            .swatches
+            .iter()
+            .filter(|swatch| swatch.name == layer.swatch_name);
+        let first = swatches.clone().next();
+        swatches
+            .find(|swatch| swatch.color_variant == layer.color_variant)
+            .or(first)
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x80), write)]
+/// Root structure of material swatch tags (`material_swatch`).
+pub struct MaterialSwatchTag {
+    /// Runtime information of the tag.
+    #[data(offset(0x00))]
+    pub any_tag: AnyTag,
+    /// Bitmap mapping the layer mask to colors.
+    #[data(offset(0x10))]
+    pub color_gradient_map: FieldReference,
+    /// Bitmap adding detail to the normals of the layer.
+    #[data(offset(0x34))]
+    pub normal_detail_map: FieldReference,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// Bitmap bound to a parameter of a material.
+pub struct MaterialTexture {
+    /// Name of the parameter in the shader.
+    pub parameter: FieldStringId,
+    /// Bitmap (`bitm`) tag bound to the parameter.
+    pub bitmap: TagId,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// Layer of a coating applied to a material.
+pub struct MaterialLayer {
+    /// Region of the material style the layer belongs to.
+    pub region: FieldStringId,
+    /// Name of the swatch of the layer.
+    pub swatch_name: FieldStringId,
+    /// Color variant of the swatch.
+    pub color_variant: FieldStringId,
+    /// Material swatch (`mwsw`) tag of the layer, [`None`] if it is not in the palette.
+    pub swatch: Option<TagId>,
+    /// Bitmap mapping the layer mask to colors, [`None`] if the swatch could not be loaded.
+    pub color_gradient_map: Option<TagId>,
+    /// Bitmap adding detail to the normals, [`None`] if the swatch could not be loaded.
+    pub normal_detail_map: Option<TagId>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// Flattened description of a material, see [`MaterialTag::resolve`].
+pub struct MaterialDescription {
+    /// Shader (`msh `) tag rendering the material.
+    pub shader: TagId,
+    /// Bitmaps bound to the parameters of the material.
+    pub textures: Vec<MaterialTexture>,
+    /// Layers of the coatings applied to the material, from the bottom to the top.
+    pub layers: Vec<MaterialLayer>,
+}
+
+/// Finds and reads a tag by its global ID, if any module of the collection contains it.
+fn read_tag_by_id<T: Default + TagStructure>(
+    collection: &mut ModuleCollection,
+    global_id: TagId,
+) -> Result<Option<T>> {
+    let Some(handle) = collection.find_tag(global_id) else {
+        return Ok(None);
+    };
+    match collection.read_tag(handle)? {
+        Some(tag) => Ok(Some(tag.read_metadata()?)),
+        None => Ok(None),
+    }
+}
+
+/// Lists the layers of a style used by a coating, without loading their swatches.
+fn style_layers(
+    style_info: &MaterialStyleInfo,
+    style: &MaterialStyleTag,
+    palette: Option<&MaterialPaletteTag>,
+) -> Vec<MaterialLayer> {
+    let supported_layers = usize::try_from(style_info.supported_layers.0).unwrap_or_default();
+    style
+        .regions
+        .iter()
+        .filter(|region| region.name == style_info.region_name)
+        .flat_map(|region| {
+            region
+                .layers
+                .iter()
+                .take(supported_layers)
+                .map(|layer| MaterialLayer {
+                    region: region.name.clone(),
+                    swatch_name: layer.swatch_name.clone(),
+                    color_variant: layer.color_variant.clone(),
+                    swatch: palette
+                        .and_then(|palette| palette.swatch(layer))
+                        .map(|swatch| swatch.swatch.global_id)
+                        .filter(|id| !id.is_null()),
+                    color_gradient_map: None,
+                    normal_detail_map: None,
+                })
+        })
+        .collect()
+}
+
+impl MaterialTag {
+    /// Resolves the textures and coatings of the material.
+    ///
+    /// Follows the material style, palette and swatch tags of every coating through the
+    /// collection. Tags that are not found in the collection, or are stored in an HD1 module that
+    /// is not loaded, are skipped, leaving the corresponding fields of the layers empty.
+    ///
+    /// # Errors
+    /// - If a referenced tag fails to be read, see [`ModuleCollection::read_tag`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use infinite_rs::TagId;
+    /// use infinite_rs::module::collection::ModuleCollection;
+    /// use infinite_rs::tag::types::material::MaterialTag;
+    ///
+    /// let description = MaterialTag::default()
+    ///     .resolve(&mut ModuleCollection::new())
+    ///     .unwrap();
+    /// assert_eq!(description.shader, TagId::default());
+    /// assert!(description.textures.is_empty() && description.layers.is_empty());
+    /// ```
+    pub fn resolve(&self, collection: &mut ModuleCollection) -> Result<MaterialDescription> {
+        let textures = self
+            .material_parameters
+            .iter()
+            .filter(|parameter| !parameter.bitmap.global_id.is_null())
+            .map(|parameter| MaterialTexture {
+                parameter: parameter.parameter_name.clone(),
+                bitmap: parameter.bitmap.global_id,
+            })
+            .collect();
+
+        let mut layers = Vec::new();
+        for style_info in &self.style_info {
+            let Some(style) = read_tag_by_id::<MaterialStyleTag>(
+                collection,
+                style_info.material_style.global_id,
+            )?
+            else {
+                continue;
+            };
+            let palette =
+                read_tag_by_id::<MaterialPaletteTag>(collection, style.palette.global_id)?;
+            for mut layer in style_layers(style_info, &style, palette.as_ref()) {
+                if let Some(swatch_id) = layer.swatch {
+                    if let Some(swatch) =
+                        read_tag_by_id::<MaterialSwatchTag>(collection, swatch_id)?
+                    {
+                        layer.color_gradient_map =
+                            Some(swatch.color_gradient_map.global_id).filter(|id| !id.is_null());
+                        layer.normal_detail_map =
+                            Some(swatch.normal_detail_map.global_id).filter(|id| !id.is_null());
+                    }
+                }
+                layers.push(layer);
+            }
+        }
+
+        Ok(MaterialDescription {
+            shader: self.material_shader.global_id,
+            textures,
+            layers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a layer of a style region.
+    fn layer(swatch_name: i32, color_variant: i32) -> MaterialStyleLayer {
+        MaterialStyleLayer {
+            swatch_name: FieldStringId(swatch_name),
+            color_variant: FieldStringId(color_variant),
+        }
+    }
+
+    /// Creates a swatch of a palette.
+    fn swatch(name: i32, color_variant: i32, swatch_id: i32) -> MaterialPaletteSwatch {
+        let mut swatch = MaterialPaletteSwatch {
+            name: FieldStringId(name),
+            color_variant: FieldStringId(color_variant),
+            ..Default::default()
+        };
+        swatch.swatch.global_id = TagId(swatch_id);
+        swatch
+    }
+
+    #[test]
+    /// Verifies that layers of the matching region are listed and matched to palette swatches.
+    fn test_style_layers() {
+        let mut region = MaterialStyleRegion {
+            name: FieldStringId(1),
+            ..Default::default()
+        };
+        region.layers.size = 3;
+        region.layers.elements = vec![layer(10, 0), layer(11, 21), layer(12, 0)];
+        let mut other_region = MaterialStyleRegion {
+            name: FieldStringId(2),
+            ..Default::default()
+        };
+        other_region.layers.size = 1;
+        other_region.layers.elements = vec![layer(13, 0)];
+        let mut style = MaterialStyleTag::default();
+        style.regions.size = 2;
+        style.regions.elements = vec![region, other_region];
+
+        let mut palette = MaterialPaletteTag::default();
+        palette.swatches.size = 3;
+        palette.swatches.elements =
+            vec![swatch(10, 0, 100), swatch(11, 20, 110), swatch(11, 21, 111)];
+
+        let style_info = MaterialStyleInfo {
+            region_name: FieldStringId(1),
+            supported_layers: FieldCharInteger(2),
+            ..Default::default()
+        };
+        let layers = style_layers(&style_info, &style, Some(&palette));
+        let swatches: Vec<_> = layers.iter().map(|layer| layer.swatch).collect();
+        assert_eq!(swatches, [Some(TagId(100)), Some(TagId(111))]);
+        assert!(layers.iter().all(|layer| layer.region == FieldStringId(1)));
+
+        let layers = style_layers(&style_info, &style, None);
+        assert_eq!(layers.len(), 2);
+        assert!(layers.iter().all(|layer| layer.swatch.is_none()));
+    }
+}
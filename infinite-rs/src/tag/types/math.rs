@@ -0,0 +1,149 @@
+//! Conversions between common field types and math library types.
+//!
+//! Conversions to [`glam`] types are available with the `glam` feature, and conversions to
+//! [`nalgebra`] types with the `nalgebra` feature.
+
+#[cfg(any(feature = "glam", feature = "nalgebra"))]
+use super::common_types::{
+    FieldRealEulerAngles2D, FieldRealEulerAngles3D, FieldRealPlane3D, FieldRealPoint2D,
+    FieldRealPoint3D, FieldRealQuaternion, FieldRealVector2D, FieldRealVector3D,
+};
+
+/// Implements [`From`] in both directions between a field type and a math type built from its
+/// components.
+#[cfg(any(feature = "glam", feature = "nalgebra"))]
+macro_rules! impl_conversions {
+    ($field:ident, $math:ty, $new:path, [$($component:ident),+], |$value:ident| [$($get:expr),+]) => {
+        impl From<$field> for $math {
+            fn from(value: $field) -> Self {
+                $new($(value.$component),+)
+            }
+        }
+
+        impl From<$math> for $field {
+            fn from($value: $math) -> Self {
+                let [$($component),+] = [$($get),+];
+                Self { $($component),+ }
+            }
+        }
+    };
+}
+
+#[cfg(feature = "glam")]
+mod glam_conversions {
+    use glam::{Quat, Vec2, Vec3, Vec4};
+
+    use super::{
+        FieldRealEulerAngles2D, FieldRealEulerAngles3D, FieldRealPlane3D, FieldRealPoint2D,
+        FieldRealPoint3D, FieldRealQuaternion, FieldRealVector2D, FieldRealVector3D,
+    };
+
+    impl_conversions!(FieldRealPoint2D, Vec2, Vec2::new, [x, y], |v| [v.x, v.y]);
+    impl_conversions!(FieldRealVector2D, Vec2, Vec2::new, [x, y], |v| [v.x, v.y]);
+    impl_conversions!(FieldRealEulerAngles2D, Vec2, Vec2::new, [x, y], |v| [
+        v.x, v.y
+    ]);
+    impl_conversions!(FieldRealPoint3D, Vec3, Vec3::new, [x, y, z], |v| [
+        v.x, v.y, v.z
+    ]);
+    impl_conversions!(FieldRealVector3D, Vec3, Vec3::new, [x, y, z], |v| [
+        v.x, v.y, v.z
+    ]);
+    impl_conversions!(FieldRealEulerAngles3D, Vec3, Vec3::new, [x, y, z], |v| [
+        v.x, v.y, v.z
+    ]);
+    impl_conversions!(
+        FieldRealQuaternion,
+        Quat,
+        Quat::from_xyzw,
+        [x, y, z, w],
+        |v| [v.x, v.y, v.z, v.w]
+    );
+    // Planes are stored as the normal followed by the distance.
+    impl_conversions!(FieldRealPlane3D, Vec4, Vec4::new, [x, y, z, d], |v| [
+        v.x, v.y, v.z, v.w
+    ]);
+}
+
+#[cfg(feature = "nalgebra")]
+mod nalgebra_conversions {
+    use nalgebra::{Point2, Point3, Quaternion, Vector2, Vector3, Vector4};
+
+    use super::{
+        FieldRealEulerAngles2D, FieldRealEulerAngles3D, FieldRealPlane3D, FieldRealPoint2D,
+        FieldRealPoint3D, FieldRealQuaternion, FieldRealVector2D, FieldRealVector3D,
+    };
+
+    impl_conversions!(FieldRealPoint2D, Point2<f32>, Point2::new, [x, y], |v| [
+        v.x, v.y
+    ]);
+    impl_conversions!(FieldRealVector2D, Vector2<f32>, Vector2::new, [x, y], |v| [
+        v.x, v.y
+    ]);
+    impl_conversions!(
+        FieldRealEulerAngles2D,
+        Vector2<f32>,
+        Vector2::new,
+        [x, y],
+        |v| [v.x, v.y]
+    );
+    impl_conversions!(FieldRealPoint3D, Point3<f32>, Point3::new, [x, y, z], |v| [
+        v.x, v.y, v.z
+    ]);
+    impl_conversions!(
+        FieldRealVector3D,
+        Vector3<f32>,
+        Vector3::new,
+        [x, y, z],
+        |v| [v.x, v.y, v.z]
+    );
+    impl_conversions!(
+        FieldRealEulerAngles3D,
+        Vector3<f32>,
+        Vector3::new,
+        [x, y, z],
+        |v| [v.x, v.y, v.z]
+    );
+    // nalgebra constructs quaternions with the scalar part first.
+    impl_conversions!(
+        FieldRealQuaternion,
+        Quaternion<f32>,
+        quaternion_from_xyzw,
+        [x, y, z, w],
+        |v| [v.i, v.j, v.k, v.w]
+    );
+    impl_conversions!(
+        FieldRealPlane3D,
+        Vector4<f32>,
+        Vector4::new,
+        [x, y, z, d],
+        |v| [v.x, v.y, v.z, v.w]
+    );
+
+    fn quaternion_from_xyzw(x: f32, y: f32, z: f32, w: f32) -> Quaternion<f32> {
+        Quaternion::new(w, x, y, z)
+    }
+}
+
+#[cfg(all(test, feature = "glam", feature = "nalgebra"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    /// Verifies that quaternion components keep their order through both libraries.
+    fn test_quaternion_round_trip() {
+        let quaternion = FieldRealQuaternion {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+            w: 4.0,
+        };
+        let glam_quaternion = glam::Quat::from(quaternion.clone());
+        assert_eq!(glam_quaternion.w, 4.0);
+        let nalgebra_quaternion = nalgebra::Quaternion::<f32>::from(quaternion.clone());
+        assert_eq!(nalgebra_quaternion.w, 4.0);
+        assert_eq!(FieldRealQuaternion::from(glam_quaternion), quaternion);
+        assert_eq!(FieldRealQuaternion::from(nalgebra_quaternion), quaternion);
+    }
+}
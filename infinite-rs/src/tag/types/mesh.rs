@@ -0,0 +1,255 @@
+//! Decoding of the meshes of a render geometry into plain vertex attributes.
+//!
+//! Vertex buffers of a [`RenderGeometry`] store their attributes in packed formats, with
+//! positions and texture coordinates normalized to the bounds of its compression info, and index
+//! buffers can be triangle strips. [`RenderGeometry::decode_mesh`] decodes the buffers of a mesh
+//! into [`f32`] positions, normals and texture coordinates, and every part of the mesh into a
+//! triangle list, so that exporters only have to deal with plain arrays.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use infinite_rs::ModuleFile;
+//! use infinite_rs::tag::types::render_geometry::read_buffer_data;
+//! use infinite_rs::tag::types::render_model::RenderModelTag;
+//!
+//! let mut module = ModuleFile::from_path("deploy/any/globals/forge/forge_objects-rtx-new.module").unwrap();
+//! let index = module.files.iter().position(|file| file.tag_group == "mode").unwrap() as u32;
+//! let model = module.read_tag(index).unwrap().unwrap().read_metadata::<RenderModelTag>().unwrap();
+//! let buffer_data = read_buffer_data(&mut module, index).unwrap();
+//! for mesh in &model.render_geometry.meshes {
+//!     let decoded = model.render_geometry.decode_mesh(mesh, &buffer_data).unwrap();
+//!     for part in &decoded.parts {
+//!         println!("{} triangles", part.indices.len() / 3);
+//!     }
+//! }
+//! ```
+
+use super::render_geometry::{
+    IndexBufferType, RasterizerVertexBuffer, RenderGeometryApiResource, VertexBufferUsage,
+    VertexFormat,
+};
+use super::render_model::{RenderGeometry, RenderMesh};
+use crate::Result;
+use crate::common::errors::TagError;
+
+#[derive(Default, Debug, Clone, PartialEq)]
+/// Part of a decoded mesh, drawn with a single material.
+pub struct DecodedMeshPart {
+    /// Index of the material of the part, [`None`] if it has no material.
+    pub material_index: Option<usize>,
+    /// Triangle list of the part, indexing the vertices of the mesh.
+    pub indices: Vec<u32>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+/// Mesh decoded by [`RenderGeometry::decode_mesh`].
+///
+/// Attributes are empty if the mesh has no vertex buffer for them, or if their vertex count
+/// differs from the positions.
+pub struct DecodedMesh {
+    /// Decompressed positions of the vertices.
+    pub positions: Vec<[f32; 3]>,
+    /// Unit normals of the vertices.
+    pub normals: Vec<[f32; 3]>,
+    /// Decompressed texture coordinates of the vertices, from the first UV set.
+    pub texcoords: Vec<[f32; 2]>,
+    /// Indices of the nodes influencing each vertex.
+    pub blend_indices: Vec<[u8; 4]>,
+    /// Weights of the nodes influencing each vertex, as stored in the buffer.
+    pub blend_weights: Vec<[f32; 4]>,
+    /// Parts of the mesh. Empty if the mesh has no index buffer, in which case its vertices
+    /// form a triangle list.
+    pub parts: Vec<DecodedMeshPart>,
+}
+
+/// Vertex buffer decoded by [`read_vertices`].
+type DecodedBuffer<'a> = (&'a RasterizerVertexBuffer, Vec<[f32; 4]>);
+
+/// Decodes the vertex buffer of a mesh for `usage`, if it has one.
+fn read_vertices<'a>(
+    mesh: &RenderMesh,
+    resource: &'a RenderGeometryApiResource,
+    usage: VertexBufferUsage,
+    buffer_data: &[u8],
+) -> Result<Option<DecodedBuffer<'a>>> {
+    let Some(buffer) = mesh
+        .vertex_buffer_index(usage)
+        .and_then(|index| resource.pc_vertex_buffers.get(index))
+    else {
+        return Ok(None);
+    };
+    Ok(Some((buffer, buffer.read_vertices(buffer_data)?)))
+}
+
+/// Converts a triangle strip to a triangle list, skipping degenerate triangles and restarting
+/// the strip at `restart` indices.
+fn strip_to_list(strip: &[u32], restart: u32) -> Vec<u32> {
+    let mut list = Vec::new();
+    for run in strip.split(|&index| index == restart) {
+        for (position, window) in run.windows(3).enumerate() {
+            let (a, b, c) = (window[0], window[1], window[2]);
+            if a == b || b == c || a == c {
+                continue;
+            }
+            // Every other triangle of a strip has the opposite winding.
+            if position % 2 == 0 {
+                list.extend([a, b, c]);
+            } else {
+                list.extend([b, a, c]);
+            }
+        }
+    }
+    list
+}
+
+/// Normalizes a vector, falling back to +Z for zero vectors.
+fn normalize(vector: [f32; 3]) -> [f32; 3] {
+    let [x, y, z] = vector;
+    let length = (x * x + y * y + z * z).sqrt();
+    if length > f32::EPSILON {
+        [x / length, y / length, z / length]
+    } else {
+        [0.0, 0.0, 1.0]
+    }
+}
+
+impl RenderGeometry {
+    /// Decodes the vertices and parts of a mesh of the geometry.
+    ///
+    /// Positions and texture coordinates stored in normalized formats are decompressed with the
+    /// [`compression`](`RenderGeometry::compression`) of the geometry. Parts stored as triangle
+    /// strips are converted to triangle lists, and triangles referencing vertices past the end
+    /// of the positions are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `mesh` - Mesh of the geometry to decode.
+    /// * `buffer_data` - Data of the resource files of the tag, read with
+    ///   [`read_buffer_data`](`crate::tag::types::render_geometry::read_buffer_data`).
+    ///
+    /// # Errors
+    /// - If the mesh resource of the geometry is not loaded [`TagError::NotLoaded`]
+    /// - If a buffer lies outside of `buffer_data` [`TagError::BufferOutOfBounds`]
+    /// - If a vertex buffer cannot be decoded [`TagError::UnsupportedVertexFormat`]
+    pub fn decode_mesh(&self, mesh: &RenderMesh, buffer_data: &[u8]) -> Result<DecodedMesh> {
+        let resource = self.api_resource().ok_or(TagError::NotLoaded)?;
+        let compression = self.compression();
+        let decompress = |buffer: &RasterizerVertexBuffer| {
+            compression.filter(|_| {
+                buffer
+                    .vertex_format()
+                    .is_some_and(VertexFormat::is_normalized)
+            })
+        };
+        let Some((position_buffer, positions)) =
+            read_vertices(mesh, resource, VertexBufferUsage::Position, buffer_data)?
+        else {
+            return Ok(DecodedMesh::default());
+        };
+        let vertex_count = positions.len();
+        let read_attribute = |usage| -> Result<Option<DecodedBuffer>> {
+            Ok(read_vertices(mesh, resource, usage, buffer_data)?
+                .filter(|(_, vertices)| vertices.len() == vertex_count))
+        };
+
+        let mut decoded = DecodedMesh {
+            positions: positions
+                .iter()
+                .map(|&[x, y, z, _]| match decompress(position_buffer) {
+                    Some(compression) => compression.decompress_position([x, y, z]),
+                    None => [x, y, z],
+                })
+                .collect(),
+            ..Default::default()
+        };
+        if let Some((_, normals)) = read_attribute(VertexBufferUsage::Normal)? {
+            decoded.normals = normals
+                .iter()
+                .map(|&[x, y, z, _]| normalize([x, y, z]))
+                .collect();
+        }
+        if let Some((texcoord_buffer, texcoords)) = read_attribute(VertexBufferUsage::UV0)? {
+            decoded.texcoords = texcoords
+                .iter()
+                .map(|&[u, v, _, _]| match decompress(texcoord_buffer) {
+                    Some(compression) => compression.decompress_texcoord([u, v]),
+                    None => [u, v],
+                })
+                .collect();
+        }
+        if let Some((_, indices)) = read_attribute(VertexBufferUsage::BlendIndices0)? {
+            #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+            let indices = indices
+                .iter()
+                .map(|index| index.map(|index| index as u8))
+                .collect();
+            decoded.blend_indices = indices;
+        }
+        if let Some((_, weights)) = read_attribute(VertexBufferUsage::BlendWeights0)? {
+            decoded.blend_weights = weights;
+        }
+        decoded.parts = decode_parts(mesh, resource, buffer_data, vertex_count)?;
+        Ok(decoded)
+    }
+}
+
+/// Decodes the indices of every part of a mesh as triangle lists.
+fn decode_parts(
+    mesh: &RenderMesh,
+    resource: &RenderGeometryApiResource,
+    buffer_data: &[u8],
+    vertex_count: usize,
+) -> Result<Vec<DecodedMeshPart>> {
+    let Some(index_buffer) = mesh
+        .index_buffer_index()
+        .and_then(|index| resource.pc_index_buffers.get(index))
+    else {
+        return Ok(Vec::new());
+    };
+    let indices = index_buffer.read_indices(buffer_data)?;
+    let restart = if index_buffer.stride.0 == 4 {
+        u32::MAX
+    } else {
+        u32::from(u16::MAX)
+    };
+
+    let mut parts = Vec::new();
+    for part in &mesh.parts {
+        let Some(part_indices) = indices.get(part.index_range()) else {
+            continue;
+        };
+        let triangles = match index_buffer.declaration_type.0 {
+            IndexBufferType::TriangleStrip => strip_to_list(part_indices, restart),
+            _ => part_indices.to_vec(),
+        };
+        parts.push(DecodedMeshPart {
+            material_index: usize::try_from(part.material_index.0).ok(),
+            indices: triangles
+                .chunks_exact(3)
+                .filter(|triangle| {
+                    triangle.iter().all(|&index| {
+                        usize::try_from(index).is_ok_and(|index| index < vertex_count)
+                    })
+                })
+                .flatten()
+                .copied()
+                .collect(),
+        });
+    }
+    Ok(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Verifies that strips are converted with alternating winding and restarts.
+    fn test_strip_to_list() {
+        assert_eq!(
+            strip_to_list(&[0, 1, 2, 3, 0xFFFF, 4, 5, 6], 0xFFFF),
+            vec![0, 1, 2, 2, 1, 3, 4, 5, 6]
+        );
+    }
+}
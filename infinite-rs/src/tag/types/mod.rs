@@ -1,3 +1,13 @@
 //! Common types and tag-specific structures.
 
+pub mod bitmap;
 pub mod common_types;
+pub mod localization;
+pub mod material;
+pub mod math;
+pub mod mesh;
+pub mod pixel_format;
+pub mod render_geometry;
+pub mod render_model;
+pub mod script;
+pub mod sound;
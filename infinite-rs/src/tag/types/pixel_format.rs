@@ -0,0 +1,463 @@
+//! Pixel formats used by textures, and their conversion to RGBA8.
+//!
+//! Bitmap tags describe their pixels with a [`BitmapFormat`](`super::bitmap::BitmapFormat`),
+//! which is converted to a [`DxgiFormat`] when the texture is uploaded. Pixels in any of the
+//! common 8-bit and BC1 to BC7 formats can be converted to RGBA8 with
+//! [`DxgiFormat::decode_rgba8`] when the `bcn` feature is enabled. BC6H and BC7 blocks are decoded
+//! with [bcdec_rs](https://github.com/ScanMountGoat/bcdec_rs).
+
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+#[cfg(feature = "bcn")]
+use crate::Result;
+#[cfg(feature = "bcn")]
+use crate::common::errors::TagError;
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive, IntoPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[repr(u32)]
+/// Pixel formats of textures, with the values of `DXGI_FORMAT`.
+///
+/// Only the formats that bitmaps can be stored in are listed.
+pub enum DxgiFormat {
+    #[default]
+    Unknown = 0,
+    R32G32B32A32Float = 2,
+    R32G32B32Float = 6,
+    R16G16B16A16Float = 10,
+    R16G16B16A16Unorm = 11,
+    R16G16B16A16Snorm = 13,
+    R10G10B10A2Unorm = 24,
+    R8G8B8A8Typeless = 27,
+    R8G8B8A8Unorm = 28,
+    R8G8B8A8UnormSrgb = 29,
+    R8G8B8A8Snorm = 31,
+    R16G16Float = 34,
+    R16G16Unorm = 35,
+    R16G16Snorm = 37,
+    R32Float = 41,
+    R8G8Unorm = 49,
+    R8G8Snorm = 51,
+    R16Float = 54,
+    R16Unorm = 56,
+    R8Unorm = 61,
+    A8Unorm = 65,
+    Bc1Typeless = 70,
+    Bc1Unorm = 71,
+    Bc1UnormSrgb = 72,
+    Bc2Typeless = 73,
+    Bc2Unorm = 74,
+    Bc2UnormSrgb = 75,
+    Bc3Typeless = 76,
+    Bc3Unorm = 77,
+    Bc3UnormSrgb = 78,
+    Bc4Typeless = 79,
+    Bc4Unorm = 80,
+    Bc4Snorm = 81,
+    Bc5Typeless = 82,
+    Bc5Unorm = 83,
+    Bc5Snorm = 84,
+    B5G6R5Unorm = 85,
+    B5G5R5A1Unorm = 86,
+    B8G8R8A8Unorm = 87,
+    B8G8R8X8Unorm = 88,
+    B8G8R8A8Typeless = 90,
+    B8G8R8A8UnormSrgb = 91,
+    B8G8R8X8Typeless = 92,
+    B8G8R8X8UnormSrgb = 93,
+    Bc6hTypeless = 94,
+    Bc6hUf16 = 95,
+    Bc6hSf16 = 96,
+    Bc7Typeless = 97,
+    Bc7Unorm = 98,
+    Bc7UnormSrgb = 99,
+    B4G4R4A4Unorm = 115,
+}
+
+impl DxgiFormat {
+    /// Whether the format is block compressed, storing blocks of 4x4 pixels.
+    #[must_use]
+    pub fn is_block_compressed(self) -> bool {
+        let value = u32::from(self);
+        (u32::from(Self::Bc1Typeless)..=u32::from(Self::Bc5Snorm)).contains(&value)
+            || (u32::from(Self::Bc6hTypeless)..=u32::from(Self::Bc7UnormSrgb)).contains(&value)
+    }
+
+    /// Size in bytes of a single pixel, or of a block of 4x4 pixels for
+    /// [block compressed](`DxgiFormat::is_block_compressed`) formats.
+    ///
+    /// Returns [`None`] for [`DxgiFormat::Unknown`].
+    #[must_use]
+    pub fn element_size(self) -> Option<u32> {
+        Some(match self {
+            Self::Unknown => return None,
+            Self::R8Unorm | Self::A8Unorm => 1,
+            Self::R8G8Unorm
+            | Self::R8G8Snorm
+            | Self::R16Float
+            | Self::R16Unorm
+            | Self::B5G6R5Unorm
+            | Self::B5G5R5A1Unorm
+            | Self::B4G4R4A4Unorm => 2,
+            Self::R10G10B10A2Unorm
+            | Self::R8G8B8A8Typeless
+            | Self::R8G8B8A8Unorm
+            | Self::R8G8B8A8UnormSrgb
+            | Self::R8G8B8A8Snorm
+            | Self::R16G16Float
+            | Self::R16G16Unorm
+            | Self::R16G16Snorm
+            | Self::R32Float
+            | Self::B8G8R8A8Unorm
+            | Self::B8G8R8X8Unorm
+            | Self::B8G8R8A8Typeless
+            | Self::B8G8R8A8UnormSrgb
+            | Self::B8G8R8X8Typeless
+            | Self::B8G8R8X8UnormSrgb => 4,
+            Self::R16G16B16A16Float
+            | Self::R16G16B16A16Unorm
+            | Self::R16G16B16A16Snorm
+            | Self::Bc1Typeless
+            | Self::Bc1Unorm
+            | Self::Bc1UnormSrgb
+            | Self::Bc4Typeless
+            | Self::Bc4Unorm
+            | Self::Bc4Snorm => 8,
+            Self::R32G32B32Float => 12,
+            Self::R32G32B32A32Float
+            | Self::Bc2Typeless
+            | Self::Bc2Unorm
+            | Self::Bc2UnormSrgb
+            | Self::Bc3Typeless
+            | Self::Bc3Unorm
+            | Self::Bc3UnormSrgb
+            | Self::Bc5Typeless
+            | Self::Bc5Unorm
+            | Self::Bc5Snorm
+            | Self::Bc6hTypeless
+            | Self::Bc6hUf16
+            | Self::Bc6hSf16
+            | Self::Bc7Typeless
+            | Self::Bc7Unorm
+            | Self::Bc7UnormSrgb => 16,
+        })
+    }
+
+    /// Converts pixels in this format to RGBA8, with 4 bytes per pixel.
+    ///
+    /// Channels missing from the format are set to 0 and alpha to 255, as when the texture is
+    /// sampled by Direct3D. sRGB data is not converted to linear, and BC6H colors are clamped to
+    /// 0..1. Floating point and signed formats other than BC4, BC5 and BC6H are not supported.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Width in pixels of the image.
+    /// * `height` - Height in pixels of the image.
+    /// * `data` - Pixels of the image, such as the largest mipmap of a bitmap.
+    ///
+    /// # Errors
+    /// - If the format cannot be converted [`TagError::UnsupportedPixelFormat`]
+    /// - If `data` is smaller than the image [`TagError::BitmapDataTooSmall`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use infinite_rs::tag::types::pixel_format::DxgiFormat;
+    ///
+    /// // Single BC1 block using the first color, pure red, for every pixel.
+    /// let block = [0x00, 0xF8, 0x1F, 0x00, 0x00, 0x00, 0x00, 0x00];
+    /// let pixels = DxgiFormat::Bc1Unorm.decode_rgba8(4, 4, &block).unwrap();
+    /// assert_eq!(pixels.len(), 4 * 4 * 4);
+    /// assert_eq!(&pixels[..4], &[255, 0, 0, 255]);
+    /// ```
+    #[cfg(feature = "bcn")]
+    pub fn decode_rgba8(self, width: u32, height: u32, data: &[u8]) -> Result<Vec<u8>> {
+        let element_size = self
+            .element_size()
+            .ok_or(TagError::UnsupportedPixelFormat(self))?;
+        let (columns, rows) = if self.is_block_compressed() {
+            (width.div_ceil(4), height.div_ceil(4))
+        } else {
+            (width, height)
+        };
+        let expected = u64::from(columns) * u64::from(rows) * u64::from(element_size);
+        let data = usize::try_from(expected)
+            .ok()
+            .and_then(|expected| data.get(..expected))
+            .ok_or(TagError::BitmapDataTooSmall {
+                expected,
+                found: data.len() as u64,
+            })?;
+
+        let width = width as usize;
+        let mut pixels = vec![0; width * height as usize * 4];
+        if !self.is_block_compressed() {
+            for (pixel, source) in pixels
+                .chunks_exact_mut(4)
+                .zip(data.chunks_exact(element_size as usize))
+            {
+                pixel.copy_from_slice(&self.decode_pixel(source)?);
+            }
+            return Ok(pixels);
+        }
+
+        for (index, block) in data.chunks_exact(element_size as usize).enumerate() {
+            let texels = self.decode_block(block)?;
+            let block_x = index % columns as usize * 4;
+            let block_y = index / columns as usize * 4;
+            for (texel_index, texel) in texels.iter().enumerate() {
+                let x = block_x + texel_index % 4;
+                let y = block_y + texel_index / 4;
+                if x < width && y < height as usize {
+                    let offset = (y * width + x) * 4;
+                    pixels[offset..offset + 4].copy_from_slice(texel);
+                }
+            }
+        }
+        Ok(pixels)
+    }
+
+    /// Converts a single pixel of an uncompressed format.
+    #[cfg(feature = "bcn")]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn decode_pixel(self, pixel: &[u8]) -> Result<[u8; 4]> {
+        let short = |index: usize| u16::from_le_bytes([pixel[index], pixel[index + 1]]);
+        Ok(match self {
+            Self::R8G8B8A8Typeless | Self::R8G8B8A8Unorm | Self::R8G8B8A8UnormSrgb => {
+                [pixel[0], pixel[1], pixel[2], pixel[3]]
+            }
+            Self::B8G8R8A8Unorm | Self::B8G8R8A8Typeless | Self::B8G8R8A8UnormSrgb => {
+                [pixel[2], pixel[1], pixel[0], pixel[3]]
+            }
+            Self::B8G8R8X8Unorm | Self::B8G8R8X8Typeless | Self::B8G8R8X8UnormSrgb => {
+                [pixel[2], pixel[1], pixel[0], 255]
+            }
+            Self::R10G10B10A2Unorm => {
+                let value = u32::from_le_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+                let channel = |shift: u32| ((value >> shift & 0x3FF) >> 2) as u8;
+                [
+                    channel(0),
+                    channel(10),
+                    channel(20),
+                    (value >> 30) as u8 * 85,
+                ]
+            }
+            Self::R16G16B16A16Unorm => [pixel[1], pixel[3], pixel[5], pixel[7]],
+            Self::R16G16Unorm => [pixel[1], pixel[3], 0, 255],
+            Self::R16Unorm => [pixel[1], 0, 0, 255],
+            Self::R8G8Unorm => [pixel[0], pixel[1], 0, 255],
+            Self::R8Unorm => [pixel[0], 0, 0, 255],
+            Self::A8Unorm => [0, 0, 0, pixel[0]],
+            Self::B5G6R5Unorm => {
+                let [red, green, blue] = expand_565(short(0));
+                [red, green, blue, 255]
+            }
+            Self::B5G5R5A1Unorm => {
+                let value = short(0);
+                [
+                    expand_bits(value >> 10, 5),
+                    expand_bits(value >> 5, 5),
+                    expand_bits(value, 5),
+                    if value & 0x8000 == 0 { 0 } else { 255 },
+                ]
+            }
+            Self::B4G4R4A4Unorm => {
+                let value = short(0);
+                [
+                    expand_bits(value >> 8, 4),
+                    expand_bits(value >> 4, 4),
+                    expand_bits(value, 4),
+                    expand_bits(value >> 12, 4),
+                ]
+            }
+            _ => return Err(TagError::UnsupportedPixelFormat(self).into()),
+        })
+    }
+
+    /// Converts a single block of a block compressed format.
+    #[cfg(feature = "bcn")]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn decode_block(self, block: &[u8]) -> Result<[[u8; 4]; 16]> {
+        Ok(match self {
+            Self::Bc1Typeless | Self::Bc1Unorm | Self::Bc1UnormSrgb => {
+                decode_color_block(block, false)
+            }
+            Self::Bc2Typeless | Self::Bc2Unorm | Self::Bc2UnormSrgb => {
+                let mut texels = decode_color_block(&block[8..], true);
+                let alpha = u64::from_le_bytes(block[..8].try_into().unwrap_or_default());
+                for (index, texel) in texels.iter_mut().enumerate() {
+                    texel[3] = expand_bits((alpha >> (index * 4)) as u16, 4);
+                }
+                texels
+            }
+            Self::Bc3Typeless | Self::Bc3Unorm | Self::Bc3UnormSrgb => {
+                let mut texels = decode_color_block(&block[8..], true);
+                let alpha = decode_channel_block(&block[..8], false);
+                for (texel, alpha) in texels.iter_mut().zip(alpha) {
+                    texel[3] = alpha;
+                }
+                texels
+            }
+            Self::Bc4Typeless | Self::Bc4Unorm | Self::Bc4Snorm => {
+                let red = decode_channel_block(block, self == Self::Bc4Snorm);
+                red.map(|red| [red, 0, 0, 255])
+            }
+            Self::Bc5Typeless | Self::Bc5Unorm | Self::Bc5Snorm => {
+                let signed = self == Self::Bc5Snorm;
+                let red = decode_channel_block(&block[..8], signed);
+                let green = decode_channel_block(&block[8..], signed);
+                let mut texels = [[0, 0, 0, 255]; 16];
+                for (index, texel) in texels.iter_mut().enumerate() {
+                    texel[0] = red[index];
+                    texel[1] = green[index];
+                }
+                texels
+            }
+            Self::Bc6hTypeless | Self::Bc6hUf16 | Self::Bc6hSf16 => {
+                let mut colors = [0.0; 16 * 3];
+                bcdec_rs::bc6h_float(block, &mut colors, 4 * 3, self == Self::Bc6hSf16);
+                let mut texels = [[0, 0, 0, 255]; 16];
+                for (texel, color) in texels.iter_mut().zip(colors.chunks_exact(3)) {
+                    for (channel, value) in texel.iter_mut().zip(color) {
+                        *channel = (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+                    }
+                }
+                texels
+            }
+            Self::Bc7Typeless | Self::Bc7Unorm | Self::Bc7UnormSrgb => {
+                let mut pixels = [0; 16 * 4];
+                bcdec_rs::bc7(block, &mut pixels, 4 * 4);
+                let mut texels = [[0; 4]; 16];
+                for (texel, pixel) in texels.iter_mut().zip(pixels.chunks_exact(4)) {
+                    texel.copy_from_slice(pixel);
+                }
+                texels
+            }
+            _ => return Err(TagError::UnsupportedPixelFormat(self).into()),
+        })
+    }
+}
+
+/// Expands the lowest `bits` bits of a value to 8 bits, by repeating its highest bits.
+#[cfg(feature = "bcn")]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn expand_bits(value: u16, bits: u32) -> u8 {
+    let value = value & ((1 << bits) - 1);
+    ((value << (8 - bits)) | (value >> (2 * bits).saturating_sub(8))) as u8
+}
+
+/// Expands a 5:6:5 color, with red in the highest bits, to 8 bits per channel.
+#[cfg(feature = "bcn")]
+fn expand_565(color: u16) -> [u8; 3] {
+    [
+        expand_bits(color >> 11, 5),
+        expand_bits(color >> 5, 6),
+        expand_bits(color, 5),
+    ]
+}
+
+/// Decodes the color part of a BC1, BC2 or BC3 block.
+///
+/// BC2 and BC3 always interpolate 4 colors, while BC1 uses 3 colors and transparent black when
+/// the first color is not greater than the second.
+#[cfg(feature = "bcn")]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn decode_color_block(block: &[u8], always_four_colors: bool) -> [[u8; 4]; 16] {
+    let color0 = u16::from_le_bytes([block[0], block[1]]);
+    let color1 = u16::from_le_bytes([block[2], block[3]]);
+    let [first, second] =
+        [expand_565(color0), expand_565(color1)].map(|color| color.map(u16::from));
+    let mix = |first_weight: u16, second_weight: u16| {
+        let total = first_weight + second_weight;
+        let channel = |index: usize| {
+            ((first[index] * first_weight + second[index] * second_weight + total / 2) / total)
+                as u8
+        };
+        [channel(0), channel(1), channel(2), 255]
+    };
+
+    let mut palette = [mix(1, 0), mix(0, 1), [0; 4], [0; 4]];
+    if always_four_colors || color0 > color1 {
+        palette[2] = mix(2, 1);
+        palette[3] = mix(1, 2);
+    } else {
+        palette[2] = mix(1, 1);
+    }
+
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    let mut texels = [[0; 4]; 16];
+    for (index, texel) in texels.iter_mut().enumerate() {
+        *texel = palette[(indices >> (index * 2) & 0b11) as usize];
+    }
+    texels
+}
+
+/// Decodes a single channel block, used by BC4, BC5 and the alpha of BC3.
+///
+/// Signed values are remapped from -1..1 to 0..255.
+#[cfg(feature = "bcn")]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn decode_channel_block(block: &[u8], signed: bool) -> [u8; 16] {
+    let (endpoint0, endpoint1, minimum, maximum) = if signed {
+        (
+            i32::from(i8::from_le_bytes([block[0]])).max(-127),
+            i32::from(i8::from_le_bytes([block[1]])).max(-127),
+            -127,
+            127,
+        )
+    } else {
+        (i32::from(block[0]), i32::from(block[1]), 0, 255)
+    };
+
+    let mut palette = [endpoint0, endpoint1, 0, 0, 0, 0, minimum, maximum];
+    let steps = if endpoint0 > endpoint1 { 7 } else { 5 };
+    for step in 1..steps {
+        palette[step as usize + 1] =
+            (endpoint0 * (steps - step) + endpoint1 * step + steps / 2).div_euclid(steps);
+    }
+
+    let mut indices = [0; 8];
+    indices[..6].copy_from_slice(&block[2..8]);
+    let indices = u64::from_le_bytes(indices);
+    let mut texels = [0; 16];
+    for (index, texel) in texels.iter_mut().enumerate() {
+        let value = palette[(indices >> (index * 3) & 0b111) as usize];
+        *texel = if signed {
+            ((value + 127) * 255 / 254) as u8
+        } else {
+            value as u8
+        };
+    }
+    texels
+}
+
+#[cfg(test)]
+#[cfg(feature = "bcn")]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Verifies that a BC6H block is decoded and its colors are clamped to 0..1.
+    fn test_decode_bc6h_block() {
+        // Mode 11 block with the first endpoint (1023, 462, 0) for every pixel, which is
+        // (65504.0, 0.5, 0.0) after unquantization.
+        let block = [
+            0xE3, 0x7F, 0xE7, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00,
+        ];
+        let pixels = DxgiFormat::Bc6hUf16.decode_rgba8(4, 4, &block).unwrap();
+        assert_eq!(pixels, [255, 128, 0, 255].repeat(16));
+    }
+
+    #[test]
+    /// Verifies that a BC7 block is decoded.
+    fn test_decode_bc7_block() {
+        // Mode 6 block with the endpoints (255, 1, 1, 255), using the first for every pixel.
+        let block = [
+            0xC0, 0xFF, 0x1F, 0x00, 0x00, 0x00, 0xFE, 0xFF, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00,
+        ];
+        let pixels = DxgiFormat::Bc7Unorm.decode_rgba8(4, 4, &block).unwrap();
+        assert_eq!(pixels, [255, 1, 1, 255].repeat(16));
+    }
+}
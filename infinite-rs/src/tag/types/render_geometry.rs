@@ -0,0 +1,430 @@
+//! Render geometry API resource, describing the vertex and index buffers of `mode` and `rtgo` tags.
+//!
+//! The resource is referenced through a [`FieldTagResource`](`super::common_types::FieldTagResource`)
+//! in the mesh resource groups of the render geometry. Only the fields required to locate buffers
+//! inside the resource data are exposed.
+
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+use super::common_types::{
+    FieldApiInterop, FieldBlock, FieldByteInteger, FieldCharEnum, FieldDwordInteger,
+    FieldLongInteger,
+};
+use crate::ModuleFile;
+use crate::Result;
+use crate::common::errors::TagError;
+use infinite_rs_derive::TagStructure;
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive, IntoPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[repr(u8)]
+/// What the contents of a vertex buffer are used for.
+///
+/// Meshes index into the vertex buffers of the resource using this order.
+pub enum VertexBufferUsage {
+    #[default]
+    Position,
+    UV0,
+    UV1,
+    UV2,
+    Color,
+    Normal,
+    Tangent,
+    BlendIndices0,
+    BlendWeights0,
+    BlendIndices1,
+    BlendWeights1,
+    PrevPosition,
+    InstanceData,
+    BlendshapePosition,
+    BlendshapeNormal,
+    BlendshapeIndex,
+    Edge,
+    EdgeIndex,
+    EdgeIndexInfo,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive, IntoPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[repr(u8)]
+/// Primitive topology of an index buffer.
+pub enum IndexBufferType {
+    #[default]
+    Default,
+    LineList,
+    LineStrip,
+    TriangleList,
+    TrianglePatch,
+    TriangleStrip,
+    QuadList,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive, IntoPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[repr(u8)]
+/// Layout of a single vertex element (`rasterizer_vertex_format`).
+pub enum VertexFormat {
+    #[default]
+    Real,
+    RealVector2D,
+    RealVector3D,
+    RealVector4D,
+    ByteVector4D,
+    ByteARGBColor,
+    ShortVector2D,
+    ShortVector2DNormalized,
+    ShortVector4DNormalized,
+    WordVector2DNormalized,
+    WordVector4DNormalized,
+    Real16Vector2D,
+    Real16Vector4D,
+    F10_10_10Normalized,
+    U10_10_10_2,
+    U10_10_10_2SignedNormalizedPackedAsUnorm,
+    DWord,
+    DWordVector2D,
+    F11_11_10Float,
+    ByteUnitVector3D,
+    WordVector3DNormalizedWith4Word,
+}
+
+impl VertexFormat {
+    /// Size in bytes of a single element.
+    #[must_use]
+    pub fn element_size(self) -> usize {
+        match self {
+            Self::Real
+            | Self::ByteVector4D
+            | Self::ByteARGBColor
+            | Self::ShortVector2D
+            | Self::ShortVector2DNormalized
+            | Self::WordVector2DNormalized
+            | Self::Real16Vector2D
+            | Self::F10_10_10Normalized
+            | Self::U10_10_10_2
+            | Self::U10_10_10_2SignedNormalizedPackedAsUnorm
+            | Self::DWord
+            | Self::F11_11_10Float
+            | Self::ByteUnitVector3D => 4,
+            Self::RealVector2D
+            | Self::ShortVector4DNormalized
+            | Self::WordVector4DNormalized
+            | Self::Real16Vector4D
+            | Self::DWordVector2D
+            | Self::WordVector3DNormalizedWith4Word => 8,
+            Self::RealVector3D => 12,
+            Self::RealVector4D => 16,
+        }
+    }
+
+    /// Whether the format stores values normalized to 0..1 or -1..1, such as compressed
+    /// positions and texture coordinates.
+    #[must_use]
+    pub fn is_normalized(self) -> bool {
+        matches!(
+            self,
+            Self::ShortVector2DNormalized
+                | Self::ShortVector4DNormalized
+                | Self::WordVector2DNormalized
+                | Self::WordVector4DNormalized
+                | Self::WordVector3DNormalizedWith4Word
+        )
+    }
+
+    /// Decodes a single element to up to four floats. Components missing from the format are 0.
+    ///
+    /// Normalized formats are decoded to 0..1 (or -1..1 when signed), and integer formats are
+    /// converted to floats without scaling.
+    ///
+    /// # Errors
+    /// - If `element` is smaller than [`element_size`](`VertexFormat::element_size`) [`TagError::BufferOutOfBounds`]
+    /// - If the format cannot be decoded [`TagError::UnsupportedVertexFormat`]
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_wrap)]
+    pub fn decode(self, element: &[u8]) -> Result<[f32; 4]> {
+        let size = self.element_size();
+        let element = element.get(..size).ok_or(TagError::BufferOutOfBounds {
+            offset: 0,
+            size: size as u64,
+            available: element.len() as u64,
+        })?;
+        let word = |index: usize| u16::from_le_bytes([element[index * 2], element[index * 2 + 1]]);
+        let dword = |index: usize| {
+            u32::from_le_bytes([
+                element[index * 4],
+                element[index * 4 + 1],
+                element[index * 4 + 2],
+                element[index * 4 + 3],
+            ])
+        };
+        let real = |index: usize| f32::from_bits(dword(index));
+        let short_normalized = |index: usize| (f32::from(word(index) as i16) / 32767.0).max(-1.0);
+        let word_normalized = |index: usize| f32::from(word(index)) / 65535.0;
+        let bits = |value: u32, shift: u32, count: u32| (value >> shift) & ((1 << count) - 1);
+        let unorm = |value: u32, count: u32| value as f32 / ((1 << count) - 1) as f32;
+
+        Ok(match self {
+            Self::Real => [real(0), 0.0, 0.0, 0.0],
+            Self::RealVector2D => [real(0), real(1), 0.0, 0.0],
+            Self::RealVector3D => [real(0), real(1), real(2), 0.0],
+            Self::RealVector4D => [real(0), real(1), real(2), real(3)],
+            Self::ByteVector4D => element_array(|index| f32::from(element[index])),
+            Self::ByteARGBColor => element_array(|index| f32::from(element[index]) / 255.0),
+            Self::ShortVector2D => [
+                f32::from(word(0) as i16),
+                f32::from(word(1) as i16),
+                0.0,
+                0.0,
+            ],
+            Self::ShortVector2DNormalized => [short_normalized(0), short_normalized(1), 0.0, 0.0],
+            Self::ShortVector4DNormalized => element_array(short_normalized),
+            Self::WordVector2DNormalized => [word_normalized(0), word_normalized(1), 0.0, 0.0],
+            Self::WordVector4DNormalized => element_array(word_normalized),
+            Self::WordVector3DNormalizedWith4Word => [
+                word_normalized(0),
+                word_normalized(1),
+                word_normalized(2),
+                f32::from(word(3)),
+            ],
+            Self::Real16Vector2D => [half_to_f32(word(0)), half_to_f32(word(1)), 0.0, 0.0],
+            Self::Real16Vector4D => element_array(|index| half_to_f32(word(index))),
+            Self::F10_10_10Normalized => {
+                let value = dword(0);
+                let signed = |shift: u32| {
+                    // Sign extend the 10 bit component.
+                    let component = ((bits(value, shift, 10) << 22) as i32) >> 22;
+                    (component as f32 / 511.0).max(-1.0)
+                };
+                [signed(0), signed(10), signed(20), 0.0]
+            }
+            Self::U10_10_10_2 => {
+                let value = dword(0);
+                [
+                    unorm(bits(value, 0, 10), 10),
+                    unorm(bits(value, 10, 10), 10),
+                    unorm(bits(value, 20, 10), 10),
+                    unorm(bits(value, 30, 2), 2),
+                ]
+            }
+            Self::U10_10_10_2SignedNormalizedPackedAsUnorm => {
+                let value = dword(0);
+                [
+                    unorm(bits(value, 0, 10), 10) * 2.0 - 1.0,
+                    unorm(bits(value, 10, 10), 10) * 2.0 - 1.0,
+                    unorm(bits(value, 20, 10), 10) * 2.0 - 1.0,
+                    unorm(bits(value, 30, 2), 2),
+                ]
+            }
+            Self::DWord => [dword(0) as f32, 0.0, 0.0, 0.0],
+            Self::DWordVector2D => [dword(0) as f32, dword(1) as f32, 0.0, 0.0],
+            Self::ByteUnitVector3D => {
+                let unit = |index: usize| f32::from(element[index]) / 255.0 * 2.0 - 1.0;
+                [unit(0), unit(1), unit(2), 0.0]
+            }
+            Self::F11_11_10Float => {
+                return Err(TagError::UnsupportedVertexFormat(self.into()).into());
+            }
+        })
+    }
+}
+
+/// Builds an array of four components from a function of the component index.
+fn element_array(component: impl Fn(usize) -> f32) -> [f32; 4] {
+    [component(0), component(1), component(2), component(3)]
+}
+
+/// Converts an IEEE 754 half precision float to a single precision float.
+fn half_to_f32(half: u16) -> f32 {
+    let sign = u32::from(half >> 15) << 31;
+    let exponent = u32::from(half >> 10) & 0x1F;
+    let mantissa = u32::from(half) & 0x3FF;
+    let bits = match (exponent, mantissa) {
+        (0, 0) => sign,
+        (0, _) => {
+            // Subnormal, normalize the mantissa.
+            let shift = mantissa.leading_zeros() - 21;
+            sign | ((113 - shift) << 23) | ((mantissa << shift) & 0x3FF) << 13
+        }
+        (0x1F, _) => sign | 0x7F80_0000 | (mantissa << 13),
+        _ => sign | ((exponent + 112) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(bits)
+}
+
+/// Gets a buffer from the data of the resource files of a geometry.
+#[allow(clippy::cast_sign_loss)]
+fn buffer_slice(data: &[u8], offset: i32, size: u64) -> Result<&[u8]> {
+    let offset = u64::from(offset as u32);
+    usize::try_from(offset)
+        .ok()
+        .zip(usize::try_from(offset + size).ok())
+        .and_then(|(start, end)| data.get(start..end))
+        .ok_or_else(|| {
+            TagError::BufferOutOfBounds {
+                offset,
+                size,
+                available: data.len() as u64,
+            }
+            .into()
+        })
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x20), write)]
+/// Descriptor of a single vertex buffer (`rasterizer_vertex_buffer`).
+pub struct RasterizerVertexBuffer {
+    /// What the vertices of this buffer contain.
+    #[data(offset(0x00))]
+    pub usage: FieldCharEnum<VertexBufferUsage>,
+    /// Format of each vertex element.
+    #[data(offset(0x01))]
+    pub format: FieldByteInteger,
+    /// Size in bytes of a single vertex.
+    #[data(offset(0x02))]
+    pub stride: FieldByteInteger,
+    /// Number of vertices in the buffer.
+    #[data(offset(0x04))]
+    pub count: FieldDwordInteger,
+    /// Offset in bytes of the buffer inside the resource data.
+    #[data(offset(0x08))]
+    pub offset: FieldLongInteger,
+    /// Runtime handle of the buffer.
+    #[data(offset(0x10))]
+    pub d3d_buffer: FieldApiInterop,
+}
+
+impl RasterizerVertexBuffer {
+    /// Size in bytes of the buffer data, computed from the stride and vertex count.
+    #[must_use]
+    pub fn byte_size(&self) -> u64 {
+        u64::from(self.stride.0) * u64::from(self.count.0)
+    }
+
+    /// Gets the layout of each vertex element, if it is known.
+    #[must_use]
+    pub fn vertex_format(&self) -> Option<VertexFormat> {
+        VertexFormat::try_from(self.format.0).ok()
+    }
+
+    /// Decodes every vertex of the buffer, see [`VertexFormat::decode`].
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Data of the resource files of the geometry, see [`read_buffer_data`].
+    ///
+    /// # Errors
+    /// - If the buffer lies outside of `data` [`TagError::BufferOutOfBounds`]
+    /// - If the format is unknown or cannot be decoded [`TagError::UnsupportedVertexFormat`]
+    pub fn read_vertices(&self, data: &[u8]) -> Result<Vec<[f32; 4]>> {
+        let format = self
+            .vertex_format()
+            .ok_or(TagError::UnsupportedVertexFormat(self.format.0))?;
+        let buffer = buffer_slice(data, self.offset.0, self.byte_size())?;
+        let stride = usize::from(self.stride.0).max(1);
+        buffer
+            .chunks_exact(stride)
+            .map(|vertex| format.decode(vertex))
+            .collect()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x20), write)]
+/// Descriptor of a single index buffer (`rasterizer_index_buffer`).
+pub struct RasterizerIndexBuffer {
+    /// Primitive topology of the indices.
+    #[data(offset(0x00))]
+    pub declaration_type: FieldCharEnum<IndexBufferType>,
+    /// Size in bytes of a single index (2 or 4).
+    #[data(offset(0x01))]
+    pub stride: FieldByteInteger,
+    /// Number of indices in the buffer.
+    #[data(offset(0x04))]
+    pub count: FieldDwordInteger,
+    /// Offset in bytes of the buffer inside the resource data.
+    #[data(offset(0x08))]
+    pub offset: FieldLongInteger,
+    /// Runtime handle of the buffer.
+    #[data(offset(0x10))]
+    pub d3d_buffer: FieldApiInterop,
+}
+
+impl RasterizerIndexBuffer {
+    /// Size in bytes of the buffer data, computed from the stride and index count.
+    #[must_use]
+    pub fn byte_size(&self) -> u64 {
+        u64::from(self.stride.0) * u64::from(self.count.0)
+    }
+
+    /// Reads every index of the buffer, stored as 16 or 32 bit integers depending on the stride.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Data of the resource files of the geometry, see [`read_buffer_data`].
+    ///
+    /// # Errors
+    /// - If the buffer lies outside of `data` [`TagError::BufferOutOfBounds`]
+    pub fn read_indices(&self, data: &[u8]) -> Result<Vec<u32>> {
+        let buffer = buffer_slice(data, self.offset.0, self.byte_size())?;
+        Ok(if self.stride.0 == 4 {
+            buffer
+                .chunks_exact(4)
+                .map(|index| u32::from_le_bytes([index[0], index[1], index[2], index[3]]))
+                .collect()
+        } else {
+            buffer
+                .chunks_exact(2)
+                .map(|index| u32::from(u16::from_le_bytes([index[0], index[1]])))
+                .collect()
+        })
+    }
+}
+
+/// Reads the vertex and index buffer data of a geometry, from the resource files of the tag it
+/// is stored in (such as a `mode` tag), concatenated in order.
+///
+/// Buffer offsets in [`RenderGeometryApiResource`] are relative to the start of this data.
+///
+/// # Arguments
+///
+/// * `module` - Module containing the tag.
+/// * `index` - Index of the tag in the module.
+///
+/// # Errors
+/// - If a resource file is stored in an HD1 module that is not loaded [`TagError::NotLoaded`]
+/// - If a resource file cannot be read [`ReadError`](`crate::Error::ReadError`)
+pub fn read_buffer_data(module: &mut ModuleFile, index: u32) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    for file_index in module.resources_of(index).to_vec() {
+        let file = module.read_tag(file_index)?.ok_or(TagError::NotLoaded)?;
+        data.extend_from_slice(&file.get_raw_data(false)?);
+    }
+    Ok(data)
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x28), write)]
+/// Root structure of the render geometry API resource (`s_render_geometry_api_resource`).
+///
+/// Meant to be used as the type parameter of a
+/// [`FieldTagResource`](`super::common_types::FieldTagResource`).
+pub struct RenderGeometryApiResource {
+    /// Vertex buffers of the geometry.
+    #[data(offset(0x00))]
+    pub pc_vertex_buffers: FieldBlock<RasterizerVertexBuffer>,
+    /// Index buffers of the geometry.
+    #[data(offset(0x14))]
+    pub pc_index_buffers: FieldBlock<RasterizerIndexBuffer>,
+}
+
+impl RenderGeometryApiResource {
+    /// Finds the first vertex buffer with the given usage.
+    #[must_use]
+    pub fn vertex_buffer(&self, usage: VertexBufferUsage) -> Option<&RasterizerVertexBuffer> {
+        self.pc_vertex_buffers
+            .iter()
+            .find(|buffer| buffer.usage.0 == usage)
+    }
+}
@@ -0,0 +1,368 @@
+//! Render model (`mode`) tags, describing the regions, permutations, skeleton and meshes of a model.
+//!
+//! A model is split into regions (such as `head` or `body`), each having permutations that can be
+//! swapped at runtime. Every permutation points to a range of meshes in the
+//! [`render_geometry`](`RenderModelTag::render_geometry`), whose vertex and index buffers are
+//! stored in the [`RenderGeometryApiResource`] of its mesh resource groups.
+//!
+//! Only the fields required to locate the meshes of a permutation and decompress their vertices
+//! are exposed.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use infinite_rs::ModuleFile;
+//! use infinite_rs::tag::types::render_geometry::VertexBufferUsage;
+//! use infinite_rs::tag::types::render_model::RenderModelTag;
+//!
+//! let mut module = ModuleFile::from_path("deploy/any/globals/forge/forge_objects-rtx-new.module").unwrap();
+//! let index = module.files.iter().position(|file| file.tag_group == "mode").unwrap() as u32;
+//! let tag = module.read_tag(index).unwrap().unwrap();
+//! let model = tag.read_metadata::<RenderModelTag>().unwrap();
+//! let resource = model.render_geometry.api_resource().unwrap();
+//! for region in model.regions.iter() {
+//!     for permutation in region.permutations.iter() {
+//!         for mesh in model.meshes(permutation) {
+//!             let positions = mesh
+//!                 .vertex_buffer_index(VertexBufferUsage::Position)
+//!                 .map(|index| &resource.pc_vertex_buffers[index]);
+//!             println!("{:?}: {:?}", region.name, positions);
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::ops::Range;
+
+use super::common_types::{
+    AnyTag, FieldBlock, FieldCharInteger, FieldLongInteger, FieldReal, FieldRealBounds,
+    FieldRealPoint3D, FieldRealQuaternion, FieldShortInteger, FieldStringId, FieldTagResource,
+    FieldWordInteger,
+};
+use super::render_geometry::{RenderGeometryApiResource, VertexBufferUsage};
+use crate::common::hash::murmur3_x86_32;
+use infinite_rs_derive::TagStructure;
+
+/// Number of vertex buffer slots of a mesh, one per [`VertexBufferUsage`].
+pub const VERTEX_BUFFER_SLOTS: usize = 19;
+
+/// Whether a string id is the hash of the given string.
+#[allow(clippy::cast_possible_wrap)]
+fn matches_name(string_id: &FieldStringId, name: &str) -> bool {
+    string_id.0 == murmur3_x86_32(name.as_bytes(), 0) as i32
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x18), write)]
+/// Permutation of a region (`render_model_permutation_block`).
+pub struct RenderModelPermutation {
+    /// Name of the permutation.
+    #[data(offset(0x00))]
+    pub name: FieldStringId,
+    /// Index of the first mesh of the permutation, or -1 if it has no meshes.
+    #[data(offset(0x04))]
+    pub mesh_index: FieldShortInteger,
+    /// Number of meshes of the permutation.
+    #[data(offset(0x06))]
+    pub mesh_count: FieldShortInteger,
+}
+
+impl RenderModelPermutation {
+    /// Range of the meshes of the permutation in [`RenderGeometry::meshes`].
+    #[must_use]
+    pub fn mesh_range(&self) -> Range<usize> {
+        match (
+            usize::try_from(self.mesh_index.0),
+            usize::try_from(self.mesh_count.0),
+        ) {
+            (Ok(start), Ok(count)) => start..start + count,
+            _ => 0..0,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x18), write)]
+/// Region of a model (`render_model_region_block`).
+pub struct RenderModelRegion {
+    /// Name of the region.
+    #[data(offset(0x00))]
+    pub name: FieldStringId,
+    /// Permutations of the region.
+    #[data(offset(0x04))]
+    pub permutations: FieldBlock<RenderModelPermutation>,
+}
+
+impl RenderModelRegion {
+    /// Finds a permutation by name.
+    #[must_use]
+    pub fn permutation(&self, name: &str) -> Option<&RenderModelPermutation> {
+        self.permutations
+            .iter()
+            .find(|permutation| matches_name(&permutation.name, name))
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x7C), write)]
+/// Node of the skeleton of a model (`render_model_node_block`).
+pub struct RenderModelNode {
+    /// Name of the node.
+    #[data(offset(0x00))]
+    pub name: FieldStringId,
+    /// Index of the parent node, or -1 for the root node.
+    #[data(offset(0x04))]
+    pub parent_node: FieldShortInteger,
+    /// Index of the first child node, or -1.
+    #[data(offset(0x06))]
+    pub first_child_node: FieldShortInteger,
+    /// Index of the next sibling node, or -1.
+    #[data(offset(0x08))]
+    pub next_sibling_node: FieldShortInteger,
+    /// Translation relative to the parent node in the default pose.
+    #[data(offset(0x0C))]
+    pub default_translation: FieldRealPoint3D,
+    /// Rotation relative to the parent node in the default pose.
+    #[data(offset(0x18))]
+    pub default_rotation: FieldRealQuaternion,
+    /// Uniform scale relative to the parent node in the default pose.
+    #[data(offset(0x28))]
+    pub default_scale: FieldReal,
+}
+
+impl RenderModelNode {
+    /// Index of the parent node, if the node has one.
+    #[must_use]
+    pub fn parent(&self) -> Option<usize> {
+        usize::try_from(self.parent_node.0).ok()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x18), write)]
+/// Range of indices drawn with a single material (`part`).
+pub struct RenderMeshPart {
+    /// Index of the material in the model, or -1 if the part has no material.
+    #[data(offset(0x00))]
+    pub material_index: FieldShortInteger,
+    /// Index of the first index of the part in the index buffer of its mesh.
+    #[data(offset(0x04))]
+    pub index_start: FieldLongInteger,
+    /// Number of indices of the part.
+    #[data(offset(0x08))]
+    pub index_count: FieldLongInteger,
+}
+
+impl RenderMeshPart {
+    /// Range of the indices of the part in the index buffer of its mesh.
+    #[must_use]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn index_range(&self) -> Range<usize> {
+        let start = self.index_start.0 as u32 as usize;
+        start..start + self.index_count.0 as u32 as usize
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x5C), write)]
+/// Single mesh of the render geometry (`mesh`), also called a section.
+pub struct RenderMesh {
+    /// Ranges of indices drawn with a single material.
+    #[data(offset(0x00))]
+    pub parts: FieldBlock<RenderMeshPart>,
+    /// Index of the vertex buffer used for each [`VertexBufferUsage`], in the
+    /// [`pc_vertex_buffers`](`RenderGeometryApiResource::pc_vertex_buffers`) of the resource.
+    /// Unused slots are -1.
+    #[data(offset(0x28))]
+    pub vertex_buffer_indices: [FieldShortInteger; VERTEX_BUFFER_SLOTS],
+    /// Index of the index buffer in the
+    /// [`pc_index_buffers`](`RenderGeometryApiResource::pc_index_buffers`) of the resource.
+    #[data(offset(0x4E))]
+    pub index_buffer_index: FieldShortInteger,
+    /// Flags of the mesh.
+    #[data(offset(0x52))]
+    pub mesh_flags: FieldWordInteger,
+    /// Node the mesh is attached to if it is not skinned, or -1.
+    #[data(offset(0x54))]
+    pub rigid_node_index: FieldCharInteger,
+    /// Layout of the vertices of the mesh.
+    #[data(offset(0x55))]
+    pub vertex_type: FieldCharInteger,
+}
+
+impl RenderMesh {
+    /// Gets the index of the vertex buffer used for `usage`, if the mesh has one.
+    #[must_use]
+    pub fn vertex_buffer_index(&self, usage: VertexBufferUsage) -> Option<usize> {
+        let slot = usize::from(u8::from(usage));
+        usize::try_from(self.vertex_buffer_indices.get(slot)?.0).ok()
+    }
+
+    /// Gets the index of the index buffer of the mesh, if it has one.
+    #[must_use]
+    pub fn index_buffer_index(&self) -> Option<usize> {
+        usize::try_from(self.index_buffer_index.0).ok()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x2C), write)]
+/// Bounds used to decompress vertex positions and texture coordinates
+/// (`compression_info`).
+///
+/// Compressed vertices store values normalized to 0..1 inside these bounds.
+pub struct RenderGeometryCompressionInfo {
+    /// Compression flags.
+    #[data(offset(0x00))]
+    pub compression_flags: FieldWordInteger,
+    /// Bounds of the X coordinates of positions.
+    #[data(offset(0x04))]
+    pub position_bounds_x: FieldRealBounds,
+    /// Bounds of the Y coordinates of positions.
+    #[data(offset(0x0C))]
+    pub position_bounds_y: FieldRealBounds,
+    /// Bounds of the Z coordinates of positions.
+    #[data(offset(0x14))]
+    pub position_bounds_z: FieldRealBounds,
+    /// Bounds of the U coordinates of texture coordinates.
+    #[data(offset(0x1C))]
+    pub texcoord_bounds_u: FieldRealBounds,
+    /// Bounds of the V coordinates of texture coordinates.
+    #[data(offset(0x24))]
+    pub texcoord_bounds_v: FieldRealBounds,
+}
+
+impl RenderGeometryCompressionInfo {
+    /// Decompresses a position normalized to 0..1 inside the position bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use infinite_rs::tag::types::common_types::FieldRealBounds;
+    /// use infinite_rs::tag::types::render_model::RenderGeometryCompressionInfo;
+    ///
+    /// let compression = RenderGeometryCompressionInfo {
+    ///     position_bounds_x: FieldRealBounds { min: -2.0, max: 2.0 },
+    ///     position_bounds_z: FieldRealBounds { min: 0.0, max: 8.0 },
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(compression.decompress_position([0.5, 1.0, 0.25]), [0.0, 0.0, 2.0]);
+    /// ```
+    #[must_use]
+    pub fn decompress_position(&self, position: [f32; 3]) -> [f32; 3] {
+        [
+            lerp_bounds(&self.position_bounds_x, position[0]),
+            lerp_bounds(&self.position_bounds_y, position[1]),
+            lerp_bounds(&self.position_bounds_z, position[2]),
+        ]
+    }
+
+    /// Decompresses a texture coordinate normalized to 0..1 inside the texture coordinate bounds.
+    #[must_use]
+    pub fn decompress_texcoord(&self, texcoord: [f32; 2]) -> [f32; 2] {
+        [
+            lerp_bounds(&self.texcoord_bounds_u, texcoord[0]),
+            lerp_bounds(&self.texcoord_bounds_v, texcoord[1]),
+        ]
+    }
+}
+
+/// Maps a value from 0..1 to the given bounds.
+fn lerp_bounds(bounds: &FieldRealBounds, value: f32) -> f32 {
+    bounds.min + (bounds.max - bounds.min) * value
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x10), write)]
+/// Group of meshes sharing a render geometry API resource (`mesh_resource_group`).
+pub struct RenderMeshResourceGroup {
+    /// Resource containing the vertex and index buffers of the meshes.
+    #[data(offset(0x00))]
+    pub mesh_resource: FieldTagResource<RenderGeometryApiResource>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0xE0), write)]
+/// Geometry of a model, stored inline in the tag (`global_render_geometry_struct`).
+pub struct RenderGeometry {
+    /// Runtime flags of the geometry.
+    #[data(offset(0x00))]
+    pub runtime_flags: FieldLongInteger,
+    /// Meshes of the geometry, referenced by the permutations of the model.
+    #[data(offset(0x08))]
+    pub meshes: FieldBlock<RenderMesh>,
+    /// Bounds used to decompress vertices. Meshes use the first element.
+    #[data(offset(0x44))]
+    pub compression_info: FieldBlock<RenderGeometryCompressionInfo>,
+    /// Resources containing the vertex and index buffers of the meshes.
+    #[data(offset(0xAC))]
+    pub mesh_resource_groups: FieldBlock<RenderMeshResourceGroup>,
+}
+
+impl RenderGeometry {
+    /// Gets the bounds used to decompress the vertices of the meshes.
+    #[must_use]
+    pub fn compression(&self) -> Option<&RenderGeometryCompressionInfo> {
+        self.compression_info.first()
+    }
+
+    /// Gets the vertex and index buffers of the meshes, from the first mesh resource group that
+    /// is loaded.
+    #[must_use]
+    pub fn api_resource(&self) -> Option<&RenderGeometryApiResource> {
+        self.mesh_resource_groups
+            .iter()
+            .find(|group| !group.mesh_resource.is_missing)
+            .map(|group| &group.mesh_resource.data)
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x280), write)]
+/// Root structure of render model tags (`render_model_definition`).
+pub struct RenderModelTag {
+    /// Runtime information of the tag.
+    #[data(offset(0x00))]
+    pub any_tag: AnyTag,
+    /// Name of the model.
+    #[data(offset(0x10))]
+    pub name: FieldStringId,
+    /// Regions of the model.
+    #[data(offset(0x28))]
+    pub regions: FieldBlock<RenderModelRegion>,
+    /// Nodes of the skeleton of the model, with the root node first.
+    #[data(offset(0x3C))]
+    pub nodes: FieldBlock<RenderModelNode>,
+    /// Geometry of the model.
+    #[data(offset(0x1A0), inline)]
+    pub render_geometry: RenderGeometry,
+}
+
+impl RenderModelTag {
+    /// Finds a region by name.
+    #[must_use]
+    pub fn region(&self, name: &str) -> Option<&RenderModelRegion> {
+        self.regions
+            .iter()
+            .find(|region| matches_name(&region.name, name))
+    }
+
+    /// Gets the meshes of a permutation. Returns an empty slice if the permutation has no meshes,
+    /// or its meshes are out of bounds.
+    #[must_use]
+    pub fn meshes(&self, permutation: &RenderModelPermutation) -> &[RenderMesh] {
+        self.render_geometry
+            .meshes
+            .get(permutation.mesh_range())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,113 @@
+//! Script source file (`hsc*`) tags, containing the compiled Lua scripts of a level or mode.
+//!
+//! Each tag stores two chunks of Lua bytecode: one run by the server, and one run by every
+//! client. [`HsSourceFileTag::buffers`] names both buffers, and [`extract_scripts`] writes the
+//! scripts of every tag of a module as `.luac` files.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use infinite_rs::ModuleFile;
+//! use infinite_rs::tag::types::script::extract_scripts;
+//!
+//! let mut module = ModuleFile::from_path("deploy/any/globals/globals-rtx-new.module").unwrap();
+//! let paths = extract_scripts(&mut module, "scripts").unwrap();
+//! println!("Extracted {} scripts", paths.len());
+//! ```
+
+use std::fs::{create_dir_all, write};
+use std::path::{Path, PathBuf};
+
+use super::common_types::FieldData;
+use crate::ModuleFile;
+use crate::Result;
+use infinite_rs_derive::TagStructure;
+
+/// Tag group of script source file tags.
+pub const SCRIPT_GROUP: &str = "hsc*";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Named buffer of Lua bytecode.
+pub struct ScriptBuffer<'a> {
+    /// Side running the script, either `"server"` or `"client"`.
+    pub name: &'static str,
+    /// Compiled Lua bytecode.
+    pub data: &'a [u8],
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x2D8), write)]
+/// Root structure of script source file tags (`hs_source_file`).
+pub struct HsSourceFileTag {
+    /// Lua bytecode run by the server.
+    #[data(offset(0x294))]
+    pub server: FieldData,
+    /// Lua bytecode run by every client.
+    #[data(offset(0x2AC))]
+    pub client: FieldData,
+}
+
+impl HsSourceFileTag {
+    /// Gets the server and client bytecode of the tag, in that order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use infinite_rs::tag::types::script::HsSourceFileTag;
+    ///
+    /// let tag = HsSourceFileTag::default();
+    /// let names: Vec<_> = tag.buffers().iter().map(|buffer| buffer.name).collect();
+    /// assert_eq!(names, ["server", "client"]);
+    /// ```
+    #[must_use]
+    pub fn buffers(&self) -> [ScriptBuffer<'_>; 2] {
+        [
+            ScriptBuffer {
+                name: "server",
+                data: &self.server.data,
+            },
+            ScriptBuffer {
+                name: "client",
+                data: &self.client.data,
+            },
+        ]
+    }
+}
+
+/// Extracts the scripts of every script source file tag in a module to a directory.
+///
+/// Each tag is written as `<tag id>_server.luac` and `<tag id>_client.luac`, and the paths of
+/// the files written are returned. Tags stored in an HD1 module that is not loaded are skipped.
+///
+/// # Arguments
+///
+/// * `module` - Module to extract the scripts of.
+/// * `out_dir` - Directory to write the files to, created if it does not exist.
+///
+/// # Errors
+/// - If a tag cannot be read [`TagError`](`crate::common::errors::TagError`)
+/// - If the files cannot be written [`ReadError`](`crate::Error::ReadError`)
+pub fn extract_scripts<P: AsRef<Path>>(
+    module: &mut ModuleFile,
+    out_dir: P,
+) -> Result<Vec<PathBuf>> {
+    let out_dir = out_dir.as_ref();
+    create_dir_all(out_dir)?;
+    let mut paths = Vec::new();
+    for index in 0..u32::try_from(module.files.len())? {
+        if module.files[index as usize].tag_group != SCRIPT_GROUP {
+            continue;
+        }
+        let Some(tag) = module.read_tag(index)? else {
+            continue;
+        };
+        let source = tag.read_metadata::<HsSourceFileTag>()?;
+        for buffer in source.buffers() {
+            let path = out_dir.join(format!("{}_{}.luac", tag.tag_id.0, buffer.name));
+            write(&path, buffer.data)?;
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
@@ -0,0 +1,400 @@
+//! Sound (`snd!`) and soundbank (`sbnk`) tags, and extraction of the Wwise audio they contain.
+//!
+//! Audio is authored with Wwise. Soundbank tags embed Wwise soundbanks (`.bnk`), which contain
+//! the event hierarchy and short sounds as embedded media. Longer sounds are streamed from
+//! separate Wwise media files (`.wem`), stored as resource files of the tags using them.
+//!
+//! [`Soundbank`] parses the chunks of a Wwise soundbank found with [`find_soundbanks`], and
+//! [`extract_audio`] writes every soundbank, embedded media and streamed media of a tag to disk.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use infinite_rs::ModuleFile;
+//! use infinite_rs::tag::types::sound::extract_audio;
+//!
+//! let mut module = ModuleFile::from_path("deploy/any/globals/forge/forge_objects-rtx-new.module").unwrap();
+//! for index in 0..module.files.len() as u32 {
+//!     if module.files[index as usize].tag_group == "sbnk" {
+//!         let paths = extract_audio(&mut module, index, "audio").unwrap();
+//!         println!("{paths:?}");
+//!     }
+//! }
+//! ```
+
+use std::fs::{create_dir_all, write};
+use std::path::{Path, PathBuf};
+
+use super::common_types::{AnyTag, FieldData, FieldReference, FieldStringId};
+use crate::ModuleFile;
+use crate::Result;
+use crate::common::errors::TagError;
+use infinite_rs_derive::TagStructure;
+
+/// Magic of the first chunk of a Wwise soundbank ("BKHD").
+const BANK_HEADER_MAGIC: [u8; 4] = *b"BKHD";
+/// Magic at the start of a Wwise media file ("RIFF").
+const RIFF_MAGIC: [u8; 4] = *b"RIFF";
+/// Type of sound objects in the `HIRC` chunk.
+const HIRC_SOUND: u8 = 0x02;
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x30), write)]
+/// Root structure of soundbank tags (`sound_bank_definition`).
+pub struct SoundBankTag {
+    /// Runtime information of the tag.
+    #[data(offset(0x00))]
+    pub any_tag: AnyTag,
+    /// Name of the soundbank.
+    #[data(offset(0x10))]
+    pub name: FieldStringId,
+    /// Wwise soundbank, parsed with [`Soundbank::parse`].
+    #[data(offset(0x18))]
+    pub bank_data: FieldData,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TagStructure)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[data(size(0x30), write)]
+/// Root structure of sound tags (`sound`), pointing to a Wwise event.
+pub struct SoundTag {
+    /// Runtime information of the tag.
+    #[data(offset(0x00))]
+    pub any_tag: AnyTag,
+    /// Name of the Wwise event played by the sound.
+    #[data(offset(0x10))]
+    pub event_name: FieldStringId,
+    /// Soundbank containing the event.
+    #[data(offset(0x14))]
+    pub sound_bank: FieldReference,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Chunk of a Wwise soundbank.
+pub struct SoundbankChunk<'a> {
+    /// Four character code of the chunk, such as `BKHD` or `DIDX`.
+    pub magic: [u8; 4],
+    /// Contents of the chunk, without its header.
+    pub data: &'a [u8],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Media embedded in a soundbank, listed in its `DIDX` chunk.
+pub struct EmbeddedMedia<'a> {
+    /// Wwise id of the media, which is also the name of its `.wem` file.
+    pub id: u32,
+    /// Contents of the `.wem` file.
+    pub data: &'a [u8],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Wwise soundbank (`.bnk`), split into its chunks.
+pub struct Soundbank<'a> {
+    /// Bytes of the whole soundbank, from its `BKHD` chunk to its last chunk.
+    pub data: &'a [u8],
+    /// Version of the soundbank format.
+    pub version: u32,
+    /// Wwise id of the soundbank, which is also the name of its `.bnk` file.
+    pub id: u32,
+    /// Chunks of the soundbank, in order.
+    pub chunks: Vec<SoundbankChunk<'a>>,
+}
+
+/// Reads a little endian [`u32`] at `offset`, if it is in bounds.
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+impl<'a> Soundbank<'a> {
+    /// Parses a soundbank starting at the start of `data`.
+    ///
+    /// Chunks are read until the end of `data`, or until a chunk header is not a valid four
+    /// character code, so that a soundbank can be parsed from a larger buffer.
+    ///
+    /// # Errors
+    /// - If `data` does not start with a complete `BKHD` chunk header [`TagError::InvalidSoundbank`]
+    /// - If a chunk is larger than the remaining data [`TagError::InvalidSoundbank`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use infinite_rs::tag::types::sound::Soundbank;
+    ///
+    /// let mut bank = Vec::new();
+    /// bank.extend_from_slice(b"BKHD");
+    /// bank.extend_from_slice(&8u32.to_le_bytes());
+    /// bank.extend_from_slice(&0x91u32.to_le_bytes());
+    /// bank.extend_from_slice(&1234u32.to_le_bytes());
+    ///
+    /// let soundbank = Soundbank::parse(&bank).unwrap();
+    /// assert_eq!(soundbank.version, 0x91);
+    /// assert_eq!(soundbank.id, 1234);
+    /// assert!(soundbank.embedded_media().unwrap().is_empty());
+    /// ```
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        if !data.starts_with(&BANK_HEADER_MAGIC) {
+            return Err(TagError::InvalidSoundbank("missing BKHD chunk").into());
+        }
+        let mut chunks = Vec::new();
+        let mut offset = 0;
+        while let Some(header) = data.get(offset..offset + 8) {
+            let magic = [header[0], header[1], header[2], header[3]];
+            if !magic.iter().all(u8::is_ascii_uppercase) {
+                break;
+            }
+            let size = read_u32(header, 4).unwrap_or_default() as usize;
+            let chunk = data
+                .get(offset + 8..offset + 8 + size)
+                .ok_or(TagError::InvalidSoundbank("chunk is out of bounds"))?;
+            chunks.push(SoundbankChunk { magic, data: chunk });
+            offset += 8 + size;
+        }
+
+        let header = chunks
+            .first()
+            .ok_or(TagError::InvalidSoundbank("truncated BKHD chunk"))?
+            .data;
+        Ok(Self {
+            data: &data[..offset],
+            version: read_u32(header, 0).unwrap_or_default(),
+            id: read_u32(header, 4).unwrap_or_default(),
+            chunks,
+        })
+    }
+
+    /// Gets the contents of the first chunk with the given four character code.
+    #[must_use]
+    pub fn chunk(&self, magic: &[u8; 4]) -> Option<&'a [u8]> {
+        self.chunks
+            .iter()
+            .find(|chunk| &chunk.magic == magic)
+            .map(|chunk| chunk.data)
+    }
+
+    /// Gets the media embedded in the soundbank, from its `DIDX` and `DATA` chunks.
+    ///
+    /// # Errors
+    /// - If a media lies outside of the `DATA` chunk [`TagError::InvalidSoundbank`]
+    pub fn embedded_media(&self) -> Result<Vec<EmbeddedMedia<'a>>> {
+        let (Some(index), Some(data)) = (self.chunk(b"DIDX"), self.chunk(b"DATA")) else {
+            return Ok(Vec::new());
+        };
+        index
+            .chunks_exact(12)
+            .map(|entry| {
+                let id = read_u32(entry, 0).unwrap_or_default();
+                let offset = read_u32(entry, 4).unwrap_or_default() as usize;
+                let size = read_u32(entry, 8).unwrap_or_default() as usize;
+                let media = data
+                    .get(offset..offset + size)
+                    .ok_or(TagError::InvalidSoundbank("media is out of bounds"))?;
+                Ok(EmbeddedMedia { id, data: media })
+            })
+            .collect()
+    }
+
+    /// Gets the ids of the media streamed by the sounds of the soundbank, from its `HIRC` chunk.
+    ///
+    /// Streamed media are stored in separate `.wem` files named after their id. Sound objects
+    /// are read with the layout of recent soundbank versions.
+    #[must_use]
+    pub fn streamed_media_ids(&self) -> Vec<u32> {
+        let Some(hierarchy) = self.chunk(b"HIRC") else {
+            return Vec::new();
+        };
+        let count = read_u32(hierarchy, 0).unwrap_or_default();
+        let mut ids = Vec::new();
+        let mut offset = 4;
+        for _ in 0..count {
+            let (Some(&object_type), Some(size)) =
+                (hierarchy.get(offset), read_u32(hierarchy, offset + 1))
+            else {
+                break;
+            };
+            let object = offset + 5;
+            // Object id, plugin id, then stream type and source id of the media.
+            if object_type == HIRC_SOUND
+                && hierarchy.get(object + 8).is_some_and(|&stream| stream != 0)
+            {
+                if let Some(source_id) = read_u32(hierarchy, object + 9) {
+                    ids.push(source_id);
+                }
+            }
+            offset = object + size as usize;
+        }
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+}
+
+/// Finds every Wwise soundbank in a buffer, such as the data of a tag or of its resource files.
+///
+/// Soundbanks that cannot be parsed are skipped.
+#[must_use]
+pub fn find_soundbanks(data: &[u8]) -> Vec<Soundbank<'_>> {
+    let mut soundbanks = Vec::new();
+    let mut offset = 0;
+    while let Some(position) = data[offset..]
+        .windows(4)
+        .position(|window| window == BANK_HEADER_MAGIC)
+    {
+        let start = offset + position;
+        match Soundbank::parse(&data[start..]) {
+            Ok(soundbank) => {
+                offset = start + soundbank.data.len().max(4);
+                soundbanks.push(soundbank);
+            }
+            Err(_) => offset = start + 4,
+        }
+    }
+    soundbanks
+}
+
+/// Extracts the audio of a tag to a directory, returning the paths of the files written.
+///
+/// Writes every soundbank found in the tag and its resource files as `<id>.bnk`, with its
+/// embedded media as `<id>.wem`. Resource files that are Wwise media themselves are streamed
+/// media, and are written as `<tag id>_<resource index>.wem`, as their Wwise id is only known
+/// from the soundbanks streaming them (see [`Soundbank::streamed_media_ids`]).
+///
+/// # Arguments
+///
+/// * `module` - Module containing the tag.
+/// * `index` - Index of the tag in the module.
+/// * `out_dir` - Directory to write the files to, created if it does not exist.
+///
+/// # Errors
+/// - If the tag is stored in an HD1 module that is not loaded [`TagError::NotLoaded`]
+/// - If the media of a soundbank are out of bounds [`TagError::InvalidSoundbank`]
+/// - If the tag cannot be read or the files cannot be written [`ReadError`](`crate::Error::ReadError`)
+pub fn extract_audio<P: AsRef<Path>>(
+    module: &mut ModuleFile,
+    index: u32,
+    out_dir: P,
+) -> Result<Vec<PathBuf>> {
+    let out_dir = out_dir.as_ref();
+    create_dir_all(out_dir)?;
+    let tag_id = module
+        .files
+        .get(index as usize)
+        .ok_or(TagError::NotLoaded)?
+        .tag_id;
+
+    let mut buffers = vec![
+        module
+            .read_tag(index)?
+            .ok_or(TagError::NotLoaded)?
+            .get_raw_data(true)?,
+    ];
+    for file_index in module.resources_of(index).to_vec() {
+        if let Some(file) = module.read_tag(file_index)? {
+            buffers.push(file.get_raw_data(false)?);
+        }
+    }
+
+    let mut paths = Vec::new();
+    for (resource_index, buffer) in buffers.iter().enumerate() {
+        if resource_index > 0 && buffer.starts_with(&RIFF_MAGIC) {
+            let path = out_dir.join(format!("{tag_id}_{}.wem", resource_index - 1));
+            write(&path, buffer)?;
+            paths.push(path);
+            continue;
+        }
+        for soundbank in find_soundbanks(buffer) {
+            let path = out_dir.join(format!("{}.bnk", soundbank.id));
+            write(&path, soundbank.data)?;
+            paths.push(path);
+            for media in soundbank.embedded_media()? {
+                let path = out_dir.join(format!("{}.wem", media.id));
+                write(&path, media.data)?;
+                paths.push(path);
+            }
+        }
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    /// Appends a chunk to a soundbank.
+    fn push_chunk(bank: &mut Vec<u8>, magic: [u8; 4], data: &[u8]) {
+        bank.extend_from_slice(&magic);
+        bank.extend_from_slice(&u32::try_from(data.len()).unwrap().to_le_bytes());
+        bank.extend_from_slice(data);
+    }
+
+    #[test]
+    /// Verifies that embedded and streamed media are found in a soundbank inside a larger buffer.
+    fn test_find_soundbanks() {
+        let mut bank = Vec::new();
+        push_chunk(&mut bank, *b"BKHD", &[0x91, 0, 0, 0, 7, 0, 0, 0]);
+        let index: Vec<u8> = [10u32, 0, 4, 11, 4, 2]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        push_chunk(&mut bank, *b"DIDX", &index);
+        push_chunk(&mut bank, *b"DATA", b"RIFFwe");
+
+        let mut hierarchy = 2u32.to_le_bytes().to_vec();
+        for (stream_type, source_id) in [(0u8, 10u32), (2, 42)] {
+            hierarchy.push(HIRC_SOUND);
+            hierarchy.extend_from_slice(&13u32.to_le_bytes());
+            hierarchy.extend_from_slice(&1u32.to_le_bytes());
+            hierarchy.extend_from_slice(&0x0004_0001u32.to_le_bytes());
+            hierarchy.push(stream_type);
+            hierarchy.extend_from_slice(&source_id.to_le_bytes());
+        }
+        push_chunk(&mut bank, *b"HIRC", &hierarchy);
+
+        let mut buffer = vec![0xAB; 0x10];
+        buffer.extend_from_slice(&bank);
+        buffer.extend_from_slice(&[0; 8]);
+
+        let soundbanks = find_soundbanks(&buffer);
+        assert_eq!(soundbanks.len(), 1);
+        let soundbank = &soundbanks[0];
+        assert_eq!(soundbank.id, 7);
+        assert_eq!(soundbank.data, &bank[..]);
+        assert_eq!(
+            soundbank.embedded_media().unwrap(),
+            vec![
+                EmbeddedMedia {
+                    id: 10,
+                    data: b"RIFF"
+                },
+                EmbeddedMedia {
+                    id: 11,
+                    data: b"we"
+                },
+            ]
+        );
+        assert_eq!(soundbank.streamed_media_ids(), vec![42]);
+    }
+
+    #[test]
+    /// Verifies that a `BKHD` magic without the rest of its chunk header is rejected.
+    fn test_parse_truncated_header() {
+        assert!(matches!(
+            Soundbank::parse(b"BKHD"),
+            Err(Error::TagError(TagError::InvalidSoundbank(_)))
+        ));
+        assert!(matches!(
+            Soundbank::parse(b"BKHD\x08\0"),
+            Err(Error::TagError(TagError::InvalidSoundbank(_)))
+        ));
+    }
+
+    #[test]
+    /// Verifies that a `BKHD` magic at the end of a buffer is skipped.
+    fn test_find_soundbanks_trailing_magic() {
+        let mut buffer = vec![0xAB; 0x10];
+        buffer.extend_from_slice(b"BKHD");
+        assert!(find_soundbanks(&buffer).is_empty());
+    }
+}
@@ -0,0 +1,97 @@
+//! Validation of [`TagStructure`] layouts against the struct definitions of a tag.
+
+use super::{loader::TagFile, structure::TagStructType};
+use crate::Result;
+use crate::common::errors::TagError;
+use crate::module::file::TagStructure;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// Difference found between a [`TagStructure`] layout and the layout of a tag.
+pub enum LayoutMismatch {
+    /// The declared size of the structure does not match the size of the main data block.
+    Size {
+        /// Size declared by `#[data(size())]`.
+        declared: u64,
+        /// Size of the main data block in the tag.
+        actual: u64,
+    },
+    /// A struct definition points to a field at an offset that the structure does not declare.
+    UndeclaredStruct {
+        /// Offset of the field inside the main data block.
+        offset: u32,
+        /// Type of the struct the field refers to.
+        struct_type: TagStructType,
+    },
+    /// A data reference points to a field at an offset that the structure does not declare.
+    UndeclaredData {
+        /// Offset of the field inside the main data block.
+        offset: u32,
+    },
+    /// A tag reference points to a field at an offset that the structure does not declare.
+    UndeclaredReference {
+        /// Offset of the field inside the main data block.
+        offset: u32,
+    },
+}
+
+impl TagFile {
+    /// Cross-checks the layout of a [`TagStructure`] against the main struct of the tag.
+    ///
+    /// The declared size is compared against the size of the main data block, and every struct
+    /// definition, data reference and tag reference located in the main data block must point at
+    /// an offset declared by the structure. This catches structures that were written for another
+    /// build of the game before they produce garbage reads.
+    ///
+    /// Returns an empty list if no mismatches were found.
+    ///
+    /// # Errors
+    /// - If the main struct definition is not found [`TagError::MainStructNotFound`]
+    pub fn validate_layout<T: Default + TagStructure>(&self) -> Result<Vec<LayoutMismatch>> {
+        let mut structure = T::default();
+        let declared = structure.size();
+        let fields = structure.fields();
+        let is_declared =
+            |offset: u32| fields.iter().any(|field| field.offset == u64::from(offset));
+
+        let main_struct = self.main_struct().ok_or(TagError::MainStructNotFound)?;
+        let main_index = main_struct.target_index;
+        let main_block = self
+            .datablock_definitions
+            .get(usize::try_from(main_index)?)
+            .ok_or(TagError::MainStructNotFound)?;
+
+        let mut mismatches = Vec::new();
+        let actual = u64::from(main_block.entry_size);
+        if declared != actual {
+            mismatches.push(LayoutMismatch::Size { declared, actual });
+        }
+
+        mismatches.extend(
+            self.struct_definitions
+                .iter()
+                .filter(|s| s.field_block == main_index && !is_declared(s.field_offset))
+                .map(|s| LayoutMismatch::UndeclaredStruct {
+                    offset: s.field_offset,
+                    struct_type: s.struct_type,
+                }),
+        );
+        mismatches.extend(
+            self.data_references
+                .iter()
+                .filter(|r| r.field_block == main_index && !is_declared(r.field_offset))
+                .map(|r| LayoutMismatch::UndeclaredData {
+                    offset: r.field_offset,
+                }),
+        );
+        mismatches.extend(
+            self.tag_references
+                .iter()
+                .filter(|r| r.field_block == main_index && !is_declared(r.field_offset))
+                .map(|r| LayoutMismatch::UndeclaredReference {
+                    offset: r.field_offset,
+                }),
+        );
+        Ok(mismatches)
+    }
+}
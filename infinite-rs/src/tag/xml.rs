@@ -0,0 +1,208 @@
+//! XML export of loaded tags.
+//!
+//! The layout follows the one used by existing Halo tag tooling: a `tag` root element containing
+//! nested `struct`, `block`, `literal` and `resource` elements, with `field` elements for data
+//! and tag references. Every element carries the offset of its field inside the parent structure,
+//! so outputs of different tags of the same group can be diffed directly.
+//!
+//! ```xml
+//! <tag root_struct_guid="0x...">
+//!   <struct guid="0x..." type="MainStruct" section="TagData" size="0x88">
+//!     <raw>...</raw>
+//!     <field offset="0x10" type="reference" group="bitm" id="0x..." name="..."/>
+//!     <block offset="0x20" guid="0x..." type="TagBlock" section="TagData" size="0x30">
+//!       <raw>...</raw>
+//!     </block>
+//!   </struct>
+//! </tag>
+//! ```
+
+use std::fmt::Write;
+
+use super::{
+    dynamic::{DynamicStruct, DynamicTagValue, to_hex},
+    loader::TagFile,
+    structure::TagStructType,
+};
+use crate::Result;
+
+impl TagFile {
+    /// Exports the entire tag as an XML document.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Bytes of the entire tag file (including header).
+    ///
+    /// # Errors
+    /// - If the main struct definition is not found [`TagError::MainStructNotFound`](`crate::common::errors::TagError::MainStructNotFound`)
+    pub fn to_xml(&self, data: &[u8]) -> Result<String> {
+        let root = DynamicStruct::read(self, data)?;
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<tag root_struct_guid=\"{:#018X}\">",
+            self.header.root_struct_guid
+        );
+        write_struct(self, &root, None, 1, &mut xml);
+        xml.push_str("</tag>\n");
+        Ok(xml)
+    }
+}
+
+/// Writes a structure element and all of its children.
+fn write_struct(
+    tag_file: &TagFile,
+    dynamic: &DynamicStruct,
+    offset: Option<u32>,
+    depth: usize,
+    xml: &mut String,
+) {
+    let indent = "  ".repeat(depth);
+    let element = match dynamic.struct_type {
+        TagStructType::TagBlock => "block",
+        TagStructType::Resource => "resource",
+        TagStructType::Literal => "literal",
+        TagStructType::MainStruct | TagStructType::Custom => "struct",
+    };
+    let _ = write!(xml, "{indent}<{element}");
+    if let Some(offset) = offset {
+        let _ = write!(xml, " offset=\"{offset:#X}\"");
+    }
+    let _ = write!(
+        xml,
+        " guid=\"{:#034X}\" type=\"{:?}\"",
+        dynamic.guid, dynamic.struct_type
+    );
+    if dynamic.datablock_index.is_some() {
+        let _ = write!(xml, " section=\"{:?}\"", dynamic.section_type);
+    }
+    let _ = write!(xml, " size=\"{:#X}\"", dynamic.data.len());
+
+    if dynamic.data.is_empty() && dynamic.fields.is_empty() {
+        xml.push_str("/>\n");
+        return;
+    }
+    xml.push_str(">\n");
+    if !dynamic.data.is_empty() {
+        let _ = writeln!(xml, "{indent}  <raw>{}</raw>", to_hex(&dynamic.data));
+    }
+    for field in &dynamic.fields {
+        write_field(tag_file, field, depth + 1, xml);
+    }
+    let _ = writeln!(xml, "{indent}</{element}>");
+}
+
+/// Writes a single field of a structure.
+fn write_field(tag_file: &TagFile, field: &DynamicTagValue, depth: usize, xml: &mut String) {
+    let indent = "  ".repeat(depth);
+    match field {
+        DynamicTagValue::Struct { offset, value } => {
+            write_struct(tag_file, value, Some(*offset), depth, xml);
+        }
+        DynamicTagValue::Data { offset, data, .. } => {
+            let _ = writeln!(
+                xml,
+                "{indent}<field offset=\"{offset:#X}\" type=\"data\" size=\"{:#X}\">{}</field>",
+                data.len(),
+                to_hex(data)
+            );
+        }
+        DynamicTagValue::Reference {
+            offset,
+            dependency_index,
+        } => {
+            let _ = write!(
+                xml,
+                "{indent}<field offset=\"{offset:#X}\" type=\"reference\""
+            );
+            if let Some(dependency) = tag_file.get_dependency(*dependency_index) {
+                let _ = write!(
+                    xml,
+                    " group=\"{}\" id=\"{:#010X}\"",
+                    escape(&dependency.tag_group),
+                    dependency.tag_id
+                );
+                if let Some(name) = &dependency.name {
+                    let _ = write!(xml, " name=\"{}\"", escape(name));
+                }
+            }
+            xml.push_str("/>\n");
+        }
+    }
+}
+
+/// Escapes characters that are not allowed inside XML attribute values.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for character in value.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TagId;
+    use crate::tag::dependency::TagDependency;
+
+    #[test]
+    /// Verifies the element layout of nested blocks and references.
+    fn test_write_struct() {
+        let tag_file = TagFile {
+            dependencies: vec![TagDependency {
+                tag_group: String::from("mat "),
+                tag_id: TagId(0x1234),
+                name: Some(String::from("cats & dogs")),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let root = DynamicStruct {
+            guid: 1,
+            struct_type: TagStructType::MainStruct,
+            datablock_index: Some(0),
+            data: vec![0xCA, 0x7F],
+            fields: vec![
+                DynamicTagValue::Reference {
+                    offset: 0x10,
+                    dependency_index: 0,
+                },
+                DynamicTagValue::Struct {
+                    offset: 0x20,
+                    value: DynamicStruct {
+                        guid: 2,
+                        struct_type: TagStructType::TagBlock,
+                        ..Default::default()
+                    },
+                },
+            ],
+            ..Default::default()
+        };
+
+        let mut xml = String::new();
+        write_struct(&tag_file, &root, None, 0, &mut xml);
+        let lines = xml.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[0],
+            "<struct guid=\"0x00000000000000000000000000000001\" type=\"MainStruct\" section=\"Header\" size=\"0x2\">"
+        );
+        assert_eq!(lines[1], "  <raw>ca7f</raw>");
+        assert_eq!(
+            lines[2],
+            "  <field offset=\"0x10\" type=\"reference\" group=\"mat \" id=\"0x00001234\" name=\"cats &amp; dogs\"/>"
+        );
+        assert_eq!(
+            lines[3],
+            "  <block offset=\"0x20\" guid=\"0x00000000000000000000000000000002\" type=\"TagBlock\" size=\"0x0\"/>"
+        );
+        assert_eq!(lines[4], "</struct>");
+    }
+}